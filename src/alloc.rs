@@ -1,15 +1,86 @@
+use crate::error::*;
 use crate::memory::Memory;
 use log::{debug, warn};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
 
-/// An extremely simple bump-allocator which never frees
-#[derive(Clone)]
+/// An extremely simple bump-allocator. It only reuses space which is
+/// explicitly freed with `free()`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Alloc {
     /// Pointer to available, unallocated memory
     cursor: u64,
 
     /// Map from allocation address to its size in bits
     sizes: HashMap<u64, u64>,
+
+    /// Map from allocation address to the number of bytes of address space
+    /// reserved for it. This is more than its size if it reused a larger
+    /// freed allocation, and lets that space be reused in full once it's
+    /// freed again.
+    capacities: HashMap<u64, u64>,
+
+    /// Addresses of freed allocations available for reuse, in the order they
+    /// were freed
+    free_list: Vec<u64>,
+
+    /// Handler for allocation and free events, if one has been installed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    event_handler: EventHandler,
+}
+
+/// An allocation or free performed by the allocator.
+/// See [`State.set_alloc_event_handler()`](struct.State.html#method.set_alloc_event_handler).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AllocEvent {
+    /// An allocation of `size` bits was made at address `base`
+    Alloc { base: u64, size: u64 },
+    /// The allocation at address `base` was freed
+    Free { base: u64 },
+}
+
+/// A handler installed with `Alloc::set_event_handler()`. Copies of an
+/// `Alloc` share its handler, which isn't part of the allocator's state: it's
+/// ignored when comparing (or serializing) `Alloc`s.
+#[derive(Clone, Default)]
+#[allow(clippy::type_complexity)]
+struct EventHandler(Option<Rc<RefCell<Box<dyn FnMut(AllocEvent)>>>>);
+
+impl EventHandler {
+    fn fire(&self, event: AllocEvent) {
+        if let Some(handler) = &self.0 {
+            (handler.borrow_mut())(event);
+        }
+    }
+}
+
+impl PartialEq for EventHandler {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for EventHandler {}
+
+impl fmt::Debug for EventHandler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(_) => write!(f, "<event handler>"),
+            None => write!(f, "<no event handler>"),
+        }
+    }
+}
+
+/// Describes a single allocation made by the allocator
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct AllocationInfo {
+    /// Address of the start of the allocation
+    pub addr: u64,
+    /// Size of the allocation, in bits
+    pub bits: u64,
 }
 
 impl Alloc {
@@ -19,9 +90,24 @@ impl Alloc {
         Self {
             cursor: Self::ALLOC_START,
             sizes: HashMap::new(),
+            capacities: HashMap::new(),
+            free_list: Vec::new(),
+            event_handler: EventHandler::default(),
         }
     }
 
+    /// Install a handler which will be called with each allocation (by
+    /// `alloc()` or `reserve_at()`) and each free (by `free()` or
+    /// `report_free()`), replacing any previously installed handler.
+    pub fn set_event_handler(&mut self, handler: Box<dyn FnMut(AllocEvent)>) {
+        self.event_handler = EventHandler(Some(Rc::new(RefCell::new(handler))));
+    }
+
+    /// Has a handler been installed with `set_event_handler()`?
+    pub fn has_event_handler(&self) -> bool {
+        self.event_handler.0.is_some()
+    }
+
     /// Allocate the specified number of bits, returning a pointer to the allocated object.
     // Internal invariants:
     //   - for sizes <= cell size, allocation never crosses a cell boundary
@@ -31,15 +117,20 @@ impl Alloc {
         if bits == 0 {
             warn!("An allocation of 0 bits was requested");
         }
-        let bits_in_byte: u64 = Memory::BITS_IN_BYTE.into();
         let cell_bytes: u64 = Memory::CELL_BYTES.into();
-        let bytes = {
-            let mut bytes = bits / bits_in_byte;
-            if bits % bits_in_byte != 0 {
-                bytes += 1; // round up to nearest byte
-            }
-            bytes
-        };
+        let bytes = bytes_rounded_up(bits);
+        if let Some(addr) = self.take_freed(bytes) {
+            self.sizes.insert(addr, bits);
+            debug!(
+                "Allocated {} bits at 0x{:x}, reusing a freed allocation",
+                bits, addr
+            );
+            self.event_handler.fire(AllocEvent::Alloc {
+                base: addr,
+                size: bits,
+            });
+            return addr;
+        }
         let current_offset_bytes = self.cursor % cell_bytes;
         let bytes_remaining_in_cell = cell_bytes - current_offset_bytes;
         if bytes > bytes_remaining_in_cell {
@@ -49,13 +140,200 @@ impl Alloc {
         let rval = self.cursor;
         self.cursor += bytes;
         self.sizes.insert(rval, bits);
+        self.capacities.insert(rval, bytes);
         debug!("Allocated {} bits at 0x{:x}", bits, rval);
+        self.event_handler.fire(AllocEvent::Alloc {
+            base: rval,
+            size: bits,
+        });
         rval
     }
 
+    /// Reserve `bits` bits at the specific address `base`, rather than wherever
+    /// `alloc()` would put them, for instance to model a memory-mapped device
+    /// at a known address. Returns `base`.
+    ///
+    /// Returns an error if the region would contain address 0, wrap around
+    /// the end of the address space, or overlap any allocation made so far
+    /// (including freed ones, which may still be reused). Later calls to
+    /// `alloc()` won't overlap the region.
+    ///
+    /// The region must also respect the same invariants as `alloc()`: a region
+    /// no larger than a memory cell can't cross a cell boundary, and a larger
+    /// region must start at a cell boundary. Other regions are an error.
+    pub fn reserve_at(&mut self, base: u64, bits: impl Into<u64>) -> Result<u64> {
+        let bits: u64 = bits.into();
+        // as in `provenance()`, an allocation of 0 bits contains its own address
+        let bytes = std::cmp::max(bytes_rounded_up(bits), 1);
+        let end = match base.checked_add(bytes) {
+            Some(end) if base != 0 => end,
+            _ => {
+                return Err(Error::OtherError(format!(
+                    "reserve_at: can't reserve {} bits at address {:#x}",
+                    bits, base
+                )))
+            },
+        };
+        let cell_bytes: u64 = Memory::CELL_BYTES.into();
+        let respects_cells = if bytes <= cell_bytes {
+            base % cell_bytes + bytes <= cell_bytes
+        } else {
+            base.is_multiple_of(cell_bytes)
+        };
+        if !respects_cells {
+            return Err(Error::OtherError(format!(
+                "reserve_at: {} bits at address {:#x} would cross a memory cell boundary",
+                bits, base
+            )));
+        }
+        if let Some((&addr, _)) = self
+            .capacities
+            .iter()
+            .find(|(&addr, &capacity)| addr < end && base < addr + std::cmp::max(capacity, 1))
+        {
+            return Err(Error::OtherError(format!(
+                "reserve_at: {} bits at address {:#x} would overlap the allocation of {} bits at address {:#x}",
+                bits, base, self.sizes[&addr], addr
+            )));
+        }
+        // later allocations come from above the region
+        self.cursor = std::cmp::max(self.cursor, end);
+        self.sizes.insert(base, bits);
+        self.capacities.insert(base, bytes_rounded_up(bits));
+        debug!("Reserved {} bits at 0x{:x}", bits, base);
+        self.event_handler
+            .fire(AllocEvent::Alloc { base, size: bits });
+        Ok(base)
+    }
+
+    /// Make the allocation at the given address available for reuse by later
+    /// calls to `alloc()` of the same size or smaller. Returns `false` (and
+    /// does nothing) if that address is not the result of an `alloc()`, or was
+    /// already freed.
+    pub fn free(&mut self, addr: u64) -> bool {
+        if !self.sizes.contains_key(&addr) || self.free_list.contains(&addr) {
+            return false;
+        }
+        self.free_list.push(addr);
+        debug!("Freed the allocation at 0x{:x}", addr);
+        self.event_handler.fire(AllocEvent::Free { base: addr });
+        true
+    }
+
+    /// Report a free of the allocation at the given address to the event
+    /// handler, without making the allocation available for reuse. Returns
+    /// `false` (and does nothing) in the same cases as `free()`.
+    pub fn report_free(&mut self, addr: u64) -> bool {
+        if !self.sizes.contains_key(&addr) || self.free_list.contains(&addr) {
+            return false;
+        }
+        self.event_handler.fire(AllocEvent::Free { base: addr });
+        true
+    }
+
+    /// Remove and return the first freed allocation which can hold `bytes`
+    /// bytes while maintaining the invariants described on `alloc()`
+    fn take_freed(&mut self, bytes: u64) -> Option<u64> {
+        let cell_bytes: u64 = Memory::CELL_BYTES.into();
+        let capacities = &self.capacities;
+        let index = self.free_list.iter().position(|addr| {
+            let fits = capacities[addr] >= bytes;
+            let cell_ok = if bytes <= cell_bytes {
+                addr % cell_bytes + bytes <= cell_bytes
+            } else {
+                addr % cell_bytes == 0
+            };
+            fits && cell_ok
+        })?;
+        Some(self.free_list.remove(index))
+    }
+
+    /// Revert to `saved`, a copy of this `Alloc` from earlier on the current
+    /// path, except that new allocations still won't overlap with any
+    /// allocations made since then.
+    pub fn revert_to(&mut self, saved: Self) {
+        let cursor = std::cmp::max(self.cursor, saved.cursor);
+        self.restore(saved);
+        self.cursor = cursor;
+    }
+
+    /// Replace this `Alloc` with `saved`, but keeping this one's event
+    /// handler. No events are reported for the allocations this undoes.
+    pub fn restore(&mut self, saved: Self) {
+        let event_handler = std::mem::take(&mut self.event_handler);
+        *self = saved;
+        self.event_handler = event_handler;
+    }
+
     /// Get the size, in bits, of the allocation at the given address, or `None`
     /// if that address is not the result of an `alloc()`.
     pub fn get_allocation_size(&self, addr: impl Into<u64>) -> Option<u64> {
         self.sizes.get(&addr.into()).copied()
     }
+
+    /// Get the total number of bytes handed out so far, including any padding
+    /// between allocations.
+    pub fn bytes_allocated(&self) -> u64 {
+        self.cursor - Self::ALLOC_START
+    }
+
+    /// Get the address just past the highest byte handed out so far. This only
+    /// ever increases, even when freed allocations are reused.
+    pub fn high_water_mark(&self) -> u64 {
+        self.cursor
+    }
+
+    /// Get the allocation containing the given address, or `None` if the
+    /// address isn't inside any allocation.
+    ///
+    /// An allocation of 0 bits is considered to contain only its own address.
+    pub fn provenance(&self, ptr: u64) -> Option<AllocationInfo> {
+        self.sizes
+            .iter()
+            .find(|(&addr, &bits)| {
+                ptr == addr || (ptr > addr && ptr - addr < bytes_rounded_up(bits))
+            })
+            .map(|(&addr, &bits)| AllocationInfo { addr, bits })
+    }
+
+    /// Merge the allocations of `other` into this `Alloc`, so that future
+    /// allocations won't overlap with allocations made by either.
+    pub fn merge(&mut self, other: &Self) {
+        self.cursor = std::cmp::max(self.cursor, other.cursor);
+        for (&addr, &bits) in other.sizes.iter() {
+            self.sizes.entry(addr).or_insert(bits);
+        }
+        for (&addr, &capacity) in other.capacities.iter() {
+            let ours = self.capacities.entry(addr).or_insert(capacity);
+            *ours = std::cmp::max(*ours, capacity);
+        }
+        // only reuse allocations which were freed in both
+        self.free_list.retain(|addr| other.free_list.contains(addr));
+    }
+
+    /// Get the address and size (in bits) of every allocation made so far, in
+    /// the order they were made (which is also increasing address order).
+    pub fn get_all_allocations(&self) -> Vec<(u64, u64)> {
+        let mut allocations: Vec<(u64, u64)> = self
+            .sizes
+            .iter()
+            .map(|(&addr, &bits)| (addr, bits))
+            .collect();
+        allocations.sort_unstable();
+        allocations
+    }
+
+    /// Like `get_all_allocations()`, but omitting allocations which have been
+    /// freed with `free()` (and not since reused).
+    pub fn get_live_allocations(&self) -> Vec<(u64, u64)> {
+        let mut allocations = self.get_all_allocations();
+        allocations.retain(|(addr, _)| !self.free_list.contains(addr));
+        allocations
+    }
+}
+
+/// Number of bytes needed to hold `bits` bits
+pub(crate) fn bytes_rounded_up(bits: u64) -> u64 {
+    let bits_in_byte: u64 = Memory::BITS_IN_BYTE.into();
+    bits.div_ceil(bits_in_byte) // round up to nearest byte
 }
@@ -23,7 +23,7 @@ pub fn malloc<B: Backend>(state: &mut State<B>, num_bytes: &Operand) -> Result<B
             Since this allocation is constant-sized, it's fine in this case, but does draw into question the assumption.", num_bytes, MAX_ALLOCATION_SIZE_BYTES);
     }
     let num_bits = num_bytes * 8;
-    Ok(state.allocate(num_bits))
+    Ok(state.allocate(num_bits).into_bv())
 }
 
 /// Allocate a number of bytes given by the `Operand`.
@@ -38,7 +38,7 @@ pub fn zalloc<B: Backend>(state: &mut State<B>, num_bytes: &Operand) -> Result<B
             Since this allocation is constant-sized, it's fine in this case, but does draw into question the assumption.", num_bytes, MAX_ALLOCATION_SIZE_BYTES);
     }
     let num_bits = num_bytes * 8;
-    let addr = state.allocate(num_bits);
+    let addr = state.allocate(num_bits).into_bv();
     state.write(&addr, state.zero(num_bits as u32))?;
     Ok(addr)
 }
@@ -58,7 +58,7 @@ pub fn calloc<B: Backend>(state: &mut State<B>, a: &Operand, b: &Operand) -> Res
             Since this allocation is constant-sized, it's fine in this case, but does draw into question the assumption.", num_bytes, MAX_ALLOCATION_SIZE_BYTES);
     }
     let num_bits = num_bytes * 8;
-    let addr = state.allocate(num_bits);
+    let addr = state.allocate(num_bits).into_bv();
     state.write(&addr, state.zero(num_bits as u32))?;
     Ok(addr)
 }
@@ -87,12 +87,12 @@ pub fn realloc<B: Backend>(
         Ok(addr)
     } else {
         // Make a new allocation
-        let new_addr = state.allocate(new_size);
+        let new_addr = state.allocate(new_size).into_bv();
         // Copy the contents of the old allocation
         let contents = state.read(&addr, old_size as u32)?;
         state.write(&new_addr, contents)?;
-        // We don't free(), as our allocator won't ever reuse allocated addresses anyway.
-        // So, we can just return
+        // Free the old allocation (which is a no-op unless `Config.reuse_freed_allocations` is set)
+        state.free(&addr)?;
         Ok(new_addr)
     }
 }
@@ -2,6 +2,7 @@
 //! etc) being used.
 
 use crate::error::Result;
+use crate::memory::MemOp;
 use boolector::{BVSolution, Btor};
 use std::fmt;
 use std::ops::Deref;
@@ -49,6 +50,10 @@ pub trait SolverRef: Clone + Deref<Target = Btor> {
     /// It's also fine to call this with an `Array` created for this `SolverRef`
     /// itself, in which case you'll just get back `Some(array.clone())`.
     fn match_array(&self, array: &Self::Array) -> Option<Self::Array>;
+
+    /// Get the bitvector variable with the given symbol in this `SolverRef`,
+    /// if there is one.
+    fn get_bv_by_symbol(&self, symbol: &str) -> Option<Self::BV>;
 }
 
 impl SolverRef for Rc<Btor> {
@@ -82,6 +87,10 @@ impl SolverRef for Rc<Btor> {
     ) -> Option<boolector::Array<Rc<Btor>>> {
         Btor::get_matching_array(self.clone(), array)
     }
+
+    fn get_bv_by_symbol(&self, symbol: &str) -> Option<boolector::BV<Rc<Btor>>> {
+        Btor::get_bv_by_symbol(self.clone(), symbol)
+    }
 }
 
 /// Trait for things which can act like bitvectors.
@@ -115,6 +124,7 @@ pub trait BV: Clone + PartialEq + Eq + fmt::Debug {
     fn is_const(&self) -> bool;
     fn has_same_width(&self, other: &Self) -> bool;
     fn assert(&self) -> Result<()>;
+    fn assume(&self);
     fn is_failed_assumption(&self) -> bool;
     fn _eq(&self, other: &Self) -> Self;
     fn _ne(&self, other: &Self) -> Self;
@@ -304,8 +314,33 @@ pub trait BV: Clone + PartialEq + Eq + fmt::Debug {
     ///
     /// A default implementation is provided in terms of the other trait methods.
     fn ssubs(&self, other: &Self) -> Self {
-        // we just negate `other` and then perform saturating addition (signed)
-        self.sadds(&other.neg())
+        let width = {
+            let width = self.get_width();
+            assert_eq!(width, other.get_width());
+            width
+        };
+        assert!(width > 0);
+
+        // signed saturating subtraction:
+        //   subtracting two values of the same sign can never saturate or overflow
+        //   positive minus negative: if there was overflow, we saturate; return the max positive value
+        //   negative minus positive: if there was overflow, we saturate; return the max negative value
+        // (we can't just negate `other` and use `sadds()`, because negating the max negative value overflows)
+        let result = self.sub(other);
+        let overflow = self.ssubo(other);
+        let max_positive =
+            Self::zero(self.get_solver(), 1).concat(&Self::ones(self.get_solver(), width - 1));
+        let max_negative =
+            Self::one(self.get_solver(), 1).concat(&Self::zero(self.get_solver(), width - 1));
+        let self_negative = self.slice(width - 1, width - 1); // `true` if the sign bit of `self` is set, meaning `self` is negative
+
+        overflow.cond_bv(
+            &self_negative.cond_bv(
+                &max_negative, // overflow, and `self` was negative, so `other` must have been positive, so return the max negative value
+                &max_positive, // overflow, and `self` was positive, so `other` must have been negative, so return the max positive value
+            ),
+            &result, // no overflow: just return the ordinary result
+        )
     }
 }
 
@@ -345,6 +380,32 @@ pub trait Memory: Clone + PartialEq + Eq {
     /// Write any number (>0) of bits of memory, at any alignment.
     fn write(&mut self, index: &Self::Index, value: Self::Value) -> Result<()>;
 
+    /// Like `read()`, but without checking whether the address can be NULL
+    /// (even with `null_detection`), for an address already known to be
+    /// non-NULL
+    fn read_non_null(&self, index: &Self::Index, bits: u32) -> Result<Self::Value>;
+
+    /// Like `write()`, but without checking whether the address can be NULL
+    /// (even with `null_detection`), for an address already known to be
+    /// non-NULL
+    fn write_non_null(&mut self, index: &Self::Index, value: Self::Value) -> Result<()>;
+
+    /// Merge `other` into this `Memory`. Afterwards, this `Memory` should have
+    /// the contents of (the original) `self` at every address where `cond` (a
+    /// `BV` of width 1) is true, and the contents of `other` where `cond` is
+    /// false.
+    ///
+    /// `other` will belong to the same solver instance as this `Memory`.
+    fn merge(&mut self, other: Self, cond: &Self::Index);
+
+    /// Start recording a trace of all reads and writes performed on this
+    /// `Memory`.
+    fn enable_tracing(&mut self);
+
+    /// Get the reads and writes performed on this `Memory` since tracing was
+    /// enabled, in the order they were performed.
+    fn trace(&self) -> Vec<MemOp>;
+
     /// Get a reference to the solver instance this `Memory` belongs to
     fn get_solver(&self) -> Self::SolverRef;
 
@@ -437,6 +498,9 @@ impl BV for boolector::BV<Rc<Btor>> {
         self.assert();
         Ok(())
     }
+    fn assume(&self) {
+        self.assume()
+    }
     fn is_failed_assumption(&self) -> bool {
         self.is_failed_assumption()
     }
@@ -612,6 +676,21 @@ impl Memory for crate::memory::Memory {
     fn write(&mut self, index: &Self::Index, value: Self::Value) -> Result<()> {
         self.write(index, value)
     }
+    fn read_non_null(&self, index: &Self::Index, bits: u32) -> Result<Self::Value> {
+        self.read_non_null(index, bits)
+    }
+    fn write_non_null(&mut self, index: &Self::Index, value: Self::Value) -> Result<()> {
+        self.write_non_null(index, value)
+    }
+    fn merge(&mut self, other: Self, cond: &Self::Index) {
+        self.merge(other, cond)
+    }
+    fn enable_tracing(&mut self) {
+        self.enable_tracing()
+    }
+    fn trace(&self) -> Vec<MemOp> {
+        self.trace()
+    }
     fn get_solver(&self) -> Rc<Btor> {
         self.get_solver()
     }
@@ -637,6 +716,21 @@ impl Memory for crate::simple_memory::Memory {
     fn write(&mut self, index: &Self::Index, value: Self::Value) -> Result<()> {
         self.write(index, value)
     }
+    fn read_non_null(&self, index: &Self::Index, bits: u32) -> Result<Self::Value> {
+        self.read_non_null(index, bits)
+    }
+    fn write_non_null(&mut self, index: &Self::Index, value: Self::Value) -> Result<()> {
+        self.write_non_null(index, value)
+    }
+    fn merge(&mut self, other: Self, cond: &Self::Index) {
+        self.merge(other, cond)
+    }
+    fn enable_tracing(&mut self) {
+        self.enable_tracing()
+    }
+    fn trace(&self) -> Vec<MemOp> {
+        self.trace()
+    }
     fn get_solver(&self) -> Rc<Btor> {
         self.get_solver()
     }
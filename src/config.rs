@@ -4,8 +4,10 @@ use crate::backend::Backend;
 use crate::callbacks::Callbacks;
 pub use crate::demangling::Demangling;
 use crate::function_hooks::FunctionHooks;
+use crate::state::Location;
 use crate::watchpoints::Watchpoint;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::time::Duration;
 
 /// Various settings which affect how the symbolic execution is performed.
@@ -29,6 +31,19 @@ pub struct Config<'p, B: Backend> {
     /// Default is `10`.
     pub loop_bound: usize,
 
+    /// Maximum number of times to take any given loop back edge on a single
+    /// path, for drivers which track loop unrolling with
+    /// [`State.on_loop_back_edge()`](../struct.State.html#method.on_loop_back_edge).
+    ///
+    /// Unlike `loop_bound`, this isn't enforced by symbolic execution itself;
+    /// it's up to the driver to stop unrolling when `on_loop_back_edge()`
+    /// says to.
+    ///
+    /// A value of `None` for this setting indicates no limit.
+    ///
+    /// Default is `None`.
+    pub max_unroll: Option<usize>,
+
     /// Maximum callstack depth to allow when symbolically executing.
     /// If symbolic execution encounters a call which would result in a
     /// stack depth exceeding this number, and the call is not hooked (see
@@ -58,6 +73,37 @@ pub struct Config<'p, B: Backend> {
     /// Default is `None`.
     pub max_callstack_depth: Option<usize>,
 
+    /// Callstack depth beyond which entering a call is an error.
+    ///
+    /// Unlike `max_callstack_depth`, which simply ignores calls that would
+    /// exceed the limit, entering a call which would exceed this limit ends
+    /// the current path with an `Error::CallDepthExceeded`. This is useful
+    /// for catching unbounded recursion.
+    ///
+    /// As with `max_callstack_depth`, this considers the LLVM callstack depth.
+    ///
+    /// A value of `None` for this setting indicates no limit.
+    ///
+    /// Default is `None`.
+    pub callstack_depth_error_limit: Option<usize>,
+
+    /// Maximum number of backtracking points to allow at any one time.
+    ///
+    /// Each time symbolic execution encounters a branch where more than one
+    /// direction is feasible, it saves a backtracking point so that it can
+    /// later come back and explore the other direction(s). On deeply branching
+    /// code, the number of outstanding backtracking points can grow very large.
+    /// If saving another backtracking point would exceed this limit, the
+    /// current path will end with an `Error::BacktrackLimitExceeded`, which
+    /// gives the caller an opportunity to switch strategies (e.g., by adding
+    /// constraints or hooks, or simply by giving up on the function).
+    ///
+    /// A value of `None` for this setting indicates no limit to the number of
+    /// backtracking points.
+    ///
+    /// Default is `None`.
+    pub max_backtrack_depth: Option<usize>,
+
     /// Maximum amount of time to allow for any single solver query.
     ///
     /// If `Some`, any solver query lasting longer than the given limit will
@@ -94,6 +140,42 @@ pub struct Config<'p, B: Backend> {
     /// Default is `None` - that is, no limit.
     pub max_memcpy_length: Option<u64>,
 
+    /// Maximum number of elements to reserve space for when allocating an
+    /// array with a symbolic element count (see
+    /// [`State.allocate_array()`](../struct.State.html#method.allocate_array)).
+    ///
+    /// The element count of such an array will be constrained to be at most
+    /// this value.
+    ///
+    /// Default is 256.
+    pub max_symbolic_array_count: u64,
+
+    /// How to handle `shl`, `lshr`, and `ashr` instructions whose shift amount
+    /// may be greater than or equal to the width of the value being shifted.
+    /// See [`ShiftSemantics`](enum.ShiftSemantics.html).
+    ///
+    /// Default is `ShiftSemantics::Poison`.
+    pub shift_semantics: ShiftSemantics,
+
+    /// Which saved backtracking point to explore next when the current path
+    /// ends. See [`SearchStrategy`](enum.SearchStrategy.html).
+    ///
+    /// This should not be changed once any backtracking points have been
+    /// saved.
+    ///
+    /// Default is `SearchStrategy::DepthFirst`.
+    pub search_strategy: SearchStrategy<'p>,
+
+    /// If `true`, allocations freed with `free()` (or by `realloc()`) may be
+    /// reused by later allocations of the same size or smaller, as a real
+    /// allocator would. This allows modeling bugs involving address reuse,
+    /// such as a use-after-free reading data from a later allocation.
+    ///
+    /// If `false`, `free()` is a no-op, and addresses are never reused.
+    ///
+    /// Default is `false`.
+    pub reuse_freed_allocations: bool,
+
     /// `Error::Unsat` is an error type which is used internally, but may not be
     /// useful for `ExecutionManager.next()` to return to consumers. In most
     /// cases, consumers probably don't care about paths which were partially
@@ -222,6 +304,47 @@ pub enum NullPointerChecking {
     None,
 }
 
+/// Enum used for the `search_strategy` option in `Config`.
+///
+/// The solver's incremental contexts are last-in-first-out, which suits
+/// `DepthFirst`. With any other strategy, each backtracking point instead
+/// keeps a copy of the constraints on its path, and reverting to it
+/// re-asserts all of them in a fresh solver context. This costs more memory
+/// and solver work. Also, only constraints asserted with
+/// [`State.assert()`](../struct.State.html#method.assert) can be re-asserted
+/// this way; constraints asserted directly with `BV::assert()` after the first
+/// backtracking point was saved are lost when reverting.
+#[allow(clippy::type_complexity)]
+#[derive(Clone)]
+pub enum SearchStrategy<'p> {
+    /// Always explore the most recently saved backtracking point next
+    DepthFirst,
+    /// Always explore the least recently saved backtracking point next
+    BreadthFirst,
+    /// Given the location where each saved backtracking point would resume
+    /// execution (in the order the points were saved), return the index of
+    /// the one to explore next
+    Custom(Rc<dyn Fn(&[Location<'p>]) -> usize + 'p>),
+}
+
+/// Enum used for the `shift_semantics` option in `Config`.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ShiftSemantics {
+    /// Follow LLVM semantics, in which shifting by an amount greater than or
+    /// equal to the width of the value produces poison.
+    ///
+    /// When executing shift instructions, `haybale` doesn't track the poison;
+    /// such shifts produce whatever value the solver's shift operation gives
+    /// (zero, or all sign bits for `ashr`). The shift helpers on `State`, such
+    /// as [`State.shl()`](../struct.State.html#method.shl), return a
+    /// condition indicating when the result is poison.
+    Poison,
+
+    /// Reduce the shift amount modulo the width of the value being shifted
+    /// before shifting, as many hardware shift instructions do.
+    ModularReduce,
+}
+
 /// Enum used for the `concretize_memcpy_lengths` option in `Config`.
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub enum Concretize {
@@ -287,11 +410,18 @@ impl<'p, B: Backend> Default for Config<'p, B> {
     fn default() -> Self {
         Self {
             loop_bound: 10,
+            max_unroll: None,
             max_callstack_depth: None,
+            callstack_depth_error_limit: None,
+            max_backtrack_depth: None,
             solver_query_timeout: Some(Duration::from_secs(300)),
             null_pointer_checking: NullPointerChecking::Simple,
             concretize_memcpy_lengths: Concretize::Symbolic,
             max_memcpy_length: None,
+            max_symbolic_array_count: 256,
+            shift_semantics: ShiftSemantics::Poison,
+            search_strategy: SearchStrategy::DepthFirst,
+            reuse_freed_allocations: false,
             squash_unsats: true,
             trust_llvm_assumes: true,
             function_hooks: FunctionHooks::default(),
@@ -13,9 +13,23 @@ pub enum Error {
     /// This error type is used internally, but (by default) isn't exposed to consumers of `ExecutionManager`;
     /// see [`Config.squash_unsats`](config/struct.Config.html#structfield.squash_unsats).
     Unsat,
+    /// A constraint passed to
+    /// [`State.assert_checked()`](struct.State.html#method.assert_checked)
+    /// contradicts the existing constraints on the current path. Unlike
+    /// `Unsat`, the path itself is still satisfiable; the constraint was not
+    /// added.
+    Infeasible,
     /// The current path has exceeded the configured `loop_bound` (see [`Config`](config/struct.Config.html)).
     /// (The `usize` here indicates the value of the configured `loop_bound`.)
     LoopBoundExceeded(usize),
+    /// Saving another backtracking point would exceed the configured
+    /// `max_backtrack_depth` (see [`Config`](config/struct.Config.html)).
+    /// (The `usize` here indicates the value of the configured `max_backtrack_depth`.)
+    BacktrackLimitExceeded(usize),
+    /// Entering another call would exceed the configured `callstack_depth_error_limit`
+    /// (see [`Config`](config/struct.Config.html)).
+    /// (The `usize` here indicates the value of the configured `callstack_depth_error_limit`.)
+    CallDepthExceeded(usize),
     /// The current path has attempted to dereference a null pointer (or
     /// more precisely, a pointer for which `NULL` is a possible value)
     NullPointerDereference,
@@ -30,6 +44,10 @@ pub enum Error {
     MalformedInstruction(String),
     /// Reached an LLVM `Unreachable` instruction
     UnreachableInstruction,
+    /// The current path has read from memory at the given (concrete) address,
+    /// none of whose bytes have been written. This is only reported if
+    /// requested with [`State.set_uninit_handler()`](struct.State.html#method.set_uninit_handler).
+    UninitializedRead(u64),
     /// Failed to interpret some symbolic value (`BV`) as a function pointer,
     /// because it has a possible solution (the `u64` here) which points to
     /// something that's not a function
@@ -48,8 +66,14 @@ impl fmt::Display for Error {
         match self {
             Error::Unsat =>
                 write!(f, "`Unsat`: the current state or path is unsat"),
+            Error::Infeasible =>
+                write!(f, "`Infeasible`: the constraint contradicts the existing constraints on the current path, so it was not added"),
             Error::LoopBoundExceeded(bound) =>
                 write!(f, "`LoopBoundExceeded`: the current path has exceeded the configured `loop_bound`, which was {}", bound),
+            Error::BacktrackLimitExceeded(limit) =>
+                write!(f, "`BacktrackLimitExceeded`: saving another backtracking point would exceed the configured `max_backtrack_depth`, which was {}", limit),
+            Error::CallDepthExceeded(limit) =>
+                write!(f, "`CallDepthExceeded`: entering another call would exceed the configured `callstack_depth_error_limit`, which was {}", limit),
             Error::NullPointerDereference =>
                 write!(f, "`NullPointerDereference`: the current path has attempted to dereference a null pointer"),
             Error::FunctionNotFound(funcname) =>
@@ -62,6 +86,8 @@ impl fmt::Display for Error {
                 write!(f, "`MalformedInstruction`: encountered an LLVM instruction which was malformed, or at least didn't conform to our expected invariants: {}", details),
             Error::UnreachableInstruction =>
                 write!(f, "`UnreachableInstruction`: Reached an LLVM 'Unreachable' instruction"),
+            Error::UninitializedRead(addr) =>
+                write!(f, "`UninitializedRead`: the current path has read from memory at address {:#x}, which has never been written", addr),
             Error::FailedToResolveFunctionPointer(solution) =>
                 write!(f, "`FailedToResolveFunctionPointer`: Can't resolve a symbolically-valued function pointer, because one possible solution for it ({:#x}) points to something that's not a function", solution),
             Error::HookReturnValueMismatch(details) =>
@@ -156,6 +156,11 @@ impl<'p, B: Backend + 'p> FunctionHooks<'p, B> {
 
     /// Adds a function hook. The `hook` will be executed instead of the body of
     /// the `hooked_function`.
+    ///
+    /// A hook which constrains the path should do so with
+    /// [`State::assert()`](struct.State.html#method.assert) rather than
+    /// `BV::assert()`, so that the constraints survive `State::merge()` and
+    /// `State::end_scope()`.
     pub fn add<H>(&mut self, hooked_function: impl Into<String>, hook: &'p H)
     where
         H: Fn(&'p Project, &mut State<'p, B>, &'p dyn IsCall) -> Result<ReturnValue<B::BV>>,
@@ -165,6 +170,25 @@ impl<'p, B: Backend + 'p> FunctionHooks<'p, B> {
         self.cur_id += 1;
     }
 
+    /// Like `add()`, but for a hook which returns a
+    /// [`HookResult`](struct.HookResult.html): a return value along with
+    /// constraints on it. After the hook runs, the constraints are asserted,
+    /// and the value is used as the return value of the call.
+    ///
+    /// This is useful for modeling functions whose return values aren't fully
+    /// determined, for instance a `read()` which returns at most the number
+    /// of bytes requested.
+    pub fn add_constrained<H>(&mut self, hooked_function: impl Into<String>, hook: &'p H)
+    where
+        H: Fn(&'p Project, &mut State<'p, B>, &'p dyn IsCall) -> Result<HookResult<B::BV>>,
+    {
+        self.hooks.insert(
+            hooked_function.into(),
+            FunctionHook::new_constrained(self.cur_id, hook),
+        );
+        self.cur_id += 1;
+    }
+
     /// Exactly like `add()`, but takes the (C++) _demangled_ name of the function
     /// to hook, so you can use a function name like "namespace::function".
     pub fn add_cpp_demangled<H>(&mut self, hooked_function: impl Into<String>, hook: &'p H)
@@ -437,6 +461,25 @@ impl<'p, B: Backend> FunctionHook<'p, B> {
         }
     }
 
+    /// Create a `FunctionHook` from a hook which returns a `HookResult`; see
+    /// `FunctionHooks::add_constrained()`
+    #[allow(clippy::type_complexity)]
+    fn new_constrained(
+        id: usize,
+        f: &'p dyn Fn(&'p Project, &mut State<'p, B>, &'p dyn IsCall) -> Result<HookResult<B::BV>>,
+    ) -> Self {
+        Self {
+            hook: Rc::new(move |proj, state, call| {
+                let HookResult { value, constraints } = f(proj, state, call)?;
+                for constraint in &constraints {
+                    state.assert(constraint)?;
+                }
+                Ok(ReturnValue::Return(value))
+            }),
+            id,
+        }
+    }
+
     pub fn call_hook(
         &self,
         proj: &'p Project,
@@ -447,6 +490,18 @@ impl<'p, B: Backend> FunctionHook<'p, B> {
     }
 }
 
+/// The result of a hook added with
+/// [`FunctionHooks::add_constrained()`](struct.FunctionHooks.html#method.add_constrained):
+/// the value returned by the hooked call, along with constraints on it.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct HookResult<V> {
+    /// The value returned by the hooked call. Typically this is a fresh
+    /// symbolic value, which the `constraints` restrict.
+    pub value: V,
+    /// Constraints (each a `BV` of width 1) to assert after the hook runs
+    pub constraints: Vec<V>,
+}
+
 /// This hook ignores the function arguments and returns an unconstrained value
 /// of the appropriate size for the function's return value (or void for
 /// void-typed functions).
@@ -1,4 +1,4 @@
-use crate::backend::{Backend, SolverRef};
+use crate::backend::{Backend, SolverRef, BV};
 use crate::function_hooks::FunctionHook;
 use llvm_ir::module::{GlobalVariable, Linkage};
 use llvm_ir::*;
@@ -387,6 +387,43 @@ impl<'p, B: Backend> GlobalAllocations<'p, B> {
             })
     }
 
+    /// Get the addresses of all the global variables which have been initialized
+    pub fn initialized_global_var_addrs(&self) -> Vec<u64> {
+        self.all_global_allocations()
+            .filter_map(|ga| match ga {
+                GlobalAllocation::GlobalVariable {
+                    addr, initialized, ..
+                } if initialized.get() => addr.as_u64(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Consider the global variable allocated at the given address (if any) to
+    /// have been initialized already
+    pub fn mark_initialized(&self, addr: u64) {
+        for ga in self.all_global_allocations() {
+            if let GlobalAllocation::GlobalVariable {
+                addr: ga_addr,
+                initialized,
+                ..
+            } = ga
+            {
+                if ga_addr.as_u64() == Some(addr) {
+                    initialized.set(true);
+                }
+            }
+        }
+    }
+
+    fn all_global_allocations(&self) -> impl Iterator<Item = &GlobalAllocation<'p, B::BV>> {
+        self.allocated_globals.values().map(Definition::get).chain(
+            self.module_private_allocated_globals
+                .values()
+                .flat_map(|hm| hm.values()),
+        )
+    }
+
     /// Get the address at which the given `FunctionHook` has been allocated; or
     /// `None` if not found.
     pub fn get_function_hook_address(&self, hook: &FunctionHook<'p, B>) -> Option<&B::BV> {
@@ -407,6 +444,39 @@ impl<'p, B: Backend> GlobalAllocations<'p, B> {
         })
     }
 
+    /// Move the `GlobalAllocations` to a completely new solver instance,
+    /// keeping the same addresses, and consider all global variables
+    /// not-yet-initialized again.
+    ///
+    /// Unlike with `change_solver()`, the new solver instance need not have
+    /// any relation to the old one.
+    pub fn reset(&mut self, new_solver: B::SolverRef) {
+        let reset_allocation = |ga: &mut GlobalAllocation<'p, B::BV>| {
+            let addr = ga
+                .get_addr()
+                .as_u64()
+                .expect("Global allocations should have constant addresses");
+            ga.set_addr(B::BV::from_u64(new_solver.clone(), addr, 64));
+            if let GlobalAllocation::GlobalVariable { initialized, .. } = ga {
+                initialized.set(false);
+            }
+        };
+        for def in self.allocated_globals.values_mut() {
+            reset_allocation(def.get_mut());
+        }
+        for hm in self.module_private_allocated_globals.values_mut() {
+            for ga in hm.values_mut() {
+                reset_allocation(ga);
+            }
+        }
+        for bv in self.allocated_hooks.values_mut() {
+            let addr = bv
+                .as_u64()
+                .expect("Function hook allocations should have constant addresses");
+            *bv = B::BV::from_u64(new_solver.clone(), addr, 64);
+        }
+    }
+
     /// Adapt the `GlobalAllocations` to a new solver instance.
     ///
     /// The new solver instance should have been created (possibly transitively)
@@ -160,15 +160,19 @@ pub fn memcpy_bv<B: Backend>(
                     max_num_bytes
                 );
             }
+            // read all of the source bytes before writing any, in case `src` and `dest` overlap
             let mut src_addr = src.clone();
+            let mut src_vals = Vec::new();
+            for _ in 0 ..= max_num_bytes {
+                src_vals.push(state.read(&src_addr, 8)?);
+                src_addr = src_addr.inc();
+            }
             let mut dest_addr = dest.clone();
             let mut bytes_written = state.zero(num_bytes.get_width());
-            for _ in 0 ..= max_num_bytes {
-                let src_val = state.read(&src_addr, 8)?;
+            for src_val in src_vals {
                 let dst_val = state.read(&dest_addr, 8)?;
                 let should_write = num_bytes.ugt(&bytes_written);
                 state.write(&dest_addr, should_write.cond_bv(&src_val, &dst_val))?;
-                src_addr = src_addr.inc();
                 dest_addr = dest_addr.inc();
                 bytes_written = bytes_written.inc();
             }
@@ -219,7 +223,7 @@ fn get_memcpy_length<B: Backend>(
                     &num_bytes.ugt(&max_memcpy_length_bv),
                 ))? {
                     warn!("Encountered a memcpy/memset/memmove with multiple possible lengths, some of which are larger than max_memcpy_length {} bytes. Constraining the length to be at most {} bytes.", max_memcpy_length, max_memcpy_length);
-                    num_bytes.ulte(&max_memcpy_length_bv).assert()?;
+                    state.assert(&num_bytes.ulte(&max_memcpy_length_bv))?;
                 }
             }
             let num_bytes_concrete = match concretize {
@@ -260,9 +264,9 @@ fn get_memcpy_length<B: Backend>(
             };
             info!("Encountered a memcpy/memset/memmove with multiple possible lengths; according to the concretization policy {:?}, chose a length of {} bytes and will constrain the length argument to be {} going forward", concretize, num_bytes_concrete, num_bytes_concrete);
             // actually constrain that `num_bytes` has to now be equal to our chosen concrete value
-            num_bytes
-                ._eq(&state.bv_from_u64(num_bytes_concrete, num_bytes.get_width()))
-                .assert()?;
+            state.assert(
+                &num_bytes._eq(&state.bv_from_u64(num_bytes_concrete, num_bytes.get_width())),
+            )?;
             Ok(MemcpyLength::Concrete(num_bytes_concrete))
         },
     }
@@ -81,11 +81,14 @@ pub fn calloc_hook<'p, B: Backend + 'p>(
 
 pub fn free_hook<'p, B: Backend + 'p>(
     _proj: &'p Project,
-    _state: &mut State<'p, B>,
-    _call: &'p dyn IsCall,
+    state: &mut State<'p, B>,
+    call: &'p dyn IsCall,
 ) -> Result<ReturnValue<B::BV>> {
-    // The simplest implementation of free() is a no-op.
-    // Our allocator won't ever reuse allocated addresses anyway.
+    assert_eq!(call.get_arguments().len(), 1);
+    // Unless `Config.reuse_freed_allocations` is set, this is a no-op, and
+    // our allocator won't ever reuse allocated addresses
+    let addr = state.operand_to_bv(&call.get_arguments()[0].0)?;
+    state.free(&addr)?;
     Ok(ReturnValue::ReturnVoid)
 }
 
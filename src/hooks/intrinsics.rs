@@ -7,7 +7,7 @@ use crate::hook_utils;
 use crate::layout;
 use crate::project::Project;
 use crate::return_value::ReturnValue;
-use crate::state::State;
+use crate::state::{BinOp, State};
 use llvm_ir::{Type, Typed};
 
 pub fn symex_memset<'p, B: Backend>(
@@ -75,59 +75,9 @@ pub fn symex_bswap<'p, B: Backend>(
 
     let arg = state.operand_to_bv(arg)?;
     match argty {
-        Type::IntegerType { bits: 16 } => {
-            assert_eq!(arg.get_width(), 16);
-            let high_byte = arg.slice(15, 8);
-            let low_byte = arg.slice(7, 0);
-            Ok(ReturnValue::Return(low_byte.concat(&high_byte)))
-        },
-        Type::IntegerType { bits: 32 } => {
-            assert_eq!(arg.get_width(), 32);
-            let byte_0 = arg.slice(7, 0);
-            let byte_1 = arg.slice(15, 8);
-            let byte_2 = arg.slice(23, 16);
-            let byte_3 = arg.slice(31, 24);
-            Ok(ReturnValue::Return(
-                byte_0.concat(&byte_1).concat(&byte_2).concat(&byte_3),
-            ))
-        },
-        Type::IntegerType { bits: 48 } => {
-            assert_eq!(arg.get_width(), 48);
-            let byte_0 = arg.slice(7, 0);
-            let byte_1 = arg.slice(15, 8);
-            let byte_2 = arg.slice(23, 16);
-            let byte_3 = arg.slice(31, 24);
-            let byte_4 = arg.slice(39, 32);
-            let byte_5 = arg.slice(47, 40);
-            Ok(ReturnValue::Return(
-                byte_0
-                    .concat(&byte_1)
-                    .concat(&byte_2)
-                    .concat(&byte_3)
-                    .concat(&byte_4)
-                    .concat(&byte_5),
-            ))
-        },
-        Type::IntegerType { bits: 64 } => {
-            assert_eq!(arg.get_width(), 64);
-            let byte_0 = arg.slice(7, 0);
-            let byte_1 = arg.slice(15, 8);
-            let byte_2 = arg.slice(23, 16);
-            let byte_3 = arg.slice(31, 24);
-            let byte_4 = arg.slice(39, 32);
-            let byte_5 = arg.slice(47, 40);
-            let byte_6 = arg.slice(55, 48);
-            let byte_7 = arg.slice(63, 56);
-            Ok(ReturnValue::Return(
-                byte_0
-                    .concat(&byte_1)
-                    .concat(&byte_2)
-                    .concat(&byte_3)
-                    .concat(&byte_4)
-                    .concat(&byte_5)
-                    .concat(&byte_6)
-                    .concat(&byte_7),
-            ))
+        Type::IntegerType { bits } if bits % 16 == 0 => {
+            assert_eq!(arg.get_width(), bits);
+            Ok(ReturnValue::Return(state.bswap(&arg)?))
         },
         _ => Err(Error::UnsupportedInstruction(format!(
             "llvm.bswap with argument type {:?}",
@@ -169,7 +119,7 @@ pub fn symex_assume<'p, B: Backend>(
     }
 
     if state.config.trust_llvm_assumes {
-        state.operand_to_bv(arg)?.assert()?;
+        state.assert(&state.operand_to_bv(arg)?)?;
     } else {
         // just ignore the assume
     }
@@ -177,6 +127,23 @@ pub fn symex_assume<'p, B: Backend>(
     Ok(ReturnValue::ReturnVoid)
 }
 
+pub fn symex_expect<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &'p dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    // `llvm.expect` takes 2 arguments, `llvm.expect.with.probability` takes 3
+    if call.get_arguments().len() < 2 {
+        return Err(Error::OtherError(format!(
+            "symex_expect: expected at least 2 arguments, got {}",
+            call.get_arguments().len()
+        )));
+    }
+    // the hint doesn't affect the semantics: just return the value
+    let val = &call.get_arguments()[0].0;
+    Ok(ReturnValue::Return(state.operand_to_bv(val)?))
+}
+
 pub fn symex_uadd_with_overflow<'p, B: Backend>(
     _proj: &'p Project,
     state: &mut State<'p, B>,
@@ -191,11 +158,12 @@ pub fn symex_uadd_with_overflow<'p, B: Backend>(
 
     let arg0 = state.operand_to_bv(arg0)?;
     let arg1 = state.operand_to_bv(arg1)?;
-    let result = arg0.add(&arg1);
-    let overflow = arg0.uaddo(&arg1);
-    assert_eq!(overflow.get_width(), 1);
-
-    Ok(ReturnValue::Return(overflow.concat(&result)))
+    Ok(ReturnValue::Return(state.binop_with_overflow(
+        BinOp::Add,
+        &arg0,
+        &arg1,
+        false,
+    )?))
 }
 
 pub fn symex_sadd_with_overflow<'p, B: Backend>(
@@ -212,11 +180,12 @@ pub fn symex_sadd_with_overflow<'p, B: Backend>(
 
     let arg0 = state.operand_to_bv(arg0)?;
     let arg1 = state.operand_to_bv(arg1)?;
-    let result = arg0.add(&arg1);
-    let overflow = arg0.saddo(&arg1);
-    assert_eq!(overflow.get_width(), 1);
-
-    Ok(ReturnValue::Return(overflow.concat(&result)))
+    Ok(ReturnValue::Return(state.binop_with_overflow(
+        BinOp::Add,
+        &arg0,
+        &arg1,
+        true,
+    )?))
 }
 
 pub fn symex_usub_with_overflow<'p, B: Backend>(
@@ -233,11 +202,12 @@ pub fn symex_usub_with_overflow<'p, B: Backend>(
 
     let arg0 = state.operand_to_bv(arg0)?;
     let arg1 = state.operand_to_bv(arg1)?;
-    let result = arg0.sub(&arg1);
-    let overflow = arg0.usubo(&arg1);
-    assert_eq!(overflow.get_width(), 1);
-
-    Ok(ReturnValue::Return(overflow.concat(&result)))
+    Ok(ReturnValue::Return(state.binop_with_overflow(
+        BinOp::Sub,
+        &arg0,
+        &arg1,
+        false,
+    )?))
 }
 
 pub fn symex_ssub_with_overflow<'p, B: Backend>(
@@ -254,11 +224,12 @@ pub fn symex_ssub_with_overflow<'p, B: Backend>(
 
     let arg0 = state.operand_to_bv(arg0)?;
     let arg1 = state.operand_to_bv(arg1)?;
-    let result = arg0.sub(&arg1);
-    let overflow = arg0.ssubo(&arg1);
-    assert_eq!(overflow.get_width(), 1);
-
-    Ok(ReturnValue::Return(overflow.concat(&result)))
+    Ok(ReturnValue::Return(state.binop_with_overflow(
+        BinOp::Sub,
+        &arg0,
+        &arg1,
+        true,
+    )?))
 }
 
 pub fn symex_umul_with_overflow<'p, B: Backend>(
@@ -275,11 +246,12 @@ pub fn symex_umul_with_overflow<'p, B: Backend>(
 
     let arg0 = state.operand_to_bv(arg0)?;
     let arg1 = state.operand_to_bv(arg1)?;
-    let result = arg0.mul(&arg1);
-    let overflow = arg0.umulo(&arg1);
-    assert_eq!(overflow.get_width(), 1);
-
-    Ok(ReturnValue::Return(overflow.concat(&result)))
+    Ok(ReturnValue::Return(state.binop_with_overflow(
+        BinOp::Mul,
+        &arg0,
+        &arg1,
+        false,
+    )?))
 }
 
 pub fn symex_smul_with_overflow<'p, B: Backend>(
@@ -296,11 +268,12 @@ pub fn symex_smul_with_overflow<'p, B: Backend>(
 
     let arg0 = state.operand_to_bv(arg0)?;
     let arg1 = state.operand_to_bv(arg1)?;
-    let result = arg0.mul(&arg1);
-    let overflow = arg0.smulo(&arg1);
-    assert_eq!(overflow.get_width(), 1);
-
-    Ok(ReturnValue::Return(overflow.concat(&result)))
+    Ok(ReturnValue::Return(state.binop_with_overflow(
+        BinOp::Mul,
+        &arg0,
+        &arg1,
+        true,
+    )?))
 }
 
 pub fn symex_uadd_sat<'p, B: Backend>(
@@ -375,6 +348,202 @@ pub fn symex_ssub_sat<'p, B: Backend>(
     Ok(ReturnValue::Return(arg0.ssubs(&arg1)))
 }
 
+pub fn symex_abs<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    assert_eq!(call.get_arguments().len(), 2);
+    let arg0 = &call.get_arguments()[0].0;
+    let arg1 = &call.get_arguments()[1].0;
+    if let Type::VectorType { .. } = arg0.get_type() {
+        return Err(Error::UnsupportedInstruction(format!(
+            "llvm.abs with argument type {:?}",
+            arg0.get_type()
+        )));
+    }
+    let is_int_min_poison = state.operand_to_bv(arg1)?.as_bool().ok_or_else(|| {
+        Error::MalformedInstruction(
+            "symex_abs: expected the second argument to be a constant".to_owned(),
+        )
+    })?;
+
+    let arg0 = state.operand_to_bv(arg0)?;
+    // as with shift instructions, we don't track the poison condition
+    let (result, _poison) = state.abs(&arg0, is_int_min_poison);
+    Ok(ReturnValue::Return(result))
+}
+
+pub fn symex_smax<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    let (arg0, arg1) = min_max_args("symex_smax", state, call)?;
+    Ok(ReturnValue::Return(state.smax(&arg0, &arg1)?))
+}
+
+pub fn symex_smin<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    let (arg0, arg1) = min_max_args("symex_smin", state, call)?;
+    Ok(ReturnValue::Return(state.smin(&arg0, &arg1)?))
+}
+
+pub fn symex_umax<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    let (arg0, arg1) = min_max_args("symex_umax", state, call)?;
+    Ok(ReturnValue::Return(state.umax(&arg0, &arg1)?))
+}
+
+pub fn symex_umin<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    let (arg0, arg1) = min_max_args("symex_umin", state, call)?;
+    Ok(ReturnValue::Return(state.umin(&arg0, &arg1)?))
+}
+
+/// Get the two arguments of a call to `llvm.smax`, `llvm.smin`, `llvm.umax`,
+/// or `llvm.umin`, checking that they are of the same (non-vector) type
+fn min_max_args<'p, B: Backend>(
+    hookname: &str,
+    state: &State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<(B::BV, B::BV)> {
+    assert_eq!(call.get_arguments().len(), 2);
+    let arg0 = &call.get_arguments()[0].0;
+    let arg1 = &call.get_arguments()[1].0;
+    if arg0.get_type() != arg1.get_type() {
+        return Err(Error::OtherError(format!(
+            "{}: expected arguments to be of the same type, but got types {:?} and {:?}",
+            hookname,
+            arg0.get_type(),
+            arg1.get_type()
+        )));
+    }
+    if let Type::VectorType { .. } = arg0.get_type() {
+        return Err(Error::UnsupportedInstruction(format!(
+            "{} with argument type {:?}",
+            hookname,
+            arg0.get_type()
+        )));
+    }
+
+    Ok((state.operand_to_bv(arg0)?, state.operand_to_bv(arg1)?))
+}
+
+pub fn symex_umul_fix<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    let (arg0, arg1, scale) = mul_fix_args("symex_umul_fix", state, call)?;
+    Ok(ReturnValue::Return(state.umul_fix(&arg0, &arg1, scale)?))
+}
+
+pub fn symex_smul_fix<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    let (arg0, arg1, scale) = mul_fix_args("symex_smul_fix", state, call)?;
+    Ok(ReturnValue::Return(state.smul_fix(&arg0, &arg1, scale)?))
+}
+
+/// Get the arguments of a call to `llvm.umul.fix` or `llvm.smul.fix`: two
+/// operands of the same (non-vector) type, and a constant scale
+fn mul_fix_args<'p, B: Backend>(
+    hookname: &str,
+    state: &State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<(B::BV, B::BV, u32)> {
+    assert_eq!(call.get_arguments().len(), 3);
+    let (arg0, arg1) = (&call.get_arguments()[0].0, &call.get_arguments()[1].0);
+    if arg0.get_type() != arg1.get_type() {
+        return Err(Error::OtherError(format!(
+            "{}: expected arguments to be of the same type, but got types {:?} and {:?}",
+            hookname,
+            arg0.get_type(),
+            arg1.get_type()
+        )));
+    }
+    if let Type::VectorType { .. } = arg0.get_type() {
+        return Err(Error::UnsupportedInstruction(format!(
+            "{} with argument type {:?}",
+            hookname,
+            arg0.get_type()
+        )));
+    }
+    let scale = state
+        .operand_to_bv(&call.get_arguments()[2].0)?
+        .as_u64()
+        .ok_or_else(|| {
+            Error::MalformedInstruction(format!(
+                "{}: expected the scale to be a constant",
+                hookname
+            ))
+        })?;
+
+    Ok((
+        state.operand_to_bv(arg0)?,
+        state.operand_to_bv(arg1)?,
+        scale as u32,
+    ))
+}
+
+pub fn symex_fshl<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    let (hi, lo, shift) = funnel_shift_args("symex_fshl", state, call)?;
+    Ok(ReturnValue::Return(state.fshl(&hi, &lo, &shift)?))
+}
+
+pub fn symex_fshr<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<ReturnValue<B::BV>> {
+    let (hi, lo, shift) = funnel_shift_args("symex_fshr", state, call)?;
+    Ok(ReturnValue::Return(state.fshr(&hi, &lo, &shift)?))
+}
+
+/// Get the three arguments of a call to `llvm.fshl` or `llvm.fshr`, checking
+/// that they are all of the same type
+fn funnel_shift_args<'p, B: Backend>(
+    hookname: &str,
+    state: &State<'p, B>,
+    call: &dyn IsCall,
+) -> Result<(B::BV, B::BV, B::BV)> {
+    assert_eq!(call.get_arguments().len(), 3);
+    let arg0 = &call.get_arguments()[0].0;
+    let arg1 = &call.get_arguments()[1].0;
+    let arg2 = &call.get_arguments()[2].0;
+    if arg0.get_type() != arg1.get_type() || arg0.get_type() != arg2.get_type() {
+        return Err(Error::OtherError(format!(
+            "{}: expected arguments to be of the same type, but got types {:?}, {:?}, and {:?}",
+            hookname,
+            arg0.get_type(),
+            arg1.get_type(),
+            arg2.get_type()
+        )));
+    }
+
+    Ok((
+        state.operand_to_bv(arg0)?,
+        state.operand_to_bv(arg1)?,
+        state.operand_to_bv(arg2)?,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -38,6 +38,29 @@ pub fn size(ty: &Type) -> usize {
     }
 }
 
+/// Get the size of the `Type`, in bits.
+///
+/// Like `size`, but returns `None` rather than panicking for opaque struct
+/// types and for types (such as labels or metadata) which have no size.
+pub fn try_size(ty: &Type) -> Option<usize> {
+    match ty {
+        Type::IntegerType { bits } => Some(*bits as usize),
+        Type::PointerType { .. } => Some(POINTER_SIZE_BITS),
+        Type::ArrayType {
+            element_type,
+            num_elements,
+        } => try_size(element_type).map(|s| s * num_elements),
+        Type::VectorType {
+            element_type,
+            num_elements,
+        } => try_size(element_type).map(|s| s * num_elements),
+        Type::StructType { element_types, .. } => element_types.iter().map(try_size).sum(),
+        Type::NamedStructType { ty, .. } => try_size(&ty.as_ref()?.upgrade()?.read().unwrap()),
+        Type::FPType(fpt) => Some(fp_size(*fpt)),
+        _ => None,
+    }
+}
+
 /// Get the size of the `Type`, in bits.
 ///
 /// Differs from the basic `size` method above in how it handles opaque struct
@@ -23,7 +23,14 @@ pub use error::*;
 mod return_value;
 pub use return_value::ReturnValue;
 
+mod testcase;
+pub use testcase::{TestCase, TestCaseAllocation, TestCaseInput};
+
+mod suspend;
+pub use suspend::SuspendedState;
+
 mod alloc;
+pub use alloc::{AllocEvent, AllocationInfo};
 pub mod alloc_utils;
 pub mod backend;
 pub mod callbacks;
@@ -35,7 +42,10 @@ pub mod hook_utils;
 mod hooks;
 pub mod layout;
 pub mod memory;
+mod pointer;
+pub use pointer::Pointer;
 pub mod simple_memory;
+mod smtlib;
 pub mod solver_utils;
 mod state;
 mod varmap;
@@ -123,7 +133,8 @@ pub fn find_zero_of_func<'p>(
         .unwrap_or_else(|| panic!("Failed to find function named {:?}", funcname));
     for (param, bv) in func.parameters.iter().zip(em.param_bvs()) {
         if let Type::PointerType { .. } = param.get_type() {
-            bv._ne(&em.state().zero(bv.get_width())).assert();
+            em.state()
+                .assert(&bv._ne(&em.state().zero(bv.get_width())))?;
         }
     }
 
@@ -137,7 +148,7 @@ pub fn find_zero_of_func<'p>(
             Ok(ReturnValue::Abort) => continue,
             Ok(ReturnValue::Return(bvretval)) => {
                 let state = em.mut_state();
-                bvretval._eq(&zero).assert();
+                state.assert(&bvretval._eq(&zero))?;
                 if state.sat()? {
                     found = true;
                     break;
@@ -251,9 +262,11 @@ pub fn get_possible_return_values_of_func<'p>(
                 // rule out all the returned values we already have - we're interested in new values
                 for candidate in candidate_values.iter() {
                     if let ReturnValue::Return(candidate) = candidate {
-                        bvretval
-                            ._ne(&state.bv_from_u64(*candidate, return_width as u32))
-                            .assert();
+                        state
+                            .assert(
+                                &bvretval._ne(&state.bv_from_u64(*candidate, return_width as u32)),
+                            )
+                            .unwrap();
                     }
                 }
                 match state.get_possible_solutions_for_bv(&bvretval, n).unwrap() {
@@ -292,9 +305,13 @@ pub fn get_possible_return_values_of_func<'p>(
                         // rule out all the thrown values we already have - we're interested in new values
                         for candidate in candidate_values.iter() {
                             if let ReturnValue::Throw(candidate) = candidate {
-                                thrown_value
-                                    ._ne(&state.bv_from_u64(*candidate, return_width as u32))
-                                    .assert();
+                                state
+                                    .assert(
+                                        &thrown_value._ne(
+                                            &state.bv_from_u64(*candidate, return_width as u32),
+                                        ),
+                                    )
+                                    .unwrap();
                             }
                         }
                         match state
@@ -8,12 +8,42 @@ use crate::solver_utils::bvs_can_be_equal;
 use boolector::Btor;
 use log::debug;
 use reduce::Reduce;
+use std::cell::RefCell;
 use std::convert::TryInto;
 use std::rc::Rc;
 
 type BV = boolector::BV<Rc<Btor>>;
 type Array = boolector::Array<Rc<Btor>>;
 
+/// A single read or write recorded in a `Memory`'s trace. See
+/// [`Memory::enable_tracing()`](struct.Memory.html#method.enable_tracing).
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct MemOp {
+    /// Whether this was a read or a write
+    pub kind: MemOpKind,
+    /// The address accessed: a hex string if the address was constant, or a
+    /// description of the symbolic expression otherwise
+    pub addr: String,
+    /// The number of bits read or written
+    pub bits: u32,
+}
+
+impl MemOp {
+    pub(crate) fn new(kind: MemOpKind, addr: &BV, bits: u32) -> Self {
+        let addr = match addr.as_u64() {
+            Some(addr) => format!("{:#x}", addr),
+            None => format!("{:?}", addr),
+        };
+        Self { kind, addr, bits }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MemOpKind {
+    Read,
+    Write,
+}
+
 #[derive(Clone, Debug)]
 pub struct Memory {
     btor: Rc<Btor>,
@@ -23,6 +53,9 @@ pub struct Memory {
     cell_bytes_as_bv: BV,
     log_bits_in_byte_as_bv: BV,
     log_bits_in_byte_as_wide_bv: BV,
+    /// Trace of memory operations, or `None` if tracing is not enabled.
+    /// See `enable_tracing()`.
+    trace: RefCell<Option<Vec<MemOp>>>,
 }
 
 impl Memory {
@@ -68,6 +101,7 @@ impl Memory {
                 u64::from(Self::LOG_BITS_IN_BYTE),
                 2 * Self::CELL_BITS,
             ),
+            trace: RefCell::new(None),
             btor, // out of order so it can be used above but moved in here
         }
     }
@@ -106,15 +140,53 @@ impl Memory {
                 u64::from(Self::LOG_BITS_IN_BYTE),
                 2 * Self::CELL_BITS,
             ),
+            trace: RefCell::new(None),
             btor, // out of order so it can be used above but moved in here
         }
     }
 
+    /// Start recording a trace of all reads and writes performed on this
+    /// `Memory`. See `trace()`.
+    ///
+    /// Tracing is off by default. Enabling it when it is already enabled has
+    /// no effect.
+    pub fn enable_tracing(&mut self) {
+        let trace = self.trace.get_mut();
+        if trace.is_none() {
+            *trace = Some(Vec::new());
+        }
+    }
+
+    /// Get the reads and writes performed on this `Memory` since tracing was
+    /// enabled with `enable_tracing()`, in the order they were performed.
+    /// If tracing is not enabled, this will be empty.
+    ///
+    /// Note that each copy of a `Memory` carries its own trace.
+    pub fn trace(&self) -> Vec<MemOp> {
+        self.trace.borrow().clone().unwrap_or_default()
+    }
+
+    /// Record the given operation in the trace, if tracing is enabled
+    fn record(&self, kind: MemOpKind, addr: &BV, bits: u32) {
+        if let Some(trace) = self.trace.borrow_mut().as_mut() {
+            trace.push(MemOp::new(kind, addr, bits));
+        }
+    }
+
     /// Get a reference to the `Btor` instance this `Memory` belongs to
     pub fn get_solver(&self) -> Rc<Btor> {
         self.btor.clone()
     }
 
+    /// Merge `other` into this `Memory`. Afterwards, this `Memory` will have
+    /// the contents of (the original) `self` at every address where `cond` is
+    /// true, and the contents of `other` where `cond` is false.
+    ///
+    /// `other` must belong to the same `Btor` instance as this `Memory`.
+    pub fn merge(&mut self, other: Self, cond: &BV) {
+        self.mem = cond.cond_array(&self.mem, &other.mem);
+    }
+
     /// Adapt the `Memory` to a new `Btor` instance.
     ///
     /// The new `Btor` instance should have been created (possibly transitively)
@@ -318,11 +390,22 @@ impl Memory {
     /// Read any number (>0) of bits of memory, at any alignment.
     /// Returned `BV` will have size `bits`.
     pub fn read(&self, addr: &BV, bits: u32) -> Result<BV> {
+        self.read_with_null_check(addr, bits, self.null_detection)
+    }
+
+    /// Like `read()`, but without checking whether the address can be NULL,
+    /// for an address already known to be non-NULL
+    pub fn read_non_null(&self, addr: &BV, bits: u32) -> Result<BV> {
+        self.read_with_null_check(addr, bits, false)
+    }
+
+    fn read_with_null_check(&self, addr: &BV, bits: u32, null_check: bool) -> Result<BV> {
         debug!("Reading {} bits from {} at {:?}", bits, &self.name, addr);
+        self.record(MemOpKind::Read, addr, bits);
         let addr_width = addr.get_width();
         assert_eq!(addr_width, Self::INDEX_BITS, "Read address has wrong width");
 
-        if self.null_detection
+        if null_check
             && bvs_can_be_equal(&self.btor, addr, &BV::zero(self.btor.clone(), addr_width))?
         {
             return Err(Error::NullPointerDereference);
@@ -381,7 +464,18 @@ impl Memory {
 
     /// Write any number (>0) of bits of memory, at any alignment.
     pub fn write(&mut self, addr: &BV, val: BV) -> Result<()> {
+        self.write_with_null_check(addr, val, self.null_detection)
+    }
+
+    /// Like `write()`, but without checking whether the address can be NULL,
+    /// for an address already known to be non-NULL
+    pub fn write_non_null(&mut self, addr: &BV, val: BV) -> Result<()> {
+        self.write_with_null_check(addr, val, false)
+    }
+
+    fn write_with_null_check(&mut self, addr: &BV, val: BV, null_check: bool) -> Result<()> {
         debug!("Writing {:?} to {} address {:?}", val, &self.name, addr);
+        self.record(MemOpKind::Write, addr, val.get_width());
         let addr_width = addr.get_width();
         assert_eq!(
             addr_width,
@@ -389,7 +483,7 @@ impl Memory {
             "Write address has wrong width"
         );
 
-        if self.null_detection
+        if null_check
             && bvs_can_be_equal(&self.btor, addr, &BV::zero(self.btor.clone(), addr_width))?
         {
             return Err(Error::NullPointerDereference);
@@ -928,6 +1022,77 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn merge() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut mem = Memory::new_uninitialized(btor.clone(), true, None);
+        let addr = BV::from_u64(btor.clone(), 0x10000, Memory::INDEX_BITS);
+        mem.write(&addr, BV::from_u64(btor.clone(), 0x4F, 8))?;
+
+        // write different data to the same address in a copy of the memory
+        let mut other = mem.clone();
+        other.write(&addr, BV::from_u64(btor.clone(), 0x3A, 8))?;
+
+        // after merging, we could read either value
+        let cond = BV::new(btor.clone(), 1, Some("cond"));
+        mem.merge(other, &cond);
+        let read_bv = mem.read(&addr, 8)?;
+        let ps = solver_utils::get_possible_solutions_for_bv(btor.clone(), &read_bv, 2)?
+            .as_u64_solutions()
+            .unwrap();
+        assert_eq!(
+            ps,
+            PossibleSolutions::Exactly(HashSet::from_iter(vec![0x4F, 0x3A]))
+        );
+
+        // and which one we read is determined by the condition
+        cond.assert();
+        let read_bv = mem.read(&addr, 8)?;
+        let ps = solver_utils::get_possible_solutions_for_bv(btor.clone(), &read_bv, 1)?
+            .as_u64_solutions()
+            .unwrap();
+        assert_eq!(
+            ps,
+            PossibleSolutions::Exactly(HashSet::from_iter(std::iter::once(0x4F)))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tracing() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut mem = Memory::new_uninitialized(btor.clone(), true, None);
+        let addr = BV::from_u64(btor.clone(), 0x10000, Memory::INDEX_BITS);
+
+        // operations before tracing is enabled aren't recorded
+        mem.write(&addr, BV::from_u64(btor.clone(), 0x4F, 8))?;
+        assert!(mem.trace().is_empty());
+
+        mem.enable_tracing();
+        mem.write(&addr, BV::from_u64(btor.clone(), 0x3A, 16))?;
+        let _ = mem.read(&addr, 8)?;
+        assert_eq!(
+            mem.trace(),
+            vec![
+                MemOp {
+                    kind: MemOpKind::Write,
+                    addr: "0x10000".to_owned(),
+                    bits: 16,
+                },
+                MemOp {
+                    kind: MemOpKind::Read,
+                    addr: "0x10000".to_owned(),
+                    bits: 8,
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn write_different_cells() -> Result<()> {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -1214,6 +1379,37 @@ mod tests {
             PossibleSolutions::Exactly(HashSet::from_iter(std::iter::once(0x78dc)))
         );
 
+        Ok(())
+    }
+    #[test]
+    fn overwrite_middle_byte() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut mem = Memory::new_uninitialized(btor.clone(), true, None);
+
+        // first within a single cell, then for a word which straddles two cells
+        for &base in &[0x10000, 0x10006] {
+            // Write a 32-bit value
+            let data = BV::from_u64(btor.clone(), 0x12345678, 32);
+            let addr = BV::from_u64(btor.clone(), base, Memory::INDEX_BITS);
+            mem.write(&addr, data)?;
+
+            // Overwrite one of its middle bytes
+            let overwrite_addr = BV::from_u64(btor.clone(), base + 2, Memory::INDEX_BITS);
+            mem.write(&overwrite_addr, BV::from_u64(btor.clone(), 0xab, 8))?;
+
+            // Reading the whole word back should show only that byte changed
+            let read_bv = mem.read(&addr, 32)?;
+            assert_eq!(solver_utils::sat(&btor), Ok(true));
+            let ps = solver_utils::get_possible_solutions_for_bv(btor.clone(), &read_bv, 1)?
+                .as_u64_solutions()
+                .unwrap();
+            assert_eq!(
+                ps,
+                PossibleSolutions::Exactly(HashSet::from_iter(std::iter::once(0x12ab5678)))
+            );
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,50 @@
+//! A typed wrapper for pointer-valued `BV`s
+
+use crate::backend::BV;
+
+/// A `BV` which is known to hold a pointer, as opposed to an integer.
+///
+/// This has the same underlying representation as the `BV` it wraps, but
+/// keeps pointers and integers distinct at compile time. Offsetting a
+/// `Pointer` gives another `Pointer`; for any other operation, get the
+/// underlying `BV` with `as_bv()` or `into_bv()`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pointer<V: BV> {
+    bv: V,
+}
+
+impl<V: BV> Pointer<V> {
+    /// Treat the given `BV` as a pointer
+    pub fn new(bv: V) -> Self {
+        Self { bv }
+    }
+
+    /// Get the underlying `BV`
+    pub fn as_bv(&self) -> &V {
+        &self.bv
+    }
+
+    /// Get the underlying `BV`, consuming the `Pointer`
+    pub fn into_bv(self) -> V {
+        self.bv
+    }
+
+    /// Get a pointer `bytes` bytes past this one (or before this one, if
+    /// `bytes` is negative)
+    pub fn offset(&self, bytes: i64) -> Self {
+        let offset = V::from_i64(self.bv.get_solver(), bytes, self.bv.get_width());
+        Self::new(self.bv.add(&offset))
+    }
+
+    /// Get a pointer `bytes` bytes past this one, where `bytes` may be
+    /// symbolic. `bytes` must have the same width as the pointer.
+    pub fn offset_by(&self, bytes: &V) -> Self {
+        Self::new(self.bv.add(bytes))
+    }
+
+    /// Get the difference, in bytes, between this pointer and `other`, as
+    /// with pointer subtraction in C. The result is an integer, not a pointer.
+    pub fn diff(&self, other: &Self) -> V {
+        self.bv.sub(&other.bv)
+    }
+}
@@ -5,21 +5,26 @@
 
 use crate::backend::SolverRef;
 use crate::error::*;
+use crate::memory::{MemOp, MemOpKind};
 use crate::solver_utils::bvs_can_be_equal;
 use boolector::Btor;
 use log::debug;
 use reduce::Reduce;
+use std::cell::RefCell;
 use std::rc::Rc;
 
 type BV = boolector::BV<Rc<Btor>>;
 type Array = boolector::Array<Rc<Btor>>;
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Memory {
     btor: Rc<Btor>,
     mem: Array,
     name: String,
     null_detection: bool,
+    /// Trace of memory operations, or `None` if tracing is not enabled.
+    /// See `enable_tracing()`.
+    trace: RefCell<Option<Vec<MemOp>>>,
 }
 
 impl Memory {
@@ -47,6 +52,7 @@ impl Memory {
             ),
             name: name.unwrap_or(default_name).into(),
             null_detection,
+            trace: RefCell::new(None),
             btor, // out of order so it can be used above but moved in here
         }
     }
@@ -69,15 +75,53 @@ impl Memory {
             ),
             name: name.unwrap_or(default_name).into(),
             null_detection,
+            trace: RefCell::new(None),
             btor, // out of order so it can be used above but moved in here
         }
     }
 
+    /// Start recording a trace of all reads and writes performed on this
+    /// `Memory`. See `trace()`.
+    ///
+    /// Tracing is off by default. Enabling it when it is already enabled has
+    /// no effect.
+    pub fn enable_tracing(&mut self) {
+        let trace = self.trace.get_mut();
+        if trace.is_none() {
+            *trace = Some(Vec::new());
+        }
+    }
+
+    /// Get the reads and writes performed on this `Memory` since tracing was
+    /// enabled with `enable_tracing()`, in the order they were performed.
+    /// If tracing is not enabled, this will be empty.
+    ///
+    /// Note that each copy of a `Memory` carries its own trace.
+    pub fn trace(&self) -> Vec<MemOp> {
+        self.trace.borrow().clone().unwrap_or_default()
+    }
+
+    /// Record the given operation in the trace, if tracing is enabled
+    fn record(&self, kind: MemOpKind, addr: &BV, bits: u32) {
+        if let Some(trace) = self.trace.borrow_mut().as_mut() {
+            trace.push(MemOp::new(kind, addr, bits));
+        }
+    }
+
     /// Get a reference to the `Btor` instance this `Memory` belongs to
     pub fn get_solver(&self) -> Rc<Btor> {
         self.btor.clone()
     }
 
+    /// Merge `other` into this `Memory`. Afterwards, this `Memory` will have
+    /// the contents of (the original) `self` at every address where `cond` is
+    /// true, and the contents of `other` where `cond` is false.
+    ///
+    /// `other` must belong to the same `Btor` instance as this `Memory`.
+    pub fn merge(&mut self, other: Self, cond: &BV) {
+        self.mem = cond.cond_array(&self.mem, &other.mem);
+    }
+
     /// Adapt the `Memory` to a new `Btor` instance.
     ///
     /// The new `Btor` instance should have been created (possibly transitively)
@@ -118,11 +162,22 @@ impl Memory {
     /// Read any number (>0) of bits of memory, at any alignment.
     /// Returned `BV` will have size `bits`.
     pub fn read(&self, addr: &BV, bits: u32) -> Result<BV> {
+        self.read_with_null_check(addr, bits, self.null_detection)
+    }
+
+    /// Like `read()`, but without checking whether the address can be NULL,
+    /// for an address already known to be non-NULL
+    pub fn read_non_null(&self, addr: &BV, bits: u32) -> Result<BV> {
+        self.read_with_null_check(addr, bits, false)
+    }
+
+    fn read_with_null_check(&self, addr: &BV, bits: u32, null_check: bool) -> Result<BV> {
         debug!("Reading {} bits from {} at {:?}", bits, &self.name, addr);
+        self.record(MemOpKind::Read, addr, bits);
         let addr_width = addr.get_width();
         assert_eq!(addr_width, Self::INDEX_BITS, "Read address has wrong width");
 
-        if self.null_detection
+        if null_check
             && bvs_can_be_equal(&self.btor, addr, &BV::zero(self.btor.clone(), addr_width))?
         {
             return Err(Error::NullPointerDereference);
@@ -153,7 +208,18 @@ impl Memory {
 
     /// Write any number (>0) of bits of memory, at any alignment.
     pub fn write(&mut self, addr: &BV, val: BV) -> Result<()> {
+        self.write_with_null_check(addr, val, self.null_detection)
+    }
+
+    /// Like `write()`, but without checking whether the address can be NULL,
+    /// for an address already known to be non-NULL
+    pub fn write_non_null(&mut self, addr: &BV, val: BV) -> Result<()> {
+        self.write_with_null_check(addr, val, false)
+    }
+
+    fn write_with_null_check(&mut self, addr: &BV, val: BV, null_check: bool) -> Result<()> {
         debug!("Writing {:?} to {} address {:?}", val, &self.name, addr);
+        self.record(MemOpKind::Write, addr, val.get_width());
         let addr_width = addr.get_width();
         assert_eq!(
             addr_width,
@@ -161,7 +227,7 @@ impl Memory {
             "Write address has wrong width"
         );
 
-        if self.null_detection
+        if null_check
             && bvs_can_be_equal(&self.btor, addr, &BV::zero(self.btor.clone(), addr_width))?
         {
             return Err(Error::NullPointerDereference);
@@ -198,6 +264,17 @@ impl Memory {
     }
 }
 
+impl PartialEq for Memory {
+    fn eq(&self, other: &Self) -> bool {
+        self.btor == other.btor
+            && self.mem == other.mem
+            && self.name == other.name
+            && self.null_detection == other.null_detection // we don't care about checking equality on `trace`
+    }
+}
+
+impl Eq for Memory {}
+
 #[cfg(test)]
 /// These tests are adapted directly from those in memory.rs, because the two
 /// modules should have exactly the same behavior, potentially with different
@@ -923,4 +1000,98 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn overwrite_middle_byte() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut mem = Memory::new_uninitialized(btor.clone(), true, None);
+
+        // first at an aligned address, then at an unaligned one
+        for &base in &[0x10000, 0x10006] {
+            // Write a 32-bit value
+            let data = BV::from_u64(btor.clone(), 0x12345678, 32);
+            let addr = BV::from_u64(btor.clone(), base, Memory::INDEX_BITS);
+            mem.write(&addr, data)?;
+
+            // Overwrite one of its middle bytes
+            let overwrite_addr = BV::from_u64(btor.clone(), base + 2, Memory::INDEX_BITS);
+            mem.write(&overwrite_addr, BV::from_u64(btor.clone(), 0xab, 8))?;
+
+            // Reading the whole word back should show only that byte changed
+            let read_bv = mem.read(&addr, 32)?;
+            assert_eq!(solver_utils::sat(&btor), Ok(true));
+            let ps = solver_utils::get_possible_solutions_for_bv(btor.clone(), &read_bv, 1)?
+                .as_u64_solutions()
+                .unwrap();
+            assert_eq!(
+                ps,
+                PossibleSolutions::Exactly(HashSet::from_iter(std::iter::once(0x12ab5678)))
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn agrees_with_cell_memory() -> Result<()> {
+        let _ = env_logger::builder().is_test(true).try_init();
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut mem = Memory::new_zero_initialized(btor.clone(), true, None);
+        let mut cell_mem =
+            crate::memory::Memory::new_zero_initialized(btor.clone(), true, Some("cell_mem"));
+
+        // the same writes to both: aligned, unaligned, straddling cells, and
+        // at an address which is symbolic (but constrained to a small range)
+        let sym_addr = BV::new(btor.clone(), Memory::INDEX_BITS, Some("sym_addr"));
+        sym_addr
+            .ugte(&BV::from_u64(btor.clone(), 0x10010, Memory::INDEX_BITS))
+            .assert();
+        sym_addr
+            .ult(&BV::from_u64(btor.clone(), 0x10014, Memory::INDEX_BITS))
+            .assert();
+        let writes = vec![
+            (
+                BV::from_u64(btor.clone(), 0x10000, Memory::INDEX_BITS),
+                BV::from_u64(btor.clone(), 0x1234_5678_9abc_def0, 64),
+            ),
+            (
+                BV::from_u64(btor.clone(), 0x10003, Memory::INDEX_BITS),
+                BV::from_u64(btor.clone(), 0xab, 8),
+            ),
+            (
+                BV::from_u64(btor.clone(), 0x10006, Memory::INDEX_BITS),
+                BV::from_u64(btor.clone(), 0xcafe_f00d, 32),
+            ),
+            (sym_addr.clone(), BV::from_u64(btor.clone(), 0x5555, 16)),
+        ];
+        for (addr, val) in writes {
+            mem.write(&addr, val.clone())?;
+            cell_mem.write(&addr, val)?;
+        }
+
+        // all of the same reads from both give the same results
+        for offset in 0 .. 0x18 {
+            let addr = BV::from_u64(btor.clone(), 0x10000 + offset, Memory::INDEX_BITS);
+            for &bits in &[8, 16, 32, 64] {
+                let val = mem.read(&addr, bits)?;
+                let cell_val = cell_mem.read(&addr, bits)?;
+                assert_eq!(
+                    solver_utils::bvs_must_be_equal(&btor, &val, &cell_val),
+                    Ok(true),
+                    "reads of {} bits at offset {:#x} differ",
+                    bits,
+                    offset
+                );
+            }
+        }
+        let val = mem.read(&sym_addr, 16)?;
+        let cell_val = cell_mem.read(&sym_addr, 16)?;
+        assert_eq!(
+            solver_utils::bvs_must_be_equal(&btor, &val, &cell_val),
+            Ok(true)
+        );
+
+        Ok(())
+    }
 }
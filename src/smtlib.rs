@@ -0,0 +1,1405 @@
+//! Utilities for the SMT-LIB text which Boolector produces when dumping
+//! constraints: parsing it into s-expressions, encoding those compactly as
+//! bytes, and rebuilding them as `BV`s and `Memory`s in another solver
+//! instance.
+
+use boolector::Btor;
+use std::collections::{HashMap, HashSet};
+
+use crate::backend::{Backend, Memory, BV};
+use crate::error::*;
+
+/// An SMT-LIB s-expression
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub(crate) enum SExpr {
+    /// A symbol, keyword, or literal, with any `|` quoting removed
+    Atom(String),
+    List(Vec<SExpr>),
+}
+
+/// Maximum nesting depth of the s-expressions which are parsed or
+/// evaluated, so that deeply nested input is rejected rather than
+/// overflowing the stack
+const MAX_DEPTH: usize = 500;
+
+fn too_deep() -> Error {
+    malformed(&format!("nested more than {} levels deep", MAX_DEPTH))
+}
+
+/// Parse any number of top-level s-expressions from SMT-LIB text
+pub(crate) fn parse(text: &str) -> Result<Vec<SExpr>> {
+    let mut stack: Vec<Vec<SExpr>> = vec![Vec::new()];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => {
+                if stack.len() > MAX_DEPTH {
+                    return Err(too_deep());
+                }
+                stack.push(Vec::new())
+            },
+            ')' => {
+                let list = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some(parent) => parent.push(SExpr::List(list)),
+                    None => return Err(malformed("unbalanced `)`")),
+                }
+            },
+            ';' => {
+                // comment until end of line
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            },
+            '|' => {
+                let mut symbol = String::new();
+                loop {
+                    match chars.next() {
+                        Some('|') => break,
+                        Some(c) => symbol.push(c),
+                        None => return Err(malformed("unterminated quoted symbol")),
+                    }
+                }
+                stack.last_mut().unwrap().push(SExpr::Atom(symbol));
+            },
+            c if c.is_whitespace() => {},
+            c => {
+                let mut atom = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == ';' || c == '|' {
+                        break;
+                    }
+                    atom.push(c);
+                    chars.next();
+                }
+                stack.last_mut().unwrap().push(SExpr::Atom(atom));
+            },
+        }
+    }
+    if stack.len() != 1 {
+        return Err(malformed("unbalanced `(`"));
+    }
+    Ok(stack.pop().unwrap())
+}
+
+/// Is this s-expression a command which doesn't contribute to the
+/// constraints, such as a variable declaration or `check-sat`?
+pub(crate) fn is_ignored_command(expr: &SExpr) -> bool {
+    match head(expr) {
+        Some(head) => IGNORED_COMMANDS.contains(&head),
+        None => false,
+    }
+}
+
+const IGNORED_COMMANDS: &[&str] = &[
+    "declare-fun",
+    "declare-const",
+    "set-logic",
+    "set-option",
+    "set-info",
+    "check-sat",
+    "exit",
+];
+
+/// Names which `free_symbols()` won't consider to be variables
+const RESERVED: &[&str] = &[
+    "_",
+    "let",
+    "assert",
+    "define-fun",
+    "true",
+    "false",
+    "not",
+    "and",
+    "or",
+    "xor",
+    "=>",
+    "=",
+    "distinct",
+    "ite",
+    "bvnot",
+    "bvneg",
+    "bvand",
+    "bvor",
+    "bvxor",
+    "bvnand",
+    "bvnor",
+    "bvxnor",
+    "bvcomp",
+    "bvadd",
+    "bvsub",
+    "bvmul",
+    "bvudiv",
+    "bvurem",
+    "bvsdiv",
+    "bvsrem",
+    "bvsmod",
+    "bvshl",
+    "bvlshr",
+    "bvashr",
+    "concat",
+    "bvult",
+    "bvule",
+    "bvugt",
+    "bvuge",
+    "bvslt",
+    "bvsle",
+    "bvsgt",
+    "bvsge",
+];
+
+/// Get the names of the variables which the given s-expressions (the
+/// top-level commands or terms of a single constraint) refer to without
+/// binding them, in order of first appearance
+pub(crate) fn free_symbols(exprs: &[SExpr]) -> Vec<String> {
+    let mut symbols = Vec::new();
+    let mut bound = Vec::new();
+    for expr in exprs {
+        match expr {
+            SExpr::List(items) if head(expr) == Some("define-fun") => {
+                if let Some(body) = items.get(4) {
+                    collect_free_symbols(body, &mut bound, &mut symbols);
+                }
+                if let Some(SExpr::Atom(name)) = items.get(1) {
+                    bound.push(name.clone());
+                }
+            },
+            _ => collect_free_symbols(expr, &mut bound, &mut symbols),
+        }
+    }
+    symbols
+}
+
+fn collect_free_symbols(expr: &SExpr, bound: &mut Vec<String>, symbols: &mut Vec<String>) {
+    match expr {
+        SExpr::Atom(atom) => {
+            let is_literal =
+                atom.starts_with('#') || atom.starts_with(|c: char| c.is_ascii_digit());
+            if !is_literal
+                && !RESERVED.contains(&atom.as_str())
+                && !bound.contains(atom)
+                && !symbols.contains(atom)
+            {
+                symbols.push(atom.clone());
+            }
+        },
+        SExpr::List(items) => match head(expr) {
+            // indexed identifiers, like `(_ extract 7 0)`, contain no variables
+            Some("_") => {},
+            Some("let") => {
+                let bindings = match items.get(1) {
+                    Some(SExpr::List(bindings)) => bindings.as_slice(),
+                    _ => &[],
+                };
+                let mut names = Vec::new();
+                for binding in bindings {
+                    if let SExpr::List(binding) = binding {
+                        if let [SExpr::Atom(name), term] = binding.as_slice() {
+                            collect_free_symbols(term, bound, symbols);
+                            names.push(name.clone());
+                        }
+                    }
+                }
+                let num_names = names.len();
+                bound.extend(names);
+                for body in items.iter().skip(2) {
+                    collect_free_symbols(body, bound, symbols);
+                }
+                bound.truncate(bound.len() - num_names);
+            },
+            _ => {
+                for item in items {
+                    collect_free_symbols(item, bound, symbols);
+                }
+            },
+        },
+    }
+}
+
+/// Count the terms in the given s-expressions (the top-level commands or
+/// terms of a single constraint): each variable, literal, and operator
+/// application. A `let`-bound or `define-fun`-defined term is counted once,
+/// where it's defined, however many times it's used.
+pub(crate) fn term_count(exprs: &[SExpr]) -> usize {
+    exprs.iter().map(count_terms).sum()
+}
+
+fn count_terms(expr: &SExpr) -> usize {
+    match expr {
+        SExpr::Atom(_) => 1,
+        SExpr::List(items) => match head(expr) {
+            // an indexed identifier, like `(_ extract 7 0)`, is a single operator
+            Some("_") => 1,
+            Some("let") => {
+                let bindings = match items.get(1) {
+                    Some(SExpr::List(bindings)) => bindings.as_slice(),
+                    _ => &[],
+                };
+                let bound_terms: usize = bindings
+                    .iter()
+                    .map(|binding| match binding {
+                        SExpr::List(binding) => binding.iter().skip(1).map(count_terms).sum(),
+                        SExpr::Atom(_) => 0,
+                    })
+                    .sum();
+                bound_terms + items.iter().skip(2).map(count_terms).sum::<usize>()
+            },
+            Some("define-fun") => items.get(4).map(count_terms).unwrap_or(0),
+            Some("assert") => items.iter().skip(1).map(count_terms).sum(),
+            _ => items.iter().map(count_terms).sum(),
+        },
+    }
+}
+
+fn head(expr: &SExpr) -> Option<&str> {
+    match expr {
+        SExpr::List(items) => match items.first() {
+            Some(SExpr::Atom(head)) => Some(head),
+            _ => None,
+        },
+        SExpr::Atom(_) => None,
+    }
+}
+
+fn malformed(details: &str) -> Error {
+    Error::OtherError(format!("Malformed serialized constraints: {}", details))
+}
+
+/// Constraints and values dumped from a solver instance with
+/// `Btor::print_constraints()`, in a form which can be converted to and from
+/// bytes and rebuilt in another solver instance with `rebuild()`.
+///
+/// Each dumped value is identified by a variable which the commands
+/// constrain: a bitvector value by a variable equal to it, and a memory by
+/// a variable equal to a read of it at an unconstrained index. These
+/// variables (and the index variables) only exist in the solver instance
+/// the dump was taken from, so `rebuild()` doesn't treat them as inputs.
+///
+/// Named variables may be identified in the same way, since once the solver
+/// has substituted a term for a variable, the variable no longer appears in
+/// its constraints.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub(crate) struct Dump {
+    /// The declarations, definitions, and assertions the solver printed
+    pub(crate) commands: Vec<SExpr>,
+    /// Symbols which the solver made up for variables and arrays which had
+    /// no symbol of their own
+    pub(crate) anonymous: Vec<String>,
+    /// For each dumped bitvector value, the symbol of the variable equal to it
+    pub(crate) bvs: Vec<String>,
+    /// For each dumped memory, the symbols of the index variable and of the
+    /// variable equal to the read at that index
+    pub(crate) mems: Vec<(String, String)>,
+    /// For each identified named variable, its symbol and the symbol of the
+    /// variable equal to it
+    pub(crate) vars: Vec<(String, String)>,
+}
+
+/// Marks the beginning of a serialized `Dump`, and the version of the format
+const MAGIC: &[u8] = b"hbc\x03";
+
+impl Dump {
+    /// Parse the output of `Btor::print_constraints()`, keeping only the
+    /// commands which contribute to the constraints. `is_symbol` tells
+    /// whether a declared name is the symbol of a variable or array, rather
+    /// than one which the solver made up.
+    pub(crate) fn parse(
+        text: &str,
+        is_symbol: impl Fn(&str) -> bool,
+        bvs: Vec<String>,
+        mems: Vec<(String, String)>,
+        vars: Vec<(String, String)>,
+    ) -> Result<Self> {
+        let commands: Vec<SExpr> = parse(text)?
+            .into_iter()
+            .filter(|command| match head(command) {
+                Some("declare-fun") | Some("define-fun") => true,
+                _ => !is_ignored_command(command),
+            })
+            .collect();
+        let anonymous = commands
+            .iter()
+            .filter_map(|command| match command {
+                SExpr::List(items) if head(command) == Some("declare-fun") => match items.get(1) {
+                    Some(SExpr::Atom(name)) if !is_symbol(name) => Some(name.clone()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        Ok(Self {
+            commands,
+            anonymous,
+            bvs,
+            mems,
+            vars,
+        })
+    }
+
+    /// The symbols of the arrays which the dump declares
+    pub(crate) fn arrays(&self) -> impl Iterator<Item = &str> {
+        self.commands.iter().filter_map(|command| match command {
+            SExpr::List(items) if head(command) == Some("declare-fun") => match items.as_slice() {
+                [_, SExpr::Atom(name), _, sort] if head(sort) == Some("Array") => {
+                    Some(name.as_str())
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    /// Each distinct atom is stored once, in a table at the beginning;
+    /// everything else is a sequence of LEB128-encoded integers.
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut atoms = AtomTable::default();
+        let mut body = Vec::new();
+        write_varint(&mut body, self.anonymous.len() as u64);
+        for symbol in &self.anonymous {
+            write_varint(&mut body, atoms.index(symbol));
+        }
+        write_varint(&mut body, self.bvs.len() as u64);
+        for symbol in &self.bvs {
+            write_varint(&mut body, atoms.index(symbol));
+        }
+        write_varint(&mut body, self.mems.len() as u64);
+        for (index, value) in &self.mems {
+            write_varint(&mut body, atoms.index(index));
+            write_varint(&mut body, atoms.index(value));
+        }
+        write_varint(&mut body, self.vars.len() as u64);
+        for (symbol, value) in &self.vars {
+            write_varint(&mut body, atoms.index(symbol));
+            write_varint(&mut body, atoms.index(value));
+        }
+        write_varint(&mut body, self.commands.len() as u64);
+        for command in &self.commands {
+            write_sexpr(&mut body, command, &mut atoms);
+        }
+        let mut bytes = MAGIC.to_vec();
+        write_varint(&mut bytes, atoms.atoms.len() as u64);
+        for atom in atoms.atoms {
+            write_varint(&mut bytes, atom.len() as u64);
+            bytes.extend_from_slice(atom.as_bytes());
+        }
+        bytes.extend(body);
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(malformed("missing header"));
+        }
+        let mut reader = Reader {
+            bytes,
+            pos: MAGIC.len(),
+        };
+        let num_atoms = reader.varint()?;
+        let mut atoms = Vec::new();
+        for _ in 0 .. num_atoms {
+            let len = reader.varint()? as usize;
+            let atom = std::str::from_utf8(reader.take(len)?)
+                .map_err(|_| malformed("atom is not valid UTF-8"))?;
+            atoms.push(atom.to_owned());
+        }
+        let num_anonymous = reader.varint()?;
+        let mut anonymous = Vec::new();
+        for _ in 0 .. num_anonymous {
+            anonymous.push(reader.atom(&atoms)?);
+        }
+        let num_bvs = reader.varint()?;
+        let mut bvs = Vec::new();
+        for _ in 0 .. num_bvs {
+            bvs.push(reader.atom(&atoms)?);
+        }
+        let num_mems = reader.varint()?;
+        let mut mems = Vec::new();
+        for _ in 0 .. num_mems {
+            let index = reader.atom(&atoms)?;
+            mems.push((index, reader.atom(&atoms)?));
+        }
+        let num_vars = reader.varint()?;
+        let mut vars = Vec::new();
+        for _ in 0 .. num_vars {
+            let symbol = reader.atom(&atoms)?;
+            vars.push((symbol, reader.atom(&atoms)?));
+        }
+        let num_commands = reader.varint()?;
+        let mut commands = Vec::new();
+        for _ in 0 .. num_commands {
+            commands.push(reader.sexpr(&atoms)?);
+        }
+        if reader.pos != bytes.len() {
+            return Err(malformed("trailing bytes"));
+        }
+        Ok(Self {
+            commands,
+            anonymous,
+            bvs,
+            mems,
+            vars,
+        })
+    }
+}
+
+#[derive(Default)]
+struct AtomTable<'a> {
+    atoms: Vec<&'a str>,
+    indices: HashMap<&'a str, u64>,
+}
+
+impl<'a> AtomTable<'a> {
+    fn index(&mut self, atom: &'a str) -> u64 {
+        let atoms = &mut self.atoms;
+        *self.indices.entry(atom).or_insert_with(|| {
+            atoms.push(atom);
+            atoms.len() as u64 - 1
+        })
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+/// An atom is written as its index shifted left by one; a list is written as
+/// its length shifted left by one with the low bit set, followed by its items
+fn write_sexpr<'a>(bytes: &mut Vec<u8>, expr: &'a SExpr, atoms: &mut AtomTable<'a>) {
+    match expr {
+        SExpr::Atom(atom) => write_varint(bytes, atoms.index(atom) << 1),
+        SExpr::List(items) => {
+            write_varint(bytes, ((items.len() as u64) << 1) | 1);
+            for item in items {
+                write_sexpr(bytes, item, atoms);
+            }
+        },
+    }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn varint(&mut self) -> Result<u64> {
+        let mut n: u64 = 0;
+        for shift in (0 .. 64).step_by(7) {
+            let byte = *self
+                .bytes
+                .get(self.pos)
+                .ok_or_else(|| malformed("unexpected end of input"))?;
+            self.pos += 1;
+            n |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(malformed("integer too large"))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'b [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| malformed("unexpected end of input"))?;
+        let taken = &self.bytes[self.pos .. end];
+        self.pos = end;
+        Ok(taken)
+    }
+
+    fn atom_at(&self, index: u64, atoms: &[String]) -> Result<String> {
+        atoms
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| malformed("atom index out of range"))
+    }
+
+    fn atom(&mut self, atoms: &[String]) -> Result<String> {
+        let index = self.varint()?;
+        self.atom_at(index, atoms)
+    }
+
+    fn sexpr(&mut self, atoms: &[String]) -> Result<SExpr> {
+        self.sexpr_at_depth(atoms, 0)
+    }
+
+    fn sexpr_at_depth(&mut self, atoms: &[String], depth: usize) -> Result<SExpr> {
+        let n = self.varint()?;
+        if n & 1 == 0 {
+            Ok(SExpr::Atom(self.atom_at(n >> 1, atoms)?))
+        } else if depth >= MAX_DEPTH {
+            Err(too_deep())
+        } else {
+            let mut items = Vec::new();
+            for _ in 0 .. n >> 1 {
+                items.push(self.sexpr_at_depth(atoms, depth + 1)?);
+            }
+            Ok(SExpr::List(items))
+        }
+    }
+}
+
+/// A term rebuilt from a `Dump`: a bitvector (Booleans are represented as
+/// `BV`s of width 1, as they are elsewhere), or an array, as a `Memory`
+pub(crate) enum Term<B: Backend> {
+    BV(B::BV),
+    Memory(B::Memory),
+}
+
+impl<B: Backend> Clone for Term<B> {
+    fn clone(&self) -> Self {
+        match self {
+            Term::BV(bv) => Term::BV(bv.clone()),
+            Term::Memory(mem) => Term::Memory(mem.clone()),
+        }
+    }
+}
+
+/// The result of `rebuild()`
+pub(crate) struct Rebuilt<B: Backend> {
+    /// The dumped constraints, other than those which only identify values
+    pub(crate) constraints: Vec<B::BV>,
+    /// The dumped bitvector values, in order
+    pub(crate) bvs: Vec<B::BV>,
+    /// The dumped memories, in order
+    pub(crate) mems: Vec<B::Memory>,
+    /// The term used for each named variable or array the dump declares
+    pub(crate) inputs: HashMap<String, Term<B>>,
+}
+
+/// Rebuild the constraints and values of `dump` in `solver`.
+///
+/// Each named variable or array which the dump declares is given the
+/// corresponding term of `inputs`, if there is one; otherwise, a fresh
+/// variable with the same symbol (which must not already exist in `solver`),
+/// or a fresh uninitialized `Memory`, is created for it. Arrays are rebuilt
+/// as `Memory`s, with `null_detection` as given, which must have cells of the
+/// size either `Memory` implementation uses: 8 bits when indexed by 64 bits,
+/// or 64 bits when indexed by 61. Each identified named variable which the
+/// dump doesn't declare, because the solver substituted it away, is created
+/// in the same way and constrained to equal its value.
+pub(crate) fn rebuild<B: Backend>(
+    dump: &Dump,
+    solver: &B::SolverRef,
+    null_detection: bool,
+    inputs: &HashMap<String, Term<B>>,
+) -> Result<Rebuilt<B>> {
+    let internal: Vec<&str> = dump
+        .anonymous
+        .iter()
+        .chain(dump.bvs.iter())
+        .chain(
+            dump.mems
+                .iter()
+                .flat_map(|(index, value)| vec![index, value]),
+        )
+        .chain(dump.vars.iter().map(|(_, value)| value))
+        .map(String::as_str)
+        .collect();
+    let mut interpreter: Interpreter<B> = Interpreter::new(solver.clone(), null_detection);
+    let mut rebuilt_inputs = HashMap::new();
+    let mut defs: HashMap<&str, &SExpr> = HashMap::new();
+    let mut roots = Vec::new();
+    for command in &dump.commands {
+        match (head(command), command) {
+            (Some("declare-fun"), SExpr::List(items)) => match items.as_slice() {
+                [_, SExpr::Atom(name), SExpr::List(params), sort] if params.is_empty() => {
+                    let sort = parse_sort(sort)?;
+                    let term = if internal.contains(&name.as_str()) {
+                        interpreter.fresh(&sort, None)?
+                    } else {
+                        let term = match inputs.get(name) {
+                            Some(term) => {
+                                check_sort(term, &sort, name)?;
+                                term.clone()
+                            },
+                            None => interpreter.fresh(&sort, Some(name))?,
+                        };
+                        rebuilt_inputs.insert(name.clone(), term.clone());
+                        term
+                    };
+                    interpreter.bind(name, term);
+                },
+                // uninterpreted functions
+                _ => return Err(unsupported(command)),
+            },
+            (Some("define-fun"), SExpr::List(items)) => match items.as_slice() {
+                [_, SExpr::Atom(name), SExpr::List(params), _, body] if params.is_empty() => {
+                    let term = interpreter.eval(body)?;
+                    interpreter.bind(name, term);
+                    defs.insert(name, body);
+                },
+                // functions with parameters
+                _ => return Err(unsupported(command)),
+            },
+            (Some("assert"), SExpr::List(items)) if items.len() == 2 => roots.push(&items[1]),
+            _ => return Err(unsupported(command)),
+        }
+    }
+
+    // the values of the identified variables come after the dumped values
+    let values: HashMap<&str, usize> = dump
+        .bvs
+        .iter()
+        .chain(dump.vars.iter().map(|(_, value)| value))
+        .enumerate()
+        .map(|(i, symbol)| (symbol.as_str(), i))
+        .collect();
+    let probes: HashMap<&str, usize> = dump
+        .mems
+        .iter()
+        .enumerate()
+        .map(|(i, (_, value))| (value.as_str(), i))
+        .collect();
+    let mut bvs: Vec<Option<B::BV>> = vec![None; values.len()];
+    let mut mems: Vec<Option<B::Memory>> = vec![None; dump.mems.len()];
+    let mut constraints = Vec::new();
+    let mut searched = HashSet::new();
+    for root in roots {
+        if let Some((i, term)) = definition(root, &values) {
+            if bvs[i].is_none() {
+                bvs[i] = Some(interpreter.eval_bv(term)?);
+                continue;
+            }
+        }
+        if let Some(i) = find_symbol(root, &probes, &defs, &mut searched) {
+            if mems[i].is_none() {
+                mems[i] = Some(interpreter.probed_memory(root, &probes, &defs)?);
+            }
+            continue;
+        }
+        let constraint = interpreter.eval_bv(root)?;
+        check_width(&constraint, 1)?;
+        constraints.push(constraint);
+    }
+    let mut bvs: Vec<B::BV> = bvs
+        .into_iter()
+        .zip(
+            dump.bvs
+                .iter()
+                .chain(dump.vars.iter().map(|(_, value)| value)),
+        )
+        .map(|(bv, symbol)| match bv {
+            Some(bv) => Ok(bv),
+            // the solver rewrote the equality with the value, so the variable
+            // itself stands for the value, constrained by the rewritten form
+            None => interpreter.lookup(symbol).and_then(Interpreter::<B>::bv),
+        })
+        .collect::<Result<_>>()?;
+    for ((symbol, _), value) in dump.vars.iter().zip(bvs.split_off(dump.bvs.len())) {
+        if rebuilt_inputs.contains_key(symbol) {
+            continue;
+        }
+        let term = match inputs.get(symbol) {
+            Some(term) => {
+                check_sort(term, &Sort::BV(value.get_width()), symbol)?;
+                term.clone()
+            },
+            None => interpreter.fresh(&Sort::BV(value.get_width()), Some(symbol))?,
+        };
+        constraints.push(Interpreter::<B>::bv(term.clone())?._eq(&value));
+        rebuilt_inputs.insert(symbol.clone(), term);
+    }
+    let mems = mems
+        .into_iter()
+        .map(|mem| mem.ok_or_else(|| malformed("missing read of a dumped memory")))
+        .collect::<Result<_>>()?;
+    Ok(Rebuilt {
+        constraints,
+        bvs,
+        mems,
+        inputs: rebuilt_inputs,
+    })
+}
+
+/// If `root` is an equality between a value variable and some term, get the
+/// index of the value and the term
+fn definition<'e>(root: &'e SExpr, values: &HashMap<&str, usize>) -> Option<(usize, &'e SExpr)> {
+    match root {
+        SExpr::List(items) if head(root) == Some("=") => match items.as_slice() {
+            [_, SExpr::Atom(symbol), term] | [_, term, SExpr::Atom(symbol)] => {
+                values.get(symbol.as_str()).map(|&i| (i, term))
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Find one of the given `symbols` which `expr` refers to, looking into the
+/// bodies of definitions; get its index
+///
+/// This doesn't recurse, as chains of definitions can be arbitrarily long.
+/// `searched` holds the definitions already known not to refer to any of the
+/// `symbols`.
+fn find_symbol<'e>(
+    expr: &'e SExpr,
+    symbols: &HashMap<&str, usize>,
+    defs: &HashMap<&'e str, &'e SExpr>,
+    searched: &mut HashSet<&'e str>,
+) -> Option<usize> {
+    let mut visited = HashSet::new();
+    let mut stack = vec![expr];
+    while let Some(expr) = stack.pop() {
+        match expr {
+            SExpr::Atom(atom) => {
+                if let Some(&i) = symbols.get(atom.as_str()) {
+                    return Some(i);
+                }
+                if let Some(body) = defs.get(atom.as_str()) {
+                    if !searched.contains(atom.as_str()) && visited.insert(atom.as_str()) {
+                        stack.push(body);
+                    }
+                }
+            },
+            SExpr::List(items) => stack.extend(items.iter().rev()),
+        }
+    }
+    searched.extend(visited);
+    None
+}
+
+fn parse_sort(sort: &SExpr) -> Result<Sort> {
+    let bitvec = |sort: &SExpr| match sort {
+        SExpr::List(items) => match items.as_slice() {
+            [SExpr::Atom(underscore), SExpr::Atom(bitvec), SExpr::Atom(width)]
+                if underscore == "_" && bitvec == "BitVec" =>
+            {
+                parse_index(width).ok().filter(|&width| width > 0)
+            },
+            _ => None,
+        },
+        SExpr::Atom(_) => None,
+    };
+    match sort {
+        SExpr::Atom(atom) if atom == "Bool" => return Ok(Sort::BV(1)),
+        SExpr::List(items) => {
+            if let [SExpr::Atom(array), index, element] = items.as_slice() {
+                if array == "Array" {
+                    if let (Some(index), Some(element)) = (bitvec(index), bitvec(element)) {
+                        return Ok(Sort::Array(index, element));
+                    }
+                }
+            }
+        },
+        SExpr::Atom(_) => {},
+    }
+    bitvec(sort).map(Sort::BV).ok_or_else(|| unsupported(sort))
+}
+
+enum Sort {
+    BV(u32),
+    /// Index width and element width
+    Array(u32, u32),
+}
+
+fn check_sort<B: Backend>(term: &Term<B>, sort: &Sort, name: &str) -> Result<()> {
+    match (term, sort) {
+        (Term::BV(bv), Sort::BV(width)) if bv.get_width() == *width => Ok(()),
+        (Term::Memory(_), Sort::Array(index, element)) => check_layout(*index, *element),
+        _ => Err(Error::OtherError(format!(
+            "the existing term for {:?} has a different sort than the dumped one",
+            name
+        ))),
+    }
+}
+
+/// The element width of an array with the given index width which can be
+/// rebuilt as a `Memory`: with 64-bit byte addresses, the index omits the
+/// bits of the address which select a byte within the element
+fn cell_bits(index_width: u32) -> Result<u32> {
+    if (58 ..= 64).contains(&index_width) {
+        Ok(8 << (64 - index_width))
+    } else {
+        Err(Error::UnsupportedInstruction(format!(
+            "dumped array indexed by {} bits, which isn't a memory layout",
+            index_width
+        )))
+    }
+}
+
+fn check_layout(index_width: u32, element_width: u32) -> Result<()> {
+    if cell_bits(index_width)? == element_width {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedInstruction(format!(
+            "dumped array indexed by {} bits with elements of {} bits, which isn't a memory layout",
+            index_width, element_width
+        )))
+    }
+}
+
+/// Rebuilds the terms of a `Dump` in a given solver instance
+struct Interpreter<B: Backend> {
+    solver: B::SolverRef,
+    null_detection: bool,
+    /// Innermost scopes are last
+    scopes: Vec<HashMap<String, Term<B>>>,
+    /// How deeply nested the expression being evaluated is
+    depth: usize,
+}
+
+impl<B: Backend> Interpreter<B> {
+    fn new(solver: B::SolverRef, null_detection: bool) -> Self {
+        Self {
+            solver,
+            null_detection,
+            scopes: vec![HashMap::new()],
+            depth: 0,
+        }
+    }
+
+    fn bind(&mut self, name: &str, term: Term<B>) {
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name.to_owned(), term);
+    }
+
+    /// A fresh variable or uninitialized `Memory` of the given sort
+    fn fresh(&self, sort: &Sort, name: Option<&str>) -> Result<Term<B>> {
+        let in_use = |name: &str| {
+            Btor::get_bv_by_symbol(&*self.solver, name).is_some()
+                || Btor::get_array_by_symbol(&*self.solver, name).is_some()
+        };
+        match *sort {
+            Sort::BV(width) => {
+                if let Some(name) = name {
+                    if in_use(name) {
+                        return Err(Error::OtherError(format!(
+                            "a variable named {:?} already exists",
+                            name
+                        )));
+                    }
+                }
+                Ok(Term::BV(B::BV::new(self.solver.clone(), width, name)))
+            },
+            Sort::Array(index, element) => {
+                check_layout(index, element)?;
+                // array symbols must be unique within the solver
+                let base_name = name.unwrap_or("mem");
+                let name = std::iter::once(base_name.to_owned())
+                    .chain((1 ..).map(|i| format!("{}_{}", base_name, i)))
+                    .find(|name| !in_use(name))
+                    .unwrap();
+                Ok(Term::Memory(B::Memory::new_uninitialized(
+                    self.solver.clone(),
+                    self.null_detection,
+                    Some(&name),
+                )))
+            },
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Result<Term<B>> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .cloned()
+            .ok_or_else(|| malformed(&format!("reference to undeclared symbol {:?}", name)))
+    }
+
+    fn is_memory(&self, name: &str) -> bool {
+        matches!(self.lookup(name), Ok(Term::Memory(_)))
+    }
+
+    fn bv(term: Term<B>) -> Result<B::BV> {
+        match term {
+            Term::BV(bv) => Ok(bv),
+            Term::Memory(_) => Err(malformed("expected a bitvector, but got an array")),
+        }
+    }
+
+    fn eval_bv(&mut self, expr: &SExpr) -> Result<B::BV> {
+        self.eval(expr).and_then(Self::bv)
+    }
+
+    /// Rebuild the memory which the assertion `root` reads from at the index
+    /// variable, to define the variable with one of the given `probes` symbols
+    fn probed_memory(
+        &mut self,
+        root: &SExpr,
+        probes: &HashMap<&str, usize>,
+        defs: &HashMap<&str, &SExpr>,
+    ) -> Result<B::Memory> {
+        match self.find_selected(root, defs) {
+            Some(array) => match self.eval(array)? {
+                Term::Memory(mem) => Ok(mem),
+                Term::BV(_) => Err(malformed("expected an array, but got a bitvector")),
+            },
+            None => {
+                // the solver evaluated the read, so the memory is constant
+                let value = match definition(root, probes) {
+                    Some((_, term)) => self.eval_bv(term)?,
+                    None => return Err(unsupported(root)),
+                };
+                match value.as_u64() {
+                    Some(0) => Ok(B::Memory::new_zero_initialized(
+                        self.solver.clone(),
+                        self.null_detection,
+                        None,
+                    )),
+                    _ => Err(unsupported(root)),
+                }
+            },
+        }
+    }
+
+    /// Find the first array which `expr` reads from, looking into the bodies
+    /// of definitions which aren't in `visited`
+    fn find_selected<'e>(
+        &self,
+        expr: &'e SExpr,
+        defs: &HashMap<&str, &'e SExpr>,
+    ) -> Option<&'e SExpr> {
+        // as in `find_symbol()`, without recursing
+        let mut visited = HashSet::new();
+        let mut stack = vec![expr];
+        while let Some(expr) = stack.pop() {
+            match expr {
+                SExpr::Atom(atom) => {
+                    if let Some(body) = defs.get(atom.as_str()) {
+                        if visited.insert(atom.as_str()) {
+                            stack.push(body);
+                        }
+                    }
+                },
+                SExpr::List(items) => match items.as_slice() {
+                    [SExpr::Atom(op), array, _] if op == "select" => return Some(array),
+                    // an array applied to an index
+                    [array @ SExpr::List(_), _]
+                        if !matches!(head(array), Some("_") | Some("as")) =>
+                    {
+                        return Some(array)
+                    },
+                    [array @ SExpr::Atom(name), _] if self.is_memory(name) => return Some(array),
+                    _ => stack.extend(items.iter().rev()),
+                },
+            }
+        }
+        None
+    }
+
+    fn eval(&mut self, expr: &SExpr) -> Result<Term<B>> {
+        if self.depth >= MAX_DEPTH {
+            return Err(too_deep());
+        }
+        self.depth += 1;
+        let result = self.eval_nested(expr);
+        self.depth -= 1;
+        result
+    }
+
+    fn eval_nested(&mut self, expr: &SExpr) -> Result<Term<B>> {
+        match expr {
+            SExpr::Atom(atom) => self.eval_atom(atom),
+            SExpr::List(items) => match items.as_slice() {
+                [] => Err(malformed("empty list")),
+                [SExpr::Atom(underscore), SExpr::Atom(value), SExpr::Atom(width)]
+                    if underscore == "_" && value.starts_with("bv") =>
+                {
+                    let width = parse_index(width)?;
+                    Ok(Term::BV(B::BV::from_dec_str(
+                        self.solver.clone(),
+                        &value[2 ..],
+                        width,
+                    )))
+                },
+                [SExpr::Atom(op), SExpr::List(bindings), body] if op == "let" => {
+                    let mut scope = HashMap::new();
+                    for binding in bindings {
+                        match binding {
+                            SExpr::List(binding) => match binding.as_slice() {
+                                [SExpr::Atom(name), term] => {
+                                    scope.insert(name.clone(), self.eval(term)?);
+                                },
+                                _ => return Err(unsupported(expr)),
+                            },
+                            _ => return Err(unsupported(expr)),
+                        }
+                    }
+                    self.scopes.push(scope);
+                    let result = self.eval(body);
+                    self.scopes.pop();
+                    result
+                },
+                [SExpr::List(indexed), args @ ..] if head(&items[0]) == Some("_") => {
+                    let args = self.eval_bv_args(args)?;
+                    self.apply_indexed(indexed, &args).map(Term::BV)
+                },
+                [SExpr::List(as_const), value] if head(&items[0]) == Some("as") => {
+                    match as_const.as_slice() {
+                        [_, SExpr::Atom(constant), sort] if constant == "const" => {
+                            let value = self.eval_bv(value)?;
+                            self.const_array(sort, &value)
+                        },
+                        _ => Err(unsupported(expr)),
+                    }
+                },
+                [array @ SExpr::List(_), index] => {
+                    let array = self.eval(array)?;
+                    let index = self.eval_bv(index)?;
+                    self.select(array, &index)
+                },
+                [SExpr::Atom(op), args @ ..] => {
+                    let args = args
+                        .iter()
+                        .map(|arg| self.eval(arg))
+                        .collect::<Result<Vec<_>>>()?;
+                    self.apply_term(op, args)
+                },
+                _ => Err(unsupported(expr)),
+            },
+        }
+    }
+
+    fn eval_atom(&self, atom: &str) -> Result<Term<B>> {
+        if atom == "true" {
+            Ok(Term::BV(B::BV::from_bool(self.solver.clone(), true)))
+        } else if atom == "false" {
+            Ok(Term::BV(B::BV::from_bool(self.solver.clone(), false)))
+        } else if let Some(bits) = atom.strip_prefix("#b") {
+            if bits.is_empty() || !bits.chars().all(|c| c == '0' || c == '1') {
+                return Err(malformed(&format!("invalid binary literal {:?}", atom)));
+            }
+            Ok(Term::BV(B::BV::from_binary_str(self.solver.clone(), bits)))
+        } else if let Some(digits) = atom.strip_prefix("#x") {
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(malformed(&format!("invalid hex literal {:?}", atom)));
+            }
+            Ok(Term::BV(B::BV::from_hex_str(
+                self.solver.clone(),
+                digits,
+                4 * digits.len() as u32,
+            )))
+        } else {
+            self.lookup(atom)
+        }
+    }
+
+    fn eval_bv_args(&mut self, args: &[SExpr]) -> Result<Vec<B::BV>> {
+        args.iter().map(|arg| self.eval_bv(arg)).collect()
+    }
+
+    /// Apply an operator which may take arrays (or be an array itself) to
+    /// the given arguments
+    fn apply_term(&self, op: &str, args: Vec<Term<B>>) -> Result<Term<B>> {
+        match (op, args.as_slice()) {
+            ("select", [array, Term::BV(index)]) => self.select(array.clone(), index),
+            ("store", [Term::Memory(mem), Term::BV(index), Term::BV(value)]) => {
+                let mut mem = mem.clone();
+                let address = address(&self.solver, index, value.get_width())?;
+                mem.write_non_null(&address, value.clone())?;
+                Ok(Term::Memory(mem))
+            },
+            ("ite", [Term::BV(cond), Term::Memory(a), Term::Memory(b)]) => {
+                check_width(cond, 1)?;
+                let mut mem = a.clone();
+                mem.merge(b.clone(), cond);
+                Ok(Term::Memory(mem))
+            },
+            (_, [Term::BV(index)]) if self.is_memory(op) => self.select(self.lookup(op)?, index),
+            _ => {
+                let args = args
+                    .into_iter()
+                    .map(|arg| match arg {
+                        Term::BV(bv) => Ok(bv),
+                        Term::Memory(_) => Err(Error::UnsupportedInstruction(format!(
+                            "dumped constraint applies {:?} to an array",
+                            op
+                        ))),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                self.apply(op, &args).map(Term::BV)
+            },
+        }
+    }
+
+    fn select(&self, array: Term<B>, index: &B::BV) -> Result<Term<B>> {
+        match array {
+            Term::Memory(mem) => {
+                let bits = cell_bits(index.get_width())?;
+                let address = address(&self.solver, index, bits)?;
+                Ok(Term::BV(mem.read_non_null(&address, bits)?))
+            },
+            Term::BV(_) => Err(Error::UnsupportedInstruction(
+                "dumped constraint applies an uninterpreted function".to_owned(),
+            )),
+        }
+    }
+
+    fn const_array(&self, sort: &SExpr, value: &B::BV) -> Result<Term<B>> {
+        match parse_sort(sort)? {
+            Sort::Array(index, element) => {
+                check_layout(index, element)?;
+                check_width(value, element)?;
+                if value.as_u64() != Some(0) {
+                    return Err(Error::UnsupportedInstruction(
+                        "dumped constraint contains an array whose elements are all a nonzero value"
+                            .to_owned(),
+                    ));
+                }
+                Ok(Term::Memory(B::Memory::new_zero_initialized(
+                    self.solver.clone(),
+                    self.null_detection,
+                    None,
+                )))
+            },
+            Sort::BV(_) => Err(unsupported(sort)),
+        }
+    }
+
+    fn apply_indexed(&self, indexed: &[SExpr], args: &[B::BV]) -> Result<B::BV> {
+        let (op, indices) = match indexed {
+            [SExpr::Atom(underscore), SExpr::Atom(op), indices @ ..] if underscore == "_" => {
+                let indices = indices
+                    .iter()
+                    .map(|index| match index {
+                        SExpr::Atom(index) => parse_index(index),
+                        _ => Err(malformed("non-numeric index")),
+                    })
+                    .collect::<Result<Vec<u32>>>()?;
+                (op.as_str(), indices)
+            },
+            _ => return Err(unsupported(&SExpr::List(indexed.to_vec()))),
+        };
+        let arg = match args {
+            [arg] => arg,
+            _ => return Err(malformed(&format!("expected one argument to {:?}", op))),
+        };
+        let width = arg.get_width();
+        match (op, indices.as_slice()) {
+            ("extract", &[high, low]) => {
+                if low > high || high >= width {
+                    return Err(malformed("extract indices out of range"));
+                }
+                Ok(arg.slice(high, low))
+            },
+            ("zero_extend", &[n]) => Ok(arg.zext(n)),
+            ("sign_extend", &[n]) => Ok(arg.sext(n)),
+            ("repeat", &[n]) if n > 0 => Ok(arg.repeat(n)),
+            ("rotate_left", &[n]) => {
+                Ok(arg.rol(&B::BV::from_u32(self.solver.clone(), n % width, width)))
+            },
+            ("rotate_right", &[n]) => {
+                Ok(arg.ror(&B::BV::from_u32(self.solver.clone(), n % width, width)))
+            },
+            _ => Err(unsupported(&SExpr::List(indexed.to_vec()))),
+        }
+    }
+
+    fn apply(&self, op: &str, args: &[B::BV]) -> Result<B::BV> {
+        match (op, args) {
+            ("not", [a]) | ("bvnot", [a]) => Ok(a.not()),
+            ("bvneg", [a]) => Ok(a.neg()),
+            ("ite", [cond, a, b]) => {
+                check_width(cond, 1)?;
+                check_same_widths(&[a.clone(), b.clone()])?;
+                Ok(cond.cond_bv(a, b))
+            },
+            ("=", _) | ("bvcomp", _) => self.chain(args, |a, b| a._eq(b)),
+            ("distinct", _) => {
+                check_same_widths(args)?;
+                let mut result = B::BV::from_bool(self.solver.clone(), true);
+                for (i, a) in args.iter().enumerate() {
+                    for b in &args[i + 1 ..] {
+                        result = result.and(&a._ne(b));
+                    }
+                }
+                Ok(result)
+            },
+            ("=>", [_, _, ..]) => {
+                check_same_widths(args)?;
+                let (last, rest) = args.split_last().unwrap();
+                Ok(rest
+                    .iter()
+                    .rev()
+                    .fold(last.clone(), |acc, a| a.implies(&acc)))
+            },
+            ("and", _) | ("bvand", _) => self.fold(args, |a, b| a.and(b)),
+            ("or", _) | ("bvor", _) => self.fold(args, |a, b| a.or(b)),
+            ("xor", _) | ("bvxor", _) => self.fold(args, |a, b| a.xor(b)),
+            ("bvadd", _) => self.fold(args, |a, b| a.add(b)),
+            ("bvmul", _) => self.fold(args, |a, b| a.mul(b)),
+            ("concat", [_, _, ..]) => Ok(args[1 ..]
+                .iter()
+                .fold(args[0].clone(), |acc, a| acc.concat(a))),
+            ("bvnand", [a, b]) => binary(a, b, |a, b| a.nand(b)),
+            ("bvnor", [a, b]) => binary(a, b, |a, b| a.nor(b)),
+            ("bvxnor", [a, b]) => binary(a, b, |a, b| a.xnor(b)),
+            ("bvsub", [a, b]) => binary(a, b, |a, b| a.sub(b)),
+            ("bvudiv", [a, b]) => binary(a, b, |a, b| a.udiv(b)),
+            ("bvurem", [a, b]) => binary(a, b, |a, b| a.urem(b)),
+            ("bvsdiv", [a, b]) => binary(a, b, |a, b| a.sdiv(b)),
+            ("bvsrem", [a, b]) => binary(a, b, |a, b| a.srem(b)),
+            ("bvsmod", [a, b]) => binary(a, b, |a, b| a.smod(b)),
+            ("bvshl", [a, b]) => binary(a, b, |a, b| a.sll(b)),
+            ("bvlshr", [a, b]) => binary(a, b, |a, b| a.srl(b)),
+            ("bvashr", [a, b]) => binary(a, b, |a, b| a.sra(b)),
+            ("bvult", [a, b]) => binary(a, b, |a, b| a.ult(b)),
+            ("bvule", [a, b]) => binary(a, b, |a, b| a.ulte(b)),
+            ("bvugt", [a, b]) => binary(a, b, |a, b| a.ugt(b)),
+            ("bvuge", [a, b]) => binary(a, b, |a, b| a.ugte(b)),
+            ("bvslt", [a, b]) => binary(a, b, |a, b| a.slt(b)),
+            ("bvsle", [a, b]) => binary(a, b, |a, b| a.slte(b)),
+            ("bvsgt", [a, b]) => binary(a, b, |a, b| a.sgt(b)),
+            ("bvsge", [a, b]) => binary(a, b, |a, b| a.sgte(b)),
+            _ => Err(Error::UnsupportedInstruction(format!(
+                "dumped constraint uses operator {:?} with {} argument(s)",
+                op,
+                args.len()
+            ))),
+        }
+    }
+
+    /// Apply a left-associative operator to two or more arguments
+    fn fold(&self, args: &[B::BV], f: impl Fn(&B::BV, &B::BV) -> B::BV) -> Result<B::BV> {
+        if args.len() < 2 {
+            return Err(malformed("expected at least two arguments"));
+        }
+        check_same_widths(args)?;
+        Ok(args[1 ..].iter().fold(args[0].clone(), |acc, a| f(&acc, a)))
+    }
+
+    /// Apply a chainable operator (like `=`) to two or more arguments
+    fn chain(&self, args: &[B::BV], f: impl Fn(&B::BV, &B::BV) -> B::BV) -> Result<B::BV> {
+        if args.len() < 2 {
+            return Err(malformed("expected at least two arguments"));
+        }
+        check_same_widths(args)?;
+        Ok(args
+            .windows(2)
+            .map(|pair| f(&pair[0], &pair[1]))
+            .fold(B::BV::from_bool(self.solver.clone(), true), |acc, b| {
+                acc.and(&b)
+            }))
+    }
+}
+
+/// The 64-bit address of the array element at `index`, for elements of
+/// `bits` bits
+fn address<V: BV>(solver: &V::SolverRef, index: &V, bits: u32) -> Result<V> {
+    let index_width = index.get_width();
+    check_layout(index_width, bits)?;
+    Ok(if index_width == 64 {
+        index.clone()
+    } else {
+        index.concat(&V::zero(solver.clone(), 64 - index_width))
+    })
+}
+
+fn binary<V: BV>(a: &V, b: &V, f: impl Fn(&V, &V) -> V) -> Result<V> {
+    check_same_widths(&[a.clone(), b.clone()])?;
+    Ok(f(a, b))
+}
+
+// the solver would abort on mismatched widths, so we check them ourselves
+fn check_same_widths<V: BV>(args: &[V]) -> Result<()> {
+    match args.split_first() {
+        Some((first, rest)) if rest.iter().any(|a| !a.has_same_width(first)) => {
+            Err(malformed("operands have mismatched widths"))
+        },
+        _ => Ok(()),
+    }
+}
+
+fn check_width<V: BV>(bv: &V, width: u32) -> Result<()> {
+    if bv.get_width() == width {
+        Ok(())
+    } else {
+        Err(malformed(&format!(
+            "expected a term of width {}, but got width {}",
+            width,
+            bv.get_width()
+        )))
+    }
+}
+
+fn parse_index(index: &str) -> Result<u32> {
+    index
+        .parse()
+        .map_err(|_| malformed(&format!("invalid numeral {:?}", index)))
+}
+
+fn unsupported(expr: &SExpr) -> Error {
+    Error::UnsupportedInstruction(format!("serialized constraint contains {:?}", expr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_free_symbols() -> Result<()> {
+        let exprs = parse(
+            "(declare-fun x () (_ BitVec 8))\n(let ((_let0 (bvadd x |y z|))) (bvult _let0 ((_ extract 7 0) #x0000)))",
+        )?;
+        assert_eq!(exprs.len(), 2);
+        assert!(is_ignored_command(&exprs[0]));
+        assert!(!is_ignored_command(&exprs[1]));
+        assert_eq!(
+            free_symbols(&exprs[1 ..]),
+            vec!["x".to_owned(), "y z".to_owned()]
+        );
+        assert!(parse("(bvadd x").is_err());
+        assert!(parse("x)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn term_count() -> Result<()> {
+        // `bvadd`, `x`, `y`, `bvult`, `_let0`, `extract`, `#x0000`
+        let exprs = parse("(let ((_let0 (bvadd x y))) (bvult _let0 ((_ extract 7 0) #x0000)))")?;
+        assert_eq!(super::term_count(&exprs), 7);
+        let exprs = parse("(define-fun a () (_ BitVec 64) (bvadd x x)) (assert (= a x))")?;
+        assert_eq!(super::term_count(&exprs), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn bytes_round_trip() -> Result<()> {
+        let dump = Dump::parse(
+            "(set-logic QF_ABV)\n(declare-fun x () (_ BitVec 64))\n(declare-fun v3 () (_ BitVec 8))\n(declare-fun mem () (Array (_ BitVec 61) (_ BitVec 64)))\n(define-fun $e5 () (_ BitVec 64) (bvadd x x))\n(assert (= $e5 x))\n(assert (= v3 ((_ extract 7 0) (select mem ((_ extract 63 3) x)))))\n(check-sat)\n(exit)\n",
+            |name| name != "v3",
+            vec!["v3".to_owned()],
+            vec![("i".to_owned(), "m".to_owned())],
+            vec![("y".to_owned(), "s".to_owned())],
+        )?;
+        assert_eq!(dump.commands.len(), 6); // without `set-logic`, `check-sat`, and `exit`
+        assert_eq!(dump.anonymous, vec!["v3".to_owned()]);
+        let bytes = dump.to_bytes();
+        assert_eq!(Dump::from_bytes(&bytes)?, dump);
+        assert!(Dump::from_bytes(&bytes[.. bytes.len() - 1]).is_err());
+        assert!(Dump::from_bytes(b"not constraints").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn too_deeply_nested() -> Result<()> {
+        // rejected instead of overflowing the stack
+        let text = "(".repeat(100_000) + &")".repeat(100_000);
+        assert!(parse(&text).is_err());
+        let mut deep = SExpr::Atom("x".to_owned());
+        for _ in 0 ..= MAX_DEPTH {
+            deep = SExpr::List(vec![SExpr::Atom("bvnot".to_owned()), deep]);
+        }
+        let dump = Dump {
+            commands: vec![SExpr::List(vec![SExpr::Atom("assert".to_owned()), deep])],
+            anonymous: Vec::new(),
+            bvs: Vec::new(),
+            mems: Vec::new(),
+            vars: Vec::new(),
+        };
+        assert!(Dump::from_bytes(&dump.to_bytes()).is_err());
+        Ok(())
+    }
+}
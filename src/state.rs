@@ -1,31 +1,49 @@
 use boolector::option::{BtorOption, ModelGen};
-use boolector::BVSolution;
+use boolector::{BVSolution, Btor};
 use either::Either;
 use itertools::Itertools;
 use llvm_ir::*;
 use log::{debug, info, warn};
 use reduce::Reduce;
-use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
+use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
-use crate::alloc::Alloc;
+use crate::alloc::{bytes_rounded_up, Alloc, AllocEvent, AllocationInfo};
 use crate::backend::*;
-use crate::config::{Config, NullPointerChecking};
+use crate::config::{Config, NullPointerChecking, SearchStrategy, ShiftSemantics};
 use crate::demangling::Demangling;
 use crate::error::*;
 use crate::function_hooks::{self, FunctionHooks};
 use crate::global_allocations::*;
 use crate::hooks;
 use crate::layout::*;
+use crate::memory::MemOp;
+use crate::pointer::Pointer;
 use crate::project::Project;
+use crate::smtlib;
 use crate::solver_utils::{self, PossibleSolutions};
+use crate::suspend::{
+    Collected,
+    Snapshot,
+    SuspendedBacktrackPoint,
+    SuspendedFrame,
+    SuspendedLocation,
+    SuspendedName,
+    SuspendedState,
+};
+use crate::testcase::{TestCase, TestCaseAllocation, TestCaseInput};
 use crate::varmap::{RestoreInfo, VarMap};
 use crate::watchpoints::{Watchpoint, Watchpoints};
 
+/// Maximum total width of the bound variables of a quantified constraint; see
+/// [`State.assert_forall()`](struct.State.html#method.assert_forall).
+const MAX_QUANTIFIED_BITS: u32 = 16;
+
 /// A `State` describes the full program state at a given moment during symbolic
 /// execution.
 #[derive(Clone)]
@@ -41,6 +59,16 @@ pub struct State<'p, B: Backend> {
     varmap: VarMap<B::BV>,
     mem: RefCell<B::Memory>,
     alloc: Alloc,
+    /// `Memory`s for address spaces other than 0, which are created on first
+    /// use; see `read_in_space()`
+    space_mems: RefCell<HashMap<u32, B::Memory>>,
+    /// Allocators for address spaces other than 0; see `allocate_in_space()`.
+    /// Like `alloc`, these persist across backtracking.
+    space_allocs: HashMap<u32, Alloc>,
+    /// The `alloc` as it was after allocating globals, for `reset()`
+    initial_alloc: Alloc,
+    /// The location the `State` was created at, for `reset()`
+    start_loc: Location<'p>,
     global_allocations: GlobalAllocations<'p, B>,
     /// Separate from the user-defined hooks in the `config`, these are built-in
     /// hooks for LLVM intrinsics. They can be overridden by hooks in the
@@ -57,6 +85,16 @@ pub struct State<'p, B: Backend> {
     /// These backtrack points are places where execution can be resumed later
     /// (efficiently, thanks to the incremental solving capabilities of Boolector).
     backtrack_points: RefCell<Vec<BacktrackPoint<'p, B>>>,
+    /// For each currently open backtracking scope (see `begin_scope()`), the
+    /// number of backtracking points which existed when the scope began.
+    /// The last entry is the innermost scope.
+    backtrack_scopes: Vec<usize>,
+    /// Under a `SearchStrategy` other than `DepthFirst`, the current path's
+    /// constraints live in a single solver context which is replaced on each
+    /// revert. This is `None` until that context is first pushed, and then
+    /// the number of `asserted_constraints` which were asserted before it
+    /// (and thus are shared by all paths).
+    search_base_len: Cell<Option<usize>>,
     /// Log of the basic blocks which have been executed to get to this point
     path: Vec<PathEntry<'p>>,
     /// Memory watchpoints (segments of memory to log reads/writes of).
@@ -82,6 +120,253 @@ pub struct State<'p, B: Backend> {
     /// anyway, and function pointers _probably_ resolve to the same value on
     /// multiple paths.
     function_ptr_cache: HashMap<Location<'p>, u64>,
+    /// Function addresses registered by the user with
+    /// `register_function_address()`, in addition to the addresses we assign to
+    /// each `Function` in the `Project` ourselves.
+    ///
+    /// These persist across backtracking.
+    registered_function_addresses: HashMap<u64, &'p Function>,
+    /// Unsupported instructions, terminators, and operands we've encountered,
+    /// in the order we first encountered each kind. See `unsupported_report()`.
+    ///
+    /// This persists across backtracking.
+    unsupported: RefCell<Vec<UnsupportedItem>>,
+    /// Map from the address of each array allocated with `allocate_array()` to
+    /// the (possibly symbolic) size of the array in bytes.
+    ///
+    /// Like allocations themselves, these persist across backtracking.
+    array_sizes: HashMap<u64, B::BV>,
+    /// Map from the address of each region allocated with `allocate_zeroed()`
+    /// to the size of the region in bytes.
+    ///
+    /// Like allocations themselves, these persist across backtracking, unless
+    /// `Config.reuse_freed_allocations` is set: then `free()` removes regions
+    /// on the current path, and backtracking restores them.
+    zeroed_regions: HashMap<u64, u64>,
+    /// Constraints which have been asserted with `State::assert()` on the
+    /// current path, in the order they were asserted.
+    /// See `constraints_mentioning()`.
+    asserted_constraints: RefCell<Vec<B::BV>>,
+    /// How to handle reads of uninitialized memory; see `set_uninit_handler()`
+    uninit_handler: UninitHandler<'p, B>,
+    /// Concrete addresses of all the bytes written on the current path (since
+    /// a handler was installed with `set_uninit_handler()` or tracking was
+    /// enabled with `enable_write_tracking()`), or `None` if we aren't
+    /// tracking this
+    initialized_bytes: RefCell<Option<HashSet<u64>>>,
+    /// Base addresses of the allocations which have been read from or
+    /// written to on the current path (since tracking was enabled with
+    /// `enable_access_tracking()`), or `None` if we aren't tracking this
+    touched_allocs: RefCell<Option<HashSet<u64>>>,
+    /// Whether `enable_write_tracking()` has been called, in which case we
+    /// keep tracking `initialized_bytes` even without an uninit handler
+    write_tracking: bool,
+    /// Variadic arguments set with `set_varargs()` which haven't yet been
+    /// consumed by `va_arg()`
+    varargs: VecDeque<B::BV>,
+    /// Map from loop ID to the number of times that loop's back edge has been
+    /// taken on the current path, as recorded by `on_loop_back_edge()`
+    back_edge_counts: HashMap<usize, usize>,
+    /// Pointers which `assume_non_null()` has constrained to be non-NULL on
+    /// the current path, by AST node ID. We keep the `BV`s themselves so that
+    /// their IDs can't be reused for other nodes.
+    non_null: HashMap<i32, B::BV>,
+    /// Allocations which `inttoptr_checked()` determined pointers to point
+    /// into on the current path, by AST node ID, for `pointer_provenance()`.
+    /// As with `non_null`, we keep the `BV`s themselves so that their IDs
+    /// can't be reused for other nodes.
+    provenance: HashMap<i32, (B::BV, AllocationInfo)>,
+    /// Metadata attached to variables with `set_var_metadata()`: map from
+    /// function name and variable name to a map of keys to values.
+    ///
+    /// This persists across backtracking.
+    var_metadata: HashMap<(String, Name), HashMap<String, String>>,
+    /// Solver symbols of the variables marked with `mark_tainted()`.
+    ///
+    /// This persists across backtracking.
+    tainted_symbols: HashSet<String>,
+    /// Basic blocks marked with `mark_block_visited()`.
+    ///
+    /// This persists across backtracking.
+    coverage: CoverageMap,
+    /// If the most recent query made with `sat()` or
+    /// `sat_with_extra_constraints()` was inconclusive, the reason why
+    reason_unknown: RefCell<Option<String>>,
+    /// If `enable_model_capture()` has been called, the model found by the
+    /// most recent model-generating query made with `get_a_solution_for_bv()`,
+    /// for `eval_in_last_model()`: each named bitvector variable, with its
+    /// value in that model
+    #[allow(clippy::type_complexity)]
+    last_model: RefCell<Option<Vec<(B::BV, BVSolution)>>>,
+    /// Whether `enable_model_capture()` has been called
+    capture_models: bool,
+    /// Number of variables created with `with_temp_var()`, used to give each
+    /// one a unique `Name`
+    temp_vars_created: usize,
+    /// Number of regions allocated with `allocate_symbolic()`, used to give
+    /// their bytes unique symbols even when an address is reused.
+    ///
+    /// This persists across backtracking, as the symbols remain in the solver.
+    symbolic_allocations_created: usize,
+    /// Whether solver queries are preprocessed more aggressively; see
+    /// `set_presimplify()`
+    presimplify: bool,
+}
+
+/// The possible values of a `BV` of width 1 under the current constraints.
+/// See [`State.bool_value()`](struct.State.html#method.bool_value).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BoolValue {
+    /// The value must be true
+    AlwaysTrue,
+    /// The value must be false
+    AlwaysFalse,
+    /// The value can be either true or false
+    Either,
+}
+
+/// Measures of how difficult a query on the current path may be for the
+/// solver. See [`State.query_complexity()`](struct.State.html#method.query_complexity).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct QueryComplexity {
+    /// The number of constraints asserted on the current path
+    pub constraints: usize,
+    /// The number of distinct variables the constraints refer to
+    pub variables: usize,
+    /// The total number of terms (variables, constants, and operations)
+    /// in the constraints
+    pub nodes: usize,
+}
+
+/// How `haybale` should handle reads of uninitialized memory.
+/// See [`State.set_uninit_handler()`](struct.State.html#method.set_uninit_handler).
+#[derive(Clone)]
+pub enum UninitHandler<'p, B: Backend> {
+    /// Don't treat reads of uninitialized memory specially: they just produce
+    /// unconstrained values. This is the default.
+    Ignore,
+    /// Reads of uninitialized memory result in an `Error::UninitializedRead`.
+    Error,
+    /// Log a warning for each read of uninitialized memory, but otherwise
+    /// treat it normally: it produces an unconstrained value.
+    Warn,
+    /// Reads of uninitialized memory produce zero.
+    Zero,
+    /// Call the given function with the address and size (in bits) of each
+    /// read of uninitialized memory; the `BV` it returns (which must be of the
+    /// given size) is used as the value read, or if it returns an `Err`,
+    /// `haybale` will propagate it accordingly.
+    #[allow(clippy::type_complexity)]
+    Custom(Rc<dyn Fn(&State<'p, B>, &B::BV, u32) -> Result<B::BV> + 'p>),
+}
+
+/// The operations an atomic read-modify-write can perform, as in the LLVM
+/// `atomicrmw` instruction.
+/// See [`State.atomic_rmw()`](struct.State.html#method.atomic_rmw).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AtomicOp {
+    /// Replace the old value with the operand
+    Xchg,
+    Add,
+    Sub,
+    And,
+    Nand,
+    Or,
+    Xor,
+    /// Signed maximum
+    Max,
+    /// Signed minimum
+    Min,
+    /// Unsigned maximum
+    UMax,
+    /// Unsigned minimum
+    UMin,
+}
+
+/// Integer binary operations, as in the corresponding LLVM instructions.
+/// See [`State.try_fold_binop()`](struct.State.html#method.try_fold_binop)
+/// and [`State.vector_binop_sat()`](struct.State.html#method.vector_binop_sat).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    UDiv,
+    SDiv,
+    URem,
+    SRem,
+    And,
+    Or,
+    Xor,
+    Shl,
+    LShr,
+    AShr,
+}
+
+/// What a driver should do on taking a loop back edge.
+/// See [`State.on_loop_back_edge()`](struct.State.html#method.on_loop_back_edge).
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LoopAction {
+    /// Continue into another iteration of the loop
+    Continue,
+    /// The back edge has been taken the configured
+    /// [`max_unroll`](config/struct.Config.html#structfield.max_unroll)
+    /// times on the current path; stop unrolling the loop
+    StopUnrolling,
+}
+
+/// One kind of unsupported LLVM item (instruction, terminator, constant, or
+/// operand) encountered during symbolic execution, along with the number of
+/// times it was encountered.
+/// See [`State.unsupported_report()`](struct.State.html#method.unsupported_report).
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct UnsupportedItem {
+    /// Describes the kind of item, e.g., `"instruction FAdd"` or `"constant FAdd"`
+    pub kind: String,
+    /// Number of times an item of this kind was encountered
+    pub count: usize,
+}
+
+/// The basic blocks visited so far on any path, as recorded with
+/// [`State.mark_block_visited()`](struct.State.html#method.mark_block_visited).
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct CoverageMap {
+    /// Map from function name to the names of the visited blocks in that function
+    blocks: HashMap<String, HashSet<Name>>,
+}
+
+impl CoverageMap {
+    /// Has the given basic block in the given function been visited
+    pub fn is_visited(&self, funcname: &str, bbname: &Name) -> bool {
+        match self.blocks.get(funcname) {
+            Some(blocks) => blocks.contains(bbname),
+            None => false,
+        }
+    }
+
+    /// Get the names of the visited basic blocks in the given function, in no
+    /// particular order
+    pub fn visited_blocks<'a>(&'a self, funcname: &str) -> impl Iterator<Item = &'a Name> + 'a {
+        self.blocks.get(funcname).into_iter().flatten()
+    }
+
+    /// Get the total number of basic blocks visited, across all functions
+    pub fn num_visited(&self) -> usize {
+        self.blocks.values().map(HashSet::len).sum()
+    }
+
+    fn insert(&mut self, funcname: &str, bbname: &Name) {
+        self.blocks
+            .entry(funcname.to_owned())
+            .or_default()
+            .insert(bbname.clone());
+    }
+
+    fn union(&mut self, other: CoverageMap) {
+        for (funcname, blocks) in other.blocks {
+            self.blocks.entry(funcname).or_default().extend(blocks);
+        }
+    }
 }
 
 /// Describes a location in LLVM IR in a format more suitable for printing - for
@@ -257,6 +542,21 @@ impl<'p> Location<'p> {
             short_module_name, self.func.name, self.bb.name, self.instr
         )
     }
+
+    /// Format this `Location` compactly as `func:bb:index`, e.g.,
+    /// `foo:bb1:3`, for use in error messages. The terminator has index
+    /// `terminator`.
+    pub fn to_string_compact(&self) -> String {
+        let bbname = match &self.bb.name {
+            Name::Name(s) => s.clone(),
+            Name::Number(n) => format!("%{}", n),
+        };
+        let index = match self.instr {
+            BBInstrIndex::Instr(i) => i.to_string(),
+            BBInstrIndex::Terminator => "terminator".to_owned(),
+        };
+        format!("{}:{}:{}", self.func.name, bbname, index)
+    }
 }
 
 impl<'p> From<Location<'p>> for LocationDescription<'p> {
@@ -349,10 +649,96 @@ struct BacktrackPoint<'p, B: Backend> {
     /// pointer), so it's not a huge concern that we need a full copy here in
     /// order to revert later.
     mem: B::Memory,
+    /// The `Memory`s for address spaces other than 0 at the `BacktrackPoint`
+    space_mems: HashMap<u32, B::Memory>,
     /// The length of `path` at the `BacktrackPoint`.
     /// If we ever revert to this `BacktrackPoint`, we will truncate the `path` to
     /// its first `path_len` entries.
     path_len: usize,
+    /// The length of `asserted_constraints` at the `BacktrackPoint`.
+    /// If we ever revert to this `BacktrackPoint`, we will truncate the
+    /// `asserted_constraints` to their first `constraints_len` entries, as the
+    /// solver will have forgotten the rest.
+    constraints_len: usize,
+    /// The `initialized_bytes` at the `BacktrackPoint`
+    initialized_bytes: Option<HashSet<u64>>,
+    /// The `touched_allocs` at the `BacktrackPoint`
+    touched_allocs: Option<HashSet<u64>>,
+    /// The unconsumed `varargs` at the `BacktrackPoint`
+    varargs: VecDeque<B::BV>,
+    /// The `back_edge_counts` at the `BacktrackPoint`
+    back_edge_counts: HashMap<usize, usize>,
+    /// The `non_null` pointers at the `BacktrackPoint`
+    non_null: HashMap<i32, B::BV>,
+    /// The `provenance` records at the `BacktrackPoint`
+    provenance: HashMap<i32, (B::BV, AllocationInfo)>,
+    /// Under a `SearchStrategy` other than `DepthFirst`, a copy of the
+    /// `asserted_constraints` at the `BacktrackPoint`, which will be
+    /// re-asserted when reverting to it. `None` under `DepthFirst`, where
+    /// the solver's own contexts take care of this.
+    constraints: Option<Vec<B::BV>>,
+    /// If `Config.reuse_freed_allocations` is set, a copy of the `Alloc` at
+    /// the `BacktrackPoint`, as which allocations are free depends on the
+    /// path. Otherwise, `None`, as allocations persist across backtracking.
+    alloc: Option<Alloc>,
+    /// The `zeroed_regions` at the `BacktrackPoint`, saved along with the
+    /// `alloc`, as `free()` removes regions only when it may reuse them
+    zeroed_regions: Option<HashMap<u64, u64>>,
+}
+
+impl<'p, B: Backend> BacktrackPoint<'p, B> {
+    /// Adapt the `BacktrackPoint` to a new solver instance; see notes on
+    /// `VarMap::change_solver()`.
+    fn change_solver(&mut self, new_solver: B::SolverRef) {
+        let match_bv = |bv: &B::BV| new_solver.match_bv(bv).unwrap();
+        for frame in self.stack.iter_mut() {
+            frame.restore_info.change_solver(new_solver.clone());
+        }
+        self.constraint = match_bv(&self.constraint);
+        self.varmap.change_solver(new_solver.clone());
+        self.mem.change_solver(new_solver.clone());
+        for mem in self.space_mems.values_mut() {
+            mem.change_solver(new_solver.clone());
+        }
+        for bv in self.varargs.iter_mut() {
+            *bv = match_bv(bv);
+        }
+        self.non_null = rematch_by_id(&self.non_null, &new_solver, |bv| bv);
+        self.provenance = rematch_by_id(&self.provenance, &new_solver, |(bv, _)| bv);
+        if let Some(constraints) = self.constraints.as_mut() {
+            for bv in constraints.iter_mut() {
+                *bv = match_bv(bv);
+            }
+        }
+    }
+}
+
+/// Get the dumped value or memory with the given index, for `State::resume()`
+fn dumped<T: Clone>(items: &[T], index: usize, what: &str) -> Result<T> {
+    items.get(index).cloned().ok_or_else(|| {
+        Error::OtherError(format!(
+            "resume: malformed SuspendedState: no {} {}",
+            what, index
+        ))
+    })
+}
+
+/// Get the copy of `map` (whose entries are keyed by the AST node ID of a
+/// `BV` in each value, given by `bv_of`) for the new solver instance
+/// `new_solver`, with each of those `BV`s replaced by its copy in `new_solver`
+fn rematch_by_id<V: BV, T: Clone>(
+    map: &HashMap<i32, T>,
+    new_solver: &V::SolverRef,
+    mut bv_of: impl FnMut(&mut T) -> &mut V,
+) -> HashMap<i32, T> {
+    map.values()
+        .map(|val| {
+            let mut val = val.clone();
+            let bv = bv_of(&mut val);
+            *bv = new_solver.match_bv(bv).unwrap();
+            (bv.get_id(), val)
+        })
+        .collect()
 }
 
 impl<'p, B: Backend> fmt::Display for BacktrackPoint<'p, B> {
@@ -367,6 +753,78 @@ impl<'p, B: Backend> fmt::Display for BacktrackPoint<'p, B> {
     }
 }
 
+/// Iterator over the possible solutions for a `BV`; see
+/// [`State.solutions_iter()`](struct.State.html#method.solutions_iter).
+///
+/// Each solution found is excluded (in a solver context which is popped when
+/// the iterator is dropped) so that the next query finds a different one.
+struct SolutionsIter<'a, V: BV> {
+    solver: &'a V::SolverRef,
+    bv: V,
+    /// Set once there are no more solutions, or a query has failed
+    done: bool,
+}
+
+impl<'a, V: BV> SolutionsIter<'a, V> {
+    fn new(solver: &'a V::SolverRef, bv: V) -> Self {
+        solver.push(1);
+        solver.set_opt(BtorOption::ModelGen(ModelGen::All));
+        Self {
+            solver,
+            bv,
+            done: false,
+        }
+    }
+
+    fn next_solution(&self) -> Result<Option<u64>> {
+        if !solver_utils::sat(self.solver)? {
+            return Ok(None);
+        }
+        let solution = self.bv.get_a_solution()?.disambiguate();
+        // Constrain that the solution can't be `solution`, so that the next query finds a different one
+        self.bv
+            ._ne(&V::from_binary_str(
+                self.solver.clone(),
+                solution.as_01x_str(),
+            ))
+            .assert()?;
+        Ok(solution.as_u64())
+    }
+}
+
+impl<'a, V: BV> Iterator for SolutionsIter<'a, V> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.done {
+            return None;
+        }
+        match self.next_solution() {
+            Ok(Some(solution)) => Some(solution),
+            Ok(None) => {
+                self.done = true;
+                None
+            },
+            Err(e) => {
+                warn!(
+                    "solutions_iter: ending iteration early because of an error: {}",
+                    e
+                );
+                self.done = true;
+                None
+            },
+        }
+    }
+}
+
+impl<'a, V: BV> Drop for SolutionsIter<'a, V> {
+    fn drop(&mut self) {
+        self.solver.pop(1);
+        self.solver
+            .set_opt(BtorOption::ModelGen(ModelGen::Disabled));
+    }
+}
+
 impl<'p, B: Backend> State<'p, B>
 where
     B: 'p,
@@ -383,16 +841,12 @@ where
         let mut state = Self {
             cur_loc: start_loc.clone(),
             varmap: VarMap::new(solver.clone(), config.loop_bound),
-            mem: RefCell::new(Memory::new_uninitialized(
-                solver.clone(),
-                match config.null_pointer_checking {
-                    NullPointerChecking::Simple => true,
-                    NullPointerChecking::SplitPath => true,
-                    NullPointerChecking::None => false,
-                },
-                None,
-            )),
+            mem: RefCell::new(Self::new_memory(solver.clone(), &config)),
+            space_mems: RefCell::new(HashMap::new()),
+            space_allocs: HashMap::new(),
             alloc: Alloc::new(),
+            initial_alloc: Alloc::new(),
+            start_loc: start_loc.clone(),
             global_allocations: GlobalAllocations::new(),
             intrinsic_hooks: {
                 let mut intrinsic_hooks = FunctionHooks::new();
@@ -408,6 +862,7 @@ where
                     &hooks::intrinsics::symex_objectsize,
                 );
                 intrinsic_hooks.add("intrinsic: llvm.assume", &hooks::intrinsics::symex_assume);
+                intrinsic_hooks.add("intrinsic: llvm.expect", &hooks::intrinsics::symex_expect);
                 intrinsic_hooks.add(
                     "intrinsic: llvm.uadd.with.overflow",
                     &hooks::intrinsics::symex_uadd_with_overflow,
@@ -448,6 +903,21 @@ where
                     "intrinsic: llvm.ssub.sat",
                     &hooks::intrinsics::symex_ssub_sat,
                 );
+                intrinsic_hooks.add("intrinsic: llvm.abs", &hooks::intrinsics::symex_abs);
+                intrinsic_hooks.add("intrinsic: llvm.smax", &hooks::intrinsics::symex_smax);
+                intrinsic_hooks.add("intrinsic: llvm.smin", &hooks::intrinsics::symex_smin);
+                intrinsic_hooks.add("intrinsic: llvm.umax", &hooks::intrinsics::symex_umax);
+                intrinsic_hooks.add("intrinsic: llvm.umin", &hooks::intrinsics::symex_umin);
+                intrinsic_hooks.add(
+                    "intrinsic: llvm.umul.fix",
+                    &hooks::intrinsics::symex_umul_fix,
+                );
+                intrinsic_hooks.add(
+                    "intrinsic: llvm.smul.fix",
+                    &hooks::intrinsics::symex_smul_fix,
+                );
+                intrinsic_hooks.add("intrinsic: llvm.fshl", &hooks::intrinsics::symex_fshl);
+                intrinsic_hooks.add("intrinsic: llvm.fshr", &hooks::intrinsics::symex_fshr);
                 intrinsic_hooks.add(
                     "intrinsic: generic_stub_hook",
                     &function_hooks::generic_stub_hook,
@@ -457,9 +927,33 @@ where
             },
             stack: Vec::new(),
             backtrack_points: RefCell::new(Vec::new()),
+            backtrack_scopes: Vec::new(),
+            search_base_len: Cell::new(None),
             path: Vec::new(),
             mem_watchpoints: config.initial_mem_watchpoints.clone().into_iter().collect(),
             function_ptr_cache: HashMap::new(),
+            registered_function_addresses: HashMap::new(),
+            unsupported: RefCell::new(Vec::new()),
+            array_sizes: HashMap::new(),
+            zeroed_regions: HashMap::new(),
+            asserted_constraints: RefCell::new(Vec::new()),
+            uninit_handler: UninitHandler::Ignore,
+            initialized_bytes: RefCell::new(None),
+            touched_allocs: RefCell::new(None),
+            write_tracking: false,
+            varargs: VecDeque::new(),
+            back_edge_counts: HashMap::new(),
+            non_null: HashMap::new(),
+            provenance: HashMap::new(),
+            var_metadata: HashMap::new(),
+            tainted_symbols: HashSet::new(),
+            coverage: CoverageMap::default(),
+            reason_unknown: RefCell::new(None),
+            last_model: RefCell::new(None),
+            capture_models: false,
+            temp_vars_created: 0,
+            symbolic_allocations_created: 0,
+            presimplify: false,
 
             // listed last (out-of-order) so that they can be used above but moved in now
             solver,
@@ -509,7 +1003,7 @@ where
                 } else {
                     size_bits
                 };
-                let addr = state.allocate(size_bits as u64);
+                let addr = state.allocate(size_bits as u64).into_bv();
                 debug!("Allocated {:?} at {:?}", var.name, addr);
                 state
                     .global_allocations
@@ -543,9 +1037,86 @@ where
                 .allocate_function_hook((*hook).clone(), addr, addr_bv);
         }
         debug!("Done allocating global variables and functions");
+        state.initial_alloc = state.alloc.clone();
         state
     }
 
+    fn new_memory(solver: B::SolverRef, config: &Config<'p, B>) -> B::Memory {
+        Memory::new_uninitialized(solver, Self::null_detection(config), None)
+    }
+
+    /// Whether memories should check for NULL accesses under the given `Config`
+    fn null_detection(config: &Config<'p, B>) -> bool {
+        match config.null_pointer_checking {
+            NullPointerChecking::Simple => true,
+            NullPointerChecking::SplitPath => true,
+            NullPointerChecking::None => false,
+        }
+    }
+
+    fn new_space_memory(solver: B::SolverRef, space: u32) -> B::Memory {
+        // array symbols must be unique within the solver, but the solver may
+        // already have a memory for this space, e.g. from another path sharing
+        // the solver, or from a path we've since backtracked out of
+        let base_name = format!("mem_addrspace{}", space);
+        let name = std::iter::once(base_name.clone())
+            .chain((1 ..).map(|i| format!("{}_{}", base_name, i)))
+            .find(|name| Btor::get_array_by_symbol(&*solver, name).is_none())
+            .unwrap();
+        // the null pointer may be a valid address in address spaces other than 0
+        Memory::new_uninitialized(solver, false, Some(&name))
+    }
+
+    /// Reset the `State` to the condition it was in when it was created: at
+    /// the start of the function it was created for, with no variables, no
+    /// memory contents, no allocations other than those for globals, no
+    /// backtracking points, and no solver constraints.
+    ///
+    /// The `config`, installed hooks and handlers, and the allocated addresses
+    /// of globals and functions are kept, as is information which persists
+    /// across backtracking (e.g., `unsupported_report()` and `coverage()`).
+    /// This is cheaper than constructing a new `State`, as it avoids
+    /// allocating the `Project`'s globals again.
+    ///
+    /// The solver can't retract constraints, so this switches to a fresh
+    /// solver instance. `BV`s created before the reset must not be used with
+    /// this `State` afterwards.
+    pub fn reset(&mut self) {
+        let solver = B::SolverRef::new();
+        solver.set_opt(BtorOption::SolverTimeout(self.config.solver_query_timeout));
+        Self::apply_presimplify(&solver, self.presimplify);
+        self.varmap = VarMap::new(solver.clone(), self.config.loop_bound);
+        self.mem = RefCell::new(Self::new_memory(solver.clone(), &self.config));
+        self.alloc.restore(self.initial_alloc.clone());
+        self.space_mems.borrow_mut().clear();
+        self.space_allocs.clear();
+        self.global_allocations.reset(solver.clone());
+        self.cur_loc = self.start_loc.clone();
+        self.stack.clear();
+        self.backtrack_points.borrow_mut().clear();
+        self.backtrack_scopes.clear();
+        self.search_base_len.set(None);
+        self.path.clear();
+        self.array_sizes.clear();
+        self.zeroed_regions.clear();
+        self.asserted_constraints.borrow_mut().clear();
+        if let Some(initialized_bytes) = self.initialized_bytes.borrow_mut().as_mut() {
+            initialized_bytes.clear();
+        }
+        if let Some(touched_allocs) = self.touched_allocs.borrow_mut().as_mut() {
+            touched_allocs.clear();
+        }
+        self.varargs.clear();
+        self.back_edge_counts.clear();
+        self.non_null.clear();
+        self.provenance.clear();
+        self.var_metadata.clear();
+        self.tainted_symbols.clear();
+        self.reason_unknown.replace(None);
+        self.last_model.replace(None);
+        self.solver = solver;
+    }
+
     /// Fully duplicate the `State`. Unlike with `clone()`, the `State` this
     /// function returns will have a fully separate (fully duplicated) solver
     /// instance. (With `clone()`, the states will still share references to the
@@ -553,1611 +1124,9460 @@ where
     pub fn fork(&self) -> Self {
         let mut cloned = self.clone();
         let new_solver = cloned.solver.duplicate();
+        let match_bv = |bv: &B::BV| new_solver.match_bv(bv).unwrap();
         cloned.varmap.change_solver(new_solver.clone());
         cloned.mem.borrow_mut().change_solver(new_solver.clone());
+        for mem in cloned.space_mems.get_mut().values_mut() {
+            mem.change_solver(new_solver.clone());
+        }
         cloned.global_allocations.change_solver(new_solver.clone());
+        for frame in cloned.stack.iter_mut() {
+            frame.restore_info.change_solver(new_solver.clone());
+        }
+        for bp in cloned.backtrack_points.get_mut().iter_mut() {
+            bp.change_solver(new_solver.clone());
+        }
+        for size_bytes in cloned.array_sizes.values_mut() {
+            *size_bytes = match_bv(size_bytes);
+        }
+        for constraint in cloned.asserted_constraints.get_mut().iter_mut() {
+            *constraint = match_bv(constraint);
+        }
+        for bv in cloned.varargs.iter_mut() {
+            *bv = match_bv(bv);
+        }
+        cloned.non_null = rematch_by_id(&self.non_null, &new_solver, |bv| bv);
+        cloned.provenance = rematch_by_id(&self.provenance, &new_solver, |(bv, _)| bv);
+        if let Some(model) = cloned.last_model.get_mut().as_mut() {
+            for (var, _) in model.iter_mut() {
+                *var = match_bv(var);
+            }
+        }
         cloned.solver = new_solver;
         cloned
     }
 
-    /// Returns `true` if current constraints are satisfiable, `false` if not.
+    /// Get a reference to the solver instance being used by this `State`.
     ///
-    /// Returns `Error::SolverError` if the query failed (e.g., was interrupted or timed out).
-    pub fn sat(&self) -> Result<bool> {
-        solver_utils::sat(&self.solver)
+    /// This is equivalent to `state.solver.clone()`. The returned `SolverRef`
+    /// doesn't borrow from the `State`, so it can be used to create `BV`s (for
+    /// instance, in helper functions) even while the `State` is mutably
+    /// borrowed. Any `BV`s created with it can be used freely with this
+    /// `State`.
+    pub fn get_solver(&self) -> B::SolverRef {
+        self.solver.clone()
     }
 
-    /// Returns `true` if the current constraints plus the given additional constraints
-    /// are together satisfiable, or `false` if not.
+    /// Get a new solver instance, separate from this `State`'s, which has the
+    /// same constraints as the current path.
     ///
-    /// Returns `Error::SolverError` if the query failed (e.g., was interrupted or timed out).
-    ///
-    /// Does not permanently add the given constraints to the solver.
-    pub fn sat_with_extra_constraints<'b>(
-        &'b self,
-        constraints: impl IntoIterator<Item = &'b B::BV>,
-    ) -> Result<bool> {
-        solver_utils::sat_with_extra_constraints(&self.solver, constraints)
+    /// This is lighter than `fork()` when only the constraints are needed (and
+    /// not the variables or memory): for instance, to run independent queries
+    /// without disturbing this `State`'s solver. To use a `BV` from this
+    /// `State` with the new solver, get its copy with `SolverRef::match_bv()`.
+    pub fn clone_constraints(&self) -> B::SolverRef {
+        self.solver.duplicate()
     }
 
-    /// Returns `true` if under the current constraints, `a` and `b` must have the
-    /// same value. Returns `false` if `a` and `b` may have different values. (If the
-    /// current constraints are themselves unsatisfiable, that will result in
-    /// `true`.)
+    /// Get a summary of the unsupported LLVM items (instructions, terminators,
+    /// constants, and operands) encountered so far, with the number of times
+    /// each kind was encountered. Items are listed in the order each kind was
+    /// first encountered.
     ///
-    /// A common use case for this function is to test whether some `BV` must be
-    /// equal to a given concrete value. You can do this with something like
-    /// `state.bvs_must_be_equal(bv, &state.bv_from_u64(...))`.
+    /// This summary covers all paths explored so far; it is not reset by
+    /// backtracking.
+    pub fn unsupported_report(&self) -> Vec<UnsupportedItem> {
+        self.unsupported.borrow().clone()
+    }
+
+    /// Record that the basic block named `bbname` in the function named
+    /// `funcname` has been visited. The engine doesn't call this itself; a
+    /// driver wanting coverage information should call it each time
+    /// execution enters a block.
+    pub fn mark_block_visited(&mut self, funcname: &str, bbname: &Name) {
+        self.coverage.insert(funcname, bbname);
+    }
+
+    /// Get the basic blocks marked with `mark_block_visited()` so far.
     ///
-    /// This function and `bvs_can_be_equal()` are both more efficient than
-    /// `get_a_solution()` or `get_possible_solutions()`-type functions, as they do
-    /// not require full model generation. You should prefer this function or
-    /// `bvs_can_be_equal()` if they are sufficient for your needs.
-    pub fn bvs_must_be_equal(&self, a: &B::BV, b: &B::BV) -> Result<bool> {
-        solver_utils::bvs_must_be_equal(&self.solver, a, b)
+    /// This covers all paths explored so far; it is not reset by
+    /// backtracking.
+    pub fn coverage(&self) -> &CoverageMap {
+        &self.coverage
     }
 
-    /// Returns `true` if under the current constraints, `a` and `b` can have the
-    /// same value. Returns `false` if `a` and `b` cannot have the same value. (If
-    /// the current constraints are themselves unsatisfiable, that will also result
-    /// in `false`.)
+    /// Record that we encountered an unsupported `item`, and return the
+    /// corresponding `Error::UnsupportedInstruction`.
     ///
-    /// A common use case for this function is to test whether some `BV` can be
-    /// equal to a given concrete value. You can do this with something like
-    /// `state.bvs_can_be_equal(bv, &state.bv_from_u64(...))`.
+    /// `category` describes what the `item` is, e.g., `"instruction"`.
+    pub(crate) fn unsupported(&self, category: &str, item: &impl fmt::Debug) -> Error {
+        let details = format!("{:?}", item);
+        let variant: String = details
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        let kind = format!("{} {}", category, variant);
+        let mut unsupported = self.unsupported.borrow_mut();
+        match unsupported.iter_mut().find(|u| u.kind == kind) {
+            Some(u) => u.count += 1,
+            None => unsupported.push(UnsupportedItem { kind, count: 1 }),
+        }
+        Error::UnsupportedInstruction(format!(
+            "{} {} at {}",
+            category,
+            details,
+            self.cur_loc.to_string_compact()
+        ))
+    }
+
+    /// Set the current location to instruction `inst_index` of the basic
+    /// block named `block` in the function named `func`, which must be in the
+    /// current module. An `inst_index` equal to the number of instructions in
+    /// the block indicates the block's terminator.
     ///
-    /// This function and `bvs_must_be_equal()` are both more efficient than
-    /// `get_a_solution()` or `get_possible_solutions()`-type functions, as they do
-    /// not require full model generation. You should prefer this function or
-    /// `bvs_must_be_equal()` if they are sufficient for your needs.
-    pub fn bvs_can_be_equal(&self, a: &B::BV, b: &B::BV) -> Result<bool> {
-        solver_utils::bvs_can_be_equal(&self.solver, a, b)
+    /// Symbolic execution keeps the current location up to date itself; this
+    /// is for drivers which process instructions on their own, so that
+    /// errors (such as `Error::UnsupportedInstruction`) and
+    /// `full_error_message_with_context()` report the right location.
+    pub fn set_current_location(
+        &mut self,
+        func: &str,
+        block: &Name,
+        inst_index: usize,
+    ) -> Result<()> {
+        let module = self.cur_loc.module;
+        let func = module.get_func_by_name(func).ok_or_else(|| {
+            Error::OtherError(format!(
+                "set_current_location: no function named {:?} in module {:?}",
+                func, module.name
+            ))
+        })?;
+        self.cur_loc = Self::location_in(module, func, block, inst_index, "set_current_location")?;
+        Ok(())
     }
 
-    /// Get one possible concrete value for the `BV`.
-    /// Returns `Ok(None)` if no possible solution, or `Error::SolverError` if the solver query failed.
-    pub fn get_a_solution_for_bv(&self, bv: &B::BV) -> Result<Option<BVSolution>> {
-        // first check if the `bv` is a constant, if so, we can avoid a solve
-        match bv.as_binary_str() {
-            Some(bstr) => Ok(Some(BVSolution::from_01x_str(bstr))),
-            None => {
-                warn!("A call to get_a_solution_for_bv() is resulting in a call to sat() with model generation enabled. Experimentally, these types of calls can be very slow. The BV is {:?}", bv);
-                self.solver.set_opt(BtorOption::ModelGen(ModelGen::All));
-                let solution = if self.sat()? {
-                    bv.get_a_solution().map(Some)
-                } else {
-                    Ok(None)
-                };
-                self.solver
-                    .set_opt(BtorOption::ModelGen(ModelGen::Disabled));
-                solution
+    /// Get the `Location` of instruction `inst_index` of the basic block named
+    /// `block` in `func`, where an `inst_index` equal to the number of
+    /// instructions in the block indicates the block's terminator
+    fn location_in(
+        module: &'p Module,
+        func: &'p Function,
+        block: &Name,
+        inst_index: usize,
+        caller: &str,
+    ) -> Result<Location<'p>> {
+        let bb = func.get_bb_by_name(block).ok_or_else(|| {
+            Error::OtherError(format!(
+                "{}: no basic block named {} in function {:?}",
+                caller, block, func.name
+            ))
+        })?;
+        let (instr, source_loc) = match inst_index.cmp(&bb.instrs.len()) {
+            std::cmp::Ordering::Less => (
+                BBInstrIndex::Instr(inst_index),
+                bb.instrs[inst_index].get_debug_loc().as_ref(),
+            ),
+            std::cmp::Ordering::Equal => {
+                (BBInstrIndex::Terminator, bb.term.get_debug_loc().as_ref())
             },
-        }
+            std::cmp::Ordering::Greater => {
+                return Err(Error::OtherError(format!(
+                    "{}: basic block {} in function {:?} has only {} instructions",
+                    caller,
+                    block,
+                    func.name,
+                    bb.instrs.len()
+                )))
+            },
+        };
+        Ok(Location {
+            module,
+            func,
+            bb,
+            instr,
+            source_loc,
+        })
     }
 
-    /// Get one possible concrete value for the given IR `Name` (from the given `Function` name).
-    /// Returns `Ok(None)` if no possible solution, or `Error::SolverError` if the solver query failed.
-    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
-    pub fn get_a_solution_for_irname(
-        &mut self,
-        funcname: &String,
-        name: &Name,
-    ) -> Result<Option<BVSolution>> {
-        let bv = self.varmap.lookup_var(funcname, name);
-        self.get_a_solution_for_bv(bv)
+    /// Assert the given constraint (a `BV` of width 1) on the current path.
+    ///
+    /// This is equivalent to `constraint.assert()`, except that the `State`
+    /// also remembers the constraint, so that it can later be returned by
+    /// `constraints_mentioning()`, and re-asserted when `merge()` or
+    /// `end_scope()` rebuilds solver contexts. Hooks, and any other code constraining
+    /// a `State`'s path, should assert through this method: constraints
+    /// asserted directly with `BV::assert()` aren't remembered, and are lost
+    /// when the context they were asserted in is rebuilt.
+    pub fn assert(&self, constraint: &B::BV) -> Result<()> {
+        constraint.assert()?;
+        self.asserted_constraints
+            .borrow_mut()
+            .push(constraint.clone());
+        Ok(())
     }
 
-    /// Get a description of the possible solutions for the `BV`.
+    /// Like `assert()`, but first checks that the constraint is consistent
+    /// with the existing constraints on the current path. If it isn't, this
+    /// returns `Error::Infeasible` without asserting it, so the `State`
+    /// remains usable.
     ///
-    /// `n`: Maximum number of distinct solutions to check for.
-    /// If there are more than `n` possible solutions, this returns a
-    /// `PossibleSolutions::AtLeast` containing `n+1` solutions.
+    /// This costs a solver query for each call, in exchange for detecting
+    /// contradictions immediately.
+    pub fn assert_checked(&self, constraint: &B::BV) -> Result<()> {
+        if self.sat_with_extra_constraints(std::iter::once(constraint))? {
+            self.assert(constraint)
+        } else {
+            Err(Error::Infeasible)
+        }
+    }
+
+    /// Assert that exactly one of the given `flags` (each a `BV` of width 1) is
+    /// true on the current path.
     ///
-    /// These solutions will be disambiguated - see docs on `boolector::BVSolution`.
+    /// If `flags` is empty, this makes the current path unsat.
+    pub fn assert_exactly_one(&self, flags: &[&B::BV]) -> Result<()> {
+        let at_most_one = self.at_most_one(flags)?;
+        let at_least_one = flags
+            .iter()
+            .fold(self.bv_from_bool(false), |acc, flag| acc.or(flag));
+        self.assert(&at_most_one.and(&at_least_one))
+    }
+
+    /// Assert that at most one of the given `flags` (each a `BV` of width 1) is
+    /// true on the current path.
+    pub fn assert_at_most_one(&self, flags: &[&B::BV]) -> Result<()> {
+        self.assert(&self.at_most_one(flags)?)
+    }
+
+    /// Get a `BV` (of width 1) which is true if at most one of the `flags` is
+    /// true
+    fn at_most_one(&self, flags: &[&B::BV]) -> Result<B::BV> {
+        if let Some(flag) = flags.iter().find(|flag| flag.get_width() != 1) {
+            return Err(Error::OtherError(format!(
+                "Expected all flags to have width 1, but found one with width {}",
+                flag.get_width()
+            )));
+        }
+        // Beyond this many flags, the number of pairs grows too quickly
+        const MAX_FLAGS_FOR_PAIRWISE: usize = 6;
+        if flags.len() <= MAX_FLAGS_FOR_PAIRWISE {
+            // no two flags are both true
+            Ok(flags
+                .iter()
+                .tuple_combinations()
+                .fold(self.bv_from_bool(true), |acc, (a, b)| {
+                    acc.and(&a.and(b).not())
+                }))
+        } else {
+            // sequential encoding: `any_so_far` is true if any of the flags
+            // before the current one is true, in which case the current flag
+            // must be false. This needs only a linear number of terms.
+            let mut constraint = self.bv_from_bool(true);
+            let mut any_so_far = self.bv_from_bool(false);
+            for flag in flags {
+                constraint = constraint.and(&any_so_far.and(flag).not());
+                any_so_far = any_so_far.or(flag);
+            }
+            Ok(constraint)
+        }
+    }
+
+    /// Get the constraints on the current path which mention (any version of)
+    /// the variable with the given `Name` in the current function, in the
+    /// order they were asserted.
     ///
-    /// If there are no possible solutions, this returns `Ok` with an empty
-    /// `PossibleSolutions`, rather than returning an `Err` with `Error::Unsat`.
-    pub fn get_possible_solutions_for_bv(
-        &self,
-        bv: &B::BV,
-        n: usize,
-    ) -> Result<PossibleSolutions<BVSolution>> {
-        solver_utils::get_possible_solutions_for_bv(self.solver.clone(), bv, n)
+    /// Only constraints asserted with `State::assert()` are considered; this
+    /// includes the branch conditions which `haybale` itself asserts. This
+    /// finds constraints syntactically referencing the variable's solver
+    /// symbol; it does not simplify the constraints first. A constraint too
+    /// large to inspect will conservatively be included.
+    pub fn constraints_mentioning(&self, name: &Name) -> Vec<B::BV> {
+        let symbols = self.varmap.symbols_of(&self.cur_loc.func.name, name);
+        if symbols.is_empty() {
+            return Vec::new();
+        }
+        self.asserted_constraints
+            .borrow()
+            .iter()
+            .filter(|constraint| dump_mentions(*constraint, &symbols))
+            .cloned()
+            .collect()
     }
 
-    /// Get a description of the possible solutions for the given IR `Name` (from the given `Function` name).
+    /// Returns `true` if no constraint asserted with [`assert()`](#method.assert)
+    /// on the current path rules anything out: that is, each of them is
+    /// trivially `true`. This can be used to recognize fully general paths.
     ///
-    /// `n`: Maximum number of distinct solutions to check for.
-    /// If there are more than `n` possible solutions, this returns a
-    /// `PossibleSolutions::AtLeast` containing `n+1` solutions.
+    /// This is a syntactic check, which doesn't query the solver; a
+    /// constraint which is a tautology, but not trivially so, counts as a
+    /// constraint. Constraints asserted directly with `BV::assert()` are not
+    /// considered.
+    pub fn is_unconstrained(&self) -> bool {
+        self.asserted_constraints
+            .borrow()
+            .iter()
+            .all(|constraint| constraint.as_bool() == Some(true))
+    }
+
+    /// Estimate the difficulty of queries on the current path, without making
+    /// one, from the constraints asserted with [`assert()`](#method.assert).
+    /// A driver can log this to correlate with slow solves.
     ///
-    /// These solutions will be disambiguated - see docs on `boolector::BVSolution`.
+    /// As with `constraints_mentioning()`, this inspects the constraints as
+    /// they were asserted, without simplifying them; terms shared within a
+    /// constraint are counted once, but terms shared by several constraints
+    /// are counted for each. A constraint too large for the solver to print
+    /// counts toward `constraints`, but not `variables` or `nodes`.
+    pub fn query_complexity(&self) -> QueryComplexity {
+        let asserted_constraints = self.asserted_constraints.borrow();
+        let mut variables: HashSet<String> = HashSet::new();
+        let mut nodes = 0;
+        for constraint in asserted_constraints.iter() {
+            let dump = match dump_of(constraint) {
+                Some(dump) => dump,
+                None => continue,
+            };
+            let exprs: Vec<smtlib::SExpr> = match smtlib::parse(&dump) {
+                Ok(exprs) => exprs
+                    .into_iter()
+                    .filter(|expr| !smtlib::is_ignored_command(expr))
+                    .collect(),
+                Err(_) => continue,
+            };
+            variables.extend(smtlib::free_symbols(&exprs));
+            nodes += smtlib::term_count(&exprs);
+        }
+        QueryComplexity {
+            constraints: asserted_constraints.len(),
+            variables: variables.len(),
+            nodes,
+        }
+    }
+
+    /// Serialize the constraints on the current path which were asserted with
+    /// [`assert()`](#method.assert), in a compact binary form which can be
+    /// loaded into another `State` with `deserialize_constraints()`.
     ///
-    /// If there are no possible solutions, this returns `Ok` with an empty
-    /// `PossibleSolutions`, rather than returning an `Err` with `Error::Unsat`.
-    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
-    pub fn get_possible_solutions_for_irname(
-        &mut self,
-        funcname: &String,
-        name: &Name,
-        n: usize,
-    ) -> Result<PossibleSolutions<BVSolution>> {
-        let bv = self.varmap.lookup_var(funcname, name);
-        self.get_possible_solutions_for_bv(bv, n)
+    /// The constraints are captured as the solver prints them, so they may
+    /// be rewritten (e.g. simplified or split up), and they come along with
+    /// any constraints the solver holds which are shared by all paths. They
+    /// may refer to memory, and to variables without a symbol.
+    pub fn serialize_constraints(&self) -> Result<Vec<u8>> {
+        Ok(self
+            .dump(&self.asserted_constraints.borrow(), &[], &[])?
+            .to_bytes())
     }
 
-    /// Get the maximum possible solution for the `BV`: that is, the highest value
-    /// for which the current set of constraints is still satisfiable.
-    /// "Maximum" will be interpreted in an unsigned fashion.
+    /// Load constraints produced by `serialize_constraints()` (possibly on a
+    /// different `State`), asserting them on the current path.
     ///
-    /// Returns `Ok(None)` if there is no solution for the `BV`, that is, if the
-    /// current set of constraints is unsatisfiable. Only returns `Err` if a solver
-    /// query itself fails. Panics if the `BV` is wider than 64 bits.
-    pub fn max_possible_solution_for_bv_as_u64(&self, bv: &B::BV) -> Result<Option<u64>> {
-        solver_utils::max_possible_solution_for_bv_as_u64(self.solver.clone(), bv)
+    /// A fresh variable is created for each variable the constraints refer
+    /// to, with the same symbol it had originally; so, no variable with that
+    /// symbol may already exist in this `State`. Memory the constraints
+    /// refer to is rebuilt over fresh arrays, so it's unrelated to this
+    /// `State`'s memory.
+    pub fn deserialize_constraints(&mut self, bytes: &[u8]) -> Result<()> {
+        let dump = smtlib::Dump::from_bytes(bytes)?;
+        // build all the constraints before asserting any, so that we don't assert only some of them
+        let rebuilt: smtlib::Rebuilt<B> = smtlib::rebuild(
+            &dump,
+            &self.solver,
+            Self::null_detection(&self.config),
+            &HashMap::new(),
+        )?;
+        for constraint in &rebuilt.constraints {
+            self.assert(constraint)?;
+        }
+        Ok(())
     }
 
-    /// Get the maximum possible solution for the given IR `Name` (from the given
-    /// `Function` name): that is, the highest value for which the current set of
-    /// constraints is still satisfiable.
-    /// "Maximum" will be interpreted in an unsigned fashion.
+    /// Dump `constraints`, along with the constraints shared by all paths,
+    /// and the given values and memories, from a copy of the solver.
     ///
-    /// Returns `Ok(None)` if there is no solution for the `BV`, that is, if the
-    /// current set of constraints is unsatisfiable. Only returns `Err` if a solver
-    /// query itself fails. Panics if the `BV` is wider than 64 bits.
-    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
-    pub fn max_possible_solution_for_irname_as_u64(
-        &mut self,
-        funcname: &String,
-        name: &Name,
-    ) -> Result<Option<u64>> {
-        let bv = self.varmap.lookup_var(funcname, name);
-        solver_utils::max_possible_solution_for_bv_as_u64(self.solver.clone(), bv)
+    /// The solver only prints constraints asserted outside of any context,
+    /// so the copy's contexts are popped first. Each value and memory is
+    /// identified in the dump by a fresh variable constrained to equal it (or
+    /// a read of it at a fresh index), whose symbol doesn't collide with any
+    /// existing one; and so is each variable created with
+    /// `new_bv_with_name()`, in case the solver substituted it away.
+    fn dump(
+        &self,
+        constraints: &[B::BV],
+        bvs: &[B::BV],
+        mems: &[&B::Memory],
+    ) -> Result<smtlib::Dump> {
+        let solver = self.solver.duplicate();
+        let depth = self.context_depth();
+        if depth > 0 {
+            solver.pop(depth);
+        }
+        // so that the copy doesn't substitute away the variables identifying values
+        solver.set_opt(BtorOption::VariableSubst(false));
+        let in_use = |name: &str| {
+            Btor::get_bv_by_symbol(&*solver, name).is_some()
+                || Btor::get_array_by_symbol(&*solver, name).is_some()
+        };
+        let matched = |bv: &B::BV| {
+            solver.match_bv(bv).ok_or_else(|| {
+                Error::OtherError("dump: value doesn't belong to this State's solver".to_owned())
+            })
+        };
+        let vars: Vec<(String, B::BV)> = std::iter::once(&self.varmap)
+            .chain(self.backtrack_points.borrow().iter().map(|bp| &bp.varmap))
+            .flat_map(|varmap| {
+                varmap
+                    .version_nums()
+                    .flat_map(move |(funcname, name, _)| varmap.symbols_of(funcname, name))
+            })
+            .sorted()
+            .dedup()
+            .filter_map(|symbol| {
+                let var = solver.get_bv_by_symbol(&symbol)?;
+                Some((symbol, var))
+            })
+            .collect();
+        let generation = (0 ..)
+            .find(|g| {
+                (0 .. bvs.len().max(mems.len()).max(vars.len())).all(|i| {
+                    ["v", "i", "m", "s"]
+                        .iter()
+                        .all(|kind| !in_use(&format!("haybale_dump{}_{}{}", g, kind, i)))
+                })
+            })
+            .unwrap();
+        let symbol = |kind: &str, i: usize| format!("haybale_dump{}_{}{}", generation, kind, i);
+
+        for constraint in constraints {
+            matched(constraint)?.assert()?;
+        }
+        let mut bv_symbols = Vec::new();
+        for (i, bv) in bvs.iter().enumerate() {
+            let bv = matched(bv)?;
+            let value = symbol("v", i);
+            B::BV::new(solver.clone(), bv.get_width(), Some(&value))
+                ._eq(&bv)
+                .assert()?;
+            bv_symbols.push(value);
+        }
+        let mut mem_symbols = Vec::new();
+        for (i, &mem) in mems.iter().enumerate() {
+            let mut mem = mem.clone();
+            mem.change_solver(solver.clone());
+            let (index, value) = (symbol("i", i), symbol("m", i));
+            let read = mem.read_non_null(&B::BV::new(solver.clone(), 64, Some(&index)), 8)?;
+            B::BV::new(solver.clone(), 8, Some(&value))
+                ._eq(&read)
+                .assert()?;
+            mem_symbols.push((index, value));
+        }
+        let mut var_symbols = Vec::new();
+        for (i, (var_symbol, var)) in vars.into_iter().enumerate() {
+            let value = symbol("s", i);
+            B::BV::new(solver.clone(), var.get_width(), Some(&value))
+                ._eq(&var)
+                .assert()?;
+            var_symbols.push((var_symbol, value));
+        }
+        smtlib::Dump::parse(
+            &solver.print_constraints(),
+            |name| in_use(name),
+            bv_symbols,
+            mem_symbols,
+            var_symbols,
+        )
     }
 
-    /// Get the minimum possible solution for the `BV`: that is, the lowest value
-    /// for which the current set of constraints is still satisfiable.
-    /// "Minimum" will be interpreted in an unsigned fashion.
+    /// If the current path is unsat, get a subset of its constraints which
+    /// the solver needed to show that it's unsat (an "unsat core"), as
+    /// evidence of why the path is unsat; or `None` if the path is sat.
     ///
-    /// Returns `Ok(None)` if there is no solution for the `BV`, that is, if the
-    /// current set of constraints is unsatisfiable. Only returns `Err` if a solver
-    /// query itself fails. Panics if the `BV` is wider than 64 bits.
-    pub fn min_possible_solution_for_bv_as_u64(&self, bv: &B::BV) -> Result<Option<u64>> {
-        solver_utils::min_possible_solution_for_bv_as_u64(self.solver.clone(), bv)
+    /// Boolector can't produce proofs, so this is the closest substitute for
+    /// one. The core is not necessarily minimal. Only constraints asserted
+    /// with [`assert()`](#method.assert) are considered, and a constraint in
+    /// the core is returned as the same `BV` which was asserted.
+    ///
+    /// This re-checks the path in the `State`'s own solver, assuming each of
+    /// the constraints so that the solver reports which of them it needed;
+    /// this costs one more solver query. Returns `Error::OtherError` if the solver
+    /// didn't need any of the constraints asserted with `assert()`, that is,
+    /// if the path is unsat due to constraints asserted some other way.
+    pub fn get_unsat_core(&self) -> Result<Option<Vec<B::BV>>> {
+        if self.sat()? {
+            return Ok(None);
+        }
+        let constraints = self.asserted_constraints.borrow().clone();
+        self.solver.push(1);
+        for constraint in &constraints {
+            constraint.assume();
+        }
+        let core = match self.sat() {
+            Ok(false) => Ok(constraints
+                .into_iter()
+                .filter(|constraint| constraint.is_failed_assumption())
+                .collect::<Vec<B::BV>>()),
+            Ok(true) => Err(Error::OtherError(
+                "get_unsat_core: the path became sat when re-checked".to_owned(),
+            )),
+            Err(e) => Err(e),
+        };
+        self.solver.pop(1);
+        match core? {
+            core if core.is_empty() => Err(Error::OtherError(
+                "get_unsat_core: the path is unsat regardless of the constraints asserted with assert()"
+                    .to_owned(),
+            )),
+            core => Ok(Some(core)),
+        }
     }
 
-    /// Get the minimum possible solution for the given IR `Name` (from the given
-    /// `Function` name): that is, the lowest value for which the current set of
-    /// constraints is still satisfiable.
-    /// "Minimum" will be interpreted in an unsigned fashion.
+    /// Capture the current path and all saved backtracking points in a
+    /// [`SuspendedState`](struct.SuspendedState.html), from which
+    /// `State::resume()` can continue exploring later (for instance, after
+    /// being written out with the `serde` feature and read back in by another
+    /// process).
     ///
-    /// Returns `Ok(None)` if there is no solution for the `BV`, that is, if the
-    /// current set of constraints is unsatisfiable. Only returns `Err` if a solver
-    /// query itself fails. Panics if the `BV` is wider than 64 bits.
-    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
-    pub fn min_possible_solution_for_irname_as_u64(
-        &self,
-        funcname: &String,
-        name: &Name,
-    ) -> Result<Option<u64>> {
-        let bv = self.varmap.lookup_var(funcname, name);
-        solver_utils::min_possible_solution_for_bv_as_u64(self.solver.clone(), bv)
+    /// This captures the constraints, call stack, variables, pending varargs,
+    /// memory contents (in every address space), allocations, and locations
+    /// of the current path and of each backtracking point. Memories are
+    /// captured as the solver represents them, so reads of bytes which were
+    /// never written are captured too, as reads of the initial memory.
+    ///
+    /// Watchpoints, variable metadata, taint, coverage, the uninit handler,
+    /// and which pointers are known to be non-null or where they point are
+    /// not captured.
+    pub fn suspend(&self) -> Result<SuspendedState> {
+        let mut collected = Collected::new();
+        let current =
+            self.backtrack_point_here(self.cur_loc.clone(), self.bv_from_bool(true), None);
+        let snapshot = Self::snapshot(&current, &mut collected);
+        let constraints = self
+            .asserted_constraints
+            .borrow()
+            .iter()
+            .map(|constraint| collected.bv(constraint))
+            .collect();
+        let mut backtrack_points = Vec::new();
+        for bp in self.backtrack_points.borrow().iter() {
+            backtrack_points.push(SuspendedBacktrackPoint {
+                loc: SuspendedLocation::from(&bp.loc),
+                constraint: collected.bv(&bp.constraint),
+                path_len: bp.path_len,
+                constraints_len: bp.constraints_len,
+                constraints: bp.constraints.as_ref().map(|constraints| {
+                    constraints
+                        .iter()
+                        .map(|constraint| collected.bv(constraint))
+                        .collect()
+                }),
+                snapshot: Self::snapshot(bp, &mut collected),
+                alloc: bp.alloc.clone(),
+                zeroed_regions: bp.zeroed_regions.as_ref().map(|zeroed_regions| {
+                    zeroed_regions
+                        .iter()
+                        .map(|(&addr, &size_bytes)| (addr, size_bytes))
+                        .sorted()
+                        .collect()
+                }),
+            });
+        }
+
+        let mems: Vec<&B::Memory> = collected.mems.iter().collect();
+        Ok(SuspendedState {
+            dump: self.dump(&[], &collected.bvs, &mems)?.to_bytes(),
+            constraints,
+            search_base_len: self.search_base_len.get(),
+            loc: SuspendedLocation::from(&self.cur_loc),
+            path: self
+                .path
+                .iter()
+                .map(|entry| SuspendedLocation::from(&entry.0))
+                .collect(),
+            snapshot,
+            backtrack_points,
+            backtrack_scopes: self.backtrack_scopes.clone(),
+            alloc: self.alloc.clone(),
+            initialized_globals: self.global_allocations.initialized_global_var_addrs(),
+            zeroed_regions: self
+                .zeroed_regions
+                .iter()
+                .map(|(&addr, &size_bytes)| (addr, size_bytes))
+                .sorted()
+                .collect(),
+            write_tracking: self.write_tracking,
+        })
     }
 
-    /// Create a `BV` constant representing the given `bool` (either constant
-    /// `true` or constant `false`).
-    /// The resulting `BV` will be either constant `0` or constant `1`, and will
-    /// have bitwidth `1`.
-    pub fn bv_from_bool(&self, b: bool) -> B::BV {
-        B::BV::from_bool(self.solver.clone(), b)
+    /// Capture the per-path state of the given `BacktrackPoint` as a
+    /// `Snapshot`, collecting the symbolic values and memories it refers to
+    fn snapshot(bp: &BacktrackPoint<'p, B>, collected: &mut Collected<B>) -> Snapshot {
+        let stack = bp
+            .stack
+            .iter()
+            .map(|frame| SuspendedFrame {
+                callsite: SuspendedLocation::from(&frame.callsite.loc),
+                caller: frame.restore_info.funcname().to_owned(),
+                caller_vars: frame
+                    .restore_info
+                    .pairs_to_restore()
+                    .iter()
+                    .map(|(name, bv)| (SuspendedName::from(name), collected.bv(bv)))
+                    .collect(),
+            })
+            .collect();
+        let vars = bp
+            .varmap
+            .active_vars()
+            .sorted_by_key(|&(f, n, _)| (f, n))
+            .map(|(funcname, name, bv)| {
+                (
+                    funcname.clone(),
+                    SuspendedName::from(name),
+                    collected.bv(bv),
+                )
+            })
+            .collect();
+        Snapshot {
+            stack,
+            vars,
+            version_nums: bp
+                .varmap
+                .version_nums()
+                .map(|(funcname, name, version_num)| {
+                    (funcname.clone(), SuspendedName::from(name), version_num)
+                })
+                .sorted()
+                .collect(),
+            mem: collected.mem(&bp.mem),
+            space_mems: bp
+                .space_mems
+                .iter()
+                .sorted_by_key(|&(&space, _)| space)
+                .map(|(&space, mem)| (space, collected.mem(mem)))
+                .collect(),
+            initialized_bytes: bp
+                .initialized_bytes
+                .as_ref()
+                .map(|initialized_bytes| initialized_bytes.iter().copied().sorted().collect()),
+            touched_allocs: bp
+                .touched_allocs
+                .as_ref()
+                .map(|touched_allocs| touched_allocs.iter().copied().sorted().collect()),
+            varargs: bp.varargs.iter().map(|bv| collected.bv(bv)).collect(),
+            back_edge_counts: bp
+                .back_edge_counts
+                .iter()
+                .map(|(&loop_id, &count)| (loop_id, count))
+                .sorted()
+                .collect(),
+        }
     }
 
-    /// Create a `BV` representing the given constant `i32` value, with the given
-    /// bitwidth.
-    pub fn bv_from_i32(&self, i: i32, width: u32) -> B::BV {
-        B::BV::from_i32(self.solver.clone(), i, width)
+    /// Continue exploring from a [`SuspendedState`](struct.SuspendedState.html)
+    /// produced by `suspend()`, in a new `State` with a fresh solver instance.
+    ///
+    /// The code locations in `suspended` are looked up by name in `project`,
+    /// which should contain the same LLVM modules as the `Project` of the
+    /// suspended `State`; and `config` should match its `Config` as well. In
+    /// particular, `config` must use the `DepthFirst` search strategy if and
+    /// only if the suspended `State` did, unless it had no backtracking
+    /// points.
+    pub fn resume(
+        suspended: &SuspendedState,
+        project: &'p Project,
+        config: Config<'p, B>,
+    ) -> Result<Self> {
+        let depth_first = matches!(config.search_strategy, SearchStrategy::DepthFirst);
+        if suspended
+            .backtrack_points
+            .iter()
+            .any(|bp| bp.constraints.is_some() == depth_first)
+        {
+            return Err(Error::OtherError(
+                "resume: the SuspendedState was produced under a different kind of search strategy"
+                    .to_owned(),
+            ));
+        }
+        let loc = Self::resume_location(project, &suspended.loc)?;
+        let mut state = Self::new(project, loc, config);
+
+        let dump = smtlib::Dump::from_bytes(&suspended.dump)?;
+        // the initial memory becomes the new `State`'s own, and the memory for
+        // each other address space is rebuilt over one which, like those
+        // from `new_space_memory()`, doesn't check for null pointers
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            "mem".to_owned(),
+            smtlib::Term::Memory(state.mem.borrow().clone()),
+        );
+        for name in dump
+            .arrays()
+            .filter(|name| name.starts_with("mem_addrspace"))
+        {
+            inputs.insert(
+                name.to_owned(),
+                smtlib::Term::Memory(Memory::new_uninitialized(
+                    state.solver.clone(),
+                    false,
+                    Some(name),
+                )),
+            );
+        }
+        let rebuilt: smtlib::Rebuilt<B> = smtlib::rebuild(
+            &dump,
+            &state.solver,
+            Self::null_detection(&state.config),
+            &inputs,
+        )?;
+        let values = |indices: &[usize]| {
+            indices
+                .iter()
+                .map(|&index| dumped(&rebuilt.bvs, index, "value"))
+                .collect::<Result<Vec<B::BV>>>()
+        };
+        let constraints = values(&suspended.constraints)?;
+        // the constraints shared by all paths belong underneath all the backtracking points
+        for constraint in &rebuilt.constraints {
+            state.assert(constraint)?;
+        }
+        let base_len = rebuilt.constraints.len();
+
+        state.alloc = suspended.alloc.clone();
+        for &addr in &suspended.initialized_globals {
+            state.global_allocations.mark_initialized(addr);
+        }
+        state.zeroed_regions = suspended.zeroed_regions.iter().copied().collect();
+        state.write_tracking = suspended.write_tracking;
+        let mut num_asserted = 0;
+        let mut assert_up_to = |state: &Self, len: usize| {
+            if len < num_asserted || len > constraints.len() {
+                return Err(Error::OtherError(
+                    "resume: malformed SuspendedState: backtracking points out of order".to_owned(),
+                ));
+            }
+            for constraint in &constraints[num_asserted .. len] {
+                state.assert(constraint)?;
+            }
+            num_asserted = len;
+            Ok(())
+        };
+        if let Some(search_base_len) = suspended.search_base_len {
+            // as in `save_backtracking_point_at_location()`
+            assert_up_to(&state, search_base_len)?;
+            state.solver.push(1);
+            state.search_base_len.set(Some(base_len + search_base_len));
+        }
+        for bp in &suspended.backtrack_points {
+            let restored = state.restore_snapshot(
+                project,
+                Self::resume_location(project, &bp.loc)?,
+                &bp.snapshot,
+                &rebuilt,
+            )?;
+            let bp_constraints = match &bp.constraints {
+                None => {
+                    assert_up_to(&state, bp.constraints_len)?;
+                    state.solver.push(1);
+                    None
+                },
+                Some(bp_constraints) => Some(
+                    rebuilt
+                        .constraints
+                        .iter()
+                        .cloned()
+                        .chain(values(bp_constraints)?)
+                        .collect(),
+                ),
+            };
+            state.backtrack_points.borrow_mut().push(BacktrackPoint {
+                constraint: dumped(&rebuilt.bvs, bp.constraint, "value")?,
+                path_len: bp.path_len,
+                constraints_len: base_len + bp.constraints_len,
+                constraints: bp_constraints,
+                alloc: bp.alloc.clone(),
+                zeroed_regions: bp
+                    .zeroed_regions
+                    .as_ref()
+                    .map(|zeroed_regions| zeroed_regions.iter().copied().collect()),
+                ..restored
+            });
+        }
+        assert_up_to(&state, constraints.len())?;
+
+        let current = state.restore_snapshot(
+            project,
+            state.cur_loc.clone(),
+            &suspended.snapshot,
+            &rebuilt,
+        )?;
+        state.stack = current.stack;
+        state.varmap = current.varmap;
+        state.mem.replace(current.mem);
+        state.space_mems.replace(current.space_mems);
+        state.initialized_bytes.replace(current.initialized_bytes);
+        state.touched_allocs.replace(current.touched_allocs);
+        state.varargs = current.varargs;
+        state.back_edge_counts = current.back_edge_counts;
+        state.path = suspended
+            .path
+            .iter()
+            .map(|loc| Ok(PathEntry(Self::resume_location(project, loc)?)))
+            .collect::<Result<_>>()?;
+        state.backtrack_scopes = suspended.backtrack_scopes.clone();
+        Ok(state)
     }
 
-    /// Create a `BV` representing the given constant `u32` value, with the given
-    /// bitwidth.
-    pub fn bv_from_u32(&self, u: u32, width: u32) -> B::BV {
-        B::BV::from_u32(self.solver.clone(), u, width)
+    /// Rebuild the per-path state of a `Snapshot` from the rebuilt values and
+    /// memories, as a `BacktrackPoint` to `loc` with no constraints
+    fn restore_snapshot(
+        &self,
+        project: &'p Project,
+        loc: Location<'p>,
+        snapshot: &Snapshot,
+        rebuilt: &smtlib::Rebuilt<B>,
+    ) -> Result<BacktrackPoint<'p, B>> {
+        let value = |index: usize| dumped(&rebuilt.bvs, index, "value");
+        let mem = |index: usize| dumped(&rebuilt.mems, index, "memory");
+        let mut stack = Vec::new();
+        for frame in &snapshot.stack {
+            let loc = Self::resume_location(project, &frame.callsite)?;
+            let instr = match loc.instr {
+                BBInstrIndex::Instr(i) => match &loc.bb.instrs[i] {
+                    Instruction::Call(call) => Either::Left(call),
+                    _ => {
+                        return Err(Error::OtherError(format!(
+                            "resume: expected a call instruction at {}",
+                            loc.to_string_compact()
+                        )))
+                    },
+                },
+                BBInstrIndex::Terminator => match &loc.bb.term {
+                    Terminator::Invoke(invoke) => Either::Right(invoke),
+                    _ => {
+                        return Err(Error::OtherError(format!(
+                            "resume: expected an invoke instruction at {}",
+                            loc.to_string_compact()
+                        )))
+                    },
+                },
+            };
+            let pairs = frame
+                .caller_vars
+                .iter()
+                .map(|(name, index)| Ok((Name::from(name), value(*index)?)))
+                .collect::<Result<_>>()?;
+            stack.push(StackFrame {
+                callsite: Callsite { loc, instr },
+                restore_info: RestoreInfo::new(frame.caller.clone(), pairs),
+            });
+        }
+        let mut varmap = VarMap::new(self.solver.clone(), self.config.loop_bound);
+        for (funcname, name, index) in &snapshot.vars {
+            varmap.restore_active_var(funcname.clone(), Name::from(name), value(*index)?);
+        }
+        for (funcname, name, version_num) in &snapshot.version_nums {
+            varmap.restore_version_num(funcname.clone(), Name::from(name), *version_num);
+        }
+        Ok(BacktrackPoint {
+            loc,
+            stack,
+            constraint: self.bv_from_bool(true),
+            varmap,
+            mem: mem(snapshot.mem)?,
+            space_mems: snapshot
+                .space_mems
+                .iter()
+                .map(|&(space, index)| Ok((space, mem(index)?)))
+                .collect::<Result<_>>()?,
+            path_len: 0,
+            constraints_len: 0,
+            initialized_bytes: snapshot
+                .initialized_bytes
+                .as_ref()
+                .map(|initialized_bytes| initialized_bytes.iter().copied().collect()),
+            touched_allocs: snapshot
+                .touched_allocs
+                .as_ref()
+                .map(|touched_allocs| touched_allocs.iter().copied().collect()),
+            varargs: snapshot
+                .varargs
+                .iter()
+                .map(|&index| value(index))
+                .collect::<Result<_>>()?,
+            back_edge_counts: snapshot.back_edge_counts.iter().copied().collect(),
+            non_null: HashMap::new(),
+            provenance: HashMap::new(),
+            constraints: None,
+            alloc: None,
+            zeroed_regions: None,
+        })
     }
 
-    /// Create a `BV` representing the given constant `i64` value, with the given
-    /// bitwidth.
-    pub fn bv_from_i64(&self, i: i64, width: u32) -> B::BV {
-        B::BV::from_i64(self.solver.clone(), i, width)
+    /// Look up a `SuspendedLocation` in the given `Project`
+    fn resume_location(project: &'p Project, loc: &SuspendedLocation) -> Result<Location<'p>> {
+        let (func, module) = project
+            .all_functions()
+            .find(|(func, module)| module.name == loc.module && func.name == loc.func)
+            .ok_or_else(|| {
+                Error::OtherError(format!(
+                    "resume: no function named {:?} in module {:?}",
+                    loc.func, loc.module
+                ))
+            })?;
+        Self::location_in(module, func, &Name::from(&loc.bb), loc.instr, "resume")
     }
 
-    /// Create a `BV` representing the given constant `u64` value, with the given
-    /// bitwidth.
-    pub fn bv_from_u64(&self, u: u64, width: u32) -> B::BV {
-        B::BV::from_u64(self.solver.clone(), u, width)
+    /// Returns `true` if current constraints are satisfiable, `false` if not.
+    ///
+    /// Returns `Error::SolverError` if the query failed (e.g., was interrupted or timed out).
+    pub fn sat(&self) -> Result<bool> {
+        self.record_reason_unknown(solver_utils::sat(&self.solver))
     }
 
-    /// Create a `BV` representing the constant `0` of the given bitwidth.
-    /// This is equivalent to `self.bv_from_i32(0, width)` but may be more
-    /// efficient.
-    pub fn zero(&self, width: u32) -> B::BV {
-        B::BV::zero(self.solver.clone(), width)
+    /// Returns `true` if the current constraints plus the given additional constraints
+    /// are together satisfiable, or `false` if not.
+    ///
+    /// Returns `Error::SolverError` if the query failed (e.g., was interrupted or timed out).
+    ///
+    /// Does not permanently add the given constraints to the solver.
+    pub fn sat_with_extra_constraints<'b>(
+        &'b self,
+        constraints: impl IntoIterator<Item = &'b B::BV>,
+    ) -> Result<bool> {
+        self.record_reason_unknown(solver_utils::sat_with_extra_constraints(
+            &self.solver,
+            constraints,
+        ))
     }
 
-    /// Create a `BV` representing the constant `1` of the given bitwidth.
-    /// This is equivalent to `self.bv_from_i32(1, width)` but may be more
-    /// efficient.
-    pub fn one(&self, width: u32) -> B::BV {
-        B::BV::one(self.solver.clone(), width)
+    /// If the most recent query made with `sat()` or
+    /// `sat_with_extra_constraints()` was inconclusive (resulting in an
+    /// `Error::SolverError`), get the reason why: `"timeout"` if the query
+    /// exceeded the configured
+    /// [`solver_query_timeout`](config/struct.Config.html#structfield.solver_query_timeout),
+    /// or `"interrupted"` otherwise. Returns `None` if that query was
+    /// conclusive.
+    ///
+    /// This can help decide whether to raise the timeout or to simplify the
+    /// constraints.
+    pub fn reason_unknown(&self) -> Option<String> {
+        self.reason_unknown.borrow().clone()
     }
 
-    /// Create a `BV` constant of the given width, where all bits are set to one.
-    /// This is equivalent to `self.bv_from_i32(-1, width)` but may be more
-    /// efficient.
-    pub fn ones(&self, width: u32) -> B::BV {
-        B::BV::ones(self.solver.clone(), width)
+    /// Enable or disable extra simplification of the constraints before each
+    /// solver query. This is disabled by default.
+    ///
+    /// The solver always simplifies the constraints by rewriting before a
+    /// query. When this is enabled, it additionally eliminates lambda terms
+    /// (which is how it represents memory writes) by eager beta reduction,
+    /// and function applications by eager Ackermann expansion, before the
+    /// query. This is more work upfront, but can make some queries much
+    /// faster.
+    ///
+    /// This setting persists across `reset()`.
+    pub fn set_presimplify(&mut self, enabled: bool) {
+        self.presimplify = enabled;
+        Self::apply_presimplify(&self.solver, enabled);
     }
 
-    /// Create a new (unconstrained) `BV` for the given `Name` (in the current function).
+    fn apply_presimplify(solver: &B::SolverRef, enabled: bool) {
+        solver.set_opt(BtorOption::BetaReduce(enabled));
+        solver.set_opt(BtorOption::Ackermann(enabled));
+    }
+
+    /// Record the reason (if any) that the query with the given result was
+    /// inconclusive, for `reason_unknown()`; then return the result
+    fn record_reason_unknown(&self, result: Result<bool>) -> Result<bool> {
+        let reason = match result {
+            // Boolector only gives up on a query when its termination callback fires, which it uses for timeouts
+            Err(Error::SolverError(_)) if self.config.solver_query_timeout.is_some() => {
+                Some("timeout".to_owned())
+            },
+            Err(Error::SolverError(_)) => Some("interrupted".to_owned()),
+            _ => None,
+        };
+        self.reason_unknown.replace(reason);
+        result
+    }
+
+    /// Returns `true` if the current constraints plus the constraints produced
+    /// by `conds` are together satisfiable when memory is temporarily overlaid
+    /// with the given concrete bytes, or `false` if not.
     ///
-    /// This function performs uniquing, so if you call it twice
-    /// with the same `Name`-`Function` pair, you will get two different `BV`s.
+    /// Each entry in `overrides` is a concrete address and the byte value which
+    /// should be stored there for the purposes of this query.
     ///
-    /// Returns the new `BV`, or `Err` if it can't be created.
+    /// `conds` is called with the overlaid `State`, so any memory reads it
+    /// performs will observe the overrides. (Values read from memory before
+    /// calling this function are unaffected by the overrides, since writes
+    /// never retroactively change the results of previous reads.)
     ///
-    /// (As of this writing, the only `Err` that might be returned is
-    /// `Error::LoopBoundExceeded`, which is returned if creating the new `BV`
-    /// would exceed `max_versions_of_name` -- see
-    /// [`Config`](struct.Config.html).)
+    /// Returns `Error::SolverError` if the query failed (e.g., was interrupted or timed out).
     ///
-    /// Also, we assume that no two `Function`s share the same name.
-    pub fn new_bv_with_name(&mut self, name: Name, bits: u32) -> Result<B::BV> {
-        self.varmap
-            .new_bv_with_name(self.cur_loc.func.name.clone(), name, bits)
+    /// Neither the overrides nor the constraints are permanently applied: once
+    /// the query is complete, memory is restored to its previous contents.
+    pub fn check_with_memory_overlay(
+        &mut self,
+        overrides: &[(u64, u8)],
+        conds: impl FnOnce(&Self) -> Result<Vec<B::BV>>,
+    ) -> Result<bool> {
+        let saved_mem = self.mem.borrow().clone();
+        let retval = overrides
+            .iter()
+            .try_for_each(|(addr, byte)| {
+                let addr = self.bv_from_u64(*addr, POINTER_SIZE_BITS as u32);
+                let byte = self.bv_from_u32(u32::from(*byte), 8);
+                self.mem.borrow_mut().write(&addr, byte)
+            })
+            .and_then(|()| conds(self))
+            .and_then(|conds| self.sat_with_extra_constraints(&conds));
+        self.mem.replace(saved_mem);
+        retval
     }
 
-    /// Assign the given `BV` to the given `Name` (in the current function).
+    /// Returns `true` if under the current constraints, `a` and `b` must have the
+    /// same value. Returns `false` if `a` and `b` may have different values. (If the
+    /// current constraints are themselves unsatisfiable, that will result in
+    /// `true`.)
     ///
-    /// This function performs uniquing, so it creates a new version of the
-    /// variable represented by the `(String, Name)` pair rather than overwriting
-    /// the current version.
+    /// A common use case for this function is to test whether some `BV` must be
+    /// equal to a given concrete value. You can do this with something like
+    /// `state.bvs_must_be_equal(bv, &state.bv_from_u64(...))`.
     ///
-    /// Returns `Err` if the assignment can't be performed.
+    /// This function and `bvs_can_be_equal()` are both more efficient than
+    /// `get_a_solution()` or `get_possible_solutions()`-type functions, as they do
+    /// not require full model generation. You should prefer this function or
+    /// `bvs_can_be_equal()` if they are sufficient for your needs.
+    pub fn bvs_must_be_equal(&self, a: &B::BV, b: &B::BV) -> Result<bool> {
+        solver_utils::bvs_must_be_equal(&self.solver, a, b)
+    }
+
+    /// Returns `true` if under the current constraints, `a` and `b` can have the
+    /// same value. Returns `false` if `a` and `b` cannot have the same value. (If
+    /// the current constraints are themselves unsatisfiable, that will also result
+    /// in `false`.)
     ///
-    /// (As of this writing, the only `Err` that might be returned is
-    /// `Error::LoopBoundExceeded`, which is returned if creating the new version
-    /// of the `BV` would exceed `max_versions_of_name` -- see
-    /// [`Config`](struct.Config.html).)
-    pub fn assign_bv_to_name(&mut self, name: Name, bv: B::BV) -> Result<()> {
-        self.varmap
-            .assign_bv_to_name(self.cur_loc.func.name.clone(), name, bv)
+    /// A common use case for this function is to test whether some `BV` can be
+    /// equal to a given concrete value. You can do this with something like
+    /// `state.bvs_can_be_equal(bv, &state.bv_from_u64(...))`.
+    ///
+    /// This function and `bvs_must_be_equal()` are both more efficient than
+    /// `get_a_solution()` or `get_possible_solutions()`-type functions, as they do
+    /// not require full model generation. You should prefer this function or
+    /// `bvs_must_be_equal()` if they are sufficient for your needs.
+    pub fn bvs_can_be_equal(&self, a: &B::BV, b: &B::BV) -> Result<bool> {
+        solver_utils::bvs_can_be_equal(&self.solver, a, b)
     }
 
-    /// Record the result of `thing` to be `resultval`.
-    /// Assumes `thing` is in the current function.
-    /// Will fail with `Error::LoopBoundExceeded` if that would exceed
-    /// `max_versions_of_name` (see [`Config`](struct.Config.html)).
-    #[cfg(debug_assertions)]
-    pub fn record_bv_result(
-        &mut self,
-        thing: &impl instruction::HasResult,
-        resultval: B::BV,
-    ) -> Result<()> {
-        if size(&thing.get_type()) as u32 != resultval.get_width() {
-            Err(Error::OtherError(format!(
-                "Computed result for an instruction has the wrong size: instruction {:?} with result size {}, but got result {:?} with size {}",
-                thing,
-                size(&thing.get_type()),
-                resultval,
-                resultval.get_width()
-            )))
-        } else {
-            self.assign_bv_to_name(thing.get_result().clone(), resultval)
+    /// Determine whether `b` (a `BV` of width 1) must be true, must be false,
+    /// or can be either, under the current constraints. For instance, a driver
+    /// can use this to avoid exploring a branch which can only go one way.
+    ///
+    /// This costs up to two solver queries. Returns `Error::Unsat` if the
+    /// current constraints are themselves unsatisfiable.
+    pub fn bool_value(&self, b: &B::BV) -> Result<BoolValue> {
+        if b.get_width() != 1 {
+            return Err(Error::OtherError(format!(
+                "bool_value: expected a value of width 1, but got width {}",
+                b.get_width()
+            )));
+        }
+        let can_be_true = self.sat_with_extra_constraints(std::iter::once(b))?;
+        let can_be_false = self.sat_with_extra_constraints(std::iter::once(&b.not()))?;
+        match (can_be_true, can_be_false) {
+            (true, true) => Ok(BoolValue::Either),
+            (true, false) => Ok(BoolValue::AlwaysTrue),
+            (false, true) => Ok(BoolValue::AlwaysFalse),
+            (false, false) => Err(Error::Unsat),
         }
     }
-    #[cfg(not(debug_assertions))]
-    pub fn record_bv_result(
-        &mut self,
-        thing: &impl instruction::HasResult,
-        resultval: B::BV,
+
+    /// Returns `true` if under the current constraints, `cond` (a `BV` of
+    /// width 1) must be true, i.e., its negation is unsatisfiable. Returns
+    /// `false` if `cond` can be false. (If the current constraints are
+    /// themselves unsatisfiable, that will result in `true`.)
+    ///
+    /// For instance, a driver can use this to avoid asserting a branch
+    /// condition which is already implied. This costs a single solver query,
+    /// compared to up to two for `bool_value()`.
+    pub fn is_implied(&self, cond: &B::BV) -> Result<bool> {
+        if cond.get_width() != 1 {
+            return Err(Error::OtherError(format!(
+                "is_implied: expected a value of width 1, but got width {}",
+                cond.get_width()
+            )));
+        }
+        Ok(!self.sat_with_extra_constraints(std::iter::once(&cond.not()))?)
+    }
+
+    /// Assert that `body` holds for all values of some bound variables, whose
+    /// widths are given by `bound_widths`.
+    ///
+    /// `body` is called with a `BV` for each of the bound variables, in order,
+    /// and should return a `BV` of width 1 which is true when the property
+    /// holds for those values. For instance, `body` could check that an
+    /// element of an array in memory, at an index given by the bound
+    /// variable, is nonzero.
+    ///
+    /// Boolector doesn't support quantifiers over formulas involving memory,
+    /// so rather than constructing a quantified formula, this expands the
+    /// quantifier: `body` is called (with constant values) once for each
+    /// possible assignment to the bound variables, and the conjunction of the
+    /// results is asserted. As a result, this never makes later solver queries
+    /// inconclusive, but the total width of the bound variables is limited to
+    /// 16 bits; wider bound variables are an `Error::OtherError`, as are bound
+    /// variables of width 0.
+    pub fn assert_forall(
+        &self,
+        bound_widths: &[u32],
+        body: impl FnMut(&[B::BV]) -> Result<B::BV>,
     ) -> Result<()> {
-        self.assign_bv_to_name(thing.get_result().clone(), resultval)
+        let instances = self.quantifier_instances("assert_forall", bound_widths, body)?;
+        self.assert(&instances.into_iter().reduce(|a, b| a.and(&b)).unwrap())
     }
 
-    /// Overwrite the latest version of the given `Name` to instead be `bv`.
-    /// Assumes `Name` is in the current function.
-    pub fn overwrite_latest_version_of_bv(&mut self, name: &Name, bv: B::BV) {
-        self.varmap
-            .overwrite_latest_version_of_bv(&self.cur_loc.func.name, name, bv)
+    /// Assert that `body` holds for some values of some bound variables, whose
+    /// widths are given by `bound_widths`.
+    ///
+    /// See notes on [`assert_forall()`](#method.assert_forall), which also
+    /// apply here; this asserts the disjunction of the instances of `body`
+    /// rather than their conjunction.
+    pub fn assert_exists(
+        &self,
+        bound_widths: &[u32],
+        body: impl FnMut(&[B::BV]) -> Result<B::BV>,
+    ) -> Result<()> {
+        let instances = self.quantifier_instances("assert_exists", bound_widths, body)?;
+        self.assert(&instances.into_iter().reduce(|a, b| a.or(&b)).unwrap())
     }
 
-    /// Convert an `Operand` to the appropriate `BV`.
-    /// Assumes the `Operand` is in the current function.
-    /// (All `Operand`s should be either a constant or a variable we previously added to the state.)
-    pub fn operand_to_bv(&self, op: &Operand) -> Result<B::BV> {
-        match op {
-            Operand::ConstantOperand(c) => self.const_to_bv(c),
-            Operand::LocalOperand { name, .. } => Ok(self
-                .varmap
-                .lookup_var(&self.cur_loc.func.name, name)
-                .clone()),
-            Operand::MetadataOperand => panic!("Can't convert {:?} to BV", op),
+    /// Get the instances of `body` for all possible assignments to bound
+    /// variables of the given widths. Always returns at least one instance.
+    fn quantifier_instances(
+        &self,
+        op: &str,
+        bound_widths: &[u32],
+        mut body: impl FnMut(&[B::BV]) -> Result<B::BV>,
+    ) -> Result<Vec<B::BV>> {
+        if bound_widths.contains(&0) {
+            return Err(Error::OtherError(format!(
+                "{}: bound variables must have nonzero width",
+                op
+            )));
         }
+        let total_bits = bound_widths
+            .iter()
+            .try_fold(0u32, |total, &width| total.checked_add(width))
+            .filter(|&total| total <= MAX_QUANTIFIED_BITS)
+            .ok_or_else(|| {
+                Error::OtherError(format!(
+                    "{}: bound variables are too wide; only up to {} bits in total are supported",
+                    op, MAX_QUANTIFIED_BITS
+                ))
+            })?;
+        (0 .. 1u64 << total_bits)
+            .map(|assignment| {
+                // each bound variable takes its bits of `assignment` in turn, starting from the low bits
+                let mut shift = 0;
+                let vars: Vec<B::BV> = bound_widths
+                    .iter()
+                    .map(|&width| {
+                        let val = (assignment >> shift) & ((1 << width) - 1);
+                        shift += width;
+                        self.bv_from_u64(val, width)
+                    })
+                    .collect();
+                let instance = body(&vars)?;
+                if instance.get_width() != 1 {
+                    return Err(Error::OtherError(format!(
+                        "{}: expected the body to have width 1, but it has width {}",
+                        op,
+                        instance.get_width()
+                    )));
+                }
+                Ok(instance)
+            })
+            .collect()
     }
 
-    /// Convert a `Constant` to the appropriate `BV`.
-    pub fn const_to_bv(&self, c: &Constant) -> Result<B::BV> {
-        match c {
-            Constant::Int { bits, value } => Ok(self.bv_from_u64(*value, *bits)),
-            Constant::Null(ty) | Constant::AggregateZero(ty) | Constant::Undef(ty) => {
-                Ok(self.zero(size(ty) as u32))
+    /// Assert that `antecedent` implies `consequent`; that is, if `antecedent`
+    /// is true, then `consequent` must be true as well.
+    ///
+    /// Both `antecedent` and `consequent` should be `BV`s of width 1.
+    pub fn assert_implies(&self, antecedent: &B::BV, consequent: &B::BV) -> Result<()> {
+        self.assert(&antecedent.implies(consequent))
+    }
+
+    /// Returns `true` if the current constraints, plus the constraint that
+    /// `antecedent` implies `consequent`, are together satisfiable, or `false`
+    /// if not.
+    ///
+    /// Both `antecedent` and `consequent` should be `BV`s of width 1.
+    ///
+    /// Does not permanently add the implication to the solver; for that, see
+    /// `assert_implies()`.
+    pub fn assume_implies(&self, antecedent: &B::BV, consequent: &B::BV) -> Result<bool> {
+        self.sat_with_extra_constraints(std::iter::once(&antecedent.implies(consequent)))
+    }
+
+    /// Constrain `ptr` to be aligned to `align` bytes; that is, assert that its
+    /// low `log2(align)` bits are zero.
+    ///
+    /// `align` must be a power of two.
+    pub fn assert_aligned(&self, ptr: &B::BV, align: u64) -> Result<()> {
+        self.assert(&self.is_aligned(ptr, align)?)
+    }
+
+    /// Returns `true` if under the current constraints, `ptr` may be misaligned
+    /// with respect to `align` bytes, or `false` if `ptr` must be aligned.
+    ///
+    /// `align` must be a power of two.
+    ///
+    /// Does not permanently add any constraints to the solver.
+    pub fn check_aligned(&self, ptr: &B::BV, align: u64) -> Result<bool> {
+        let aligned = self.is_aligned(ptr, align)?;
+        self.sat_with_extra_constraints(std::iter::once(&aligned.not()))
+    }
+
+    /// Constrain `bv` to be one of the values in `allowed`, as for a value of
+    /// an enum type; that is, assert the disjunction of `bv == v` for each
+    /// `v` in `allowed`.
+    ///
+    /// If `allowed` is empty, this makes the current path unsat.
+    pub fn assert_in_set(&self, bv: &B::BV, allowed: &[u64]) -> Result<()> {
+        self.assert(&self.is_in_set(bv, allowed))
+    }
+
+    /// Returns `true` if under the current constraints, `bv` can be one of the
+    /// values in `allowed`, or `false` if it can't be any of them.
+    ///
+    /// Does not permanently add any constraints to the solver; for that, see
+    /// `assert_in_set()`.
+    pub fn check_in_set(&self, bv: &B::BV, allowed: &[u64]) -> Result<bool> {
+        self.sat_with_extra_constraints(std::iter::once(&self.is_in_set(bv, allowed)))
+    }
+
+    /// Get a `BV` of width 1 which is true exactly when `bv` is one of the
+    /// values in `allowed`
+    fn is_in_set(&self, bv: &B::BV, allowed: &[u64]) -> B::BV {
+        let width = bv.get_width();
+        allowed
+            .iter()
+            .map(|&val| bv._eq(&self.bv_from_u64(val, width)))
+            .reduce(|a, b| a.or(&b))
+            .unwrap_or_else(|| self.bv_from_bool(false))
+    }
+
+    /// Constrain the given pointers to be in increasing order, as unsigned
+    /// values: `ptrs[0] < ptrs[1] < ...` if `strict`, or
+    /// `ptrs[0] <= ptrs[1] <= ...` if not. For instance, this can describe
+    /// the layout of stack slots, or of the elements of a sorted buffer.
+    ///
+    /// All of the `ptrs` must have the same width.
+    pub fn assert_ordered(&self, ptrs: &[&B::BV], strict: bool) -> Result<()> {
+        let mut ordered = self.bv_from_bool(true);
+        for (a, b) in ptrs.iter().tuple_windows() {
+            if a.get_width() != b.get_width() {
+                return Err(Error::OtherError(format!(
+                    "assert_ordered: expected all pointers to have the same width, but found widths {} and {}",
+                    a.get_width(),
+                    b.get_width()
+                )));
+            }
+            let in_order = if strict { a.ult(b) } else { a.ulte(b) };
+            ordered = ordered.and(&in_order);
+        }
+        self.assert(&ordered)
+    }
+
+    /// Constrain `ptr` to point into the allocation of `alloc_size` bytes
+    /// starting at address `alloc_base`; that is, assert that
+    /// `alloc_base <= ptr < alloc_base + alloc_size`.
+    ///
+    /// `alloc_size` must be nonzero, and the allocation must not wrap around
+    /// the end of the address space.
+    pub fn assume_points_into(&self, ptr: &B::BV, alloc_base: u64, alloc_size: u64) -> Result<()> {
+        let alloc_end = match alloc_base.checked_add(alloc_size) {
+            Some(end) if alloc_size > 0 => end,
+            _ => {
+                return Err(Error::OtherError(format!(
+                    "assume_points_into: invalid allocation of {} bytes at {:#x}",
+                    alloc_size, alloc_base
+                )))
             },
-            Constant::Struct {
-                values: elements, ..
+        };
+        let width = ptr.get_width();
+        let in_bounds = ptr
+            .ugte(&self.bv_from_u64(alloc_base, width))
+            .and(&ptr.ult(&self.bv_from_u64(alloc_end, width)));
+        self.assert(&in_bounds)
+    }
+
+    /// Clear the bits of `ptr` which are zero in `mask`, as with the LLVM
+    /// `llvm.ptrmask` intrinsic.
+    pub fn ptr_mask(&self, ptr: &B::BV, mask: u64) -> B::BV {
+        ptr.and(&self.bv_from_u64(mask, ptr.get_width()))
+    }
+
+    /// Store `tag` in the low `tag_bits` bits of `ptr`, replacing whatever
+    /// was there, as in tagged-pointer schemes which rely on pointers being
+    /// aligned.
+    ///
+    /// The tag can be recovered with `ptr_mask()` (using a mask of the low
+    /// `tag_bits` bits), and the original pointer with `ptr_untag()`.
+    pub fn ptr_tag(&self, ptr: &B::BV, tag: u64, tag_bits: u32) -> Result<B::BV> {
+        let tag_mask = self.tag_mask(ptr, tag_bits)?;
+        if tag & !tag_mask != 0 {
+            return Err(Error::OtherError(format!(
+                "Tag {:#x} doesn't fit in {} bits",
+                tag, tag_bits
+            )));
+        }
+        let width = ptr.get_width();
+        let untagged = ptr.and(&self.bv_from_u64(tag_mask, width).not());
+        Ok(untagged.or(&self.bv_from_u64(tag, width)))
+    }
+
+    /// Clear the low `tag_bits` bits of `ptr`, removing a tag stored with
+    /// `ptr_tag()`.
+    pub fn ptr_untag(&self, ptr: &B::BV, tag_bits: u32) -> Result<B::BV> {
+        let tag_mask = self.tag_mask(ptr, tag_bits)?;
+        Ok(ptr.and(&self.bv_from_u64(tag_mask, ptr.get_width()).not()))
+    }
+
+    /// Get a mask of the low `tag_bits` bits, checking that those bits are
+    /// only part of `ptr`
+    fn tag_mask(&self, ptr: &B::BV, tag_bits: u32) -> Result<u64> {
+        if tag_bits == 0 || tag_bits >= ptr.get_width() || tag_bits >= 64 {
+            return Err(Error::OtherError(format!(
+                "Can't use the low {} bits of a {}-bit pointer as a tag",
+                tag_bits,
+                ptr.get_width()
+            )));
+        }
+        Ok((1 << tag_bits) - 1)
+    }
+
+    /// Attach the metadata `value` under `key` to the variable with the given
+    /// `Name` in the current function, replacing any previous value under that
+    /// key. For instance, analyses built on `haybale` can use this to tag
+    /// variables as `"user_input"` or `"length_field"`.
+    ///
+    /// `haybale` itself never reads this metadata. The metadata applies to all
+    /// versions of the variable, and is not rolled back by backtracking.
+    pub fn set_var_metadata(&mut self, name: &Name, key: &str, value: String) -> Result<()> {
+        let funcname = &self.cur_loc.func.name;
+        if self.varmap.width_of(funcname, name).is_none() {
+            return Err(Error::OtherError(format!(
+                "set_var_metadata: no variable {} in function {:?}",
+                name, funcname
+            )));
+        }
+        self.var_metadata
+            .entry((funcname.clone(), name.clone()))
+            .or_default()
+            .insert(key.to_owned(), value);
+        Ok(())
+    }
+
+    /// Get the metadata attached under `key` to the variable with the given
+    /// `Name` in the current function with `set_var_metadata()`, if any.
+    pub fn get_var_metadata(&self, name: &Name, key: &str) -> Option<&str> {
+        self.var_metadata
+            .get(&(self.cur_loc.func.name.clone(), name.clone()))
+            .and_then(|metadata| metadata.get(key))
+            .map(String::as_str)
+    }
+
+    /// Mark the variable with the given `Name` in the current function as
+    /// tainted, for instance because it holds untrusted input. Any `BV`
+    /// computed from it is then tainted as well; see `is_tainted()`.
+    ///
+    /// This applies to all versions of the variable created so far, and is
+    /// not rolled back by backtracking.
+    pub fn mark_tainted(&mut self, name: &Name) -> Result<()> {
+        let symbols = self.varmap.symbols_of(&self.cur_loc.func.name, name);
+        if symbols.is_empty() {
+            return Err(Error::OtherError(format!(
+                "mark_tainted: no variable {} in function {:?}",
+                name, self.cur_loc.func.name
+            )));
+        }
+        self.tainted_symbols.extend(symbols);
+        Ok(())
+    }
+
+    /// Returns `true` if the `BV` is tainted: that is, if it was computed
+    /// (through any operations) from a variable marked with `mark_tainted()`.
+    ///
+    /// As with `constraints_mentioning()`, this is a syntactic check of
+    /// whether the `BV` references a tainted variable's solver symbol, and a
+    /// `BV` too large to inspect is conservatively considered tainted. Values
+    /// which only depend on a tainted variable through a constraint (e.g.,
+    /// a value chosen by a branch on it) are not tainted.
+    pub fn is_tainted(&self, bv: &B::BV) -> bool {
+        if self.tainted_symbols.is_empty() {
+            return false;
+        }
+        dump_mentions(bv, &self.tainted_symbols)
+    }
+
+    /// Get which of the given inputs (variables in the current function, with
+    /// their widths in bits) the `BV` `output` is computed from, in the order
+    /// they were given.
+    ///
+    /// Like `is_tainted()`, this is a syntactic check of whether `output`
+    /// references (any version of) each input's solver symbol, which is much
+    /// cheaper than marking the inputs tainted and checking the result, and
+    /// has the same caveats: if `output` is too large to inspect, all the
+    /// inputs are reported.
+    pub fn influencing_inputs(&self, output: &B::BV, inputs: &[(&Name, u32)]) -> Result<Vec<Name>> {
+        let funcname = &self.cur_loc.func.name;
+        for (name, bits) in inputs {
+            match self.varmap.width_of(funcname, name) {
+                Some(width) if width == *bits => {},
+                Some(width) => {
+                    return Err(Error::OtherError(format!(
+                    "influencing_inputs: input {} was expected to be {} bits, but it is {} bits",
+                    name, bits, width
+                )))
+                },
+                None => {
+                    return Err(Error::OtherError(format!(
+                        "influencing_inputs: no variable {} in function {:?}",
+                        name, funcname
+                    )))
+                },
             }
-            | Constant::Array { elements, .. }
-            | Constant::Vector(elements) => elements
-                .iter()
-                .map(|c| self.const_to_bv(c)) // produces an iterator over Result<B::BV>
-                .reduce(|a, b| Ok(b?.concat(&a?))) // the lambda has type Fn(Result<B::BV>, Result<B::BV>) -> Result<B::BV>
-                .unwrap(), // unwrap the Option<> produced by reduce(), leaving the final return type Result<B::BV>
-            Constant::GlobalReference { name, .. } => {
-                if let Some(ga) = self
-                    .global_allocations
-                    .get_global_allocation(name, self.cur_loc.module)
-                {
-                    match ga {
-                        GlobalAllocation::Function { addr, .. } => Ok(addr.clone()),
-                        GlobalAllocation::GlobalVariable {
-                            addr,
-                            initializer,
-                            initialized,
-                        } => {
-                            // First, initialize the global if it hasn't been already.
-                            // As mentioned in comments in `State::new()`, we lazily
-                            // initialize globals upon first reference to them.
-                            //
-                            // We assume that global-variable initializers can only refer to the
-                            // *addresses* of other globals, and not the *values* of other
-                            // global constants, so that it's fine that any referred-to globals
-                            // may have been allocated but not initialized at this point.
-                            // This assumption seems to hold empirically: in my tests,
-                            // (1) clang performs constant-folding, even at -O0, on global
-                            //     variable initializers so that these initializers do not refer to
-                            //     the values of other global constants at the LLVM level. For
-                            //     instance, the C code
-                            //       `const int a = 1; const int b = a + 3;`
-                            //     is translated into the LLVM equivalent of
-                            //       `const int a = 1; const int b = 4;`
-                            // (2) clang rejects programs where global variable initializers refer
-                            //     to the value of externally-defined global constants, in which
-                            //     case the constant-folding described above would be impossible.
-                            //     Note, however, that clang does allow referring to the *addresses*
-                            //     of externally-defined global variables.
-                            // Therefore, we can go ahead and set our `.initialized` flag early,
-                            // because even if `const_to_bv` on our initializer references other
-                            // globals (possibly causing their lazy initialization as well),
-                            // those globals can't refer to our contents, so won't know that we
-                            // are lying about being initialized.
-                            // Setting the flag early prevents an infinite loop where I try to
-                            // initialize, but my initializer refers to your address so you try
-                            // to initialize, but your initializer refers to my address so I try
-                            // to initialize, etc.
-                            if !initialized.get() {
-                                debug!(
-                                    "Initializing {:?} with initializer {:?}",
-                                    name, &initializer
-                                );
-                                initialized.set(true);
-                                let write_val = self.const_to_bv(initializer)?;
-                                self.write_without_mut(addr, write_val)?;
-                            }
-                            Ok(addr.clone())
-                        },
-                    }
-                } else if let Some(alias) = self
-                    .cur_loc
-                    .module
-                    .global_aliases
+        }
+        // print `output` once, rather than once per input
+        let dump = dump_of(output);
+        Ok(inputs
+            .iter()
+            .filter(|(name, _)| match &dump {
+                Some(dump) => self
+                    .varmap
+                    .symbols_of(funcname, name)
                     .iter()
-                    .find(|a| &a.name == name)
-                {
-                    self.const_to_bv(&alias.aliasee)
+                    .any(|symbol| mentions_symbol(dump, symbol)),
+                None => true,
+            })
+            .map(|(name, _)| (*name).clone())
+            .collect())
+    }
+
+    /// Assert a precondition over the given parameters (or other variables) of
+    /// the current function.
+    ///
+    /// `pred` is called with the current `BV` for each of the `params`, in
+    /// order, and should return a `BV` of width 1 which is true when the
+    /// precondition holds. For instance, this can be used to constrain a
+    /// function's inputs before beginning symbolic execution of it.
+    pub fn assume_precondition(
+        &self,
+        params: &[&Name],
+        pred: impl FnOnce(&[B::BV]) -> B::BV,
+    ) -> Result<()> {
+        let funcname = &self.cur_loc.func.name;
+        let bvs = params
+            .iter()
+            .map(|&param| match self.varmap.width_of(funcname, param) {
+                Some(_) => Ok(self.varmap.lookup_var(funcname, param).clone()),
+                None => Err(Error::OtherError(format!(
+                    "assume_precondition: no variable {} in function {:?}",
+                    param, funcname
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let precondition = pred(&bvs);
+        if precondition.get_width() != 1 {
+            return Err(Error::OtherError(format!(
+                "assume_precondition: expected the predicate to have width 1, but it has width {}",
+                precondition.get_width()
+            )));
+        }
+        self.assert(&precondition)
+    }
+
+    /// Model a call of the no-op hint intrinsic with the given `name`, whose
+    /// arguments have already been evaluated to `args`.
+    ///
+    /// - `llvm.assume(cond)` asserts `cond`, if
+    ///   [`Config.trust_llvm_assumes`](config/struct.Config.html#structfield.trust_llvm_assumes)
+    ///   is set, and otherwise is ignored;
+    /// - `llvm.expect.*(val, ...)` returns `val` unchanged;
+    /// - `llvm.lifetime.*` is ignored: regions remain live for as long as the
+    ///   allocator considers them live.
+    ///
+    /// Returns `Some` with the intrinsic's return value, or `None` if it
+    /// returns void. Returns `Error::FunctionNotFound` if `name` isn't one of
+    /// the intrinsics above.
+    pub fn handle_intrinsic(&mut self, name: &str, args: &[B::BV]) -> Result<Option<B::BV>> {
+        let expect_args = |min: usize, max: usize| {
+            if args.len() < min || args.len() > max {
+                Err(Error::OtherError(format!(
+                    "handle_intrinsic: {} expected between {} and {} arguments, got {}",
+                    name,
+                    min,
+                    max,
+                    args.len()
+                )))
+            } else {
+                Ok(())
+            }
+        };
+        if name == "llvm.assume" {
+            expect_args(1, 1)?;
+            let cond = &args[0];
+            if cond.get_width() != 1 {
+                return Err(Error::OtherError(format!(
+                    "handle_intrinsic: llvm.assume expected an argument of width 1, but it has width {}",
+                    cond.get_width()
+                )));
+            }
+            if self.config.trust_llvm_assumes {
+                self.assert(cond)?;
+            }
+            Ok(None)
+        } else if name.starts_with("llvm.expect") {
+            // `llvm.expect.with.probability` takes an extra argument
+            expect_args(2, 3)?;
+            Ok(Some(args[0].clone()))
+        } else if name.starts_with("llvm.lifetime") {
+            expect_args(2, 2)?;
+            Ok(None)
+        } else {
+            Err(Error::FunctionNotFound(name.to_owned()))
+        }
+    }
+
+    /// Get a `BV` (of width 1) which is true if `ptr` is aligned to `align` bytes
+    fn is_aligned(&self, ptr: &B::BV, align: u64) -> Result<B::BV> {
+        if !align.is_power_of_two() {
+            return Err(Error::OtherError(format!(
+                "Alignment must be a power of two, but got {}",
+                align
+            )));
+        }
+        let width = ptr.get_width();
+        let mask = self.bv_from_u64(align - 1, width);
+        Ok(ptr.and(&mask)._eq(&self.zero(width)))
+    }
+
+    /// Get one possible concrete value for the `BV`.
+    /// Returns `Ok(None)` if no possible solution, or `Error::SolverError` if the solver query failed.
+    pub fn get_a_solution_for_bv(&self, bv: &B::BV) -> Result<Option<BVSolution>> {
+        // first check if the `bv` is a constant, if so, we can avoid a solve
+        match bv.as_binary_str() {
+            Some(bstr) => Ok(Some(BVSolution::from_01x_str(bstr))),
+            None => {
+                warn!("A call to get_a_solution_for_bv() is resulting in a call to sat() with model generation enabled. Experimentally, these types of calls can be very slow. The BV is {:?}", bv);
+                self.solver.set_opt(BtorOption::ModelGen(ModelGen::All));
+                let solution = if self.sat()? {
+                    if self.capture_models {
+                        self.record_model();
+                    }
+                    bv.get_a_solution().map(Some)
                 } else {
-                    Err(Error::OtherError(format!("const_to_bv: GlobalReference to {:?} which was not found (current module is {:?})", name, &self.cur_loc.module.name)))
-                }
+                    Ok(None)
+                };
+                self.solver
+                    .set_opt(BtorOption::ModelGen(ModelGen::Disabled));
+                solution
             },
-            Constant::Add(a) => Ok(self
-                .const_to_bv(&a.operand0)?
-                .add(&self.const_to_bv(&a.operand1)?)),
-            Constant::Sub(s) => Ok(self
-                .const_to_bv(&s.operand0)?
-                .sub(&self.const_to_bv(&s.operand1)?)),
-            Constant::Mul(m) => Ok(self
-                .const_to_bv(&m.operand0)?
-                .mul(&self.const_to_bv(&m.operand1)?)),
-            Constant::UDiv(u) => Ok(self
-                .const_to_bv(&u.operand0)?
-                .udiv(&self.const_to_bv(&u.operand1)?)),
-            Constant::SDiv(s) => Ok(self
-                .const_to_bv(&s.operand0)?
-                .sdiv(&self.const_to_bv(&s.operand1)?)),
-            Constant::URem(u) => Ok(self
-                .const_to_bv(&u.operand0)?
-                .urem(&self.const_to_bv(&u.operand1)?)),
-            Constant::SRem(s) => Ok(self
-                .const_to_bv(&s.operand0)?
-                .srem(&self.const_to_bv(&s.operand1)?)),
-            Constant::And(a) => Ok(self
-                .const_to_bv(&a.operand0)?
-                .and(&self.const_to_bv(&a.operand1)?)),
-            Constant::Or(o) => Ok(self
-                .const_to_bv(&o.operand0)?
-                .or(&self.const_to_bv(&o.operand1)?)),
-            Constant::Xor(x) => Ok(self
-                .const_to_bv(&x.operand0)?
-                .xor(&self.const_to_bv(&x.operand1)?)),
-            Constant::Shl(s) => Ok(self
-                .const_to_bv(&s.operand0)?
-                .sll(&self.const_to_bv(&s.operand1)?)),
-            Constant::LShr(s) => Ok(self
-                .const_to_bv(&s.operand0)?
-                .srl(&self.const_to_bv(&s.operand1)?)),
-            Constant::AShr(s) => Ok(self
-                .const_to_bv(&s.operand0)?
-                .sra(&self.const_to_bv(&s.operand1)?)),
-            Constant::ExtractElement(ee) => match &ee.index {
-                Constant::Int { value: index, .. } => match &ee.vector {
-                    Constant::Vector(els) => {
-                        let el = els.get(*index as usize).ok_or_else(|| {
-                            Error::MalformedInstruction(
-                                "Constant::ExtractElement index out of range".to_owned(),
-                            )
-                        })?;
-                        self.const_to_bv(el)
-                    },
-                    c => Err(Error::MalformedInstruction(format!(
-                        "Expected ExtractElement.vector to be a Constant::Vector, got {:?}",
-                        c
-                    ))),
-                },
-                index => Err(Error::MalformedInstruction(format!(
-                    "Expected ExtractElement.index to be a Constant::Int, but got {:?}",
-                    index
-                ))),
-            },
-            Constant::InsertElement(ie) => match &ie.index {
-                Constant::Int { value: index, .. } => match &ie.vector {
-                    Constant::Vector(els) => {
-                        let mut els = els.clone();
-                        let el: &mut Constant = els.get_mut(*index as usize).ok_or_else(|| {
-                            Error::MalformedInstruction(
-                                "Constant::InsertElement index out of range".to_owned(),
-                            )
-                        })?;
-                        *el = ie.element.clone();
-                        self.const_to_bv(&Constant::Vector(els))
+        }
+    }
+
+    /// Get one possible concrete value for the `BV`, interpreted as a
+    /// two's-complement signed integer of the `BV`'s width.
+    /// Returns `Ok(None)` if no possible solution, or `Error::SolverError` if the solver query failed.
+    ///
+    /// Returns `Error::OtherError` if the `BV` is more than 64 bits wide.
+    pub fn get_a_solution_for_bv_signed(&self, bv: &B::BV) -> Result<Option<i64>> {
+        let width = bv.get_width();
+        if width > 64 {
+            return Err(Error::OtherError(format!(
+                "get_a_solution_for_bv_signed: BV is {} bits wide; only BVs up to 64 bits are supported",
+                width
+            )));
+        }
+        Ok(self.get_a_solution_for_bv(bv)?.map(|solution| {
+            let unsigned = solution
+                .as_u64()
+                .expect("BV is at most 64 bits, so the solution should fit in a u64");
+            let shift = 64 - width;
+            ((unsigned << shift) as i64) >> shift // sign-extend from `width` bits
+        }))
+    }
+
+    /// Get one possible concrete value for the given IR `Name` (from the given `Function` name).
+    /// Returns `Ok(None)` if no possible solution, or `Error::SolverError` if the solver query failed.
+    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
+    pub fn get_a_solution_for_irname(
+        &mut self,
+        funcname: &String,
+        name: &Name,
+    ) -> Result<Option<BVSolution>> {
+        let bv = self.varmap.lookup_var(funcname, name);
+        self.get_a_solution_for_bv(bv)
+    }
+
+    /// Record the solver's current model for `eval_in_last_model()`. Model
+    /// generation must be enabled, and the most recent query must have been
+    /// satisfiable.
+    fn record_model(&self) {
+        // each line of a model in the "btor" format is an ID, a value, and
+        // (for named variables) a symbol, which may itself contain spaces;
+        // array elements have an index along with the ID
+        let model = self
+            .solver
+            .print_model()
+            .lines()
+            .filter_map(
+                |line| match line.splitn(3, ' ').collect::<Vec<_>>().as_slice() {
+                    [id, _, symbol] if !id.contains('[') => {
+                        let var = self.solver.get_bv_by_symbol(symbol)?;
+                        let value = var.get_a_solution().ok()?.disambiguate();
+                        Some((var, value))
                     },
-                    c => Err(Error::MalformedInstruction(format!(
-                        "Expected InsertElement.vector to be a Constant::Vector, got {:?}",
-                        c
-                    ))),
+                    _ => None,
                 },
-                index => Err(Error::MalformedInstruction(format!(
-                    "Expected InsertElement.index to be a Constant::Int, but got {:?}",
-                    index
-                ))),
-            },
-            Constant::ExtractValue(ev) => self.const_to_bv(Self::simplify_const_ev(
-                &ev.aggregate,
-                ev.indices.iter().copied(),
-            )?),
-            Constant::InsertValue(iv) => self.const_to_bv(&Self::simplify_const_iv(
-                iv.aggregate.clone(),
-                iv.element.clone(),
-                iv.indices.iter().copied(),
-            )?),
-            Constant::GetElementPtr(gep) => {
-                // heavily inspired by `ExecutionManager::symex_gep()` in symex.rs. TODO could try to share more code
-                let bvbase = self.const_to_bv(&gep.address)?;
-                let offset = self.get_offset_recursive(
-                    gep.indices.iter(),
-                    &gep.address.get_type(),
-                    bvbase.get_width(),
-                )?;
-                Ok(bvbase.add(&offset))
-            },
-            Constant::Trunc(t) => self
-                .const_to_bv(&t.operand)
-                .map(|bv| bv.slice(size(&t.to_type) as u32 - 1, 0)),
-            Constant::ZExt(z) => self
-                .const_to_bv(&z.operand)
-                .map(|bv| bv.zero_extend_to_bits(size(&z.to_type) as u32)),
-            Constant::SExt(s) => self
-                .const_to_bv(&s.operand)
-                .map(|bv| bv.sign_extend_to_bits(size(&s.to_type) as u32)),
-            Constant::PtrToInt(pti) => {
-                let bv = self.const_to_bv(&pti.operand)?;
-                assert_eq!(bv.get_width(), size(&pti.to_type) as u32);
-                Ok(bv) // just a cast, it's the same bits underneath
-            },
-            Constant::IntToPtr(itp) => {
-                let bv = self.const_to_bv(&itp.operand)?;
-                assert_eq!(bv.get_width(), size(&itp.to_type) as u32);
-                Ok(bv) // just a cast, it's the same bits underneath
-            },
-            Constant::BitCast(bc) => {
-                let bv = self.const_to_bv(&bc.operand)?;
-                assert_eq!(bv.get_width(), size(&bc.to_type) as u32);
-                Ok(bv) // just a cast, it's the same bits underneath
-            },
-            Constant::AddrSpaceCast(ac) => {
-                let bv = self.const_to_bv(&ac.operand)?;
-                assert_eq!(bv.get_width(), size(&ac.to_type) as u32);
-                Ok(bv) // just a cast, it's the same bits underneath
-            },
-            Constant::ICmp(icmp) => {
-                let bv0 = self.const_to_bv(&icmp.operand0)?;
-                let bv1 = self.const_to_bv(&icmp.operand1)?;
-                Ok(match icmp.predicate {
-                    IntPredicate::EQ => bv0._eq(&bv1),
-                    IntPredicate::NE => bv0._ne(&bv1),
-                    IntPredicate::UGT => bv0.ugt(&bv1),
-                    IntPredicate::UGE => bv0.ugte(&bv1),
-                    IntPredicate::ULT => bv0.ult(&bv1),
-                    IntPredicate::ULE => bv0.ulte(&bv1),
-                    IntPredicate::SGT => bv0.sgt(&bv1),
-                    IntPredicate::SGE => bv0.sgte(&bv1),
-                    IntPredicate::SLT => bv0.slt(&bv1),
-                    IntPredicate::SLE => bv0.slte(&bv1),
-                })
-            },
-            Constant::Select(s) => {
-                let b = self.const_to_bv(&s.condition)?;
-                match b.as_bool() {
-                    None => Err(Error::MalformedInstruction(
-                        "Constant::Select: Expected a constant condition".to_owned(),
-                    )),
-                    Some(true) => self.const_to_bv(&s.true_value),
-                    Some(false) => self.const_to_bv(&s.false_value),
-                }
-            },
-            _ => unimplemented!("const_to_bv for {:?}", c),
+            )
+            .collect();
+        self.last_model.replace(Some(model));
+    }
+
+    /// Start recording the model found by each model-generating query (that
+    /// is, each call of `get_a_solution_for_bv()` or of a function using it,
+    /// such as `model_diff()`, which has to solve for a value), so that
+    /// expressions can be evaluated in it with `eval_in_last_model()`.
+    ///
+    /// Recording is off by default, as it costs a pass over the whole model
+    /// after each of those queries.
+    pub fn enable_model_capture(&mut self) {
+        self.capture_models = true;
+    }
+
+    /// Evaluate `bv` in the model found by the most recent model-generating
+    /// query since `enable_model_capture()` was called.
+    ///
+    /// This fixes each variable of the model to its value, in a solver
+    /// context which is popped afterwards, and checks that `bv` then has
+    /// exactly one possible value; with every variable fixed, these two
+    /// queries are much cheaper than solving for a value afresh.
+    ///
+    /// Returns `None` if there is no such model, if `bv` is more than 64 bits
+    /// wide, or if its value isn't determined by the model: for instance,
+    /// because it refers to memory, or to variables created since the model
+    /// was found. The model isn't updated as constraints are added; if
+    /// constraints which were asserted since rule it out, this also returns
+    /// `None`.
+    pub fn eval_in_last_model(&self, bv: &B::BV) -> Option<u64> {
+        if let Some(value) = bv.as_u64() {
+            return Some(value);
+        }
+        if bv.get_width() > 64 {
+            return None;
         }
+        let last_model = self.last_model.borrow();
+        let model = last_model.as_ref()?;
+        self.solver.push(1);
+        let value = self.eval_with_fixed_vars(model, bv);
+        self.solver.pop(1);
+        value
     }
 
-    /// Given a `Constant::Struct` and a series of `ExtractValue` indices, get the
-    /// final `Constant` referred to
-    fn simplify_const_ev(
-        s: &Constant,
-        mut indices: impl Iterator<Item = u32>,
-    ) -> Result<&Constant> {
-        match indices.next() {
-            None => Ok(s),
-            Some(index) => {
-                if let Constant::Struct { values, .. } = s {
-                    let val = values.get(index as usize).ok_or_else(|| {
-                        Error::MalformedInstruction(
-                            "Constant::ExtractValue index out of range".to_owned(),
-                        )
-                    })?;
-                    Self::simplify_const_ev(val, indices)
-                } else {
-                    panic!("simplify_const_ev: not a Constant::Struct: {:?}", s)
-                }
-            },
+    /// Assert that each of `vars` has its given value, and get the value of
+    /// `bv` if that determines it
+    fn eval_with_fixed_vars(&self, vars: &[(B::BV, BVSolution)], bv: &B::BV) -> Option<u64> {
+        for (var, value) in vars {
+            var._eq(&B::BV::from_binary_str(
+                self.solver.clone(),
+                value.as_01x_str(),
+            ))
+            .assert()
+            .ok()?;
+        }
+        self.solver.set_opt(BtorOption::ModelGen(ModelGen::All));
+        let value = match solver_utils::sat(&self.solver) {
+            Ok(true) => bv
+                .get_a_solution()
+                .ok()
+                .and_then(|solution| solution.disambiguate().as_u64()),
+            _ => None,
+        };
+        self.solver
+            .set_opt(BtorOption::ModelGen(ModelGen::Disabled));
+        let other = bv._ne(&B::BV::from_u64(
+            self.solver.clone(),
+            value?,
+            bv.get_width(),
+        ));
+        match solver_utils::sat_with_extra_constraints(&self.solver, std::iter::once(&other)) {
+            Ok(false) => value,
+            _ => None,
         }
     }
 
-    /// Given a `Constant::Struct`, a value to insert, and a series of
-    /// `InsertValue` indices, get the final `Constant` referred to
-    fn simplify_const_iv(
-        s: Constant,
-        val: Constant,
-        mut indices: impl Iterator<Item = u32>,
-    ) -> Result<Constant> {
-        match indices.next() {
-            None => Ok(val),
-            Some(index) => {
-                if let Constant::Struct {
-                    name,
-                    mut values,
-                    is_packed,
-                } = s
-                {
-                    let to_replace = values
-                        .get(index as usize)
-                        .ok_or_else(|| {
-                            Error::MalformedInstruction(
-                                "Constant::InsertValue index out of range".to_owned(),
-                            )
-                        })?
-                        .clone();
-                    values[index as usize] = Self::simplify_const_iv(to_replace, val, indices)?;
-                    Ok(Constant::Struct {
-                        name,
-                        values,
-                        is_packed,
-                    })
-                } else {
-                    panic!("simplify_const_iv: not a Constant::Struct: {:?}", s)
-                }
-            },
+    /// Number of solver contexts currently pushed for backtracking, that is,
+    /// above the constraints which are shared by all paths
+    fn context_depth(&self) -> u32 {
+        match self.config.search_strategy {
+            SearchStrategy::DepthFirst => self.backtrack_points.borrow().len() as u32,
+            _ => self.search_base_len.get().map_or(0, |_| 1),
         }
     }
 
-    /// Get the offset of the element (in bytes, as a `BV` of `result_bits` bits)
-    fn get_offset_recursive<'a>(
-        &self,
-        mut indices: impl Iterator<Item = &'a Constant>,
-        base_type: &Type,
-        result_bits: u32,
-    ) -> Result<B::BV> {
-        match indices.next() {
-            None => Ok(self.zero(result_bits)),
-            Some(index) => match base_type {
-                Type::PointerType { .. } | Type::ArrayType { .. } | Type::VectorType { .. } => {
-                    let index = self.const_to_bv(index)?.zero_extend_to_bits(result_bits);
-                    let (offset, nested_ty) =
-                        get_offset_bv_index(base_type, &index, self.solver.clone())?;
-                    self.get_offset_recursive(indices, nested_ty, result_bits)
-                        .map(|bv| bv.add(&offset))
+    /// Compare a model (set of concrete solution values) of this `State` with
+    /// a model of `other`, for instance to understand why two paths diverge.
+    ///
+    /// `vars` are IR `Name`s from the current function of each `State`, each
+    /// of which must have a width of at most 64 bits. For each of them whose
+    /// value in the model of this `State` differs from its value in the model
+    /// of `other`, the result contains the `Name` and the two values (this
+    /// `State`'s first). The values of all the `vars` in each `State` come
+    /// from a single model.
+    ///
+    /// Returns `Error::Unsat` if either `State`'s constraints are
+    /// unsatisfiable.
+    pub fn model_diff(&self, other: &Self, vars: &[&Name]) -> Result<Vec<(Name, u64, u64)>> {
+        let values = self.model_values("model_diff", vars)?;
+        let other_values = other.model_values("model_diff", vars)?;
+        Ok(vars
+            .iter()
+            .zip(values.into_iter().zip(other_values))
+            .filter(|(_, (value, other_value))| value != other_value)
+            .map(|(&name, (value, other_value))| (name.clone(), value, other_value))
+            .collect())
+    }
+
+    /// Get the values of the given IR `Name`s (from the current function) in
+    /// a single model
+    fn model_values(&self, caller: &str, vars: &[&Name]) -> Result<Vec<u64>> {
+        let funcname = &self.cur_loc.func.name;
+        let mut combined: Option<B::BV> = None;
+        for &name in vars {
+            match self.varmap.width_of(funcname, name) {
+                None => {
+                    return Err(Error::OtherError(format!(
+                        "{}: no variable {:?} in function {:?}",
+                        caller, name, funcname
+                    )))
                 },
-                Type::StructType { .. } => match index {
-                    Constant::Int { value: index, .. } => {
-                        let (offset, nested_ty) =
-                            get_offset_constant_index(base_type, *index as usize)?;
-                        self.get_offset_recursive(indices, &nested_ty, result_bits)
-                            .map(|bv| bv.add(&self.bv_from_u64(offset as u64, result_bits)))
-                    },
-                    _ => Err(Error::MalformedInstruction(format!(
-                        "Expected index into struct type to be a constant int, but got index {:?}",
-                        index
-                    ))),
-                },
-                Type::NamedStructType { ty, .. } => {
-                    let arc: Arc<RwLock<Type>> = ty
-                        .as_ref()
-                        .ok_or_else(|| {
-                            Error::MalformedInstruction(
-                                "get_offset on an opaque struct type".to_owned(),
-                            )
-                        })?
-                        .upgrade()
-                        .expect("Failed to upgrade weak reference");
-                    let actual_ty: &Type = &arc.read().unwrap();
-                    if let Type::StructType { .. } = actual_ty {
-                        // this code copied from the StructType case
-                        match index {
-                            Constant::Int { value: index, .. } => {
-                                let (offset, nested_ty) = get_offset_constant_index(base_type, *index as usize)?;
-                                self.get_offset_recursive(indices, &nested_ty, result_bits).map(|bv| bv.add(&self.bv_from_u64(offset as u64, result_bits)))
-                            },
-                            _ => Err(Error::MalformedInstruction(format!("Expected index into struct type to be a constant int, but got index {:?}", index))),
-                        }
-                    } else {
-                        Err(Error::MalformedInstruction(format!(
-                            "Expected NamedStructType inner type to be a StructType, but got {:?}",
-                            actual_ty
-                        )))
-                    }
+                Some(width) if width > 64 => {
+                    return Err(Error::OtherError(format!(
+                        "{}: variable {:?} is {} bits wide, which is more than 64 bits",
+                        caller, name, width
+                    )))
                 },
-                _ => panic!("get_offset_recursive with base type {:?}", base_type),
+                Some(_) => {},
+            }
+            let bv = self.varmap.lookup_var(funcname, name).clone();
+            combined = Some(match combined {
+                None => bv,
+                Some(earlier) => earlier.concat(&bv),
+            });
+        }
+        let combined = match combined {
+            None => return Ok(Vec::new()),
+            Some(combined) => combined,
+        };
+        let solution = self
+            .get_a_solution_for_bv(&combined)?
+            .ok_or(Error::Unsat)?
+            .disambiguate();
+        // the first variable is in the most significant bits, which come first in the string
+        let mut bits = solution.as_01x_str();
+        vars.iter()
+            .map(|&name| {
+                let width = self.varmap.lookup_var(funcname, name).get_width() as usize;
+                let (value, rest) = bits.split_at(width);
+                bits = rest;
+                u64::from_str_radix(value, 2).map_err(|e| {
+                    Error::OtherError(format!(
+                        "{}: unexpected solution {:?}: {}",
+                        caller, value, e
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Get a description of the possible solutions for the `BV`.
+    ///
+    /// `n`: Maximum number of distinct solutions to check for.
+    /// If there are more than `n` possible solutions, this returns a
+    /// `PossibleSolutions::AtLeast` containing `n+1` solutions.
+    ///
+    /// These solutions will be disambiguated - see docs on `boolector::BVSolution`.
+    ///
+    /// If there are no possible solutions, this returns `Ok` with an empty
+    /// `PossibleSolutions`, rather than returning an `Err` with `Error::Unsat`.
+    pub fn get_possible_solutions_for_bv(
+        &self,
+        bv: &B::BV,
+        n: usize,
+    ) -> Result<PossibleSolutions<BVSolution>> {
+        solver_utils::get_possible_solutions_for_bv(self.solver.clone(), bv, n)
+    }
+
+    /// Get a description of the possible solutions for the given IR `Name` (from the given `Function` name).
+    ///
+    /// `n`: Maximum number of distinct solutions to check for.
+    /// If there are more than `n` possible solutions, this returns a
+    /// `PossibleSolutions::AtLeast` containing `n+1` solutions.
+    ///
+    /// These solutions will be disambiguated - see docs on `boolector::BVSolution`.
+    ///
+    /// If there are no possible solutions, this returns `Ok` with an empty
+    /// `PossibleSolutions`, rather than returning an `Err` with `Error::Unsat`.
+    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
+    pub fn get_possible_solutions_for_irname(
+        &mut self,
+        funcname: &String,
+        name: &Name,
+        n: usize,
+    ) -> Result<PossibleSolutions<BVSolution>> {
+        let bv = self.varmap.lookup_var(funcname, name);
+        self.get_possible_solutions_for_bv(bv, n)
+    }
+
+    /// Iterate over the possible solutions for the `BV`, finding each
+    /// solution lazily, as `next()` is called.
+    ///
+    /// Unlike `get_possible_solutions_for_bv()`, this doesn't require choosing
+    /// a maximum number of solutions up front. The solutions are distinct, but
+    /// are produced in no particular order. Each call to `next()` is a solver
+    /// query; if a query fails (e.g., times out), the iteration ends early.
+    ///
+    /// The iterator holds the `State` mutably borrowed, and the constraints
+    /// it uses to exclude previous solutions are removed from the solver when
+    /// it is dropped.
+    ///
+    /// Returns `Error::OtherError` if the `BV` is more than 64 bits wide.
+    pub fn solutions_iter<'a>(&'a mut self, bv: &B::BV) -> Result<impl Iterator<Item = u64> + 'a> {
+        let width = bv.get_width();
+        if width > 64 {
+            return Err(Error::OtherError(format!(
+                "solutions_iter: BV is {} bits wide; only BVs up to 64 bits are supported",
+                width
+            )));
+        }
+        Ok(SolutionsIter::new(&self.solver, bv.clone()))
+    }
+
+    /// If both `a` and `b` have exactly one possible solution under the current
+    /// constraints, compute `op` on those solutions and return the result as a
+    /// constant `BV`. Otherwise, return `None`.
+    ///
+    /// This is useful for keeping expressions small in regions of the program
+    /// where all the values involved are effectively concrete. `a` and `b`
+    /// must have the same width.
+    ///
+    /// Also returns `None` if the result would be undefined or poison (division
+    /// by zero, or a shift by at least the width), or if a solver query fails.
+    pub fn try_fold_binop(&self, op: BinOp, a: &B::BV, b: &B::BV) -> Option<B::BV> {
+        let a = self.as_constant(a)?;
+        let b = self.as_constant(b)?;
+        let width = b.get_width();
+        let b_is_zero = b.as_binary_str()?.chars().all(|c| c == '0');
+        match op {
+            BinOp::UDiv | BinOp::SDiv | BinOp::URem | BinOp::SRem if b_is_zero => return None,
+            BinOp::Shl | BinOp::LShr | BinOp::AShr
+                if b.ugte(&self.bv_from_u32(width, width)).as_bool() != Some(false) =>
+            {
+                return None
+            },
+            _ => {},
+        }
+        Some(match op {
+            BinOp::Add => a.add(&b),
+            BinOp::Sub => a.sub(&b),
+            BinOp::Mul => a.mul(&b),
+            BinOp::UDiv => a.udiv(&b),
+            BinOp::SDiv => a.sdiv(&b),
+            BinOp::URem => a.urem(&b),
+            BinOp::SRem => a.srem(&b),
+            BinOp::And => a.and(&b),
+            BinOp::Or => a.or(&b),
+            BinOp::Xor => a.xor(&b),
+            BinOp::Shl => a.sll(&b),
+            BinOp::LShr => a.srl(&b),
+            BinOp::AShr => a.sra(&b),
+        })
+    }
+
+    /// If `bv` has exactly one possible solution under the current
+    /// constraints, return it as a constant `BV`
+    fn as_constant(&self, bv: &B::BV) -> Option<B::BV> {
+        if bv.as_binary_str().is_some() {
+            return Some(bv.clone());
+        }
+        match self.get_possible_solutions_for_bv(bv, 1) {
+            Ok(PossibleSolutions::Exactly(solutions)) if solutions.len() == 1 => {
+                let solution = solutions.into_iter().next().unwrap();
+                Some(B::BV::from_binary_str(
+                    self.solver.clone(),
+                    solution.as_01x_str(),
+                ))
             },
+            _ => None,
+        }
+    }
+
+    /// Get the maximum possible solution for the `BV`: that is, the highest value
+    /// for which the current set of constraints is still satisfiable.
+    /// "Maximum" will be interpreted in an unsigned fashion.
+    ///
+    /// Returns `Ok(None)` if there is no solution for the `BV`, that is, if the
+    /// current set of constraints is unsatisfiable. Only returns `Err` if a solver
+    /// query itself fails. Panics if the `BV` is wider than 64 bits.
+    pub fn max_possible_solution_for_bv_as_u64(&self, bv: &B::BV) -> Result<Option<u64>> {
+        solver_utils::max_possible_solution_for_bv_as_u64(self.solver.clone(), bv)
+    }
+
+    /// Get the maximum possible solution for the given IR `Name` (from the given
+    /// `Function` name): that is, the highest value for which the current set of
+    /// constraints is still satisfiable.
+    /// "Maximum" will be interpreted in an unsigned fashion.
+    ///
+    /// Returns `Ok(None)` if there is no solution for the `BV`, that is, if the
+    /// current set of constraints is unsatisfiable. Only returns `Err` if a solver
+    /// query itself fails. Panics if the `BV` is wider than 64 bits.
+    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
+    pub fn max_possible_solution_for_irname_as_u64(
+        &mut self,
+        funcname: &String,
+        name: &Name,
+    ) -> Result<Option<u64>> {
+        let bv = self.varmap.lookup_var(funcname, name);
+        solver_utils::max_possible_solution_for_bv_as_u64(self.solver.clone(), bv)
+    }
+
+    /// Get the minimum possible solution for the `BV`: that is, the lowest value
+    /// for which the current set of constraints is still satisfiable.
+    /// "Minimum" will be interpreted in an unsigned fashion.
+    ///
+    /// Returns `Ok(None)` if there is no solution for the `BV`, that is, if the
+    /// current set of constraints is unsatisfiable. Only returns `Err` if a solver
+    /// query itself fails. Panics if the `BV` is wider than 64 bits.
+    pub fn min_possible_solution_for_bv_as_u64(&self, bv: &B::BV) -> Result<Option<u64>> {
+        solver_utils::min_possible_solution_for_bv_as_u64(self.solver.clone(), bv)
+    }
+
+    /// Get the minimum possible solution for the given IR `Name` (from the given
+    /// `Function` name): that is, the lowest value for which the current set of
+    /// constraints is still satisfiable.
+    /// "Minimum" will be interpreted in an unsigned fashion.
+    ///
+    /// Returns `Ok(None)` if there is no solution for the `BV`, that is, if the
+    /// current set of constraints is unsatisfiable. Only returns `Err` if a solver
+    /// query itself fails. Panics if the `BV` is wider than 64 bits.
+    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
+    pub fn min_possible_solution_for_irname_as_u64(
+        &self,
+        funcname: &String,
+        name: &Name,
+    ) -> Result<Option<u64>> {
+        let bv = self.varmap.lookup_var(funcname, name);
+        solver_utils::min_possible_solution_for_bv_as_u64(self.solver.clone(), bv)
+    }
+
+    /// Export the current path as a concrete, reproducible [`TestCase`](struct.TestCase.html).
+    ///
+    /// `inputs`: the IR `Name`s (in the current function) of the inputs to
+    /// record, each paired with its expected width in bits. For instance, to
+    /// record the parameters of the top-level function, pass their `Name`s and
+    /// sizes here.
+    ///
+    /// The concrete values are all taken from a single solution to the current
+    /// constraints, so they are consistent with each other. Returns
+    /// `Error::Unsat` if the current constraints are unsatisfiable.
+    ///
+    /// Inputs wider than 64 bits are not currently supported, and inputs which
+    /// have no value in the current function are an `Error::OtherError`.
+    ///
+    /// The `TestCase` records the layout of memory, but not its contents: in
+    /// particular, it doesn't include the bytes that pointer inputs point to.
+    /// Use [`dump_memory()`](#method.dump_memory) to get concrete contents for
+    /// regions of interest.
+    pub fn export_testcase(&mut self, inputs: &[(&Name, u32)]) -> Result<TestCase> {
+        let bvs = inputs
+            .iter()
+            .map(|(name, bits)| {
+                let bv = self
+                    .varmap
+                    .try_lookup_var(&self.cur_loc.func.name, name)
+                    .ok_or_else(|| {
+                        Error::OtherError(format!(
+                            "export_testcase: no input named {} in function {:?}",
+                            name, self.cur_loc.func.name
+                        ))
+                    })?;
+                if bv.get_width() != *bits {
+                    Err(Error::OtherError(format!(
+                        "export_testcase: input {} was expected to be {} bits, but it is {} bits",
+                        name,
+                        bits,
+                        bv.get_width()
+                    )))
+                } else if *bits > 64 {
+                    Err(Error::OtherError(format!(
+                        "export_testcase: input {} is {} bits wide; inputs wider than 64 bits are not supported",
+                        name, bits
+                    )))
+                } else {
+                    Ok(bv.clone())
+                }
+            })
+            .collect::<Result<Vec<B::BV>>>()?;
+        self.solver.set_opt(BtorOption::ModelGen(ModelGen::All));
+        let values = if self.sat()? {
+            bvs.iter()
+                .map(|bv| {
+                    bv.get_a_solution().map(|sol| {
+                        sol.as_u64()
+                            .expect("input is at most 64 bits, so its solution fits in a u64")
+                    })
+                })
+                .collect::<Result<Vec<u64>>>()
+        } else {
+            Err(Error::Unsat)
+        };
+        self.solver
+            .set_opt(BtorOption::ModelGen(ModelGen::Disabled));
+        Ok(TestCase {
+            inputs: inputs
+                .iter()
+                .zip(values?)
+                .map(|((name, bits), value)| TestCaseInput {
+                    name: match name {
+                        Name::Name(s) => s.clone(),
+                        Name::Number(n) => format!("%{}", n),
+                    },
+                    bits: *bits,
+                    value,
+                })
+                .collect(),
+            allocations: self
+                .alloc
+                .get_all_allocations()
+                .into_iter()
+                .map(|(addr, bits)| TestCaseAllocation { addr, bits })
+                .collect(),
+        })
+    }
+
+    /// Get a concrete snapshot of the `len` bytes of memory starting at the
+    /// concrete address `addr`, in order.
+    ///
+    /// All of the bytes are taken from a single solution to the current
+    /// constraints. Returns `Ok(None)` if the contents of the region aren't
+    /// uniquely determined by the current constraints, i.e., if some other
+    /// solution gives different contents. Returns `Error::Unsat` if the
+    /// current constraints are unsatisfiable.
+    pub fn dump_memory(&mut self, addr: u64, len: usize) -> Result<Option<Vec<u8>>> {
+        if len == 0 {
+            return Ok(Some(Vec::new()));
+        }
+        let bits = (len as u64)
+            .checked_mul(8)
+            .filter(|&bits| bits <= u64::from(u32::MAX))
+            .ok_or_else(|| {
+                Error::OtherError(format!("dump_memory: {} bytes is too large a region", len))
+            })?;
+        let contents = self.read(&self.bv_from_u64(addr, 64), bits as u32)?;
+        self.solver.set_opt(BtorOption::ModelGen(ModelGen::All));
+        let solution = if self.sat()? {
+            contents.get_a_solution().map(|sol| sol.disambiguate())
+        } else {
+            Err(Error::Unsat)
+        };
+        self.solver
+            .set_opt(BtorOption::ModelGen(ModelGen::Disabled));
+        let solution = solution?;
+        let solution_bv = B::BV::from_binary_str(self.solver.clone(), solution.as_01x_str());
+        if !self.bvs_must_be_equal(&contents, &solution_bv)? {
+            return Ok(None);
         }
+        // the solution string has the most significant bit first, and the
+        // lowest-addressed byte is the least significant
+        let bytes = solution
+            .as_01x_str()
+            .as_bytes()
+            .rchunks(8)
+            .map(|byte| {
+                byte.iter()
+                    .fold(0u8, |acc, &bit| (acc << 1) | u8::from(bit == b'1'))
+            })
+            .collect();
+        Ok(Some(bytes))
+    }
+
+    /// Create a `BV` constant representing the given `bool` (either constant
+    /// `true` or constant `false`).
+    /// The resulting `BV` will be either constant `0` or constant `1`, and will
+    /// have bitwidth `1`.
+    pub fn bv_from_bool(&self, b: bool) -> B::BV {
+        B::BV::from_bool(self.solver.clone(), b)
+    }
+
+    /// Create a `BV` representing the given constant `i32` value, with the given
+    /// bitwidth.
+    pub fn bv_from_i32(&self, i: i32, width: u32) -> B::BV {
+        B::BV::from_i32(self.solver.clone(), i, width)
+    }
+
+    /// Create a `BV` representing the given constant `u32` value, with the given
+    /// bitwidth.
+    pub fn bv_from_u32(&self, u: u32, width: u32) -> B::BV {
+        B::BV::from_u32(self.solver.clone(), u, width)
+    }
+
+    /// Create a `BV` representing the given constant `i64` value, with the given
+    /// bitwidth.
+    pub fn bv_from_i64(&self, i: i64, width: u32) -> B::BV {
+        B::BV::from_i64(self.solver.clone(), i, width)
+    }
+
+    /// Create a `BV` representing the given constant `u64` value, with the given
+    /// bitwidth.
+    pub fn bv_from_u64(&self, u: u64, width: u32) -> B::BV {
+        B::BV::from_u64(self.solver.clone(), u, width)
+    }
+
+    /// Create a `BV` representing the constant `0` of the given bitwidth.
+    /// This is equivalent to `self.bv_from_i32(0, width)` but may be more
+    /// efficient.
+    pub fn zero(&self, width: u32) -> B::BV {
+        B::BV::zero(self.solver.clone(), width)
+    }
+
+    /// Create a `BV` representing the constant `1` of the given bitwidth.
+    /// This is equivalent to `self.bv_from_i32(1, width)` but may be more
+    /// efficient.
+    pub fn one(&self, width: u32) -> B::BV {
+        B::BV::one(self.solver.clone(), width)
+    }
+
+    /// Create a `BV` constant of the given width, where all bits are set to one.
+    /// This is equivalent to `self.bv_from_i32(-1, width)` but may be more
+    /// efficient.
+    pub fn ones(&self, width: u32) -> B::BV {
+        B::BV::ones(self.solver.clone(), width)
+    }
+
+    /// Create a new (unconstrained) `BV` for the given `Name` (in the current function).
+    ///
+    /// This function performs uniquing, so if you call it twice
+    /// with the same `Name`-`Function` pair, you will get two different `BV`s.
+    ///
+    /// Returns the new `BV`, or `Err` if it can't be created.
+    ///
+    /// (As of this writing, the only `Err` that might be returned is
+    /// `Error::LoopBoundExceeded`, which is returned if creating the new `BV`
+    /// would exceed `max_versions_of_name` -- see
+    /// [`Config`](struct.Config.html).)
+    ///
+    /// Also, we assume that no two `Function`s share the same name.
+    pub fn new_bv_with_name(&mut self, name: Name, bits: u32) -> Result<B::BV> {
+        self.varmap
+            .new_bv_with_name(self.cur_loc.func.name.clone(), name, bits)
+    }
+
+    /// Create a new (unconstrained) temporary `BV` of width `bits` in the
+    /// current function, and run `f` with it. The temporary is bound to a
+    /// fresh `Name` while `f` runs; afterwards the binding is removed, so it
+    /// doesn't clutter the variables in `all_vars_in_cur_fn()`. Any
+    /// constraints mentioning the temporary remain in place.
+    ///
+    /// Returns the result of `f`.
+    pub fn with_temp_var<R>(
+        &mut self,
+        bits: u32,
+        f: impl FnOnce(&mut Self, &B::BV) -> R,
+    ) -> Result<R> {
+        let funcname = self.cur_loc.func.name.clone();
+        let name = Name::from(format!("haybale_temp_{}", self.temp_vars_created));
+        self.temp_vars_created += 1;
+        let bv = self
+            .varmap
+            .new_bv_with_name(funcname.clone(), name.clone(), bits)?;
+        let result = f(self, &bv);
+        self.varmap.remove_var(&funcname, &name);
+        Ok(result)
+    }
+
+    /// Assign the given `BV` to the given `Name` (in the current function).
+    ///
+    /// This function performs uniquing, so it creates a new version of the
+    /// variable represented by the `(String, Name)` pair rather than overwriting
+    /// the current version.
+    ///
+    /// Returns `Err` if the assignment can't be performed.
+    ///
+    /// (As of this writing, the only `Err` that might be returned is
+    /// `Error::LoopBoundExceeded`, which is returned if creating the new version
+    /// of the `BV` would exceed `max_versions_of_name` -- see
+    /// [`Config`](struct.Config.html).)
+    pub fn assign_bv_to_name(&mut self, name: Name, bv: B::BV) -> Result<()> {
+        self.varmap
+            .assign_bv_to_name(self.cur_loc.func.name.clone(), name, bv)
+    }
+
+    /// Bind `to` to the same `BV` which `from` is currently bound to, both in
+    /// the current function, as with an `llvm.ssa.copy`. Unlike assigning a
+    /// fresh variable and constraining it to be equal, this creates no new
+    /// `BV` or constraint.
+    ///
+    /// Returns an error if `from` isn't bound, or (as with
+    /// `assign_bv_to_name()`) if the new version of `to` would exceed
+    /// `max_versions_of_name`.
+    pub fn copy_var(&mut self, from: &Name, to: Name) -> Result<()> {
+        self.varmap
+            .copy_binding(self.cur_loc.func.name.clone(), from, to)
+    }
+
+    /// Record the result of `thing` to be `resultval`.
+    /// Assumes `thing` is in the current function.
+    /// Will fail with `Error::LoopBoundExceeded` if that would exceed
+    /// `max_versions_of_name` (see [`Config`](struct.Config.html)).
+    #[cfg(debug_assertions)]
+    pub fn record_bv_result(
+        &mut self,
+        thing: &impl instruction::HasResult,
+        resultval: B::BV,
+    ) -> Result<()> {
+        if size(&thing.get_type()) as u32 != resultval.get_width() {
+            Err(Error::OtherError(format!(
+                "Computed result for an instruction has the wrong size: instruction {:?} with result size {}, but got result {:?} with size {}",
+                thing,
+                size(&thing.get_type()),
+                resultval,
+                resultval.get_width()
+            )))
+        } else {
+            self.assign_bv_to_name(thing.get_result().clone(), resultval)
+        }
+    }
+    #[cfg(not(debug_assertions))]
+    pub fn record_bv_result(
+        &mut self,
+        thing: &impl instruction::HasResult,
+        resultval: B::BV,
+    ) -> Result<()> {
+        self.assign_bv_to_name(thing.get_result().clone(), resultval)
+    }
+
+    /// Get the width (in bits) of the `BV` currently assigned to the given
+    /// `Name`, or `None` if the `Name` has not been assigned a value.
+    /// Assumes `Name` is in the current function.
+    pub fn var_width(&self, name: &Name) -> Option<u32> {
+        self.varmap.width_of(&self.cur_loc.func.name, name)
+    }
+
+    /// Overwrite the latest version of the given `Name` to instead be `bv`.
+    /// Assumes `Name` is in the current function.
+    pub fn overwrite_latest_version_of_bv(&mut self, name: &Name, bv: B::BV) {
+        self.varmap
+            .overwrite_latest_version_of_bv(&self.cur_loc.func.name, name, bv)
+    }
+
+    /// Convert an `Operand` to the appropriate `BV`.
+    /// Assumes the `Operand` is in the current function.
+    /// (All `Operand`s should be either a constant or a variable we previously added to the state.)
+    pub fn operand_to_bv(&self, op: &Operand) -> Result<B::BV> {
+        match op {
+            Operand::ConstantOperand(c) => self.const_to_bv(c),
+            Operand::LocalOperand { name, .. } => Ok(self
+                .varmap
+                .lookup_var(&self.cur_loc.func.name, name)
+                .clone()),
+            Operand::MetadataOperand => Err(self.unsupported("operand", op)),
+        }
+    }
+
+    /// Reverse the byte order of `bv`, as with the LLVM `bswap` intrinsic.
+    ///
+    /// The width of `bv` must be a multiple of 8 bits.
+    pub fn bswap(&self, bv: &B::BV) -> Result<B::BV> {
+        let width = bv.get_width();
+        if width == 0 || width % 8 != 0 {
+            return Err(Error::OtherError(format!(
+                "bswap: can't reverse the bytes of a value whose width ({} bits) isn't a multiple of 8",
+                width
+            )));
+        }
+        Ok((0 .. width / 8)
+            .map(|i| bv.slice(i * 8 + 7, i * 8))
+            .reduce(|a, b| a.concat(&b))
+            .expect("width is nonzero, so there is at least one byte"))
+    }
+
+    /// Get bit `index` of `bv` (where bit 0 is the least significant), as a
+    /// `BV` of width 1.
+    pub fn extract_bit(&self, bv: &B::BV, index: u32) -> Result<B::BV> {
+        Self::check_bit_index("extract_bit", bv, index)?;
+        Ok(bv.slice(index, index))
+    }
+
+    /// Get a copy of `bv` with bit `index` (where bit 0 is the least
+    /// significant) replaced by `value`, which must be a `BV` of width 1.
+    pub fn set_bit(&self, bv: &B::BV, index: u32, value: &B::BV) -> Result<B::BV> {
+        Self::check_bit_index("set_bit", bv, index)?;
+        if value.get_width() != 1 {
+            return Err(Error::OtherError(format!(
+                "set_bit: expected a value of width 1, but got width {}",
+                value.get_width()
+            )));
+        }
+        let width = bv.get_width();
+        let upper = if index == width - 1 {
+            value.clone()
+        } else {
+            bv.slice(width - 1, index + 1).concat(value)
+        };
+        if index == 0 {
+            Ok(upper)
+        } else {
+            Ok(upper.concat(&bv.slice(index - 1, 0)))
+        }
+    }
+
+    fn check_bit_index(op: &str, bv: &B::BV, index: u32) -> Result<()> {
+        if index >= bv.get_width() {
+            return Err(Error::OtherError(format!(
+                "{}: bit index {} is out of range for a value of width {}",
+                op,
+                index,
+                bv.get_width()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Rearrange the lanes of the vectors `a` and `b` (each of which consists
+    /// of lanes of `elem_bits` bits), as with the LLVM `shufflevector`
+    /// instruction.
+    ///
+    /// Lane `i` of the result is lane `mask[i]` of the concatenation of `a` and
+    /// `b`; that is, indices less than the number of lanes in `a` select lanes
+    /// from `a`, and subsequent indices select lanes from `b`. A mask entry of
+    /// `-1` gives an undef lane, which (as with other undef values) is 0.
+    pub fn shuffle_vector(
+        &self,
+        a: &B::BV,
+        b: &B::BV,
+        mask: &[i32],
+        elem_bits: u32,
+    ) -> Result<B::BV> {
+        if a.get_width() != b.get_width() {
+            return Err(Error::MalformedInstruction(format!(
+                "shuffle_vector: operands have different widths: {} and {}",
+                a.get_width(),
+                b.get_width()
+            )));
+        }
+        if elem_bits == 0 || a.get_width() % elem_bits != 0 {
+            return Err(Error::MalformedInstruction(format!(
+                "shuffle_vector: operand width {} isn't a multiple of the element size {}",
+                a.get_width(),
+                elem_bits
+            )));
+        }
+        let num_elements = a.get_width() / elem_bits;
+        mask.iter()
+            .map(|&idx| {
+                if idx == -1 {
+                    Ok(self.zero(elem_bits))
+                } else if idx < 0 || idx as u32 >= 2 * num_elements {
+                    Err(Error::MalformedInstruction(format!(
+                        "shuffle_vector: mask index {} is out of range for two vectors of {} elements",
+                        idx, num_elements
+                    )))
+                } else if (idx as u32) < num_elements {
+                    let idx = idx as u32;
+                    Ok(a.slice((idx + 1) * elem_bits - 1, idx * elem_bits))
+                } else {
+                    let idx = idx as u32 - num_elements;
+                    Ok(b.slice((idx + 1) * elem_bits - 1, idx * elem_bits))
+                }
+            })
+            .reduce(|a, b| Ok(b?.concat(&a?)))
+            .unwrap_or_else(|| {
+                Err(Error::MalformedInstruction(
+                    "shuffle_vector: mask has 0 elements".to_owned(),
+                ))
+            })
+    }
+
+    /// Extract the bit-field of `width` bits starting at bit `offset` of
+    /// `word`, as with the `lshr`-then-`trunc` sequence LLVM uses for reading
+    /// a struct bit-field. The result has width `width`.
+    ///
+    /// Returns `Error::OtherError` if `width` is zero or if `offset + width`
+    /// exceeds the width of `word`.
+    pub fn bitfield_extract(&self, word: &B::BV, offset: u32, width: u32) -> Result<B::BV> {
+        let end = Self::check_bitfield("bitfield_extract", word, offset, width)?;
+        Ok(word.slice(end - 1, offset))
+    }
+
+    /// Replace the bit-field of `width` bits starting at bit `offset` of
+    /// `word` with `value`, as with the `and`/`shl`/`or` sequence LLVM uses
+    /// for writing a struct bit-field. `value` must have width `width`; the
+    /// result has the same width as `word`.
+    ///
+    /// Returns `Error::OtherError` if `width` is zero, if `offset + width`
+    /// exceeds the width of `word`, or if `value` doesn't have width `width`.
+    pub fn bitfield_insert(
+        &self,
+        word: &B::BV,
+        value: &B::BV,
+        offset: u32,
+        width: u32,
+    ) -> Result<B::BV> {
+        Self::check_bitfield("bitfield_insert", word, offset, width)?;
+        if value.get_width() != width {
+            return Err(Error::OtherError(format!(
+                "bitfield_insert: expected a value of width {}, but got width {}",
+                width,
+                value.get_width()
+            )));
+        }
+        let word_width = word.get_width();
+        let offset_bv = self.bv_from_u32(offset, word_width);
+        let field_mask = self.ones(width).zext(word_width - width).sll(&offset_bv);
+        let shifted_value = value.zext(word_width - width).sll(&offset_bv);
+        Ok(word.and(&field_mask.not()).or(&shifted_value))
+    }
+
+    /// Check that a bit-field of `width` bits starting at bit `offset` is
+    /// nonempty and fits in `word`, returning the bit index just past its end
+    fn check_bitfield(op: &str, word: &B::BV, offset: u32, width: u32) -> Result<u32> {
+        if width == 0 {
+            return Err(Error::OtherError(format!(
+                "{}: bit-field width must be nonzero",
+                op
+            )));
+        }
+        match offset.checked_add(width) {
+            Some(end) if end <= word.get_width() => Ok(end),
+            _ => Err(Error::OtherError(format!(
+                "{}: field at offset {} with width {} doesn't fit in a word of width {}",
+                op,
+                offset,
+                width,
+                word.get_width()
+            ))),
+        }
+    }
+
+    /// Shift `bv` left by `amount`, as with the LLVM `shl` instruction.
+    /// `bv` and `amount` must have the same width.
+    ///
+    /// Under [`ShiftSemantics::Poison`](config/enum.ShiftSemantics.html)
+    /// (the default), this also returns a `BV` of width 1 which is true
+    /// exactly when the result is poison, i.e., when `amount` is at least the
+    /// width of `bv`. Under `ShiftSemantics::ModularReduce`, `amount` is
+    /// first reduced modulo the width, and the result is never poison.
+    pub fn shl(&self, bv: &B::BV, amount: &B::BV) -> Result<(B::BV, Option<B::BV>)> {
+        self.shift("shl", bv, amount, false, B::BV::sll)
+    }
+
+    /// Logical shift right, as with the LLVM `lshr` instruction.
+    /// See notes on [`shl()`](#method.shl).
+    ///
+    /// If `exact` (as with the LLVM flag of the same name), the result is also
+    /// poison if any of the bits shifted out are nonzero, so a poison `BV` is
+    /// returned under either `ShiftSemantics`.
+    pub fn lshr(&self, bv: &B::BV, amount: &B::BV, exact: bool) -> Result<(B::BV, Option<B::BV>)> {
+        self.shift("lshr", bv, amount, exact, B::BV::srl)
+    }
+
+    /// Arithmetic shift right, as with the LLVM `ashr` instruction.
+    /// See notes on [`lshr()`](#method.lshr).
+    pub fn ashr(&self, bv: &B::BV, amount: &B::BV, exact: bool) -> Result<(B::BV, Option<B::BV>)> {
+        self.shift("ashr", bv, amount, exact, B::BV::sra)
+    }
+
+    fn shift(
+        &self,
+        opname: &str,
+        bv: &B::BV,
+        amount: &B::BV,
+        exact: bool,
+        op: impl Fn(&B::BV, &B::BV) -> B::BV,
+    ) -> Result<(B::BV, Option<B::BV>)> {
+        Self::check_same_width(opname, bv, amount)?;
+        let width = bv.get_width();
+        let (amount, poison) = match self.config.shift_semantics {
+            ShiftSemantics::Poison => (
+                amount.clone(),
+                Some(amount.ugte(&self.bv_from_u32(width, width))),
+            ),
+            ShiftSemantics::ModularReduce => (reduce_shift_amount(amount), None),
+        };
+        let poison = if exact {
+            // the bits shifted out of a right shift are the low `amount` bits
+            let shifted_out = bv.and(&self.ones(width).sll(&amount).not());
+            let inexact = shifted_out._ne(&self.zero(width));
+            Some(match poison {
+                Some(poison) => poison.or(&inexact),
+                None => inexact,
+            })
+        } else {
+            poison
+        };
+        Ok((op(bv, &amount), poison))
+    }
+
+    /// Unsigned division, as with the LLVM `udiv` instruction.
+    /// `a` and `b` must have the same width.
+    ///
+    /// If `exact` (as with the LLVM flag of the same name), this also returns
+    /// a `BV` of width 1 which is true exactly when the result is poison,
+    /// i.e., when `b` doesn't divide `a` evenly. Otherwise, the result is
+    /// never poison. Division by zero is undefined behavior rather than
+    /// poison, and isn't reflected in the poison `BV`.
+    pub fn udiv(&self, a: &B::BV, b: &B::BV, exact: bool) -> Result<(B::BV, Option<B::BV>)> {
+        self.division("udiv", a, b, exact, B::BV::udiv, B::BV::urem)
+    }
+
+    /// Signed division, as with the LLVM `sdiv` instruction.
+    /// See notes on [`udiv()`](#method.udiv); dividing the minimum value by
+    /// -1 is also undefined behavior, and isn't reflected in the poison `BV`
+    /// either.
+    pub fn sdiv(&self, a: &B::BV, b: &B::BV, exact: bool) -> Result<(B::BV, Option<B::BV>)> {
+        self.division("sdiv", a, b, exact, B::BV::sdiv, B::BV::srem)
+    }
+
+    fn division(
+        &self,
+        opname: &str,
+        a: &B::BV,
+        b: &B::BV,
+        exact: bool,
+        div: impl Fn(&B::BV, &B::BV) -> B::BV,
+        rem: impl Fn(&B::BV, &B::BV) -> B::BV,
+    ) -> Result<(B::BV, Option<B::BV>)> {
+        Self::check_same_width(opname, a, b)?;
+        let poison = if exact {
+            Some(rem(a, b)._ne(&self.zero(a.get_width())))
+        } else {
+            None
+        };
+        Ok((div(a, b), poison))
+    }
+
+    /// Funnel shift left, as with the LLVM `fshl` intrinsic: concatenate `hi`
+    /// and `lo` (with `hi` as the most significant bits), shift the result left
+    /// by `shift` modulo the width of `hi`, and return the most significant
+    /// half.
+    ///
+    /// `hi`, `lo`, and `shift` must all have the same width.
+    pub fn fshl(&self, hi: &B::BV, lo: &B::BV, shift: &B::BV) -> Result<B::BV> {
+        let width = self.funnel_shift_width("fshl", hi, lo, shift)?;
+        let shift = shift
+            .urem(&self.bv_from_u32(width, width))
+            .zero_extend_to_bits(2 * width);
+        Ok(hi.concat(lo).sll(&shift).slice(2 * width - 1, width))
+    }
+
+    /// Funnel shift right, as with the LLVM `fshr` intrinsic: concatenate `hi`
+    /// and `lo` (with `hi` as the most significant bits), shift the result
+    /// right by `shift` modulo the width of `hi`, and return the least
+    /// significant half.
+    ///
+    /// `hi`, `lo`, and `shift` must all have the same width.
+    pub fn fshr(&self, hi: &B::BV, lo: &B::BV, shift: &B::BV) -> Result<B::BV> {
+        let width = self.funnel_shift_width("fshr", hi, lo, shift)?;
+        let shift = shift
+            .urem(&self.bv_from_u32(width, width))
+            .zero_extend_to_bits(2 * width);
+        Ok(hi.concat(lo).srl(&shift).slice(width - 1, 0))
+    }
+
+    /// Check that the operands of a funnel shift all have the same (nonzero)
+    /// width, and return that width
+    fn funnel_shift_width(&self, op: &str, hi: &B::BV, lo: &B::BV, shift: &B::BV) -> Result<u32> {
+        let width = hi.get_width();
+        if width == 0 || lo.get_width() != width || shift.get_width() != width {
+            return Err(Error::MalformedInstruction(format!(
+                "{}: expected operands of the same width, but got widths {}, {}, and {}",
+                op,
+                width,
+                lo.get_width(),
+                shift.get_width()
+            )));
+        }
+        Ok(width)
+    }
+
+    /// Unsigned saturating addition, as with the LLVM `uadd.sat` intrinsic:
+    /// returns `a + b`, clamped to the maximum unsigned value on overflow.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn uadd_sat(&self, a: &B::BV, b: &B::BV) -> Result<B::BV> {
+        Self::check_same_width("uadd_sat", a, b)?;
+        Ok(a.uadds(b))
+    }
+
+    /// Signed saturating addition, as with the LLVM `sadd.sat` intrinsic:
+    /// returns `a + b`, clamped to the maximum or minimum signed value on
+    /// overflow.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn sadd_sat(&self, a: &B::BV, b: &B::BV) -> Result<B::BV> {
+        Self::check_same_width("sadd_sat", a, b)?;
+        Ok(a.sadds(b))
+    }
+
+    /// Unsigned saturating subtraction, as with the LLVM `usub.sat` intrinsic:
+    /// returns `a - b`, clamped to zero on overflow.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn usub_sat(&self, a: &B::BV, b: &B::BV) -> Result<B::BV> {
+        Self::check_same_width("usub_sat", a, b)?;
+        Ok(a.usubs(b))
+    }
+
+    /// Signed saturating subtraction, as with the LLVM `ssub.sat` intrinsic:
+    /// returns `a - b`, clamped to the maximum or minimum signed value on
+    /// overflow.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn ssub_sat(&self, a: &B::BV, b: &B::BV) -> Result<B::BV> {
+        Self::check_same_width("ssub_sat", a, b)?;
+        Ok(a.ssubs(b))
+    }
+
+    /// Perform `op` on `a` and `b`, also computing whether it overflows, as
+    /// with the LLVM `uadd.with.overflow`, `sadd.with.overflow`,
+    /// `usub.with.overflow`, `ssub.with.overflow`, `umul.with.overflow`, and
+    /// `smul.with.overflow` intrinsics. Overflow is signed if `signed` is true,
+    /// or unsigned otherwise.
+    ///
+    /// Like those intrinsics' `{ result, i1 }` return value, the returned `BV`
+    /// has the (wrapped) result in its low bits and the overflow flag in its
+    /// single highest bit, which is how `extractvalue` would slice it.
+    ///
+    /// `op` must be `BinOp::Add`, `BinOp::Sub`, or `BinOp::Mul`. `a` and `b`
+    /// must have the same width.
+    pub fn binop_with_overflow(
+        &self,
+        op: BinOp,
+        a: &B::BV,
+        b: &B::BV,
+        signed: bool,
+    ) -> Result<B::BV> {
+        Self::check_same_width("binop_with_overflow", a, b)?;
+        let (result, overflow) = match (op, signed) {
+            (BinOp::Add, false) => (a.add(b), a.uaddo(b)),
+            (BinOp::Add, true) => (a.add(b), a.saddo(b)),
+            (BinOp::Sub, false) => (a.sub(b), a.usubo(b)),
+            (BinOp::Sub, true) => (a.sub(b), a.ssubo(b)),
+            (BinOp::Mul, false) => (a.mul(b), a.umulo(b)),
+            (BinOp::Mul, true) => (a.mul(b), a.smulo(b)),
+            _ => {
+                return Err(Error::OtherError(format!(
+                    "binop_with_overflow: no overflow-checking version of {:?}",
+                    op
+                )))
+            },
+        };
+        assert_eq!(overflow.get_width(), 1);
+        Ok(overflow.concat(&result))
+    }
+
+    /// Saturating addition or subtraction of the vectors `a` and `b` (each of
+    /// which consists of lanes of `elem_bits` bits), lane by lane, as with the
+    /// vector forms of the LLVM `uadd.sat`, `sadd.sat`, `usub.sat`, and
+    /// `ssub.sat` intrinsics. The lanes are interpreted as signed if `signed`
+    /// is true, or unsigned otherwise.
+    ///
+    /// `op` must be `BinOp::Add` or `BinOp::Sub`. `a` and `b` must have the
+    /// same width, which must be a multiple of `elem_bits`.
+    pub fn vector_binop_sat(
+        &self,
+        op: BinOp,
+        a: &B::BV,
+        b: &B::BV,
+        elem_bits: u32,
+        signed: bool,
+    ) -> Result<B::BV> {
+        Self::check_same_width("vector_binop_sat", a, b)?;
+        if elem_bits == 0 || a.get_width() % elem_bits != 0 {
+            return Err(Error::MalformedInstruction(format!(
+                "vector_binop_sat: operand width {} isn't a multiple of the element size {}",
+                a.get_width(),
+                elem_bits
+            )));
+        }
+        let lane_op = match (op, signed) {
+            (BinOp::Add, false) => Self::uadd_sat,
+            (BinOp::Add, true) => Self::sadd_sat,
+            (BinOp::Sub, false) => Self::usub_sat,
+            (BinOp::Sub, true) => Self::ssub_sat,
+            _ => {
+                return Err(Error::OtherError(format!(
+                    "vector_binop_sat: no saturating version of {:?}",
+                    op
+                )))
+            },
+        };
+        let num_elements = a.get_width() / elem_bits;
+        let mut result: Option<B::BV> = None;
+        for i in 0 .. num_elements {
+            let (high, low) = ((i + 1) * elem_bits - 1, i * elem_bits);
+            let lane = lane_op(self, &a.slice(high, low), &b.slice(high, low))?;
+            result = Some(match result {
+                None => lane,
+                Some(lower_lanes) => lane.concat(&lower_lanes),
+            });
+        }
+        Ok(result.expect("vector_binop_sat: expected at least one lane"))
+    }
+
+    /// Unsigned fixed-point multiplication, as with the LLVM `umul.fix`
+    /// intrinsic: `a` and `b` are interpreted as unsigned fixed-point values
+    /// with `scale` fractional bits, and the product is computed at double
+    /// width and then scaled back down, rounding toward zero. Like LLVM, this
+    /// wraps if the result doesn't fit.
+    ///
+    /// `a` and `b` must have the same width, and `scale` must be at most that
+    /// width.
+    pub fn umul_fix(&self, a: &B::BV, b: &B::BV, scale: u32) -> Result<B::BV> {
+        self.mul_fix("umul_fix", a, b, scale, false)
+    }
+
+    /// Signed fixed-point multiplication, as with the LLVM `smul.fix`
+    /// intrinsic. See notes on [`umul_fix()`](#method.umul_fix); here, `a` and
+    /// `b` are interpreted as signed values, and `scale` must be less than
+    /// their width.
+    pub fn smul_fix(&self, a: &B::BV, b: &B::BV, scale: u32) -> Result<B::BV> {
+        self.mul_fix("smul_fix", a, b, scale, true)
+    }
+
+    fn mul_fix(&self, op: &str, a: &B::BV, b: &B::BV, scale: u32, signed: bool) -> Result<B::BV> {
+        Self::check_same_width(op, a, b)?;
+        let width = a.get_width();
+        if scale > width || (signed && scale == width) {
+            return Err(Error::MalformedInstruction(format!(
+                "{}: scale {} is too large for operands of width {}",
+                op, scale, width
+            )));
+        }
+        let product = if signed {
+            a.sext(width).mul(&b.sext(width))
+        } else {
+            a.zext(width).mul(&b.zext(width))
+        };
+        let scaled = if scale == 0 {
+            product
+        } else if signed {
+            // an arithmetic shift rounds toward negative infinity, so for
+            // negative products, first add `2^scale - 1` to round toward zero
+            let double_width = 2 * width;
+            let negative = product.slt(&self.zero(double_width));
+            let bias = self.ones(scale).zext(double_width - scale);
+            negative
+                .cond_bv(&product.add(&bias), &product)
+                .sra(&self.bv_from_u32(scale, double_width))
+        } else {
+            product.srl(&self.bv_from_u32(scale, 2 * width))
+        };
+        Ok(scaled.slice(width - 1, 0))
+    }
+
+    /// Absolute value, as with the LLVM `abs` intrinsic, interpreting `bv` as
+    /// a signed value. The absolute value of the minimum signed value is
+    /// itself.
+    ///
+    /// If `is_int_min_poison`, this also returns a `BV` of width 1 which is
+    /// true exactly when the result is poison, i.e., when `bv` is the minimum
+    /// signed value. (This corresponds to the second argument of the LLVM
+    /// intrinsic.)
+    pub fn abs(&self, bv: &B::BV, is_int_min_poison: bool) -> (B::BV, Option<B::BV>) {
+        let width = bv.get_width();
+        let negative = bv.slt(&self.zero(width));
+        let result = negative.cond_bv(&bv.neg(), bv);
+        let poison = if is_int_min_poison {
+            let int_min = self.one(width).sll(&self.bv_from_u32(width - 1, width));
+            Some(bv._eq(&int_min))
+        } else {
+            None
+        };
+        (result, poison)
+    }
+
+    /// Signed maximum, as with the LLVM `smax` intrinsic.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn smax(&self, a: &B::BV, b: &B::BV) -> Result<B::BV> {
+        Self::check_same_width("smax", a, b)?;
+        Ok(a.sgt(b).cond_bv(a, b))
+    }
+
+    /// Signed minimum, as with the LLVM `smin` intrinsic.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn smin(&self, a: &B::BV, b: &B::BV) -> Result<B::BV> {
+        Self::check_same_width("smin", a, b)?;
+        Ok(a.slt(b).cond_bv(a, b))
+    }
+
+    /// Unsigned maximum, as with the LLVM `umax` intrinsic.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn umax(&self, a: &B::BV, b: &B::BV) -> Result<B::BV> {
+        Self::check_same_width("umax", a, b)?;
+        Ok(a.ugt(b).cond_bv(a, b))
+    }
+
+    /// Unsigned minimum, as with the LLVM `umin` intrinsic.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn umin(&self, a: &B::BV, b: &B::BV) -> Result<B::BV> {
+        Self::check_same_width("umin", a, b)?;
+        Ok(a.ult(b).cond_bv(a, b))
+    }
+
+    /// Three-way comparison of `a` and `b`, as with C++'s `<=>` or the result
+    /// of a `qsort()` comparator: returns a `BV` of width 32 which is -1 if
+    /// `a < b`, 0 if `a == b`, or 1 if `a > b`. The comparison is signed if
+    /// `signed` is true, or unsigned otherwise.
+    ///
+    /// `a` and `b` must have the same width.
+    pub fn three_way_compare(&self, a: &B::BV, b: &B::BV, signed: bool) -> Result<B::BV> {
+        Self::check_same_width("three_way_compare", a, b)?;
+        let less = if signed { a.slt(b) } else { a.ult(b) };
+        Ok(less.cond_bv(
+            &self.ones(32),
+            &a._eq(b).cond_bv(&self.zero(32), &self.one(32)),
+        ))
+    }
+
+    /// Check that `a` and `b` have the same (nonzero) width, as required by
+    /// the saturating arithmetic and min/max operations
+    fn check_same_width(op: &str, a: &B::BV, b: &B::BV) -> Result<()> {
+        if a.get_width() == 0 || a.get_width() != b.get_width() {
+            return Err(Error::MalformedInstruction(format!(
+                "{}: expected operands of the same width, but got widths {} and {}",
+                op,
+                a.get_width(),
+                b.get_width()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reinterpret the bits of `val` (which has type `from_ty`) as a value of
+    /// type `to_ty`, as with the LLVM `bitcast` instruction.
+    ///
+    /// `from_ty` and `to_ty` must have the same size, and `val` must have
+    /// exactly that width. Values of floating-point type are represented as
+    /// `BV`s holding their IEEE bit patterns, so casts between floating-point
+    /// and integer types require no conversion: the returned `BV` is simply
+    /// `val` itself.
+    ///
+    /// If either type has no known size (see
+    /// [`layout::try_size()`](layout/fn.try_size.html)), the sizes aren't
+    /// checked, and `val` is returned as-is.
+    pub fn bitcast(&self, val: &B::BV, from_ty: &Type, to_ty: &Type) -> Result<B::BV> {
+        // types whose size we can't determine (e.g., opaque structs) aren't
+        // checked; as with any other cast, the bit pattern is unchanged
+        let (from_size, to_size) = match (try_size(from_ty), try_size(to_ty)) {
+            (Some(from_size), Some(to_size)) => (from_size, to_size),
+            _ => return Ok(val.clone()),
+        };
+        if from_size != to_size {
+            return Err(Error::MalformedInstruction(format!(
+                "Bitcast between types of different sizes: from {:?} ({} bits) to {:?} ({} bits)",
+                from_ty, from_size, to_ty, to_size
+            )));
+        }
+        if val.get_width() as usize != from_size {
+            return Err(Error::MalformedInstruction(format!(
+                "Bitcast of a value of width {} bits, but its type {:?} has size {} bits",
+                val.get_width(),
+                from_ty,
+                from_size
+            )));
+        }
+        Ok(val.clone())
+    }
+
+    /// Get a `BV` (of width 1) which is true if the floating-point values `a`
+    /// and `b` are within `max_ulps` units in the last place of each other.
+    ///
+    /// As with `bitcast()`, floating-point values are represented as `BV`s
+    /// holding their IEEE bit patterns; `a` and `b` must both be half-,
+    /// single-, or double-precision values of the same width. Positive and
+    /// negative zero are 0 ULPs apart, an infinity is 1 ULP from the largest
+    /// finite value of the same sign, and a NaN is never approximately equal
+    /// to anything (including itself).
+    pub fn float_approx_eq(&self, a: &B::BV, b: &B::BV, max_ulps: u64) -> Result<B::BV> {
+        let width = a.get_width();
+        let (_, mantissa_bits) = float_format(width, "float_approx_eq")?;
+        if b.get_width() != width {
+            return Err(Error::OtherError(format!(
+                "float_approx_eq: values have different widths: {} and {}",
+                width,
+                b.get_width()
+            )));
+        }
+        // Map each bit pattern to a (width + 1)-bit signed integer such that
+        // adjacent floats map to adjacent integers, and both zeroes map to 0
+        let to_ordered = |x: &B::BV| {
+            let magnitude = x.slice(width - 2, 0).zext(2);
+            x.slice(width - 1, width - 1)
+                .cond_bv(&magnitude.neg(), &magnitude)
+        };
+        let diff = to_ordered(a).sub(&to_ordered(b));
+        let zero = self.zero(width + 1);
+        let distance = diff.slt(&zero).cond_bv(&diff.neg(), &diff);
+        // no two distances are as large as 2^width, so clamping there keeps the bound in range
+        let max_ulps = if width < 64 {
+            std::cmp::min(max_ulps, 1 << width)
+        } else {
+            max_ulps
+        };
+        let max_ulps = self
+            .bv_from_u64(max_ulps, std::cmp::min(width + 1, 64))
+            .zero_extend_to_bits(width + 1);
+        Ok(distance
+            .ulte(&max_ulps)
+            .and(&float_is_nan(a, mantissa_bits).not())
+            .and(&float_is_nan(b, mantissa_bits).not()))
+    }
+
+    /// Constrain the floating-point value `f` to be finite on the current path:
+    /// neither a NaN nor an infinity. This is useful for analyses which assume
+    /// well-behaved floats.
+    ///
+    /// As with `bitcast()`, floating-point values are represented as `BV`s
+    /// holding their IEEE bit patterns; `f` must be a half-, single-, or
+    /// double-precision value.
+    pub fn assert_finite(&self, f: &B::BV) -> Result<()> {
+        let (_, mantissa_bits) = float_format(f.get_width(), "assert_finite")?;
+        self.assert(&float_is_nan(f, mantissa_bits).not())?;
+        self.assert(&float_is_infinite(f, mantissa_bits).not())
+    }
+
+    /// Returns `true` if the floating-point value `f` can be a NaN under the
+    /// current constraints. `f` is represented as described on
+    /// `assert_finite()`.
+    pub fn check_can_be_nan(&self, f: &B::BV) -> Result<bool> {
+        let (_, mantissa_bits) = float_format(f.get_width(), "check_can_be_nan")?;
+        self.sat_with_extra_constraints(std::iter::once(&float_is_nan(f, mantissa_bits)))
+    }
+
+    /// Convert the signed integer `val` to a floating-point value of width
+    /// `to_width`, as with the LLVM `sitofp` instruction.
+    ///
+    /// As with `bitcast()`, floating-point values are represented as `BV`s
+    /// holding their IEEE bit patterns; `to_width` must be 16, 32, or 64.
+    /// Values which can't be represented exactly are rounded to nearest, ties
+    /// to even, and values too large for the floating-point type become
+    /// infinities.
+    pub fn sitofp(&self, val: &B::BV, to_width: u32) -> Result<B::BV> {
+        let width = val.get_width();
+        let negative = val.slice(width - 1, width - 1);
+        // for the most negative value, `neg()` gives the same bit pattern, which is the correct magnitude when interpreted as unsigned
+        let magnitude = negative.cond_bv(&val.neg(), val);
+        self.int_to_float(&magnitude, &negative, to_width, "sitofp")
+    }
+
+    /// Convert the unsigned integer `val` to a floating-point value of width
+    /// `to_width`, as with the LLVM `uitofp` instruction.
+    ///
+    /// Rounding is as described on `sitofp()`.
+    pub fn uitofp(&self, val: &B::BV, to_width: u32) -> Result<B::BV> {
+        self.int_to_float(val, &self.bv_from_bool(false), to_width, "uitofp")
+    }
+
+    /// Convert the floating-point value `val` to a signed integer of width
+    /// `to_width`, as with the LLVM `fptosi` instruction.
+    ///
+    /// The value is rounded toward zero. Where LLVM would produce a poison
+    /// value, this saturates instead, as with the `llvm.fptosi.sat`
+    /// intrinsic: values too large (or too small) for the integer type,
+    /// including infinities, become the largest (or smallest) integer value,
+    /// and NaNs become 0.
+    pub fn fptosi(&self, val: &B::BV, to_width: u32) -> Result<B::BV> {
+        self.float_to_int(val, to_width, true, "fptosi")
+    }
+
+    /// Convert the floating-point value `val` to an unsigned integer of width
+    /// `to_width`, as with the LLVM `fptoui` instruction.
+    ///
+    /// Rounding and saturation are as described on `fptosi()`; in particular,
+    /// negative values become 0.
+    pub fn fptoui(&self, val: &B::BV, to_width: u32) -> Result<B::BV> {
+        self.float_to_int(val, to_width, false, "fptoui")
+    }
+
+    /// Convert the unsigned integer `magnitude` to a floating-point value of
+    /// width `to_width`, which is negative if `negative` (of width 1) is true
+    fn int_to_float(
+        &self,
+        magnitude: &B::BV,
+        negative: &B::BV,
+        to_width: u32,
+        caller: &str,
+    ) -> Result<B::BV> {
+        let (exponent_bits, mantissa_bits) = float_format(to_width, caller)?;
+        let width = magnitude.get_width();
+        // width in which we compute the exponent
+        let exp_width = 32;
+
+        // find the highest set bit of `magnitude`, and shift it up to the top
+        let mut highest_bit = self.zero(exp_width);
+        let mut leading_zeros = self.bv_from_u64(u64::from(width - 1), width);
+        for i in 1 .. width {
+            let bit_set = magnitude.slice(i, i);
+            highest_bit = bit_set.cond_bv(&self.bv_from_u32(i, exp_width), &highest_bit);
+            leading_zeros = bit_set.cond_bv(
+                &self.bv_from_u64(u64::from(width - 1 - i), width),
+                &leading_zeros,
+            );
+        }
+        let normalized = magnitude.sll(&leading_zeros);
+
+        // the bits below the (implicit) leading 1 form the mantissa, rounded
+        // to nearest, ties to even, if there are too many of them
+        let fraction_bits = width - 1;
+        let (mantissa, exponent_carry) = if fraction_bits <= mantissa_bits {
+            let mantissa = if fraction_bits == 0 {
+                self.zero(mantissa_bits)
+            } else if fraction_bits == mantissa_bits {
+                normalized.slice(fraction_bits - 1, 0)
+            } else {
+                normalized
+                    .slice(fraction_bits - 1, 0)
+                    .concat(&self.zero(mantissa_bits - fraction_bits))
+            };
+            (mantissa, self.zero(exp_width))
+        } else {
+            let dropped_bits = fraction_bits - mantissa_bits;
+            let kept = normalized.slice(fraction_bits - 1, dropped_bits);
+            let guard = normalized.slice(dropped_bits - 1, dropped_bits - 1);
+            let sticky = if dropped_bits >= 2 {
+                normalized.slice(dropped_bits - 2, 0).redor()
+            } else {
+                self.bv_from_bool(false)
+            };
+            let round_up = guard.and(&sticky.or(&kept.slice(0, 0)));
+            let rounded = kept.zext(1).add(&round_up.zext(mantissa_bits));
+            // if rounding up carries out of the mantissa, the mantissa is all zeroes, and the exponent is one higher
+            (
+                rounded.slice(mantissa_bits - 1, 0),
+                rounded
+                    .slice(mantissa_bits, mantissa_bits)
+                    .zext(exp_width - 1),
+            )
+        };
+
+        let bias = (1 << (exponent_bits - 1)) - 1;
+        let exponent = self
+            .bv_from_u32(bias, exp_width)
+            .add(&highest_bit)
+            .add(&exponent_carry);
+        let overflow = exponent.ugte(&self.bv_from_u32((1 << exponent_bits) - 1, exp_width));
+        let exponent = overflow.cond_bv(
+            &self.ones(exponent_bits),
+            &exponent.slice(exponent_bits - 1, 0),
+        );
+        let mantissa = overflow.cond_bv(&self.zero(mantissa_bits), &mantissa);
+
+        let is_zero = magnitude._eq(&self.zero(width));
+        Ok(is_zero.cond_bv(
+            &self.zero(to_width),
+            &negative.concat(&exponent).concat(&mantissa),
+        ))
+    }
+
+    /// Convert the floating-point value `val` to an integer of width
+    /// `to_width`, rounding toward zero and saturating
+    fn float_to_int(
+        &self,
+        val: &B::BV,
+        to_width: u32,
+        signed: bool,
+        caller: &str,
+    ) -> Result<B::BV> {
+        let width = val.get_width();
+        let (exponent_bits, mantissa_bits) = float_format(width, caller)?;
+        let negative = val.slice(width - 1, width - 1);
+        let exponent = val.slice(width - 2, mantissa_bits);
+        let infinite = exponent.redand(); // or NaN, but we check for that separately
+
+        // width in which we compute the integer value: enough for the
+        // significand shifted by any amount which doesn't saturate
+        let calc_width = to_width + mantissa_bits + 1;
+        let bias = (1 << (exponent_bits - 1)) - 1;
+        // unbiased exponent, as a signed value
+        let unbiased = exponent
+            .zext(calc_width - exponent_bits)
+            .sub(&self.bv_from_u64(bias, calc_width));
+        // the significand with its implicit leading 1. This is wrong for
+        // zeroes and subnormals, but their magnitude is less than one anyway
+        let significand = self
+            .bv_from_bool(true)
+            .concat(&val.slice(mantissa_bits - 1, 0))
+            .zext(to_width);
+        let mantissa_bits_bv = self.bv_from_u64(u64::from(mantissa_bits), calc_width);
+        let magnitude = unbiased
+            .sgte(&mantissa_bits_bv)
+            .cond_bv(
+                &significand.sll(&unbiased.sub(&mantissa_bits_bv)),
+                &significand.srl(&mantissa_bits_bv.sub(&unbiased)),
+            )
+            .slice(to_width - 1, 0);
+        let less_than_one = unbiased.slt(&self.zero(calc_width));
+
+        let zero = self.zero(to_width);
+        let result = if signed {
+            // a magnitude of at least 2^(to_width - 1) saturates. (For negative
+            // values, a magnitude of exactly that gives the smallest integer
+            // value, which is also the saturated value.)
+            let too_large = unbiased
+                .sgte(&self.bv_from_u64(u64::from(to_width - 1), calc_width))
+                .or(&infinite);
+            let int_max = self.ones(to_width).srl(&self.one(to_width));
+            let int_min = int_max.not();
+            less_than_one.cond_bv(
+                &zero,
+                &too_large.cond_bv(
+                    &negative.cond_bv(&int_min, &int_max),
+                    &negative.cond_bv(&magnitude.neg(), &magnitude),
+                ),
+            )
+        } else {
+            let too_large = unbiased
+                .sgte(&self.bv_from_u64(u64::from(to_width), calc_width))
+                .or(&infinite);
+            less_than_one
+                .or(&negative)
+                .cond_bv(&zero, &too_large.cond_bv(&self.ones(to_width), &magnitude))
+        };
+        Ok(float_is_nan(val, mantissa_bits).cond_bv(&zero, &result))
+    }
+
+    /// Convert a `Constant` to the appropriate `BV`.
+    pub fn const_to_bv(&self, c: &Constant) -> Result<B::BV> {
+        match c {
+            Constant::Int { bits, value } => Ok(self.bv_from_u64(*value, *bits)),
+            Constant::Null(ty) | Constant::AggregateZero(ty) | Constant::Undef(ty) => {
+                Ok(self.zero(size(ty) as u32))
+            },
+            Constant::Struct {
+                values: elements, ..
+            }
+            | Constant::Array { elements, .. }
+            | Constant::Vector(elements) => elements
+                .iter()
+                .map(|c| self.const_to_bv(c)) // produces an iterator over Result<B::BV>
+                .reduce(|a, b| Ok(b?.concat(&a?))) // the lambda has type Fn(Result<B::BV>, Result<B::BV>) -> Result<B::BV>
+                .unwrap(), // unwrap the Option<> produced by reduce(), leaving the final return type Result<B::BV>
+            Constant::GlobalReference { name, .. } => {
+                if let Some(ga) = self
+                    .global_allocations
+                    .get_global_allocation(name, self.cur_loc.module)
+                {
+                    match ga {
+                        GlobalAllocation::Function { addr, .. } => Ok(addr.clone()),
+                        GlobalAllocation::GlobalVariable {
+                            addr,
+                            initializer,
+                            initialized,
+                        } => {
+                            // First, initialize the global if it hasn't been already.
+                            // As mentioned in comments in `State::new()`, we lazily
+                            // initialize globals upon first reference to them.
+                            //
+                            // We assume that global-variable initializers can only refer to the
+                            // *addresses* of other globals, and not the *values* of other
+                            // global constants, so that it's fine that any referred-to globals
+                            // may have been allocated but not initialized at this point.
+                            // This assumption seems to hold empirically: in my tests,
+                            // (1) clang performs constant-folding, even at -O0, on global
+                            //     variable initializers so that these initializers do not refer to
+                            //     the values of other global constants at the LLVM level. For
+                            //     instance, the C code
+                            //       `const int a = 1; const int b = a + 3;`
+                            //     is translated into the LLVM equivalent of
+                            //       `const int a = 1; const int b = 4;`
+                            // (2) clang rejects programs where global variable initializers refer
+                            //     to the value of externally-defined global constants, in which
+                            //     case the constant-folding described above would be impossible.
+                            //     Note, however, that clang does allow referring to the *addresses*
+                            //     of externally-defined global variables.
+                            // Therefore, we can go ahead and set our `.initialized` flag early,
+                            // because even if `const_to_bv` on our initializer references other
+                            // globals (possibly causing their lazy initialization as well),
+                            // those globals can't refer to our contents, so won't know that we
+                            // are lying about being initialized.
+                            // Setting the flag early prevents an infinite loop where I try to
+                            // initialize, but my initializer refers to your address so you try
+                            // to initialize, but your initializer refers to my address so I try
+                            // to initialize, etc.
+                            if !initialized.get() {
+                                debug!(
+                                    "Initializing {:?} with initializer {:?}",
+                                    name, &initializer
+                                );
+                                initialized.set(true);
+                                let write_val = self.const_to_bv(initializer)?;
+                                self.write_without_mut(addr, write_val)?;
+                            }
+                            Ok(addr.clone())
+                        },
+                    }
+                } else if let Some(alias) = self
+                    .cur_loc
+                    .module
+                    .global_aliases
+                    .iter()
+                    .find(|a| &a.name == name)
+                {
+                    self.const_to_bv(&alias.aliasee)
+                } else {
+                    Err(Error::OtherError(format!("const_to_bv: GlobalReference to {:?} which was not found (current module is {:?})", name, &self.cur_loc.module.name)))
+                }
+            },
+            Constant::Add(a) => Ok(self
+                .const_to_bv(&a.operand0)?
+                .add(&self.const_to_bv(&a.operand1)?)),
+            Constant::Sub(s) => Ok(self
+                .const_to_bv(&s.operand0)?
+                .sub(&self.const_to_bv(&s.operand1)?)),
+            Constant::Mul(m) => Ok(self
+                .const_to_bv(&m.operand0)?
+                .mul(&self.const_to_bv(&m.operand1)?)),
+            Constant::UDiv(u) => Ok(self
+                .const_to_bv(&u.operand0)?
+                .udiv(&self.const_to_bv(&u.operand1)?)),
+            Constant::SDiv(s) => Ok(self
+                .const_to_bv(&s.operand0)?
+                .sdiv(&self.const_to_bv(&s.operand1)?)),
+            Constant::URem(u) => Ok(self
+                .const_to_bv(&u.operand0)?
+                .urem(&self.const_to_bv(&u.operand1)?)),
+            Constant::SRem(s) => Ok(self
+                .const_to_bv(&s.operand0)?
+                .srem(&self.const_to_bv(&s.operand1)?)),
+            Constant::And(a) => Ok(self
+                .const_to_bv(&a.operand0)?
+                .and(&self.const_to_bv(&a.operand1)?)),
+            Constant::Or(o) => Ok(self
+                .const_to_bv(&o.operand0)?
+                .or(&self.const_to_bv(&o.operand1)?)),
+            Constant::Xor(x) => Ok(self
+                .const_to_bv(&x.operand0)?
+                .xor(&self.const_to_bv(&x.operand1)?)),
+            Constant::Shl(s) => Ok(self
+                .const_to_bv(&s.operand0)?
+                .sll(&self.const_to_bv(&s.operand1)?)),
+            Constant::LShr(s) => Ok(self
+                .const_to_bv(&s.operand0)?
+                .srl(&self.const_to_bv(&s.operand1)?)),
+            Constant::AShr(s) => Ok(self
+                .const_to_bv(&s.operand0)?
+                .sra(&self.const_to_bv(&s.operand1)?)),
+            Constant::ExtractElement(ee) => match &ee.index {
+                Constant::Int { value: index, .. } => match &ee.vector {
+                    Constant::Vector(els) => {
+                        let el = els.get(*index as usize).ok_or_else(|| {
+                            Error::MalformedInstruction(
+                                "Constant::ExtractElement index out of range".to_owned(),
+                            )
+                        })?;
+                        self.const_to_bv(el)
+                    },
+                    c => Err(Error::MalformedInstruction(format!(
+                        "Expected ExtractElement.vector to be a Constant::Vector, got {:?}",
+                        c
+                    ))),
+                },
+                index => Err(Error::MalformedInstruction(format!(
+                    "Expected ExtractElement.index to be a Constant::Int, but got {:?}",
+                    index
+                ))),
+            },
+            Constant::InsertElement(ie) => match &ie.index {
+                Constant::Int { value: index, .. } => match &ie.vector {
+                    Constant::Vector(els) => {
+                        let mut els = els.clone();
+                        let el: &mut Constant = els.get_mut(*index as usize).ok_or_else(|| {
+                            Error::MalformedInstruction(
+                                "Constant::InsertElement index out of range".to_owned(),
+                            )
+                        })?;
+                        *el = ie.element.clone();
+                        self.const_to_bv(&Constant::Vector(els))
+                    },
+                    c => Err(Error::MalformedInstruction(format!(
+                        "Expected InsertElement.vector to be a Constant::Vector, got {:?}",
+                        c
+                    ))),
+                },
+                index => Err(Error::MalformedInstruction(format!(
+                    "Expected InsertElement.index to be a Constant::Int, but got {:?}",
+                    index
+                ))),
+            },
+            Constant::ExtractValue(ev) => self.const_to_bv(Self::simplify_const_ev(
+                &ev.aggregate,
+                ev.indices.iter().copied(),
+            )?),
+            Constant::InsertValue(iv) => self.const_to_bv(&Self::simplify_const_iv(
+                iv.aggregate.clone(),
+                iv.element.clone(),
+                iv.indices.iter().copied(),
+            )?),
+            Constant::GetElementPtr(gep) => {
+                // heavily inspired by `ExecutionManager::symex_gep()` in symex.rs. TODO could try to share more code
+                let bvbase = self.const_to_bv(&gep.address)?;
+                let offset = self.get_offset_recursive(
+                    gep.indices.iter(),
+                    &gep.address.get_type(),
+                    bvbase.get_width(),
+                )?;
+                Ok(bvbase.add(&offset))
+            },
+            Constant::Trunc(t) => self
+                .const_to_bv(&t.operand)
+                .map(|bv| bv.slice(size(&t.to_type) as u32 - 1, 0)),
+            Constant::ZExt(z) => self
+                .const_to_bv(&z.operand)
+                .map(|bv| bv.zero_extend_to_bits(size(&z.to_type) as u32)),
+            Constant::SExt(s) => self
+                .const_to_bv(&s.operand)
+                .map(|bv| bv.sign_extend_to_bits(size(&s.to_type) as u32)),
+            Constant::PtrToInt(pti) => {
+                let bv = self.const_to_bv(&pti.operand)?;
+                assert_eq!(bv.get_width(), size(&pti.to_type) as u32);
+                Ok(bv) // just a cast, it's the same bits underneath
+            },
+            Constant::IntToPtr(itp) => {
+                let bv = self.const_to_bv(&itp.operand)?;
+                assert_eq!(bv.get_width(), size(&itp.to_type) as u32);
+                Ok(bv) // just a cast, it's the same bits underneath
+            },
+            Constant::BitCast(bc) => {
+                let bv = self.const_to_bv(&bc.operand)?;
+                assert_eq!(bv.get_width(), size(&bc.to_type) as u32);
+                Ok(bv) // just a cast, it's the same bits underneath
+            },
+            Constant::AddrSpaceCast(ac) => {
+                let bv = self.const_to_bv(&ac.operand)?;
+                assert_eq!(bv.get_width(), size(&ac.to_type) as u32);
+                Ok(bv) // just a cast, it's the same bits underneath
+            },
+            Constant::ICmp(icmp) => {
+                let bv0 = self.const_to_bv(&icmp.operand0)?;
+                let bv1 = self.const_to_bv(&icmp.operand1)?;
+                Ok(match icmp.predicate {
+                    IntPredicate::EQ => bv0._eq(&bv1),
+                    IntPredicate::NE => bv0._ne(&bv1),
+                    IntPredicate::UGT => bv0.ugt(&bv1),
+                    IntPredicate::UGE => bv0.ugte(&bv1),
+                    IntPredicate::ULT => bv0.ult(&bv1),
+                    IntPredicate::ULE => bv0.ulte(&bv1),
+                    IntPredicate::SGT => bv0.sgt(&bv1),
+                    IntPredicate::SGE => bv0.sgte(&bv1),
+                    IntPredicate::SLT => bv0.slt(&bv1),
+                    IntPredicate::SLE => bv0.slte(&bv1),
+                })
+            },
+            Constant::Select(s) => {
+                let b = self.const_to_bv(&s.condition)?;
+                match b.as_bool() {
+                    None => Err(Error::MalformedInstruction(
+                        "Constant::Select: Expected a constant condition".to_owned(),
+                    )),
+                    Some(true) => self.const_to_bv(&s.true_value),
+                    Some(false) => self.const_to_bv(&s.false_value),
+                }
+            },
+            _ => Err(self.unsupported("constant", c)),
+        }
+    }
+
+    /// Given a `Constant::Struct` and a series of `ExtractValue` indices, get the
+    /// final `Constant` referred to
+    fn simplify_const_ev(
+        s: &Constant,
+        mut indices: impl Iterator<Item = u32>,
+    ) -> Result<&Constant> {
+        match indices.next() {
+            None => Ok(s),
+            Some(index) => {
+                if let Constant::Struct { values, .. } = s {
+                    let val = values.get(index as usize).ok_or_else(|| {
+                        Error::MalformedInstruction(
+                            "Constant::ExtractValue index out of range".to_owned(),
+                        )
+                    })?;
+                    Self::simplify_const_ev(val, indices)
+                } else {
+                    panic!("simplify_const_ev: not a Constant::Struct: {:?}", s)
+                }
+            },
+        }
+    }
+
+    /// Given a `Constant::Struct`, a value to insert, and a series of
+    /// `InsertValue` indices, get the final `Constant` referred to
+    fn simplify_const_iv(
+        s: Constant,
+        val: Constant,
+        mut indices: impl Iterator<Item = u32>,
+    ) -> Result<Constant> {
+        match indices.next() {
+            None => Ok(val),
+            Some(index) => {
+                if let Constant::Struct {
+                    name,
+                    mut values,
+                    is_packed,
+                } = s
+                {
+                    let to_replace = values
+                        .get(index as usize)
+                        .ok_or_else(|| {
+                            Error::MalformedInstruction(
+                                "Constant::InsertValue index out of range".to_owned(),
+                            )
+                        })?
+                        .clone();
+                    values[index as usize] = Self::simplify_const_iv(to_replace, val, indices)?;
+                    Ok(Constant::Struct {
+                        name,
+                        values,
+                        is_packed,
+                    })
+                } else {
+                    panic!("simplify_const_iv: not a Constant::Struct: {:?}", s)
+                }
+            },
+        }
+    }
+
+    /// Get the offset of the element (in bytes, as a `BV` of `result_bits` bits)
+    fn get_offset_recursive<'a>(
+        &self,
+        mut indices: impl Iterator<Item = &'a Constant>,
+        base_type: &Type,
+        result_bits: u32,
+    ) -> Result<B::BV> {
+        match indices.next() {
+            None => Ok(self.zero(result_bits)),
+            Some(index) => match base_type {
+                Type::PointerType { .. } | Type::ArrayType { .. } | Type::VectorType { .. } => {
+                    let index = self.const_to_bv(index)?.zero_extend_to_bits(result_bits);
+                    let (offset, nested_ty) =
+                        get_offset_bv_index(base_type, &index, self.solver.clone())?;
+                    self.get_offset_recursive(indices, nested_ty, result_bits)
+                        .map(|bv| bv.add(&offset))
+                },
+                Type::StructType { .. } => match index {
+                    Constant::Int { value: index, .. } => {
+                        let (offset, nested_ty) =
+                            get_offset_constant_index(base_type, *index as usize)?;
+                        self.get_offset_recursive(indices, &nested_ty, result_bits)
+                            .map(|bv| bv.add(&self.bv_from_u64(offset as u64, result_bits)))
+                    },
+                    _ => Err(Error::MalformedInstruction(format!(
+                        "Expected index into struct type to be a constant int, but got index {:?}",
+                        index
+                    ))),
+                },
+                Type::NamedStructType { ty, .. } => {
+                    let arc: Arc<RwLock<Type>> = ty
+                        .as_ref()
+                        .ok_or_else(|| {
+                            Error::MalformedInstruction(
+                                "get_offset on an opaque struct type".to_owned(),
+                            )
+                        })?
+                        .upgrade()
+                        .expect("Failed to upgrade weak reference");
+                    let actual_ty: &Type = &arc.read().unwrap();
+                    if let Type::StructType { .. } = actual_ty {
+                        // this code copied from the StructType case
+                        match index {
+                            Constant::Int { value: index, .. } => {
+                                let (offset, nested_ty) = get_offset_constant_index(base_type, *index as usize)?;
+                                self.get_offset_recursive(indices, &nested_ty, result_bits).map(|bv| bv.add(&self.bv_from_u64(offset as u64, result_bits)))
+                            },
+                            _ => Err(Error::MalformedInstruction(format!("Expected index into struct type to be a constant int, but got index {:?}", index))),
+                        }
+                    } else {
+                        Err(Error::MalformedInstruction(format!(
+                            "Expected NamedStructType inner type to be a StructType, but got {:?}",
+                            actual_ty
+                        )))
+                    }
+                },
+                _ => panic!("get_offset_recursive with base type {:?}", base_type),
+            },
+        }
+    }
+
+    /// Given a `BV`, interpret it as a function pointer, and return a
+    /// description of the possible `Function`s which it would point to.
+    ///
+    /// `n`: Maximum number of distinct `Callable`s to check for.
+    /// If there are more than `n` possible `Callable`s, this returns a
+    /// `PossibleSolutions::AtLeast` with `n+1` `Callable`s.
+    ///
+    /// Possible errors:
+    ///   - `Error::SolverError` if the solver query fails
+    ///   - `Error::FailedToResolveFunctionPointer` if it finds that it is possible
+    ///     that the `BV` points to something that's not a `Function` in the
+    ///     `Project`
+    pub(crate) fn interpret_as_function_ptr(
+        &mut self,
+        bv: B::BV,
+        n: usize,
+    ) -> Result<PossibleSolutions<Callable<'p, B>>> {
+        if n == 0 {
+            unimplemented!("n == 0 in interpret_as_function_ptr")
+        }
+
+        // First try to interpret without a full solve (i.e., with `as_u64()`)
+        let addrs: Vec<u64> = match bv.as_u64() {
+            Some(addr) => vec![addr], // there is only one possible solution, and it's this `addr`
+            None => {
+                // Check if whatever solution we used last time for this `Location` still applies
+                // (see notes on the `function_ptr_cache` field of `State`)
+                match self.function_ptr_cache.get(&self.cur_loc) {
+                    Some(addr)
+                        if self
+                            .bvs_must_be_equal(&bv, &self.bv_from_u64(*addr, bv.get_width()))? =>
+                    {
+                        vec![*addr]
+                    },
+                    _ => {
+                        // Ok, use `get_possible_solutions_for_bv()`
+                        match self
+                            .get_possible_solutions_for_bv(&bv, n)?
+                            .as_u64_solutions()
+                            .unwrap()
+                        {
+                            PossibleSolutions::Exactly(v) => v.into_iter().collect(),
+                            PossibleSolutions::AtLeast(v) => v.into_iter().collect(),
+                        }
+                    },
+                }
+            },
+        };
+
+        // save the value we found into the cache for next time
+        if addrs.len() == 1 {
+            self.function_ptr_cache
+                .insert(self.cur_loc.clone(), addrs[0]);
+        }
+
+        let callables = addrs
+            .into_iter()
+            .map(|addr| {
+                self.global_allocations
+                    .get_func_for_address(addr, self.cur_loc.module)
+                    .or_else(|| {
+                        self.registered_function_addresses
+                            .get(&addr)
+                            .map(|func| Callable::LLVMFunction(func))
+                    })
+                    .ok_or_else(|| Error::FailedToResolveFunctionPointer(addr))
+            })
+            .collect::<Result<HashSet<_>>>()?;
+        if callables.len() > n {
+            Ok(PossibleSolutions::AtLeast(callables))
+        } else {
+            Ok(PossibleSolutions::Exactly(callables))
+        }
+    }
+
+    /// Register `addr` as an address of the given `Function`.
+    ///
+    /// Every `Function` in the `Project` is automatically assigned an address
+    /// (see `get_pointer_to_function()`), but this allows the user to teach us
+    /// about additional addresses: for instance, function addresses which
+    /// appear in data initialized outside the code being analyzed. Function
+    /// pointers with registered addresses can then be resolved, both by
+    /// `resolve_call_target()` and when executing indirect calls.
+    pub fn register_function_address(&mut self, addr: u64, func: &'p Function) {
+        self.registered_function_addresses.insert(addr, func);
+    }
+
+    /// Determine which `Function`s the function pointer `fptr` could point to,
+    /// under the current constraints.
+    ///
+    /// The `candidates` (pairs of address and `Function`) are checked, as are
+    /// all the addresses registered with `register_function_address()`. Each
+    /// `Function` for which `fptr` can be equal to its address is returned
+    /// (once), with the `candidates` first, in order, followed by any
+    /// registered `Function`s in increasing address order.
+    pub fn resolve_call_target<'f>(
+        &self,
+        fptr: &B::BV,
+        candidates: &[(u64, &'f Function)],
+    ) -> Result<Vec<&'f Function>>
+    where
+        'p: 'f,
+    {
+        let mut registered: Vec<(u64, &'f Function)> = self
+            .registered_function_addresses
+            .iter()
+            .map(|(addr, func)| (*addr, *func as &'f Function))
+            .collect();
+        registered.sort_by_key(|(addr, _)| *addr);
+        let mut targets: Vec<&'f Function> = Vec::new();
+        for (addr, func) in candidates.iter().copied().chain(registered) {
+            if targets.iter().any(|target| std::ptr::eq(*target, func)) {
+                continue;
+            }
+            if self.bvs_can_be_equal(fptr, &self.bv_from_u64(addr, fptr.get_width()))? {
+                targets.push(func);
+            }
+        }
+        Ok(targets)
+    }
+
+    /// Set the variadic arguments of the current call, for instance in a hook
+    /// for a variadic function. They can then be consumed, in order, with
+    /// `va_arg()`. Any variadic arguments previously set and not yet consumed
+    /// are discarded.
+    pub fn set_varargs(&mut self, args: &[B::BV]) {
+        self.varargs = args.iter().cloned().collect();
+    }
+
+    /// Record that the back edge of the loop identified by `loop_id` (which
+    /// may be any ID the caller chooses, as long as it's consistent) is being
+    /// taken on the current path, and get whether to keep unrolling it.
+    ///
+    /// Returns `LoopAction::StopUnrolling` if the back edge has already been
+    /// taken the configured
+    /// [`max_unroll`](config/struct.Config.html#structfield.max_unroll) times
+    /// on the current path, or `LoopAction::Continue` otherwise. The counts
+    /// are restored along with the rest of the path state when backtracking.
+    pub fn on_loop_back_edge(&mut self, loop_id: usize) -> LoopAction {
+        let count = self.back_edge_counts.entry(loop_id).or_insert(0);
+        match self.config.max_unroll {
+            Some(max) if *count >= max => LoopAction::StopUnrolling,
+            _ => {
+                *count += 1;
+                LoopAction::Continue
+            },
+        }
+    }
+
+    /// Consume the next variadic argument set with `set_varargs()`, as with
+    /// C's `va_arg`, returning it as a `BV` of width `bits`. If the argument
+    /// has a different width, it is zero-extended or truncated to `bits`
+    /// (which must be nonzero).
+    ///
+    /// Returns `None` if there are no more variadic arguments.
+    pub fn va_arg(&mut self, bits: u32) -> Option<B::BV> {
+        let arg = self.varargs.pop_front()?;
+        let width = arg.get_width();
+        Some(if width < bits {
+            arg.zext(bits - width)
+        } else if width > bits {
+            arg.slice(bits - 1, 0)
+        } else {
+            arg
+        })
+    }
+
+    /// Get a pointer to the given function name. The name must be the
+    /// fully-mangled function name, as it appears in the LLVM. The name will be
+    /// resolved in the current module; this means that it will first look for a
+    /// module-private (e.g., C `static`) definition in the current module, then
+    /// search for a public definition in the same or different module. It will
+    /// never return a module-private definition from a different module.
+    ///
+    /// Returns `None` if no function was found with that name.
+    pub fn get_pointer_to_function(&self, funcname: impl Into<String>) -> Option<&B::BV> {
+        self.global_allocations
+            .get_global_allocation(&Name::from(funcname.into()), self.cur_loc.module)
+            .map(|ga| ga.get_addr())
+    }
+
+    /// Get a pointer to the currently active _hook_ for the given function name.
+    ///
+    /// Returns `None` if no function was found with that name, _or_ if there is no currently
+    /// active hook for that function.
+    pub fn get_pointer_to_function_hook(&self, funcname: &str) -> Option<&B::BV> {
+        self.global_allocations
+            .get_function_hook_address(self.config.function_hooks.get_hook_for(funcname)?)
+    }
+
+    /// Get a `Function` by name. The name must be the fully-mangled function
+    /// name, as it appears in the LLVM. The name will be resolved in the current
+    /// module; this means that it will first look for a module-private (e.g., C
+    /// `static`) definition in the current module, then search for a public
+    /// definition in the same or different module. It will never return a
+    /// module-private definition from a different module.
+    ///
+    /// Also returns the `Module` in which the prevailing definition of the `Function` was found.
+    ///
+    /// Returns `None` if no function was found with that name.
+    pub fn get_func_by_name(
+        &self,
+        funcname: impl Into<String>,
+    ) -> Option<(&'p Function, &'p Module)> {
+        let funcname = funcname.into();
+        self.global_allocations
+            .get_global_allocation(&Name::from(funcname.clone()), self.cur_loc.module)
+            .and_then(|ga| match ga {
+                GlobalAllocation::Function { func, module, .. } => Some((*func, *module)),
+                GlobalAllocation::GlobalVariable { .. } => panic!(
+                    "get_func_by_name: {} refers to a global variable, not a function",
+                    funcname
+                ),
+            })
+    }
+
+    /// Compute the address given by a `getelementptr` with the given base
+    /// `address` and `indices`, as with the LLVM `getelementptr` instruction.
+    ///
+    /// If `inbounds` is `true`, this also returns a `BV` of width 1 which is
+    /// true exactly when the result would be poison under the semantics of
+    /// `getelementptr inbounds`: that is, when `address` points into an
+    /// allocation, but the result points outside of it (pointing just past the
+    /// end is allowed). A caller can assert that this is false to assume the
+    /// program has no undefined behavior, or check whether it can be true to
+    /// find bugs. Since computing it involves every allocation, `haybale`
+    /// doesn't compute it when executing `getelementptr` instructions itself.
+    pub fn gep(
+        &self,
+        address: &Operand,
+        indices: &[Operand],
+        inbounds: bool,
+    ) -> Result<(B::BV, Option<B::BV>)> {
+        let bvbase = self.operand_to_bv(address)?;
+        let offset = self.get_offset_recursive_operands(
+            indices.iter(),
+            &address.get_type(),
+            bvbase.get_width(),
+        )?;
+        let result = bvbase.add(&offset);
+        let poison = if inbounds {
+            Some(self.leaves_allocation(&bvbase, &result))
+        } else {
+            None
+        };
+        Ok((result, poison))
+    }
+
+    /// Get a `BV` of width 1 which is true exactly when `base` points into an
+    /// allocation, but `ptr` doesn't point into that allocation or just past
+    /// its end
+    fn leaves_allocation(&self, base: &B::BV, ptr: &B::BV) -> B::BV {
+        let width = base.get_width();
+        self.alloc
+            .get_all_allocations()
+            .into_iter()
+            .map(|(addr, alloc_bits)| {
+                let start = self.bv_from_u64(addr, width);
+                let end = match self.array_sizes.get(&addr) {
+                    Some(size_bytes) => start.add(size_bytes),
+                    None => start.add(&self.bv_from_u64(bytes_rounded_up(alloc_bits), width)),
+                };
+                let base_inside = base.ugte(&start).and(&base.ult(&end));
+                let ptr_inside = ptr.ugte(&start).and(&ptr.ulte(&end));
+                base_inside.and(&ptr_inside.not())
+            })
+            .fold(self.bv_from_bool(false), |a, b| a.or(&b))
+    }
+
+    /// Get the offset of the element (in bytes, as a `BV` of `result_bits` bits)
+    /// indicated by the given (non-constant) GEP `indices`
+    fn get_offset_recursive_operands<'a>(
+        &self,
+        mut indices: impl Iterator<Item = &'a Operand>,
+        base_type: &Type,
+        result_bits: u32,
+    ) -> Result<B::BV> {
+        match indices.next() {
+            None => Ok(self.zero(result_bits)),
+            Some(index) => {
+                match base_type {
+                    Type::PointerType { .. } | Type::ArrayType { .. } | Type::VectorType { .. } => {
+                        let index = self.operand_to_bv(index)?.zero_extend_to_bits(result_bits);
+                        let (offset, nested_ty) =
+                            get_offset_bv_index(base_type, &index, self.solver.clone())?;
+                        self.get_offset_recursive_operands(indices, nested_ty, result_bits)
+                            .map(|bv| bv.add(&offset))
+                    },
+                    Type::StructType { .. } => match index {
+                        Operand::ConstantOperand(Constant::Int { value: index, .. }) => {
+                            let (offset, nested_ty) =
+                                get_offset_constant_index(base_type, *index as usize)?;
+                            self.get_offset_recursive_operands(indices, &nested_ty, result_bits)
+                                .map(|bv| bv.add(&self.bv_from_u32(offset as u32, result_bits)))
+                        },
+                        _ => Err(Error::MalformedInstruction(format!(
+                            "Expected index into struct type to be constant, but got index {:?}",
+                            index
+                        ))),
+                    },
+                    Type::NamedStructType { ty, .. } => {
+                        let arc: Arc<RwLock<Type>> = ty
+                            .as_ref()
+                            .ok_or_else(|| {
+                                Error::MalformedInstruction(
+                                    "get_offset on an opaque struct type".to_owned(),
+                                )
+                            })?
+                            .upgrade()
+                            .expect("Failed to upgrade weak reference");
+                        let actual_ty: &Type = &arc.read().unwrap();
+                        if let Type::StructType { .. } = actual_ty {
+                            // this code copied from the StructType case
+                            match index {
+                            Operand::ConstantOperand(Constant::Int { value: index, .. }) => {
+                                let (offset, nested_ty) = get_offset_constant_index(actual_ty, *index as usize)?;
+                                self.get_offset_recursive_operands(indices, &nested_ty, result_bits)
+                                    .map(|bv| bv.add(&self.bv_from_u32(offset as u32, result_bits)))
+                            },
+                            _ => Err(Error::MalformedInstruction(format!("Expected index into struct type to be constant, but got index {:?}", index))),
+                        }
+                        } else {
+                            Err(Error::MalformedInstruction(format!("Expected NamedStructType inner type to be a StructType, but got {:?}", actual_ty)))
+                        }
+                    },
+                    _ => panic!("get_offset_recursive with base type {:?}", base_type),
+                }
+            },
+        }
+    }
+
+    /// Read a value `bits` bits long from memory at `addr`.
+    /// Note that `bits` can be arbitrarily large.
+    pub fn read(&self, addr: &B::BV, bits: u32) -> Result<B::BV> {
+        let result = if self.is_known_non_null(addr) {
+            self.mem.borrow().read_non_null(addr, bits)
+        } else {
+            self.mem.borrow().read(addr, bits)
+        };
+        let retval = match result {
+            Ok(val) => val,
+            e @ Err(Error::NullPointerDereference) => {
+                if self.config.null_pointer_checking == NullPointerChecking::SplitPath {
+                    self.split_off_non_null(addr);
+                }
+                return e; // report the null-pointer dereference
+            },
+            e @ Err(_) => return e, // propagate any other kind of error
+        };
+        self.record_touched(addr);
+        let retval = self.handle_uninit_read(addr, bits, retval)?;
+        for (name, watchpoint) in self.mem_watchpoints.get_triggered_watchpoints(addr, bits)? {
+            let pretty_loc = if self.config.print_module_name {
+                self.cur_loc.to_string_with_module()
+            } else {
+                self.cur_loc.to_string_no_module()
+            };
+            info!(
+                "Memory watchpoint {:?} {} read by {{{}}}",
+                name, watchpoint, pretty_loc
+            );
+        }
+        Ok(retval)
+    }
+
+    /// Read `bits` bits from memory at `base + offset`, where `offset` (a byte
+    /// offset) may be symbolic, for instance for an access to a field of an
+    /// element of an array of structs where both the index and the field are
+    /// symbolic. An `offset` narrower than `base` is sign-extended, as with
+    /// the indices of a `getelementptr`.
+    ///
+    /// If `assert_in_bounds` is true, this also constrains the entire read to
+    /// lie within the allocation containing `base`; in that case, `base`
+    /// must have only one possible value on the current path.
+    pub fn read_dynamic(
+        &self,
+        base: &B::BV,
+        offset: &B::BV,
+        bits: u32,
+        assert_in_bounds: bool,
+    ) -> Result<B::BV> {
+        let width = base.get_width();
+        if offset.get_width() > width {
+            return Err(Error::OtherError(format!(
+                "read_dynamic: offset of width {} is wider than the base address, of width {}",
+                offset.get_width(),
+                width
+            )));
+        }
+        let addr = base.add(&offset.sign_extend_to_bits(width));
+        if assert_in_bounds {
+            let base = self.concrete_address(base, "read_dynamic")?;
+            let alloc = self.alloc.provenance(base).ok_or_else(|| {
+                Error::OtherError(format!(
+                    "read_dynamic: base address {:#x} is not inside any allocation",
+                    base
+                ))
+            })?;
+            let alloc_bytes = bytes_rounded_up(alloc.bits);
+            let read_bytes = bytes_rounded_up(bits.into());
+            if read_bytes > alloc_bytes {
+                return Err(Error::OtherError(format!(
+                    "read_dynamic: a read of {} bits can't fit in the allocation of {} bits at {:#x}",
+                    bits, alloc.bits, alloc.addr
+                )));
+            }
+            // the offset of the read from the start of the allocation, which
+            // (as it's unsigned) is also out of bounds if the read is before the allocation
+            let rel = addr.sub(&self.bv_from_u64(alloc.addr, width));
+            self.assert(&rel.ulte(&self.bv_from_u64(alloc_bytes - read_bytes, width)))?;
+        }
+        self.read(&addr, bits)
+    }
+
+    /// Start tracking which allocations are read from or written to on each
+    /// path, so that they can be queried with `touched_allocations()`.
+    ///
+    /// Tracking is off by default.
+    pub fn enable_access_tracking(&mut self) {
+        if self.touched_allocs.borrow().is_none() {
+            self.touched_allocs.replace(Some(HashSet::new()));
+        }
+    }
+
+    /// Get the base addresses of the allocations which have been read from or
+    /// written to on the current path since tracking was enabled with
+    /// `enable_access_tracking()`, in increasing order. If tracking is not
+    /// enabled, this will be empty.
+    ///
+    /// As with `was_written()`, only accesses at constant addresses are
+    /// tracked. Accesses outside of any allocation (e.g., by a hook to
+    /// memory it manages itself) aren't reported.
+    pub fn touched_allocations(&self) -> Vec<u64> {
+        self.touched_allocs
+            .borrow()
+            .iter()
+            .flatten()
+            .copied()
+            .sorted()
+            .collect()
+    }
+
+    /// Record an access at `addr` for `touched_allocations()`
+    fn record_touched(&self, addr: &B::BV) {
+        if let Some(touched_allocs) = self.touched_allocs.borrow_mut().as_mut() {
+            if let Some(info) = addr.as_u64().and_then(|addr| self.alloc.provenance(addr)) {
+                touched_allocs.insert(info.addr);
+            }
+        }
+    }
+
+    /// Start recording a trace of all memory reads and writes on this path.
+    /// See `memory_trace()`.
+    ///
+    /// Tracing is off by default.
+    pub fn enable_memory_tracing(&mut self) {
+        self.mem.borrow_mut().enable_tracing()
+    }
+
+    /// Get the memory reads and writes performed on the current path since
+    /// tracing was enabled with `enable_memory_tracing()`, in the order they
+    /// were performed. If tracing is not enabled, this will be empty.
+    ///
+    /// Like the rest of memory, the trace is restored when backtracking, so it
+    /// only contains the operations performed on the current path.
+    pub fn memory_trace(&self) -> Vec<MemOp> {
+        self.mem.borrow().trace()
+    }
+
+    /// Set how reads of uninitialized memory should be handled; see
+    /// [`UninitHandler`](enum.UninitHandler.html).
+    ///
+    /// A read is considered a read of uninitialized memory if its address is
+    /// constant, and none of the bytes it reads have been written on the
+    /// current path since the first time a handler (other than
+    /// `UninitHandler::Ignore`) was installed. Reads and writes at symbolic
+    /// addresses are not tracked, so memory initialized only by writes at
+    /// symbolic addresses may be reported as uninitialized.
+    pub fn set_uninit_handler(&mut self, handler: UninitHandler<'p, B>) {
+        if let UninitHandler::Ignore = handler {
+            if !self.write_tracking {
+                self.initialized_bytes.replace(None);
+            }
+        } else if self.initialized_bytes.borrow().is_none() {
+            self.initialized_bytes.replace(Some(HashSet::new()));
+        }
+        self.uninit_handler = handler;
+    }
+
+    /// Start tracking which bytes of memory are written on each path, so that
+    /// they can be queried with `was_written()`.
+    ///
+    /// Tracking is off by default, unless an uninit handler is installed
+    /// with `set_uninit_handler()`, which uses the same tracking.
+    pub fn enable_write_tracking(&mut self) {
+        self.write_tracking = true;
+        if self.initialized_bytes.borrow().is_none() {
+            self.initialized_bytes.replace(Some(HashSet::new()));
+        }
+    }
+
+    /// Has the byte at `addr` been written on the current path since write
+    /// tracking was enabled (with `enable_write_tracking()` or
+    /// `set_uninit_handler()`)?
+    ///
+    /// As described on `set_uninit_handler()`, only writes at constant
+    /// addresses are tracked. `addr` must have only one possible value on the
+    /// current path. Returns an error if write tracking isn't enabled.
+    pub fn was_written(&self, addr: &B::BV) -> Result<bool> {
+        if self.initialized_bytes.borrow().is_none() {
+            return Err(Error::OtherError(
+                "was_written: write tracking is not enabled; see enable_write_tracking()"
+                    .to_owned(),
+            ));
+        }
+        let addr = self.concrete_address(addr, "was_written")?;
+        Ok(self
+            .initialized_bytes
+            .borrow()
+            .as_ref()
+            .map(|initialized_bytes| initialized_bytes.contains(&addr))
+            .unwrap_or(false))
+    }
+
+    /// Read `bits` bits from memory at `addr`, where each byte which hasn't
+    /// been written on the current path (as with `was_written()`) reads as the
+    /// corresponding byte of `default` instead. This is useful for modeling
+    /// sparse memory whose unwritten regions hold a known sentinel.
+    ///
+    /// `default` is truncated or zero-extended to `bits` bits, and its bytes
+    /// are laid out in memory in the same order as those of any other value.
+    /// As with `was_written()`, `addr` must have only one possible value on
+    /// the current path, and write tracking must be enabled.
+    pub fn read_or_default(&self, addr: &B::BV, bits: u32, default: u64) -> Result<B::BV> {
+        if self.initialized_bytes.borrow().is_none() {
+            return Err(Error::OtherError(
+                "read_or_default: write tracking is not enabled; see enable_write_tracking()"
+                    .to_owned(),
+            ));
+        }
+        if bits == 0 {
+            return Err(Error::OtherError(
+                "read_or_default: can't read 0 bits".to_owned(),
+            ));
+        }
+        let base = self.concrete_address(addr, "read_or_default")?;
+        let default = self.bv_from_u64(default, 64);
+        let default = if bits > 64 {
+            default.zext(bits - 64)
+        } else {
+            default.slice(bits - 1, 0)
+        };
+        let bits_in_byte = crate::memory::Memory::BITS_IN_BYTE;
+        let mut result: Option<B::BV> = None;
+        for i in 0 .. bytes_rounded_up(bits.into()) {
+            let low = i as u32 * bits_in_byte;
+            let high = std::cmp::min(low + bits_in_byte, bits) - 1;
+            let byte_addr = self.bv_from_u64(base + i, 64);
+            // read only bytes which were written, so that the uninit handler doesn't apply
+            let byte = if self.was_written(&byte_addr)? {
+                self.read(&byte_addr, high - low + 1)?
+            } else {
+                default.slice(high, low)
+            };
+            result = Some(match result {
+                None => byte,
+                Some(lower_bytes) => byte.concat(&lower_bytes),
+            });
+        }
+        Ok(result.expect("read_or_default: expected at least one byte"))
+    }
+
+    /// Apply the `uninit_handler` to a read of `bits` bits at `addr`, which
+    /// produced the value `val`. Returns the value the read should produce.
+    fn handle_uninit_read(&self, addr: &B::BV, bits: u32, val: B::BV) -> Result<B::BV> {
+        let uninit_addr = match (self.initialized_bytes.borrow().as_ref(), addr.as_u64()) {
+            (Some(initialized_bytes), Some(addr)) => {
+                if let Some(val) = self.zero_unwritten_bytes(initialized_bytes, addr, bits, &val) {
+                    return Ok(val);
+                }
+                if (addr .. addr + bytes_rounded_up(bits.into()))
+                    .any(|byte| initialized_bytes.contains(&byte))
+                {
+                    return Ok(val);
+                }
+                addr
+            },
+            _ => return Ok(val), // not tracking, or the address is symbolic
+        };
+        match &self.uninit_handler {
+            UninitHandler::Ignore => Ok(val),
+            UninitHandler::Error => Err(Error::UninitializedRead(uninit_addr)),
+            UninitHandler::Warn => {
+                warn!(
+                    "Read of {} bits of uninitialized memory at address {:#x}",
+                    bits, uninit_addr
+                );
+                Ok(val)
+            },
+            UninitHandler::Zero => Ok(self.zero(bits)),
+            UninitHandler::Custom(handler) => {
+                let val = handler(self, addr, bits)?;
+                if val.get_width() != bits {
+                    return Err(Error::OtherError(format!(
+                        "Uninitialized-read handler returned a value of width {} bits, but the read was {} bits",
+                        val.get_width(),
+                        bits
+                    )));
+                }
+                Ok(val)
+            },
+        }
+    }
+
+    /// If any of the bytes of a read of `bits` bits at `addr`, which produced
+    /// the value `val`, are in a region allocated with `allocate_zeroed()`,
+    /// get the value the read should produce: `val`, but with zero for each of
+    /// those bytes which hasn't been written
+    fn zero_unwritten_bytes(
+        &self,
+        initialized_bytes: &HashSet<u64>,
+        addr: u64,
+        bits: u32,
+        val: &B::BV,
+    ) -> Option<B::BV> {
+        if self.zeroed_regions.is_empty() {
+            return None;
+        }
+        let zeroed = |byte: u64| {
+            !initialized_bytes.contains(&byte)
+                && self
+                    .zeroed_regions
+                    .iter()
+                    .any(|(&start, &size_bytes)| byte >= start && byte - start < size_bytes)
+        };
+        let num_bytes = bytes_rounded_up(bits.into());
+        let zeroed_bytes: Vec<bool> = (addr .. addr + num_bytes).map(zeroed).collect();
+        if !zeroed_bytes.contains(&true) {
+            return None;
+        }
+        if !zeroed_bytes.contains(&false) {
+            return Some(self.zero(bits));
+        }
+        let bits_in_byte = crate::memory::Memory::BITS_IN_BYTE;
+        let mut result: Option<B::BV> = None;
+        for (i, &zeroed) in zeroed_bytes.iter().enumerate() {
+            let low = i as u32 * bits_in_byte;
+            let high = std::cmp::min(low + bits_in_byte, bits) - 1;
+            let byte = if zeroed {
+                self.zero(high - low + 1)
+            } else {
+                val.slice(high, low)
+            };
+            result = Some(match result {
+                None => byte,
+                Some(lower_bytes) => byte.concat(&lower_bytes),
+            });
+        }
+        result
+    }
+
+    /// Read a pointer-sized value from memory at `addr`.
+    pub fn read_pointer(&self, addr: &B::BV) -> Result<B::BV> {
+        self.read(addr, POINTER_SIZE_BITS as u32)
+    }
+
+    /// Read a pointer from memory at `ptr_addr`, and then read a value `bits`
+    /// bits long from memory at the address that pointer points to.
+    ///
+    /// The pointer read from memory may be symbolic, in which case the second
+    /// read is a read from a symbolic address.
+    pub fn deref(&self, ptr_addr: &B::BV, bits: u32) -> Result<B::BV> {
+        let ptr = self.read_pointer(ptr_addr)?;
+        self.read(&ptr, bits)
+    }
+
+    /// Walk a linked list starting at the node at address `head`, where each
+    /// node holds a pointer to the next node at byte offset `next_offset`.
+    ///
+    /// Returns the addresses of the nodes visited, in order. The walk stops
+    /// before the first node address which can be null under the current
+    /// constraints, or after `max_nodes` nodes, whichever comes first.
+    ///
+    /// Node addresses (other than `head`) are read from memory and so may be
+    /// symbolic, in which case the subsequent reads are reads from symbolic
+    /// addresses.
+    pub fn walk_linked_list(
+        &self,
+        head: &B::BV,
+        next_offset: u64,
+        max_nodes: usize,
+    ) -> Result<Vec<B::BV>> {
+        let width = head.get_width();
+        let null = self.zero(width);
+        let offset = self.bv_from_u64(next_offset, width);
+        let mut nodes = Vec::new();
+        let mut node = head.clone();
+        while nodes.len() < max_nodes && !self.bvs_can_be_equal(&node, &null)? {
+            let next = self.read_pointer(&node.add(&offset))?;
+            nodes.push(node);
+            node = next;
+        }
+        Ok(nodes)
+    }
+
+    /// Read a value `bits` bits long from memory at `addr_true` if `cond` (a
+    /// `BV` of width 1) is true, or at `addr_false` otherwise.
+    ///
+    /// This is equivalent to reading from `cond.cond_bv(addr_true,
+    /// addr_false)`, but reads each (typically concrete) address separately
+    /// rather than reading from a symbolic address. Unless `cond` is constant,
+    /// both addresses are read, so both must be valid to read from.
+    pub fn read_conditional(
+        &self,
+        cond: &B::BV,
+        addr_true: &B::BV,
+        addr_false: &B::BV,
+        bits: u32,
+    ) -> Result<B::BV> {
+        if cond.get_width() != 1 {
+            return Err(Error::OtherError(format!(
+                "read_conditional: expected the condition to have width 1, but it has width {}",
+                cond.get_width()
+            )));
+        }
+        match cond.as_bool() {
+            Some(true) => self.read(addr_true, bits),
+            Some(false) => self.read(addr_false, bits),
+            None => Ok(cond.cond_bv(&self.read(addr_true, bits)?, &self.read(addr_false, bits)?)),
+        }
+    }
+
+    /// Write a value into memory at `addr`.
+    /// Note that `val` can be an arbitrarily large bitvector.
+    pub fn write(&mut self, addr: &B::BV, val: B::BV) -> Result<()> {
+        self.write_without_mut(addr, val)
+    }
+
+    /// Copy `num_bytes` bytes of memory from address `src` to address `dst`, as
+    /// with the C `memmove()`. `src` and `dst` may overlap: all of the source
+    /// bytes are read before any of the destination bytes are written.
+    pub fn memmove(&mut self, dst: &B::BV, src: &B::BV, num_bytes: u64) -> Result<()> {
+        if num_bytes == 0 {
+            return Ok(());
+        }
+        let bits = num_bytes
+            .checked_mul(8)
+            .filter(|&bits| bits <= u64::from(u32::MAX))
+            .ok_or_else(|| {
+                Error::OtherError(format!("memmove: {} bytes is too large a copy", num_bytes))
+            })?;
+        let val = self.read(src, bits as u32)?;
+        self.write(dst, val)
+    }
+
+    /// Write `val` to each of `num_elements` consecutive elements of
+    /// `elem_bytes` bytes each, starting at address `addr`, as with the LLVM
+    /// `memset.element.unordered.atomic` intrinsic (but with an element value
+    /// rather than a byte value).
+    ///
+    /// `val` must have width `elem_bytes * 8`, and `elem_bytes` must be
+    /// nonzero.
+    pub fn memset_elements(
+        &mut self,
+        addr: &B::BV,
+        val: &B::BV,
+        num_elements: u64,
+        elem_bytes: u32,
+    ) -> Result<()> {
+        if elem_bytes == 0 || u64::from(val.get_width()) != u64::from(elem_bytes) * 8 {
+            return Err(Error::OtherError(format!(
+                "memset_elements: expected a value of {} bytes, but got a value of width {} bits",
+                elem_bytes,
+                val.get_width()
+            )));
+        }
+        let width = addr.get_width();
+        for i in 0 .. num_elements {
+            let offset = i.checked_mul(u64::from(elem_bytes)).ok_or_else(|| {
+                Error::OtherError(format!(
+                    "memset_elements: {} elements of {} bytes is too large a region",
+                    num_elements, elem_bytes
+                ))
+            })?;
+            self.write(&addr.add(&self.bv_from_u64(offset, width)), val.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Constrain the `num_bytes` bytes of memory starting at address `a` to be
+    /// equal, byte for byte, to the `num_bytes` bytes starting at address `b`.
+    pub fn assert_regions_equal(&self, a: &B::BV, b: &B::BV, num_bytes: u64) -> Result<()> {
+        self.assert(&self.regions_equal(a, b, num_bytes)?)
+    }
+
+    /// Returns `true` if under the current constraints, the `num_bytes` bytes
+    /// of memory starting at address `a` must be equal, byte for byte, to the
+    /// `num_bytes` bytes starting at address `b`; or `false` if some byte
+    /// could differ.
+    ///
+    /// Does not permanently add any constraints to the solver; for that, see
+    /// `assert_regions_equal()`.
+    pub fn check_regions_equal(&self, a: &B::BV, b: &B::BV, num_bytes: u64) -> Result<bool> {
+        let equal = self.regions_equal(a, b, num_bytes)?;
+        Ok(!self.sat_with_extra_constraints(std::iter::once(&equal.not()))?)
+    }
+
+    /// Returns `true` if the given regions of memory (each given as a concrete
+    /// address and a length in bytes) must have the same contents in this
+    /// `State` and in `other`, or `false` if their contents can differ, under
+    /// the constraints on both states' current paths. For instance, this can
+    /// check that two independently executed versions of a function have the
+    /// same effect on memory.
+    ///
+    /// The states are compared in a fresh solver instance, in which variables
+    /// with the same symbol in both states (such as the parameters of
+    /// functions with the same name) are the same input, and all other
+    /// variables are independent. Each state's constraints and memory are
+    /// rebuilt there from the solver's dump, as for
+    /// `serialize_constraints()`; the initial memory is shared in the same
+    /// way, so bytes which neither state wrote compare equal.
+    ///
+    /// Returns `Error::OtherError` if a region extends past the end of the
+    /// address space.
+    pub fn memory_equivalent(&self, other: &Self, regions: &[(u64, u64)]) -> Result<bool> {
+        if let Some(&(addr, len)) = regions
+            .iter()
+            .find(|&&(addr, len)| addr.checked_add(len).is_none())
+        {
+            return Err(Error::OtherError(format!(
+                "memory_equivalent: the region of {} bytes at {:#x} extends past the end of the address space",
+                len, addr
+            )));
+        }
+        let dump = |state: &Self| {
+            state.dump(
+                &state.asserted_constraints.borrow(),
+                &[],
+                &[&state.mem.borrow()],
+            )
+        };
+        let ours = dump(self)?;
+        let theirs = dump(other)?;
+
+        let solver = B::SolverRef::new();
+        solver.set_opt(BtorOption::SolverTimeout(self.config.solver_query_timeout));
+        let null_detection = Self::null_detection(&self.config);
+        let ours: smtlib::Rebuilt<B> =
+            smtlib::rebuild(&ours, &solver, null_detection, &HashMap::new())?;
+        // inputs with the same symbol in both dumps are the same input
+        let theirs: smtlib::Rebuilt<B> =
+            smtlib::rebuild(&theirs, &solver, null_detection, &ours.inputs)?;
+        for constraint in ours.constraints.iter().chain(theirs.constraints.iter()) {
+            constraint.assert()?;
+        }
+        let mut differ = B::BV::from_bool(solver.clone(), false);
+        for addr in regions.iter().flat_map(|&(addr, len)| addr .. addr + len) {
+            let addr = B::BV::from_u64(solver.clone(), addr, crate::memory::Memory::INDEX_BITS);
+            differ = differ.or(&ours.mems[0]
+                .read(&addr, 8)?
+                ._ne(&theirs.mems[0].read(&addr, 8)?));
+        }
+        differ.assert()?;
+        Ok(!solver_utils::sat(&solver)?)
+    }
+
+    /// Get a `BV` of width 1 which is true exactly when the `num_bytes` bytes
+    /// starting at `a` are equal to the `num_bytes` bytes starting at `b`
+    fn regions_equal(&self, a: &B::BV, b: &B::BV, num_bytes: u64) -> Result<B::BV> {
+        let width = a.get_width();
+        let mut equal = self.bv_from_bool(true);
+        for i in 0 .. num_bytes {
+            let offset = self.bv_from_u64(i, width);
+            let byte_a = self.read(&a.add(&offset), 8)?;
+            let byte_b = self.read(&b.add(&offset), 8)?;
+            equal = equal.and(&byte_a._eq(&byte_b));
+        }
+        Ok(equal)
+    }
+
+    /// Perform an atomic read-modify-write of `bits` bits at `addr`, as with
+    /// the LLVM `atomicrmw` instruction: read the value there, combine it
+    /// with `operand` according to `op`, write the result back, and return
+    /// the value originally read.
+    ///
+    /// `operand` must have width `bits`. Since `haybale` executes only one
+    /// thread, this is simply a read followed by a write.
+    pub fn atomic_rmw(
+        &mut self,
+        op: AtomicOp,
+        addr: &B::BV,
+        operand: &B::BV,
+        bits: u32,
+    ) -> Result<B::BV> {
+        if operand.get_width() != bits {
+            return Err(Error::MalformedInstruction(format!(
+                "atomic_rmw: expected an operand of width {}, but got width {}",
+                bits,
+                operand.get_width()
+            )));
+        }
+        let old = self.read(addr, bits)?;
+        let new = match op {
+            AtomicOp::Xchg => operand.clone(),
+            AtomicOp::Add => old.add(operand),
+            AtomicOp::Sub => old.sub(operand),
+            AtomicOp::And => old.and(operand),
+            AtomicOp::Nand => old.and(operand).not(),
+            AtomicOp::Or => old.or(operand),
+            AtomicOp::Xor => old.xor(operand),
+            AtomicOp::Max => old.sgt(operand).cond_bv(&old, operand),
+            AtomicOp::Min => old.slt(operand).cond_bv(&old, operand),
+            AtomicOp::UMax => old.ugt(operand).cond_bv(&old, operand),
+            AtomicOp::UMin => old.ult(operand).cond_bv(&old, operand),
+        };
+        self.write(addr, new)?;
+        Ok(old)
+    }
+
+    /// Read a value `bits` bits long from memory at `addr` in the LLVM address
+    /// space `space`.
+    ///
+    /// Each address space has its own memory, so pointers in different
+    /// address spaces never alias, even if they have the same numeric value.
+    /// Address space 0, the default, is the memory used by `read()` and
+    /// `write()` (and by symbolic execution of LLVM loads and stores, global
+    /// initialization, allocas, atomics, and the memory intrinsics), and with
+    /// `space` 0 this is the same as `read()`. Other address spaces are only
+    /// used through this and the other `*_in_space()` methods.
+    ///
+    /// Watchpoints, memory tracing, uninitialized-read handling, and null
+    /// pointer checking apply only to address space 0.
+    pub fn read_in_space(&self, space: u32, addr: &B::BV, bits: u32) -> Result<B::BV> {
+        if space == 0 {
+            return self.read(addr, bits);
+        }
+        self.space_mems
+            .borrow_mut()
+            .entry(space)
+            .or_insert_with(|| Self::new_space_memory(self.solver.clone(), space))
+            .read(addr, bits)
+    }
+
+    /// Write a value into memory at `addr` in the LLVM address space `space`.
+    /// See notes on [`read_in_space()`](#method.read_in_space).
+    pub fn write_in_space(&mut self, space: u32, addr: &B::BV, val: B::BV) -> Result<()> {
+        if space == 0 {
+            return self.write(addr, val);
+        }
+        let solver = self.solver.clone();
+        self.space_mems
+            .get_mut()
+            .entry(space)
+            .or_insert_with(|| Self::new_space_memory(solver, space))
+            .write(addr, val)
+    }
+
+    /// For internal use: since `self.mem` is a `RefCell`, we can write even
+    /// without having a `&mut self` reference. This is necessary to support,
+    /// for instance, lazy global initialization. But, we don't want to skip
+    /// watchpoint checks by calling `self.mem.borrow_mut()` directly, so we
+    /// have this
+    fn write_without_mut(&self, addr: &B::BV, val: B::BV) -> Result<()> {
+        let write_width = val.get_width();
+        let result = if self.is_known_non_null(addr) {
+            self.mem.borrow_mut().write_non_null(addr, val)
+        } else {
+            self.mem.borrow_mut().write(addr, val)
+        };
+        // we do this awkward `let result` / `match result` because it forces
+        // the mutable borrow of self.mem to end, which is necessary because
+        // save_backtracking_point_at_location requires a borrow of self.mem
+        match result {
+            Ok(()) => (),
+            e @ Err(Error::NullPointerDereference) => {
+                if self.config.null_pointer_checking == NullPointerChecking::SplitPath {
+                    self.split_off_non_null(addr);
+                }
+                return e; // report the null-pointer dereference
+            },
+            e @ Err(_) => return e, // propagate any other kind of error
+        };
+        if let Some(initialized_bytes) = self.initialized_bytes.borrow_mut().as_mut() {
+            if let Some(addr) = addr.as_u64() {
+                initialized_bytes.extend(addr .. addr + bytes_rounded_up(write_width.into()));
+            }
+        }
+        self.record_touched(addr);
+        for (name, watchpoint) in self
+            .mem_watchpoints
+            .get_triggered_watchpoints(addr, write_width)?
+        {
+            let pretty_loc = if self.config.print_module_name {
+                self.cur_loc.to_string_with_module()
+            } else {
+                self.cur_loc.to_string_no_module()
+            };
+            // Log the new value of the watched location (regardless of which part of the watched location the write may have touched).
+            // Note that the write operation itself has already been performed, so we get the updated value with a `read()`.
+            let watchpoint_low = self.bv_from_u64(
+                watchpoint.get_lower_bound(),
+                crate::layout::POINTER_SIZE_BITS as u32,
+            );
+            let watchpoint_size_bits =
+                (watchpoint.get_upper_bound() - watchpoint.get_lower_bound() + 1) * 8;
+            let new_value = self
+                .mem
+                .borrow()
+                .read(&watchpoint_low, watchpoint_size_bits as u32)?; // performs a read without using `state.read()` which would trigger watchpoints (we don't want to trigger watchpoints with this read)
+            info!(
+                "Memory watchpoint {:?} {} written by {{{}}}; new value is {:?}",
+                name, watchpoint, pretty_loc, new_value
+            );
+        }
+        Ok(())
+    }
+
+    /// Add a memory watchpoint. It will be enabled unless/until
+    /// `disable_watchpoint()` is called on it.
+    ///
+    /// If a watchpoint with the same name was previously added, this will
+    /// replace that watchpoint and return `true`. Otherwise, this will return
+    /// `false`.
+    ///
+    /// When any watched memory is read or written to, an INFO-level log message
+    /// will be generated.
+    pub fn add_mem_watchpoint(&mut self, name: impl Into<String>, watchpoint: Watchpoint) -> bool {
+        self.mem_watchpoints.add(name, watchpoint)
+    }
+
+    /// Remove the memory watchpoint with the given `name`.
+    ///
+    /// Returns `true` if the operation was successful, or `false` if no
+    /// watchpoint with that name was found.
+    pub fn rm_mem_watchpoint(&mut self, name: &str) -> bool {
+        self.mem_watchpoints.remove(name)
+    }
+
+    /// Disable the memory watchpoint with the given `name`. Disabled
+    /// watchpoints will not generate any log messages unless/until
+    /// `enable_watchpoint()` is called on them.
+    ///
+    /// Returns `true` if the operation is successful, or `false` if no
+    /// watchpoint with that name was found. Disabling an already-disabled
+    /// watchpoint will have no effect and will return `true`.
+    pub fn disable_watchpoint(&mut self, name: &str) -> bool {
+        self.mem_watchpoints.disable(name)
+    }
+
+    /// Enable the memory watchpoint(s) with the given name.
+    ///
+    /// Returns `true` if the operation is successful, or `false` if no
+    /// watchpoint with that name was found. Enabling an already-enabled
+    /// watchpoint will have no effect and will return `true`.
+    pub fn enable_watchpoint(&mut self, name: &str) -> bool {
+        self.mem_watchpoints.enable(name)
+    }
+
+    /// Allocate a value of size `bits`; return a pointer to the newly allocated object
+    pub fn allocate(&mut self, bits: impl Into<u64>) -> Pointer<B::BV> {
+        let raw_ptr = self.alloc.alloc(bits);
+        Pointer::new(self.bv_from_u64(raw_ptr, 64))
+    }
+
+    /// Allocate a value of size `bits` at the specific address `addr`, for
+    /// instance to model a memory-mapped device at a known address; return a
+    /// pointer to the newly allocated object.
+    ///
+    /// Returns an error if the allocation would contain address 0, or overlap
+    /// any existing allocation. Later allocations won't overlap this one.
+    pub fn allocate_at(&mut self, addr: u64, bits: impl Into<u64>) -> Result<Pointer<B::BV>> {
+        let raw_ptr = self.alloc.reserve_at(addr, bits)?;
+        Ok(Pointer::new(self.bv_from_u64(raw_ptr, 64)))
+    }
+
+    /// Allocate a value of size `bits` in the LLVM address space `space`;
+    /// return a pointer to the newly allocated object.
+    ///
+    /// Each address space has its own allocator, so objects in different
+    /// address spaces may have the same numeric address; see
+    /// [`read_in_space()`](#method.read_in_space). With `space` 0, this is the
+    /// same as `allocate()`.
+    pub fn allocate_in_space(&mut self, space: u32, bits: impl Into<u64>) -> Pointer<B::BV> {
+        if space == 0 {
+            return self.allocate(bits);
+        }
+        let raw_ptr = self
+            .space_allocs
+            .entry(space)
+            .or_insert_with(Alloc::new)
+            .alloc(bits);
+        Pointer::new(self.bv_from_u64(raw_ptr, 64))
+    }
+
+    /// Allocate a value of size `bits`, and fill it with fresh symbolic
+    /// contents; return a pointer to the newly allocated object.
+    ///
+    /// Unlike with `allocate()`, which leaves the new region with the default
+    /// memory contents, each byte of the new region is a distinct unconstrained
+    /// variable, so reads from the region are independent unknowns.
+    pub fn allocate_symbolic(&mut self, bits: impl Into<u64>) -> Result<B::BV> {
+        let bits = bits.into();
+        let (ptr, raw_ptr) = self.allocate_concrete(bits);
+        let id = self.symbolic_allocations_created;
+        self.symbolic_allocations_created += 1;
+        for i in 0 .. bytes_rounded_up(bits) {
+            let name = format!("allocate_symbolic{}_{:#x}_byte{}", id, raw_ptr, i);
+            let byte = B::BV::new(
+                self.solver.clone(),
+                crate::memory::Memory::BITS_IN_BYTE,
+                Some(&name),
+            );
+            self.write(&self.bv_from_u64(raw_ptr + i, 64), byte)?;
+        }
+        Ok(ptr)
+    }
+
+    /// Allocate `bytes` bytes of memory which read as zero until they are
+    /// written; return a pointer to the newly allocated region.
+    ///
+    /// Unlike allocating and then writing zeroes, this takes the same time
+    /// no matter how large the region is: instead of writing to memory, it
+    /// enables write tracking (as with `enable_write_tracking()`), and reads
+    /// at constant addresses produce zero for each byte of the region which
+    /// hasn't been written on the current path, taking precedence over any
+    /// uninit handler (see `set_uninit_handler()`). As with the uninit
+    /// handler, reads at symbolic addresses don't get this treatment, so
+    /// prefer writing the zeroes for regions which may be read that way.
+    ///
+    /// Returns `Error::OtherError` if `bytes` is too large to allocate.
+    pub fn allocate_zeroed(&mut self, bytes: u64) -> Result<B::BV> {
+        let bits = bytes.checked_mul(8).ok_or_else(|| {
+            Error::OtherError(format!(
+                "allocate_zeroed: {} bytes is too large to allocate",
+                bytes
+            ))
+        })?;
+        self.enable_write_tracking();
+        let (ptr, raw_ptr) = self.allocate_concrete(bits);
+        self.zeroed_regions.insert(raw_ptr, bytes);
+        Ok(ptr)
+    }
+
+    /// Allocate a value of size `bits`; return a pointer to the newly allocated
+    /// object, along with the concrete address of the object.
+    ///
+    /// This is like `allocate()`, but saves solving for the address when the
+    /// concrete layout is needed.
+    pub fn allocate_concrete(&mut self, bits: impl Into<u64>) -> (B::BV, u64) {
+        let raw_ptr = self.alloc.alloc(bits);
+        (self.bv_from_u64(raw_ptr, 64), raw_ptr)
+    }
+
+    /// Allocate an array of `count` elements, each of size `elem_bits`; return
+    /// a pointer to the newly allocated array, along with the element count.
+    ///
+    /// `count` may be symbolic, in which case space is reserved for
+    /// [`config.max_symbolic_array_count`](config/struct.Config.html#structfield.max_symbolic_array_count)
+    /// elements, and `count` is constrained to be at most that value.
+    /// Either way, `check_in_bounds()` will consider the end of the array to be
+    /// after `count` elements, not at the end of the reserved space.
+    pub fn allocate_array(&mut self, elem_bits: u32, count: &B::BV) -> Result<(B::BV, B::BV)> {
+        let count_width = count.get_width();
+        if count_width > 64 {
+            return Err(Error::OtherError(format!(
+                "allocate_array: element count is {} bits wide; only counts up to 64 bits are supported",
+                count_width
+            )));
+        }
+        let elem_bytes = bytes_rounded_up(elem_bits.into());
+        let reserved_count = count
+            .as_u64()
+            .unwrap_or(self.config.max_symbolic_array_count);
+        let reserved_bits = reserved_count
+            .checked_mul(elem_bytes)
+            .and_then(|bytes| bytes.checked_mul(8))
+            .ok_or_else(|| {
+                Error::OtherError(format!(
+                    "allocate_array: {} elements of {} bytes each is too large to allocate",
+                    reserved_count, elem_bytes
+                ))
+            })?;
+        // only constrain the count once we know the reservation can be made,
+        // so a failed allocation leaves the path unchanged
+        if count.as_u64().is_none() {
+            self.assert(
+                &count
+                    .zero_extend_to_bits(64)
+                    .ulte(&self.bv_from_u64(reserved_count, 64)),
+            )?;
+        }
+        let raw_ptr = self.alloc.alloc(reserved_bits);
+        let size_bytes = count
+            .zero_extend_to_bits(64)
+            .mul(&self.bv_from_u64(elem_bytes, 64));
+        self.array_sizes.insert(raw_ptr, size_bytes);
+        Ok((self.bv_from_u64(raw_ptr, 64), count.clone()))
+    }
+
+    /// Returns `true` if an access of size `bits` at address `ptr` can lie
+    /// entirely within a single allocation under the current constraints, or
+    /// `false` if it is provably out of bounds of every allocation.
+    ///
+    /// For arrays allocated with `allocate_array()`, the end of the array is
+    /// determined by its (possibly symbolic) element count.
+    pub fn check_in_bounds(&self, ptr: &B::BV, bits: u32) -> Result<bool> {
+        let access_bytes = bytes_rounded_up(bits.into());
+        let ptr_end = ptr.add(&self.bv_from_u64(access_bytes, ptr.get_width()));
+        let in_bounds =
+            self.alloc
+                .get_all_allocations()
+                .into_iter()
+                .map(|(addr, alloc_bits)| {
+                    let base = self.bv_from_u64(addr, ptr.get_width());
+                    let end = match self.array_sizes.get(&addr) {
+                        Some(size_bytes) => base.add(size_bytes),
+                        None => base
+                            .add(&self.bv_from_u64(bytes_rounded_up(alloc_bits), ptr.get_width())),
+                    };
+                    ptr.ugte(&base)
+                        .and(&ptr_end.ulte(&end))
+                        .and(&ptr_end.ugte(ptr))
+                })
+                .reduce(|a, b| a.or(&b));
+        match in_bounds {
+            None => Ok(false), // no allocations at all
+            Some(in_bounds) => self.sat_with_extra_constraints(std::iter::once(&in_bounds)),
+        }
+    }
+
+    /// Free the allocation at the given address, as with the C `free()`.
+    ///
+    /// If
+    /// [`Config.reuse_freed_allocations`](config/struct.Config.html#structfield.reuse_freed_allocations)
+    /// is set, later allocations of the same size or smaller may reuse the
+    /// freed allocation's address. Otherwise, this is a no-op.
+    ///
+    /// Freeing a null pointer is always a no-op. Otherwise, `addr` must have
+    /// only one possible value on the current path.
+    pub fn free(&mut self, addr: &B::BV) -> Result<()> {
+        let reuse = self.config.reuse_freed_allocations;
+        if !reuse && !self.alloc.has_event_handler() {
+            return Ok(());
+        }
+        let addr = self.concrete_address(addr, "free")?;
+        if addr == 0 {
+            return Ok(());
+        }
+        let freed = if reuse {
+            self.alloc.free(addr)
+        } else {
+            self.alloc.report_free(addr)
+        };
+        if !freed {
+            warn!(
+                "free() of {:#x}, which is not the start of a live allocation",
+                addr
+            );
+        }
+        if reuse {
+            // a later allocation reusing the address shouldn't read as zero
+            self.zeroed_regions.remove(&addr);
+        }
+        Ok(())
+    }
+
+    /// Install a handler which `haybale` will call with each allocation and
+    /// free in address space 0 (see [`AllocEvent`](enum.AllocEvent.html)),
+    /// in the order they happen, replacing any previously installed handler.
+    /// For instance, this can track how the heap evolves along a path.
+    ///
+    /// Events are reported from `free()` even if
+    /// [`Config.reuse_freed_allocations`](config/struct.Config.html#structfield.reuse_freed_allocations)
+    /// isn't set; but then, installing a handler means that `free()` requires
+    /// its address to have only one possible value. Backtracking doesn't
+    /// report any events for the allocations it undoes.
+    pub fn set_alloc_event_handler(&mut self, handler: Box<dyn FnMut(AllocEvent)>) {
+        self.alloc.set_event_handler(handler);
+    }
+
+    /// Get the size, in bits, of the allocation at the given address, or `None`
+    /// if that address is not the result of an `alloc()`.
+    pub fn get_allocation_size(&mut self, addr: &B::BV) -> Result<Option<u64>> {
+        let addr = self.concrete_address(addr, "get_allocation_size")?;
+        Ok(self.alloc.get_allocation_size(addr))
+    }
+
+    /// Get the allocation which the given pointer points into, or `None` if it
+    /// doesn't point into any allocation. The pointer may point anywhere inside
+    /// the allocation, not just to its start.
+    ///
+    /// The pointer must have only one possible value on the current path,
+    /// unless it was produced by `inttoptr_checked()` and (as recorded there)
+    /// can only point into one allocation.
+    pub fn pointer_provenance(&mut self, ptr: &B::BV) -> Result<Option<AllocationInfo>> {
+        if let Some((_, info)) = self.provenance.get(&ptr.get_id()) {
+            return Ok(Some(*info));
+        }
+        let ptr = self.concrete_address(ptr, "pointer_provenance")?;
+        Ok(self.alloc.provenance(ptr))
+    }
+
+    /// Constrain `ptr` to be non-NULL on the current path, for instance to
+    /// model a documented precondition of a function. Afterwards, reads and
+    /// writes at exactly `ptr` (the same expression, not merely one with the
+    /// same value) skip the NULL check which
+    /// [`Config.null_pointer_checking`](config/struct.Config.html#structfield.null_pointer_checking)
+    /// would otherwise make, saving a solver query for each access; and so
+    /// does `can_be_null()`.
+    pub fn assume_non_null(&mut self, ptr: &B::BV) -> Result<()> {
+        self.assert(&ptr._ne(&self.zero(ptr.get_width())))?;
+        self.non_null.insert(ptr.get_id(), ptr.clone());
+        Ok(())
+    }
+
+    /// Has `ptr` been constrained to be non-NULL on the current path with
+    /// `assume_non_null()`? This doesn't query the solver, so it's `false`
+    /// for pointers which are non-NULL for any other reason.
+    pub fn is_known_non_null(&self, ptr: &B::BV) -> bool {
+        self.non_null.contains_key(&ptr.get_id())
+    }
+
+    /// Returns `true` if `ptr` can be NULL under the current constraints.
+    /// For a pointer passed to `assume_non_null()`, this returns `false`
+    /// without querying the solver.
+    pub fn can_be_null(&self, ptr: &B::BV) -> Result<bool> {
+        if self.is_known_non_null(ptr) {
+            return Ok(false);
+        }
+        self.sat_with_extra_constraints(std::iter::once(&ptr._eq(&self.zero(ptr.get_width()))))
+    }
+
+    /// Cast the integer `int` to a pointer, as with the LLVM `inttoptr`
+    /// instruction, but checking that the pointer points into some live
+    /// allocation (one which hasn't been freed). This catches wild pointers
+    /// produced by integer arithmetic, which a plain cast lets through.
+    ///
+    /// If `int` can point into a live allocation, it's constrained to do so on
+    /// the current path; otherwise, this returns an error. If this leaves only
+    /// one allocation `int` can point into, that allocation is recorded as the
+    /// provenance of the returned pointer, so that `pointer_provenance()` can
+    /// report it even if the pointer itself is still symbolic. `int` must be
+    /// pointer-sized.
+    pub fn inttoptr_checked(&mut self, int: &B::BV) -> Result<B::BV> {
+        let width = int.get_width();
+        if width != POINTER_SIZE_BITS as u32 {
+            return Err(Error::OtherError(format!(
+                "inttoptr_checked: expected a value of width {}, got width {}",
+                POINTER_SIZE_BITS, width
+            )));
+        }
+        // an allocation of 0 bits is considered to contain only its own address, as in `pointer_provenance()`
+        let live: Vec<(u64, u64)> = self
+            .alloc
+            .get_live_allocations()
+            .into_iter()
+            .map(|(addr, bits)| (addr, std::cmp::max(bytes_rounded_up(bits), 1)))
+            .collect();
+        if let Some(addr) = int.as_u64() {
+            return if let Some(&(base, _)) = live
+                .iter()
+                .find(|&&(base, bytes)| addr >= base && addr - base < bytes)
+            {
+                self.record_provenance(int, base);
+                Ok(int.clone())
+            } else {
+                Err(Error::OtherError(format!(
+                    "inttoptr_checked: {:#x} doesn't point into any live allocation",
+                    addr
+                )))
+            };
+        }
+        let in_allocation = |base: u64, bytes: u64| {
+            // as this is unsigned, it's also out of bounds if `int` is before the allocation
+            let rel = int.sub(&self.bv_from_u64(base, width));
+            rel.ult(&self.bv_from_u64(bytes, width))
+        };
+        let in_live_allocation = live
+            .iter()
+            .map(|&(base, bytes)| in_allocation(base, bytes))
+            .fold(self.bv_from_bool(false), |a, b| a.or(&b));
+        if !self.sat_with_extra_constraints(std::iter::once(&in_live_allocation))? {
+            return Err(Error::OtherError(
+                "inttoptr_checked: the value can't point into any live allocation".to_owned(),
+            ));
+        }
+        self.assert(&in_live_allocation)?;
+        // find one allocation `int` can point into, and check whether it's the only one
+        let addr = self
+            .get_a_solution_for_bv(int)?
+            .and_then(|sol| sol.as_u64())
+            .ok_or(Error::Unsat)?;
+        if let Some(&(base, bytes)) = live
+            .iter()
+            .find(|&&(base, bytes)| addr >= base && addr - base < bytes)
+        {
+            let elsewhere = in_allocation(base, bytes).not();
+            if !self.sat_with_extra_constraints(std::iter::once(&elsewhere))? {
+                self.record_provenance(int, base);
+            }
+        }
+        Ok(int.clone())
+    }
+
+    /// Record that the pointer `ptr` points into the allocation at `base`
+    fn record_provenance(&mut self, ptr: &B::BV, base: u64) {
+        if let Some(info) = self.alloc.provenance(base) {
+            self.provenance.insert(ptr.get_id(), (ptr.clone(), info));
+        }
+    }
+
+    /// Get the value of `addr`, which must have only one possible value on the
+    /// current path. `caller` is used in error messages.
+    fn concrete_address(&self, addr: &B::BV, caller: &str) -> Result<u64> {
+        // First try to obtain the address without a full solve (i.e., with `as_u64()`)
+        match addr.as_u64() {
+            Some(addr) => Ok(addr),
+            None => {
+                match self.get_possible_solutions_for_bv(addr, 1)? {
+                    PossibleSolutions::AtLeast(_) => Err(Error::OtherError(format!(
+                        "{}: address is not a constant: {:?}",
+                        caller, addr
+                    ))), // must be at least 2 solutions, since we passed in n==1
+                    PossibleSolutions::Exactly(v) => v
+                        .iter()
+                        .next()
+                        .ok_or(Error::Unsat)?
+                        .as_u64()
+                        .ok_or_else(|| {
+                            Error::OtherError(format!(
+                                "{}: address is more than 64 bits wide",
+                                caller
+                            ))
+                        }),
+                }
+            },
+        }
+    }
+
+    /// Get the total number of bytes allocated so far, including any padding
+    /// between allocations, and the space for global variables and functions.
+    ///
+    /// Like allocations themselves, this persists across backtracking.
+    pub fn memory_usage(&self) -> u64 {
+        self.alloc.bytes_allocated()
+    }
+
+    /// Get the address just past the highest byte allocated so far.
+    pub fn allocation_high_water_mark(&self) -> u64 {
+        self.alloc.high_water_mark()
+    }
+
+    /// Record the current location as a `PathEntry` in the current path.
+    pub fn record_path_entry(&mut self) {
+        let entry = PathEntry(self.cur_loc.clone());
+        debug!("Recording a path entry {:?}", entry);
+        self.path.push(entry);
+    }
+
+    /// Get the `PathEntry`s that have been recorded, in order
+    pub fn get_path(&self) -> &Vec<PathEntry<'p>> {
+        &self.path
+    }
+
+    /// Record entering a normal `Call` at the current location
+    ///
+    /// Returns `Error::CallDepthExceeded` if this would exceed the configured
+    /// `callstack_depth_error_limit` (see [`Config`](config/struct.Config.html)).
+    pub fn push_callsite(&mut self, call: &'p instruction::Call) -> Result<()> {
+        self.push_generic_callsite(Either::Left(call))
+    }
+
+    /// Record entering the given `Invoke` at the current location
+    ///
+    /// Returns `Error::CallDepthExceeded` if this would exceed the configured
+    /// `callstack_depth_error_limit` (see [`Config`](config/struct.Config.html)).
+    pub fn push_invokesite(&mut self, invoke: &'p terminator::Invoke) -> Result<()> {
+        self.push_generic_callsite(Either::Right(invoke))
+    }
+
+    fn push_generic_callsite(
+        &mut self,
+        instr: Either<&'p instruction::Call, &'p terminator::Invoke>,
+    ) -> Result<()> {
+        if let Some(max) = self.config.callstack_depth_error_limit {
+            if self.stack.len() >= max {
+                return Err(Error::CallDepthExceeded(max));
+            }
+        }
+        self.stack.push(StackFrame {
+            callsite: Callsite {
+                loc: self.cur_loc.clone(),
+                instr,
+            },
+            // TODO: taking this `restore_info` every time a callsite is pushed
+            // may be expensive, and is only necessary if the call we're going
+            // to make will eventually (directly or indirectly) recurse. In the
+            // future we could check the LLVM 'norecurse' attribute to know when
+            // this is not necessary.
+            restore_info: self
+                .varmap
+                .get_restore_info_for_fn(self.cur_loc.func.name.clone()),
+        });
+        Ok(())
+    }
+
+    /// Record leaving the current function. Returns the `Callsite` at which the
+    /// current function was called, or `None` if the current function was the
+    /// top-level function.
+    ///
+    /// Also restores the caller's local variables.
+    pub fn pop_callsite(&mut self) -> Option<Callsite<'p>> {
+        if let Some(StackFrame {
+            callsite,
+            restore_info,
+        }) = self.stack.pop()
+        {
+            self.varmap.restore_fn_vars(restore_info);
+            Some(callsite)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the current callstack depth. `0` indicates we're in the toplevel
+    /// function, `1` indicates we're in a function directly called by the
+    /// toplevel function, etc.
+    pub fn current_callstack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Save the current state, about to enter the `BasicBlock` with the given `Name` (which must be
+    /// in the same `Module` and `Function` as `state.cur_loc`), as a backtracking point.
+    /// The constraint will be added only if we end up backtracking to this point, and only then.
+    ///
+    /// Returns `Error::BacktrackLimitExceeded` (and saves nothing) if this would
+    /// exceed the `max_backtrack_depth` in the `Config`.
+    pub fn save_backtracking_point(&mut self, bb_to_enter: &Name, constraint: B::BV) -> Result<()> {
+        debug!(
+            "Saving a backtracking point, which would enter bb {:?} with constraint {:?}",
+            bb_to_enter, constraint
+        );
+        let bb_to_enter = self
+            .cur_loc
+            .func
+            .get_bb_by_name(&bb_to_enter)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Failed to find bb named {} in function {:?}",
+                    bb_to_enter, self.cur_loc.func.name
+                )
+            });
+        let backtrack_loc = Location {
+            module: self.cur_loc.module,
+            func: self.cur_loc.func,
+            bb: bb_to_enter,
+            instr: BBInstrIndex::Instr(0),
+            source_loc: None,
+        };
+        self.save_backtracking_point_at_location(backtrack_loc, constraint)
+    }
+
+    /// Like `save_backtracking_point()`, but first checks that the constraint is
+    /// consistent with the existing constraints on the current path, and only
+    /// saves the backtracking point if it is. This avoids later reverting to a
+    /// backtracking point which is immediately unsat.
+    ///
+    /// Returns `true` if the backtracking point was saved, or `false` if the
+    /// constraint was infeasible and nothing was saved.
+    pub fn save_backtracking_point_pruned(
+        &mut self,
+        bb_to_enter: &Name,
+        constraint: B::BV,
+    ) -> Result<bool> {
+        if self.sat_with_extra_constraints(std::iter::once(&constraint))? {
+            self.save_backtracking_point(bb_to_enter, constraint)?;
+            Ok(true)
+        } else {
+            debug!(
+                "Not saving a backtracking point to enter bb {:?}, as its constraint is infeasible",
+                bb_to_enter
+            );
+            Ok(false)
+        }
+    }
+
+    /// For `NullPointerChecking::SplitPath`: save a backtracking point to
+    /// re-execute the current instruction with `addr` constrained to be
+    /// non-null, and continue from there.
+    ///
+    /// Failing to save it (e.g., because of `max_backtrack_depth`) only means
+    /// the non-null case goes unexplored, so this logs the failure rather than
+    /// returning it, and the caller can still report the null dereference.
+    fn split_off_non_null(&self, addr: &B::BV) {
+        if let Err(e) = self.save_backtracking_point_at_location(
+            self.cur_loc.clone(),
+            addr._ne(&self.zero(addr.get_width())),
+        ) {
+            warn!(
+                "Not exploring the case where the dereferenced pointer is non-null: {}",
+                e
+            );
+        }
+    }
+
+    /// Internal version of `save_backtracking_point()` which takes an arbitrary
+    /// `Location` instead of just the basic block to start at.
+    ///
+    /// Also it doesn't require `&mut self`. This allows us to save backtracking
+    /// points even when we're inside methods that only have `&self`.
+    fn save_backtracking_point_at_location(
+        &self,
+        loc_to_start_at: Location<'p>,
+        constraint: B::BV,
+    ) -> Result<()> {
+        if let Some(max_depth) = self.config.max_backtrack_depth {
+            if self.backtrack_points.borrow().len() >= max_depth {
+                return Err(Error::BacktrackLimitExceeded(max_depth));
+            }
+        }
+        let constraints = match self.config.search_strategy {
+            SearchStrategy::DepthFirst => {
+                self.solver.push(1);
+                None
+            },
+            _ => {
+                if self.search_base_len.get().is_none() {
+                    // from now on, the current path's constraints go in their own context
+                    self.solver.push(1);
+                    self.search_base_len
+                        .set(Some(self.asserted_constraints.borrow().len()));
+                }
+                Some(self.asserted_constraints.borrow().clone())
+            },
+        };
+        let bp = self.backtrack_point_here(loc_to_start_at, constraint, constraints);
+        self.backtrack_points.borrow_mut().push(bp);
+        Ok(())
+    }
+
+    /// Capture the current path in a `BacktrackPoint`, without pushing a
+    /// solver context for it or saving it
+    fn backtrack_point_here(
+        &self,
+        loc: Location<'p>,
+        constraint: B::BV,
+        constraints: Option<Vec<B::BV>>,
+    ) -> BacktrackPoint<'p, B> {
+        BacktrackPoint {
+            loc,
+            stack: self.stack.clone(),
+            constraint,
+            varmap: self.varmap.clone(),
+            mem: self.mem.borrow().clone(),
+            space_mems: self.space_mems.borrow().clone(),
+            path_len: self.path.len(),
+            constraints_len: self.asserted_constraints.borrow().len(),
+            initialized_bytes: self.initialized_bytes.borrow().clone(),
+            touched_allocs: self.touched_allocs.borrow().clone(),
+            varargs: self.varargs.clone(),
+            back_edge_counts: self.back_edge_counts.clone(),
+            non_null: self.non_null.clone(),
+            provenance: self.provenance.clone(),
+            constraints,
+            alloc: if self.config.reuse_freed_allocations {
+                Some(self.alloc.clone())
+            } else {
+                None
+            },
+            zeroed_regions: if self.config.reuse_freed_allocations {
+                Some(self.zeroed_regions.clone())
+            } else {
+                None
+            },
+        }
+    }
+
+    /// returns `Ok(true)` if the operation was successful, `Ok(false)` if there are
+    /// no saved backtracking points, or `Err` for other errors
+    ///
+    /// Which backtracking point is reverted to is determined by
+    /// [`Config.search_strategy`](config/struct.Config.html#structfield.search_strategy).
+    pub fn revert_to_backtracking_point(&mut self) -> Result<bool> {
+        let index = match self.next_backtracking_point()? {
+            Some(index) => index,
+            None => return Ok(false),
+        };
+        let bp = self.backtrack_points.borrow_mut().remove(index);
+        debug!("Reverting to backtracking point {}", bp);
+        match bp.constraints {
+            None => {
+                self.solver.pop(1);
+                self.asserted_constraints
+                    .borrow_mut()
+                    .truncate(bp.constraints_len);
+            },
+            Some(constraints) => {
+                // replace the current path's context with one containing this point's constraints
+                let base_len = self
+                    .search_base_len
+                    .get()
+                    .expect("Backtracking point has constraints, but there is no path context");
+                self.solver.pop(1);
+                self.solver.push(1);
+                for constraint in &constraints[base_len ..] {
+                    constraint.assert()?;
+                }
+                self.asserted_constraints.replace(constraints);
+            },
+        }
+        self.varmap = bp.varmap;
+        self.mem.replace(bp.mem);
+        self.space_mems.replace(bp.space_mems);
+        self.stack = bp.stack;
+        self.path.truncate(bp.path_len);
+        self.initialized_bytes.replace(bp.initialized_bytes);
+        self.touched_allocs.replace(bp.touched_allocs);
+        self.varargs = bp.varargs;
+        self.back_edge_counts = bp.back_edge_counts;
+        self.non_null = bp.non_null;
+        self.provenance = bp.provenance;
+        if let Some(alloc) = bp.alloc {
+            self.alloc.revert_to(alloc);
+        }
+        if let Some(zeroed_regions) = bp.zeroed_regions {
+            self.zeroed_regions = zeroed_regions;
+        }
+        self.cur_loc = bp.loc;
+        self.assert(&bp.constraint)?;
+        Ok(true)
+    }
+
+    /// Get the index in `backtrack_points` of the backtracking point to revert
+    /// to next, according to the configured `SearchStrategy`, or `None` if
+    /// there are no saved backtracking points
+    fn next_backtracking_point(&self) -> Result<Option<usize>> {
+        let backtrack_points = self.backtrack_points.borrow();
+        let num_points = backtrack_points.len();
+        if num_points == 0 {
+            return Ok(None);
+        }
+        match &self.config.search_strategy {
+            SearchStrategy::DepthFirst => Ok(Some(num_points - 1)),
+            SearchStrategy::BreadthFirst => Ok(Some(0)),
+            SearchStrategy::Custom(choose) => {
+                let locs: Vec<Location<'p>> =
+                    backtrack_points.iter().map(|bp| bp.loc.clone()).collect();
+                let index = choose(&locs);
+                if index < num_points {
+                    Ok(Some(index))
+                } else {
+                    Err(Error::OtherError(format!(
+                        "Custom search strategy chose backtracking point {}, but there are only {}",
+                        index, num_points
+                    )))
+                }
+            },
+        }
+    }
+
+    /// returns the number of saved backtracking points
+    pub fn count_backtracking_points(&self) -> usize {
+        self.backtrack_points.borrow().len()
+    }
+
+    /// Begin a backtracking scope. All backtracking points saved after this
+    /// call (and not yet reverted to) will be discarded by the matching call
+    /// to `end_scope()`.
+    ///
+    /// Scopes can be nested; each `end_scope()` ends the innermost open scope.
+    ///
+    /// Within a scope, constrain the path with `State::assert()` rather than
+    /// `BV::assert()`: `end_scope()` can only re-assert the constraints the
+    /// `State` remembers.
+    pub fn begin_scope(&mut self) {
+        self.backtrack_scopes
+            .push(self.backtrack_points.borrow().len());
+    }
+
+    /// End the innermost backtracking scope (see `begin_scope()`), discarding
+    /// any unexplored backtracking points which were saved since it began,
+    /// and popping the corresponding solver contexts. Constraints this path
+    /// asserted with `State::assert()` since the first discarded backtracking
+    /// point are re-asserted, so the current path is otherwise unaffected;
+    /// but any asserted there directly with `BV::assert()` are dropped along
+    /// with the popped contexts.
+    ///
+    /// Returns `Error::OtherError` if there is no open scope.
+    pub fn end_scope(&mut self) -> Result<()> {
+        let scope_start = self.backtrack_scopes.pop().ok_or_else(|| {
+            Error::OtherError("end_scope: no backtracking scope is open".to_owned())
+        })?;
+        let discarded: Vec<BacktrackPoint<'p, B>> = {
+            let mut backtrack_points = self.backtrack_points.borrow_mut();
+            // if we already reverted to points from before the scope began, there's nothing to discard
+            let scope_start = std::cmp::min(scope_start, backtrack_points.len());
+            backtrack_points.drain(scope_start ..).collect()
+        };
+        if let Some(first) = discarded.first() {
+            debug!(
+                "Discarding {} backtracking points at end of scope",
+                discarded.len()
+            );
+            // under strategies other than `DepthFirst`, backtracking points
+            // don't have solver contexts of their own, so there's nothing to pop
+            if first.constraints.is_none() {
+                self.solver.pop(discarded.len() as u32);
+                // popping the solver contexts also discarded whatever the current
+                // path asserted since the first of them was pushed
+                for constraint in &self.asserted_constraints.borrow()[first.constraints_len ..] {
+                    constraint.assert()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge `other` into this `State`, so that a single `State` represents
+    /// both paths. `path_pred` (a `BV` of width 1) distinguishes the paths:
+    /// where it is true, the merged `State` behaves like (the original) `self`,
+    /// and where it is false, like `other`.
+    ///
+    /// Specifically:
+    ///   - each variable bound in both `State`s (to different values) is bound
+    ///     to `ite(path_pred, self_val, other_val)`; variables bound only in
+    ///     `other` get their values from `other`;
+    ///   - memory contents are merged in the same way;
+    ///   - the constraints which the two `State`s asserted (with
+    ///     `State::assert()`) since they diverged are replaced with a single
+    ///     disjunction, guarded by `path_pred`.
+    ///
+    /// Both `State`s must be at the same `Location` with the same call stack,
+    /// and must share a solver instance (e.g., `other` was created by cloning
+    /// this `State`). The constraints either `State` asserted since diverging
+    /// were asserted on the shared solver, so they are retracted by popping
+    /// the solver's innermost context and rebuilding it with the disjunction
+    /// in their place. This requires that the `State`s diverged within that
+    /// context, i.e., after the most recent backtracking point was saved (or,
+    /// under search strategies other than `DepthFirst`, after the first one
+    /// was), and that neither `State` saved backtracking points since;
+    /// otherwise this returns `Error::OtherError`. Only the constraints
+    /// asserted with `State::assert()` can be re-asserted in the rebuilt
+    /// context, so any asserted there directly with `BV::assert()` are
+    /// dropped. Backtracking points, the path, and other bookkeeping are kept
+    /// from `self`.
+    pub fn merge(&mut self, other: Self, path_pred: &B::BV) -> Result<()> {
+        if self.cur_loc != other.cur_loc {
+            return Err(Error::OtherError(format!(
+                "merge: can't merge states at different locations: {:?} and {:?}",
+                self.cur_loc, other.cur_loc
+            )));
+        }
+        let self_callsites = self.stack.iter().map(|frame| &frame.callsite);
+        let other_callsites = other.stack.iter().map(|frame| &frame.callsite);
+        if !self_callsites.eq(other_callsites) {
+            return Err(Error::OtherError(
+                "merge: can't merge states with different call stacks".to_owned(),
+            ));
+        }
+
+        let self_constraints = self.asserted_constraints.borrow().clone();
+        let other_constraints = other.asserted_constraints.into_inner();
+        let common_len = self_constraints
+            .iter()
+            .zip(other_constraints.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let conjunction = |constraints: &[B::BV]| {
+            constraints
+                .iter()
+                .fold(self.bv_from_bool(true), |acc, c| acc.and(c))
+        };
+        let self_only = conjunction(&self_constraints[common_len ..]);
+        let other_only = conjunction(&other_constraints[common_len ..]);
+        let disjunction = path_pred
+            .and(&self_only)
+            .or(&path_pred.not().and(&other_only));
+        if common_len < self_constraints.len() || common_len < other_constraints.len() {
+            if self.backtrack_points.borrow().len() != other.backtrack_points.borrow().len()
+                || self.search_base_len.get() != other.search_base_len.get()
+            {
+                return Err(Error::OtherError(
+                    "merge: can't merge states which saved different backtracking points"
+                        .to_owned(),
+                ));
+            }
+            // where the innermost solver context begins, in terms of asserted constraints
+            let context_start = match self.config.search_strategy {
+                SearchStrategy::DepthFirst => self
+                    .backtrack_points
+                    .borrow()
+                    .last()
+                    .map(|bp| bp.constraints_len),
+                _ => self.search_base_len.get(),
+            };
+            match context_start {
+                Some(start) if start <= common_len => {
+                    self.solver.pop(1);
+                    self.solver.push(1);
+                    for constraint in &self_constraints[start .. common_len] {
+                        constraint.assert()?;
+                    }
+                },
+                _ => {
+                    return Err(Error::OtherError(
+                        "merge: the states diverged outside the innermost solver context, so the constraints they asserted since can't be retracted".to_owned(),
+                    ))
+                },
+            }
+        }
+        self.asserted_constraints.borrow_mut().truncate(common_len);
+        self.assert(&disjunction)?;
+
+        self.varmap.merge(other.varmap, path_pred);
+        self.mem
+            .borrow_mut()
+            .merge(other.mem.into_inner(), path_pred);
+        for (space, other_mem) in other.space_mems.into_inner() {
+            self.space_mems
+                .borrow_mut()
+                .entry(space)
+                .or_insert_with(|| Self::new_space_memory(self.solver.clone(), space))
+                .merge(other_mem, path_pred);
+        }
+        self.alloc.merge(&other.alloc);
+        for (space, other_alloc) in other.space_allocs {
+            self.space_allocs
+                .entry(space)
+                .or_insert_with(Alloc::new)
+                .merge(&other_alloc);
+        }
+        if let (Some(touched_allocs), Some(other_touched)) = (
+            self.touched_allocs.borrow_mut().as_mut(),
+            other.touched_allocs.into_inner(),
+        ) {
+            touched_allocs.extend(other_touched);
+        }
+        for (loop_id, other_count) in other.back_edge_counts {
+            let count = self.back_edge_counts.entry(loop_id).or_insert(0);
+            *count = std::cmp::max(*count, other_count);
+        }
+        // a pointer is only known to be non-NULL on the merged path if it was on both
+        let other_non_null = other.non_null;
+        self.non_null
+            .retain(|id, _| other_non_null.contains_key(id));
+        let other_provenance = other.provenance;
+        self.provenance.retain(|id, (_, info)| {
+            other_provenance
+                .get(id)
+                .is_some_and(|(_, other_info)| other_info == info)
+        });
+        for (addr, size_bytes) in other.array_sizes {
+            self.array_sizes.entry(addr).or_insert(size_bytes);
+        }
+        for (addr, size_bytes) in other.zeroed_regions {
+            self.zeroed_regions.entry(addr).or_insert(size_bytes);
+        }
+        self.coverage.union(other.coverage);
+        self.symbolic_allocations_created = std::cmp::max(
+            self.symbolic_allocations_created,
+            other.symbolic_allocations_created,
+        );
+        Ok(())
+    }
+
+    /// returns a `String` containing a formatted view of the current backtrace
+    /// (in terms of LLVM locations, and possibly also source locations depending
+    /// on the `Config`)
+    pub fn pretty_backtrace(&self) -> String {
+        let mut locdescrs = std::iter::once(LocationDescription::from(self.cur_loc.clone()))
+            .chain(
+                self.stack
+                    .iter()
+                    .rev()
+                    .map(|frame| LocationDescription::from(frame.callsite.loc.clone())),
+            )
+            .collect::<Vec<LocationDescription>>();
+        for locdescr in locdescrs.iter_mut() {
+            self.demangle_locdescr(locdescr);
+        }
+        locdescrs
+            .into_iter()
+            .zip(1 ..)
+            .map(|(locdescr, framenum)| {
+                let pretty_locdescr = if self.config.print_module_name {
+                    locdescr.to_string_with_module()
+                } else {
+                    locdescr.to_string_no_module()
+                };
+                let mut frame_string = format!("  #{}: {}\n", framenum, pretty_locdescr);
+                match locdescr.source_loc {
+                    Some(source_loc) if self.config.print_source_info => {
+                        frame_string
+                            .push_str(&format!("         ({})\n", pretty_source_loc(source_loc)));
+                    },
+                    _ => {},
+                };
+                frame_string
+            })
+            .collect()
+    }
+
+    /// returns a `String` containing a formatted view of the full path which led
+    /// to this point, in terms of LLVM locations
+    pub fn pretty_path_llvm(&self) -> String {
+        let mut path_str = String::new();
+        for path_entry in self.get_path() {
+            path_str.push_str(&format!(
+                "  {}\n",
+                if self.config.print_module_name {
+                    path_entry.to_string_with_module()
+                } else {
+                    path_entry.to_string_no_module()
+                },
+            ));
+        }
+        path_str
+    }
+
+    /// returns a `String` containing a formatted view of the full path which led
+    /// to this point, in terms of source locations
+    pub fn pretty_path_source(&self) -> String {
+        let mut path_str = String::new();
+        let mut source_locs = self
+            .get_path()
+            .iter()
+            .flat_map(|path_entry| path_entry.get_all_source_locs());
+        // handle the first one special, so we can print this help message if necessary
+        match source_locs.next() {
+            None => {
+                path_str.push_str("  No source locations available in the path.\n");
+                path_str.push_str(
+                    "  This may be because the LLVM bitcode was not compiled with debuginfo.\n",
+                );
+                path_str.push_str(
+                    "  To compile C/C++ or Rust sources with debuginfo, pass the `-g` flag\n",
+                );
+                path_str.push_str("    to `clang`, `clang++`, or `rustc`.\n");
+            },
+            Some(first_source_loc) => {
+                path_str.push_str(&format!("  {}\n", pretty_source_loc(first_source_loc)))
+            },
+        }
+        for source_loc in source_locs {
+            path_str.push_str(&format!("  {}\n", pretty_source_loc(source_loc)));
+        }
+        path_str
+    }
+
+    /// returns a `String` containing a formatted view of the full path which led
+    /// to this point, in terms of both LLVM and source locations (interleaved
+    /// appropriately)
+    pub fn pretty_path_interleaved(&self) -> String {
+        let mut path_str = String::new();
+        for path_entry in self.get_path() {
+            path_str.push_str(&format!(
+                "  {}:\n",
+                if self.config.print_module_name {
+                    path_entry.to_string_with_module()
+                } else {
+                    path_entry.to_string_no_module()
+                },
+            ));
+            let mut source_locs = path_entry.get_all_source_locs();
+            // handle the first one special, so we can print this help message if necessary
+            match source_locs.next() {
+                None => path_str.push_str("    (no source locations available)\n"),
+                Some(first_source_loc) => {
+                    path_str.push_str(&format!("    {}\n", pretty_source_loc(first_source_loc)))
+                },
+            }
+            for source_loc in source_locs {
+                path_str.push_str(&format!("    {}\n", pretty_source_loc(source_loc)));
+            }
+        }
+        path_str
+    }
+
+    /// Attempt to demangle the given `funcname` as appropriate based on the
+    /// `Config`.
+    ///
+    /// If this fails to demangle `funcname`, it just returns a copy of
+    /// `funcname` unchanged.
+    pub fn demangle(&self, funcname: &str) -> String {
+        match self.config.demangling {
+            Some(demangling) => demangling.maybe_demangle(funcname),
+            None => panic!("Demangling shouldn't be None here"), // we should resolve it to Some() in the State constructor
+        }
+    }
+
+    /// Attempts to demangle the function name in the `LocationDescription`, as
+    /// appropriate based on the `Config`.
+    fn demangle_locdescr(&self, locdescr: &mut LocationDescription) {
+        locdescr.funcname = self.demangle(&locdescr.funcname);
+    }
+
+    /// Get the most recent `BV` created for each `Name` in the current function.
+    /// Returns pairs of the `Name` and the `BV` assigned to that `Name`.
+    ///
+    /// Returned pairs will be sorted by `Name`.
+    pub fn all_vars_in_cur_fn(&self) -> impl Iterator<Item = (&Name, &B::BV)> {
+        self.varmap.get_all_vars_in_fn(&self.cur_loc.func.name)
+    }
+
+    /// returns a `String` describing a set of satisfying assignments for all variables
+    pub fn current_assignments_as_pretty_string(&self) -> Result<String> {
+        self.solver.set_opt(BtorOption::ModelGen(ModelGen::All));
+        let string = if self.sat()? {
+            let printed = self.solver.print_model();
+            let sorted = itertools::sorted(printed.lines());
+            sorted.fold(String::new(), |s, line| s + "\n" + line)
+        } else {
+            "<state is unsatisfiable>".to_owned()
+        };
+        self.solver
+            .set_opt(BtorOption::ModelGen(ModelGen::Disabled));
+        Ok(string)
+    }
+
+    /// Returns a `String` describing both the error and the context in which it
+    /// occurred (backtrace, full path to error, variable values at the point of
+    /// error, etc). Exactly which information is included is partially dependent
+    /// on the environment variables `HAYBALE_DUMP_PATH` and `HAYBALE_DUMP_VARS`,
+    /// as explained in the message.
+    pub fn full_error_message_with_context(&self, e: Error) -> String {
+        let mut err_msg = format!("{}\n\n", e);
+        err_msg.push_str(&format!("Backtrace:\n{}\n", self.pretty_backtrace()));
+        match PathDumpType::get_from_env_var() {
+            PathDumpType::None => {
+                err_msg.push_str("note: For a dump of the path that led to this error, rerun with the environment variable `HAYBALE_DUMP_PATH` set to:\n");
+                err_msg
+                    .push_str("        `LLVM` for a list of the LLVM basic blocks in the path\n");
+                err_msg.push_str(
+                    "        `SRC` for a list of the source-language locations in the path\n",
+                );
+                err_msg.push_str("        `BOTH` for both of the above\n");
+                err_msg.push_str(
+                    "      To get source-language locations, the LLVM bitcode must also contain\n",
+                );
+                err_msg.push_str("      debuginfo. For example, C/C++ or Rust sources must be compiled with the\n");
+                err_msg.push_str("      `-g` flag to `clang`, `clang++`, or `rustc`.\n");
+            },
+            PathDumpType::LLVM => {
+                err_msg.push_str("LLVM path to error:\n");
+                err_msg.push_str(&self.pretty_path_llvm());
+                err_msg.push_str("note: to also get a dump of the source-language locations in this path, rerun with `HAYBALE_DUMP_PATH=BOTH`.\n");
+            },
+            PathDumpType::Source => {
+                err_msg.push_str("Source-language path to error:\n");
+                err_msg.push_str(&self.pretty_path_source());
+                err_msg.push_str("note: to also get a dump of the LLVM basic blocks in this path, rerun with `HAYBALE_DUMP_PATH=BOTH`.\n");
+            },
+            PathDumpType::Interleaved => {
+                err_msg.push_str("Full path to error:\n");
+                err_msg.push_str(&self.pretty_path_interleaved());
+            },
+        }
+        if std::env::var("HAYBALE_DUMP_VARS") == Ok("1".to_owned()) {
+            err_msg
+                .push_str("\nLatest values of variables at time of error, in current function:\n");
+            err_msg.push_str("(Ignore any values from past the point of error, they may be from other paths)\n\n");
+            for (varname, value) in self.all_vars_in_cur_fn() {
+                err_msg.push_str(&format!("  {}: {:?}\n", varname, value));
+            }
+        } else {
+            err_msg.push_str("\nnote: For a dump of variable values at time of error, rerun with `HAYBALE_DUMP_VARS=1` environment variable.\n");
+        }
+        err_msg.push_str("\nnote: to enable detailed logs, ensure that debug-level logging messages are visible.\n");
+        err_msg.push_str("  See the documentation for your chosen logging backend (e.g., env_logger, log4rs, etc)\n");
+        err_msg.push_str("  for help with configuration.\n");
+        err_msg.push_str("\n");
+        err_msg
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum PathDumpType {
+    /// Don't dump the path
+    None,
+    /// Dump just the LLVM path
+    LLVM,
+    /// Dump just the source path
+    Source,
+    /// Dump both LLVM and source path (interleaved)
+    Interleaved,
+}
+
+impl PathDumpType {
+    fn get_from_env_var() -> Self {
+        match std::env::var("HAYBALE_DUMP_PATH") {
+            Err(_) => Self::None,
+            Ok(mut val) => {
+                val.make_ascii_uppercase();
+                match val.deref() {
+                    "" => Self::None,
+                    "LLVM" => Self::LLVM,
+                    "SRC" => Self::Source,
+                    "BOTH" => Self::Interleaved,
+                    "1" => Self::Interleaved, // previous versions of `haybale` used HAYBALE_DUMP_PATH=1, we now treat that equivalently to `BOTH`
+                    _ => Self::Interleaved,
+                }
+            },
+        }
+    }
+}
+
+/// Does `text` contain `symbol` as a complete symbol (not just as a prefix of
+/// another symbol)?
+fn mentions_symbol(text: &str, symbol: &str) -> bool {
+    text.match_indices(symbol).any(
+        |(idx, _)| match text[idx + symbol.len() ..].chars().next() {
+            None => true,
+            Some(c) => !(c.is_alphanumeric() || c == '_'),
+        },
+    )
+}
+
+/// Get the text Boolector prints for `bv`, or `None` if `bv` is too large for
+/// it to print
+fn dump_of<V: BV>(bv: &V) -> Option<String> {
+    let dump = format!("{:?}", bv);
+    // placeholder which Boolector uses for too-large BVs
+    if dump == "<output too large to display>" {
+        None
+    } else {
+        Some(dump)
+    }
+}
+
+/// Does the printed form of `bv` mention any of the `symbols` (see
+/// `mentions_symbol()`)? A `BV` too large to print conservatively counts as
+/// mentioning them.
+fn dump_mentions<V: BV, S: AsRef<str>>(bv: &V, symbols: impl IntoIterator<Item = S>) -> bool {
+    match dump_of(bv) {
+        Some(dump) => symbols
+            .into_iter()
+            .any(|symbol| mentions_symbol(&dump, symbol.as_ref())),
+        None => true,
+    }
+}
+
+/// Reduce the shift amount `amount` modulo its width, which is also the width
+/// of the value being shifted, for `ShiftSemantics::ModularReduce`
+pub(crate) fn reduce_shift_amount<V: BV>(amount: &V) -> V {
+    let width = amount.get_width();
+    amount.urem(&V::from_u32(amount.get_solver(), width, width))
+}
+
+/// Get the number of exponent bits and the number of (explicitly stored)
+/// mantissa bits of the IEEE floating-point format of the given width
+fn float_format(width: u32, caller: &str) -> Result<(u32, u32)> {
+    match width {
+        16 => Ok((5, 10)),
+        32 => Ok((8, 23)),
+        64 => Ok((11, 52)),
+        _ => Err(Error::OtherError(format!(
+            "{}: expected a floating-point value of width 16, 32, or 64, but got width {}",
+            caller, width
+        ))),
+    }
+}
+
+/// Get a `BV` (of width 1) which is true if `x`, the bit pattern of a
+/// floating-point value with `mantissa_bits` mantissa bits, is a NaN
+fn float_is_nan<V: BV>(x: &V, mantissa_bits: u32) -> V {
+    let width = x.get_width();
+    let exponent_all_ones = x.slice(width - 2, mantissa_bits).redand();
+    let mantissa_nonzero = x.slice(mantissa_bits - 1, 0).redor();
+    exponent_all_ones.and(&mantissa_nonzero)
+}
+
+/// Get a `BV` (of width 1) which is true if `x`, the bit pattern of a
+/// floating-point value with `mantissa_bits` mantissa bits, is an infinity
+/// (of either sign)
+fn float_is_infinite<V: BV>(x: &V, mantissa_bits: u32) -> V {
+    let width = x.get_width();
+    let exponent_all_ones = x.slice(width - 2, mantissa_bits).redand();
+    let mantissa_zero = x.slice(mantissa_bits - 1, 0).redor().not();
+    exponent_all_ones.and(&mantissa_zero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemOpKind;
+    use crate::solver_utils::SolutionCount;
+    use crate::test_utils::*;
+
+    // we don't include tests here for Memory, Alloc, VarMap, or Watchpoints; those are tested in their own modules.
+    // Instead, here we just test the nontrivial functionality that `State` has itself.
+    // We do repeat many of the tests from the `solver_utils` module, making sure that they also pass when
+    // we use the `State` interfaces.
+
+    #[test]
+    fn sat() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // empty state should be sat
+        assert_eq!(state.sat(), Ok(true));
+
+        // adding True constraint should still be sat
+        state.bv_from_bool(true).assert();
+        assert_eq!(state.sat(), Ok(true));
+
+        // adding x > 0 constraint should still be sat
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        x.sgt(&state.zero(64)).assert();
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_solver() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // build a `BV` with the solver, without involving the `State` itself
+        fn make_bv_greater_than_5(
+            solver: <BtorBackend as Backend>::SolverRef,
+        ) -> <BtorBackend as Backend>::BV {
+            let bv = boolector::BV::new(solver.clone(), 64, Some("x"));
+            bv.ugt(&boolector::BV::from_u64(solver, 5, 64)).assert();
+            bv
+        }
+        let x = make_bv_greater_than_5(state.get_solver());
+
+        // use that `BV` in the `State`
+        state.assign_bv_to_name(Name::from("x"), x.clone())?;
+        assert_eq!(state.sat(), Ok(true));
+        assert!(
+            state
+                .get_a_solution_for_bv(&x)?
+                .expect("Expected a solution for x")
+                .as_u64()
+                .unwrap()
+                > 5
+        );
+
+        // the constraint added via the returned solver is in effect in the `State`
+        assert_eq!(
+            state.sat_with_extra_constraints(std::iter::once(&x.ult(&state.bv_from_u64(5, 64)))),
+            Ok(false)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsat() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        // adding False constraint should be unsat
+        state.bv_from_bool(false).assert();
+        assert_eq!(state.sat(), Ok(false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn unsat_with_extra_constraints() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // adding x > 3 constraint should still be sat
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        x.ugt(&state.bv_from_u64(3, 64)).assert();
+        assert_eq!(state.sat(), Ok(true));
+
+        // adding x < 3 constraint should make us unsat
+        let bad_constraint = x.ult(&state.bv_from_u64(3, 64));
+        assert_eq!(
+            state.sat_with_extra_constraints(std::iter::once(&bad_constraint)),
+            Ok(false)
+        );
+
+        // the state itself should still be sat, extra constraints weren't permanently added
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn memory_overlay() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // allocate a byte and initialize it to 0
+        let addr = state.allocate(8_u64).into_bv();
+        let addr_u64 = addr.as_u64().expect("Expected a concrete address");
+        state.write(&addr, state.zero(8))?;
+
+        // constraint that the byte (as read from memory) is 0x42
+        let byte_is_42 = |state: &State<BtorBackend>| -> Result<Vec<_>> {
+            let read = state.read(&state.bv_from_u64(addr_u64, 64), 8)?;
+            Ok(vec![read._eq(&state.bv_from_u32(0x42, 8))])
+        };
+
+        // without an overlay, that constraint is unsat
+        assert_eq!(state.check_with_memory_overlay(&[], byte_is_42), Ok(false));
+
+        // with an overlay writing 0x42 to the byte, it becomes sat
+        assert_eq!(
+            state.check_with_memory_overlay(&[(addr_u64, 0x42)], byte_is_42),
+            Ok(true)
+        );
+
+        // afterwards, memory should be restored: the byte must still be 0
+        let read = state.read(&addr, 8)?;
+        assert_eq!(state.bvs_must_be_equal(&read, &state.zero(8)), Ok(true));
+
+        // and the state itself should still be sat
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn implies() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let a = state.new_bv_with_name(Name::from("a"), 1)?;
+        let b = state.new_bv_with_name(Name::from("b"), 1)?;
+
+        // assuming a => b along with a && !b is unsat, but doesn't affect the state
+        a.and(&b.not()).assert();
+        assert_eq!(state.assume_implies(&a, &b), Ok(false));
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_implies() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let a = state.new_bv_with_name(Name::from("a"), 1)?;
+        let b = state.new_bv_with_name(Name::from("b"), 1)?;
+
+        // with just a => b, b can still be false
+        state.assert_implies(&a, &b)?;
+        assert_eq!(
+            state.bvs_can_be_equal(&b, &state.bv_from_bool(false)),
+            Ok(true)
+        );
+
+        // but once we also assert a, b must be true
+        a.assert();
+        assert_eq!(
+            state.bvs_must_be_equal(&b, &state.bv_from_bool(true)),
+            Ok(true)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn quantified_constraints() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // an array of 256 symbolic bytes, every one of which is nonzero
+        let arr = state.allocate_symbolic(256 * 8_u64)?;
+        let elem = |state: &State<'_, BtorBackend>, idx: &<BtorBackend as Backend>::BV| {
+            state.read(&arr.add(&idx.zext(56)), 8)
+        };
+        state.assert_forall(&[8], |vars| Ok(elem(&state, &vars[0])?._ne(&state.zero(8))))?;
+        assert_eq!(state.sat(), Ok(true));
+        let some_elem = elem(&state, &state.bv_from_u32(37, 8))?;
+        assert!(!state.bvs_can_be_equal(&some_elem, &state.zero(8))?);
+
+        // if all but one element of a 16-element array are 0, and some element is
+        // 42, then it must be that one
+        let arr = state.allocate_symbolic(16 * 8_u64)?;
+        let elem = |state: &State<'_, BtorBackend>, idx: u32| {
+            state.read(&arr.add(&state.bv_from_u32(idx, 64)), 8)
+        };
+        for idx in (0 .. 16).filter(|&idx| idx != 3) {
+            state.assert(&elem(&state, idx)?._eq(&state.zero(8)))?;
+        }
+        state.assert_exists(&[4], |vars| {
+            let val = state.read(&arr.add(&vars[0].zext(60)), 8)?;
+            Ok(val._eq(&state.bv_from_u32(42, 8)))
+        })?;
+        assert_eq!(state.sat(), Ok(true));
+        assert_eq!(
+            state
+                .get_a_solution_for_bv(&elem(&state, 3)?)?
+                .and_then(|s| s.as_u64()),
+            Some(42)
+        );
+
+        // bound variables which are too wide should be rejected
+        match state.assert_forall(&[8, 16], |_| Ok(state.bv_from_bool(true))) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        // as should bound variables of width 0
+        match state.assert_exists(&[4, 0], |_| Ok(state.bv_from_bool(true))) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn alignment() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // an unconstrained pointer may be misaligned
+        let ptr = state.new_bv_with_name(Name::from("ptr"), 64)?;
+        assert_eq!(state.check_aligned(&ptr, 8), Ok(true));
+
+        // after asserting 8-byte alignment, it must be aligned (to 8, and also to 4)
+        state.assert_aligned(&ptr, 8)?;
+        assert_eq!(state.check_aligned(&ptr, 8), Ok(false));
+        assert_eq!(state.check_aligned(&ptr, 4), Ok(false));
+
+        // but `ptr + 4` is still 4-aligned, and is definitely not 8-aligned
+        let offset_ptr = ptr.add(&state.bv_from_u64(4, 64));
+        assert_eq!(state.check_aligned(&offset_ptr, 4), Ok(false));
+        assert_eq!(state.check_aligned(&offset_ptr, 8), Ok(true));
+        assert_eq!(
+            state.sat_with_extra_constraints(std::iter::once(
+                &offset_ptr.slice(2, 0)._eq(&state.zero(3))
+            )),
+            Ok(false)
+        );
+
+        // alignments which aren't a power of two should be rejected
+        match state.check_aligned(&ptr, 6) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+        match state.assert_aligned(&ptr, 0) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        // the state itself should still be sat
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn in_set() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 32)?;
+        assert_eq!(state.check_in_set(&x, &[3]), Ok(true));
+        state.assert_in_set(&x, &[1, 2, 4])?;
+
+        // now 2 is feasible, but 3 isn't
+        assert!(state.bvs_can_be_equal(&x, &state.bv_from_u32(2, 32))?);
+        assert!(!state.bvs_can_be_equal(&x, &state.bv_from_u32(3, 32))?);
+        assert_eq!(state.check_in_set(&x, &[3, 5]), Ok(false));
+        assert_eq!(state.check_in_set(&x, &[3, 4]), Ok(true));
+        assert_eq!(state.check_in_set(&x, &[]), Ok(false));
+
+        // the state itself should still be sat
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_ordered() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let p = state.new_bv_with_name(Name::from("p"), 64)?;
+        let q = state.new_bv_with_name(Name::from("q"), 64)?;
+        let r = state.new_bv_with_name(Name::from("r"), 64)?;
+        state.assert_ordered(&[&p, &q, &r], true)?;
+        assert_eq!(state.sat(), Ok(true));
+
+        // any other order is now infeasible
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&r.ult(&q)))?);
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&q.ult(&p)))?);
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&r.ulte(&p)))?);
+        // and the ordering is strict
+        assert!(!state.bvs_can_be_equal(&p, &q)?);
+
+        // non-strict ordering allows equal pointers
+        let s = state.new_bv_with_name(Name::from("s"), 64)?;
+        state.assert_ordered(&[&r, &s], false)?;
+        assert!(state.bvs_can_be_equal(&r, &s)?);
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&s.ult(&r)))?);
+
+        // pointers of different widths are rejected
+        let narrow = state.new_bv_with_name(Name::from("narrow"), 32)?;
+        match state.assert_ordered(&[&p, &narrow], true) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn assume_points_into() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let base = state.allocate(64u64).into_bv().as_u64().unwrap();
+        let ptr = state.new_bv_with_name(Name::from("ptr"), 64)?;
+        state.assume_points_into(&ptr, base, 8)?;
+
+        // the pointer can point to the first or last byte of the region
+        let points_to = |addr: u64| ptr._eq(&state.bv_from_u64(addr, 64));
+        assert_eq!(
+            state.sat_with_extra_constraints(std::iter::once(&points_to(base))),
+            Ok(true)
+        );
+        assert_eq!(
+            state.sat_with_extra_constraints(std::iter::once(&points_to(base + 7))),
+            Ok(true)
+        );
+
+        // but not just past it, or just before it
+        assert_eq!(
+            state.sat_with_extra_constraints(std::iter::once(&points_to(base + 8))),
+            Ok(false)
+        );
+        assert_eq!(
+            state.sat_with_extra_constraints(std::iter::once(&points_to(base - 1))),
+            Ok(false)
+        );
+
+        // empty allocations should be rejected
+        match state.assume_points_into(&ptr, base, 0) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        // the state itself should still be sat
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn get_a_solution() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // add x > 3 constraint
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        x.ugt(&state.bv_from_u64(3, 64)).assert();
+
+        // check that the computed value of x is > 3
+        let x_value = state
+            .get_a_solution_for_bv(&x)
+            .unwrap()
+            .expect("Expected a solution for x")
+            .as_u64()
+            .unwrap();
+        assert!(x_value > 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn possible_solutions() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // add x > 3 constraint
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        x.ugt(&state.bv_from_u64(3, 64)).assert();
+
+        // check that there are more than 2 solutions
+        let num_solutions = state.get_possible_solutions_for_bv(&x, 2).unwrap().count();
+        assert_eq!(num_solutions, SolutionCount::AtLeast(3));
+
+        // add x < 6 constraint
+        x.ult(&state.bv_from_u64(6, 64)).assert();
+
+        // check that there are now exactly two solutions
+        let solutions = state
+            .get_possible_solutions_for_bv(&x, 2)
+            .unwrap()
+            .as_u64_solutions();
+        assert_eq!(
+            solutions,
+            Some(PossibleSolutions::Exactly(vec![4, 5].into_iter().collect()))
+        );
+
+        // add x < 5 constraint
+        x.ult(&state.bv_from_u64(5, 64)).assert();
+
+        // check that there is now exactly one solution
+        let solutions = state
+            .get_possible_solutions_for_bv(&x, 2)
+            .unwrap()
+            .as_u64_solutions();
+        assert_eq!(
+            solutions,
+            Some(PossibleSolutions::Exactly(std::iter::once(4).collect()))
+        );
+
+        // add x < 3 constraint
+        x.ult(&state.bv_from_u64(3, 64)).assert();
+
+        // check that there are now no solutions
+        let solutions = state
+            .get_possible_solutions_for_bv(&x, 2)
+            .unwrap()
+            .as_u64_solutions();
+        assert_eq!(solutions, Some(PossibleSolutions::Exactly(HashSet::new())));
+
+        Ok(())
+    }
+
+    #[test]
+    fn lookup_vars_via_operand() {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // create llvm-ir names
+        let name1 = Name::from("val");
+        let name2 = Name::from(2);
+
+        // create corresponding BV values
+        let var1 = state.new_bv_with_name(name1.clone(), 64).unwrap();
+        let var2 = state.new_bv_with_name(name2.clone(), 1).unwrap(); // these clone()s wouldn't normally be necessary but we want to reuse the names to create `Operand`s later
+
+        // check that we can look up the correct BV values via LocalOperands
+        let op1 = Operand::LocalOperand {
+            name: name1,
+            ty: Type::i32(),
+        };
+        let op2 = Operand::LocalOperand {
+            name: name2,
+            ty: Type::bool(),
+        };
+        assert_eq!(state.operand_to_bv(&op1), Ok(var1));
+        assert_eq!(state.operand_to_bv(&op2), Ok(var2));
+    }
+
+    #[test]
+    fn const_bv() {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        // create an llvm-ir value which is constant 3
+        let constint = Constant::Int { bits: 64, value: 3 };
+
+        // this should create a corresponding BV value which is also constant 3
+        let bv = state
+            .operand_to_bv(&Operand::ConstantOperand(constint))
+            .unwrap();
+
+        // check that the BV value was evaluated to 3
+        let solution = state
+            .get_a_solution_for_bv(&bv)
+            .unwrap()
+            .expect("Expected a solution for the bv")
+            .as_u64()
+            .unwrap();
+        assert_eq!(solution, 3);
+    }
+
+    #[test]
+    fn const_arithmetic() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        let int8 = |value| Constant::Int { bits: 8, value };
+        let add = Constant::Add(Box::new(constant::Add {
+            operand0: int8(100),
+            operand1: int8(27),
+        }));
+        let bv = state.const_to_bv(&add)?;
+        assert_eq!(bv.get_width(), 8);
+        assert_eq!(bv.as_u64(), Some(127));
+
+        // arithmetic wraps at the declared width
+        let add = Constant::Add(Box::new(constant::Add {
+            operand0: int8(200),
+            operand1: int8(100),
+        }));
+        assert_eq!(state.const_to_bv(&add)?.as_u64(), Some(44));
+        let sub = Constant::Sub(Box::new(constant::Sub {
+            operand0: int8(1),
+            operand1: int8(2),
+        }));
+        assert_eq!(state.const_to_bv(&sub)?.as_u64(), Some(0xff));
+
+        // constant expressions nest
+        let mul = Constant::Mul(Box::new(constant::Mul {
+            operand0: add,
+            operand1: int8(3),
+        }));
+        let xor = Constant::Xor(Box::new(constant::Xor {
+            operand0: mul,
+            operand1: int8(0x0f),
+        }));
+        assert_eq!(state.const_to_bv(&xor)?.as_u64(), Some((44 * 3) ^ 0x0f));
+
+        Ok(())
+    }
+
+    #[test]
+    fn const_null() {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        // create an llvm-ir null pointer constant
+        let constnull = Constant::Null(Type::pointer_to(Type::i32()));
+
+        // this should create a BV which is a pointer-sized constant 0
+        let bv = state
+            .operand_to_bv(&Operand::ConstantOperand(constnull))
+            .unwrap();
+        assert_eq!(bv.get_width(), POINTER_SIZE_BITS as u32);
+        assert_eq!(bv.as_u64(), Some(0));
+    }
+
+    #[test]
+    fn const_aggregate_zero() {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        // create an llvm-ir zeroinitializer of type { i32, i32 }
+        let constzero = Constant::AggregateZero(Type::StructType {
+            element_types: vec![Type::i32(), Type::i32()],
+            is_packed: false,
+        });
+
+        // this should create a BV which is a 64-bit constant 0
+        let bv = state
+            .operand_to_bv(&Operand::ConstantOperand(constzero))
+            .unwrap();
+        assert_eq!(bv.get_width(), 64);
+        assert_eq!(bv.as_u64(), Some(0));
+    }
+
+    #[test]
+    fn unsupported_report() {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+        assert!(state.unsupported_report().is_empty());
+
+        // floating-point constants aren't supported; encounter one twice
+        let constfloat = Operand::ConstantOperand(Constant::Float(constant::Float::Single(1.0)));
+        match state.operand_to_bv(&constfloat) {
+            Err(Error::UnsupportedInstruction(_)) => {},
+            r => panic!("Expected UnsupportedInstruction, got {:?}", r),
+        }
+        assert!(state.operand_to_bv(&constfloat).is_err());
+
+        // metadata operands aren't supported either
+        match state.operand_to_bv(&Operand::MetadataOperand) {
+            Err(Error::UnsupportedInstruction(_)) => {},
+            r => panic!("Expected UnsupportedInstruction, got {:?}", r),
+        }
+
+        assert_eq!(
+            state.unsupported_report(),
+            vec![
+                UnsupportedItem {
+                    kind: "constant Float".to_owned(),
+                    count: 2,
+                },
+                UnsupportedItem {
+                    kind: "operand MetadataOperand".to_owned(),
+                    count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn array_bounds() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // allocate an array of 32-bit elements with a symbolic count of at most 4
+        let n = state.new_bv_with_name(Name::from("n"), 64)?;
+        n.ulte(&state.bv_from_u64(4, 64)).assert();
+        let (arr, count) = state.allocate_array(32, &n)?;
+        assert_eq!(
+            state.get_allocation_size(&arr)?,
+            Some(32 * state.config.max_symbolic_array_count)
+        );
+
+        // accessing index `count` is provably out of bounds
+        let four = state.bv_from_u64(4, 64);
+        let ptr_at_count = arr.add(&count.mul(&four));
+        assert!(!state.check_in_bounds(&ptr_at_count, 32)?);
+
+        // but accessing index `count - 1` may be in bounds
+        let one = state.one(64);
+        let ptr_at_last = arr.add(&count.sub(&one).mul(&four));
+        assert!(state.check_in_bounds(&ptr_at_last, 32)?);
+
+        // and accessing index 4 is out of bounds even though we reserved space for it
+        let ptr_at_four = arr.add(&state.bv_from_u64(16, 64));
+        assert!(!state.check_in_bounds(&ptr_at_four, 32)?);
+
+        // a count narrower than 64 bits is still constrained to the reserved space
+        state.config.max_symbolic_array_count = 100;
+        let m = state.new_bv_with_name(Name::from("m"), 8)?;
+        state.allocate_array(8, &m)?;
+        assert!(!state
+            .sat_with_extra_constraints(std::iter::once(&m.ugt(&state.bv_from_u64(100, 8))))?);
+
+        // and a reservation too large to represent is an error, which
+        // doesn't constrain the count
+        state.config.max_symbolic_array_count = u64::MAX / 2;
+        let k = state.new_bv_with_name(Name::from("k"), 64)?;
+        match state.allocate_array(32, &k) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+        assert!(state.sat_with_extra_constraints(std::iter::once(
+            &k.ugt(&state.bv_from_u64(u64::MAX / 2, 64))
+        ))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn gep_inbounds() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // `arr` is a `[4 x i32]*`, and `idx` is a symbolic `i64`
+        let arr = state.allocate(128_u64).into_bv();
+        state.assign_bv_to_name(Name::from("arr"), arr.clone())?;
+        let idx = state.new_bv_with_name(Name::from("idx"), 64)?;
+        let address = Operand::LocalOperand {
+            name: Name::from("arr"),
+            ty: Type::pointer_to(Type::ArrayType {
+                element_type: Box::new(Type::i32()),
+                num_elements: 4,
+            }),
+        };
+        let indices = vec![
+            Operand::ConstantOperand(Constant::Int { bits: 64, value: 0 }),
+            Operand::LocalOperand {
+                name: Name::from("idx"),
+                ty: Type::i64(),
+            },
+        ];
+
+        // without `inbounds`, we just get the address
+        let (result, poison) = state.gep(&address, &indices, false)?;
+        assert!(poison.is_none());
+        let expected = arr.add(&idx.mul(&state.bv_from_u64(4, 64)));
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&result._ne(&expected)))?);
+
+        // with `inbounds`, the result can be poison for large `idx`
+        let (_, poison) = state.gep(&address, &indices, true)?;
+        let poison = poison.expect("Expected a poison condition for an inbounds GEP");
+        assert!(state.sat_with_extra_constraints(std::iter::once(&poison))?);
+
+        // but pointing one past the end is fine
+        idx.ulte(&state.bv_from_u64(4, 64)).assert();
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&poison))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn assume_precondition() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let a = state.new_bv_with_name(Name::from("a"), 64)?;
+        let b = state.new_bv_with_name(Name::from("b"), 64)?;
+        let ten = state.bv_from_u64(10, 64);
+        state.assume_precondition(&[&Name::from("a"), &Name::from("b")], |params| {
+            params[0].add(&params[1])._eq(&ten)
+        })?;
+
+        let a_sol = state
+            .get_a_solution_for_bv(&a)?
+            .expect("Expected a solution")
+            .as_u64()
+            .unwrap();
+        let b_sol = state
+            .get_a_solution_for_bv(&b)?
+            .expect("Expected a solution")
+            .as_u64()
+            .unwrap();
+        assert_eq!(a_sol.wrapping_add(b_sol), 10);
+
+        // unknown variables are an error
+        assert!(state
+            .assume_precondition(&[&Name::from("c")], |params| params[0]._eq(&ten))
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn tagged_pointers() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // a symbolic 16-aligned pointer
+        let ptr = state.new_bv_with_name(Name::from("ptr"), 64)?;
+        state.assert_aligned(&ptr, 16)?;
+
+        let tagged = state.ptr_tag(&ptr, 3, 4)?;
+        let tag = state.ptr_mask(&tagged, 0xf);
+        assert!(state.bvs_must_be_equal(&tag, &state.bv_from_u64(3, 64))?);
+        let untagged = state.ptr_untag(&tagged, 4)?;
+        assert!(state.bvs_must_be_equal(&untagged, &ptr)?);
+        assert!(!state.bvs_can_be_equal(&tagged, &ptr)?);
+
+        // retagging replaces the old tag
+        let retagged = state.ptr_tag(&tagged, 5, 4)?;
+        assert!(
+            state.bvs_must_be_equal(&state.ptr_mask(&retagged, 0xf), &state.bv_from_u64(5, 64))?
+        );
+
+        // tags which don't fit are an error
+        assert!(state.ptr_tag(&ptr, 16, 4).is_err());
+        assert!(state.ptr_untag(&ptr, 0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn coverage() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("bb1"), Name::from("bb2")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let t = state.bv_from_bool(true);
+        state.save_backtracking_point(&Name::from("bb2"), t)?;
+        state.mark_block_visited("test_func", &Name::from("bb1"));
+        assert!(state.revert_to_backtracking_point()?);
+        state.mark_block_visited("test_func", &Name::from("bb2"));
+
+        // coverage from before the backtrack is still there
+        let coverage = state.coverage();
+        assert!(coverage.is_visited("test_func", &Name::from("bb1")));
+        assert!(coverage.is_visited("test_func", &Name::from("bb2")));
+        assert!(!coverage.is_visited("other_func", &Name::from("bb1")));
+        assert_eq!(coverage.num_visited(), 2);
+        assert_eq!(coverage.visited_blocks("test_func").count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn shift_semantics() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // shift 1 (a 32-bit value) by a symbolic amount which may exceed 31
+        let one = state.one(32);
+        let amount = state.new_bv_with_name(Name::from("amount"), 32)?;
+        let amount_is_33 = amount._eq(&state.bv_from_u32(33, 32));
+
+        // under `Poison`, the shift is poison exactly when the amount is out of range
+        let (result, poison) = state.shl(&one, &amount)?;
+        let poison = poison.expect("Expected a poison condition under ShiftSemantics::Poison");
+        assert!(state.sat_with_extra_constraints(&[amount_is_33.clone(), poison.clone()])?);
+        let amount_is_3 = amount._eq(&state.bv_from_u32(3, 32));
+        assert!(!state.sat_with_extra_constraints(&[amount_is_3.clone(), poison.clone()])?);
+        let result_is_8 = result._eq(&state.bv_from_u32(8, 32));
+        assert!(!state.sat_with_extra_constraints(&[amount_is_3, result_is_8.not()])?);
+        let (_, poison) = state.lshr(&one, &amount, false)?;
+        assert!(state.sat_with_extra_constraints(&[amount_is_33.clone(), poison.unwrap()])?);
+
+        // under `ModularReduce`, shifting by 33 is the same as shifting by 1, and never poison
+        state.config.shift_semantics = ShiftSemantics::ModularReduce;
+        let (result, poison) = state.shl(&one, &amount)?;
+        assert!(poison.is_none());
+        let result_is_2 = result._eq(&state.bv_from_u32(2, 32));
+        assert!(!state.sat_with_extra_constraints(&[amount_is_33.clone(), result_is_2.not()])?);
+        let minus_eight = state.bv_from_i32(-8, 32);
+        let (result, _) = state.ashr(&minus_eight, &amount, false)?;
+        let result_is_minus_four = result._eq(&state.bv_from_i32(-4, 32));
+        assert!(!state.sat_with_extra_constraints(&[amount_is_33, result_is_minus_four.not()])?);
+
+        // mismatched widths are an error
+        assert!(state.shl(&one, &state.one(8)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn exact_flags() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        let one = state.one(8);
+        let must_be = |poison: Option<<BtorBackend as Backend>::BV>, value: bool| {
+            let poison = poison.expect("Expected a poison condition");
+            state.bvs_must_be_equal(&poison, &state.bv_from_bool(value))
+        };
+
+        // exact `lshr` of 0x06 by 1 loses no bits, but of 0x07 by 1 loses one
+        let (result, poison) = state.lshr(&state.bv_from_u32(0x06, 8), &one, true)?;
+        assert!(must_be(poison, false)?);
+        assert!(state.bvs_must_be_equal(&result, &state.bv_from_u32(0x03, 8))?);
+        let (_, poison) = state.lshr(&state.bv_from_u32(0x07, 8), &one, true)?;
+        assert!(must_be(poison, true)?);
+        // without `exact`, only the shift amount matters
+        let (_, poison) = state.lshr(&state.bv_from_u32(0x07, 8), &one, false)?;
+        assert!(must_be(poison, false)?);
+
+        // same for `ashr`, including for negative values
+        let (_, poison) = state.ashr(&state.bv_from_i32(-8, 8), &state.bv_from_u32(3, 8), true)?;
+        assert!(must_be(poison, false)?);
+        let (_, poison) = state.ashr(&state.bv_from_i32(-7, 8), &one, true)?;
+        assert!(must_be(poison, true)?);
+
+        // exact division is poison when there is a remainder
+        let three = state.bv_from_u32(3, 8);
+        let (result, poison) = state.udiv(&state.bv_from_u32(6, 8), &three, true)?;
+        assert!(must_be(poison, false)?);
+        assert!(state.bvs_must_be_equal(&result, &state.bv_from_u32(2, 8))?);
+        let (_, poison) = state.udiv(&state.bv_from_u32(7, 8), &three, true)?;
+        assert!(must_be(poison, true)?);
+        let (_, poison) = state.udiv(&state.bv_from_u32(7, 8), &three, false)?;
+        assert!(poison.is_none());
+        let (result, poison) = state.sdiv(&state.bv_from_i32(-6, 8), &three, true)?;
+        assert!(must_be(poison, false)?);
+        assert!(state.bvs_must_be_equal(&result, &state.bv_from_i32(-2, 8))?);
+        let (_, poison) = state.sdiv(&state.bv_from_i32(-7, 8), &three, true)?;
+        assert!(must_be(poison, true)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_checked() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.assert_checked(&x.ugt(&state.bv_from_u64(5, 64)))?;
+        assert_eq!(
+            state.assert_checked(&x.ult(&state.bv_from_u64(3, 64))),
+            Err(Error::Infeasible)
+        );
+
+        // the contradicting constraint wasn't added
+        assert!(state.sat()?);
+        assert_eq!(state.constraints_mentioning(&Name::from("x")).len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn atomic_rmw() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let addr = state.allocate(32_u64).into_bv();
+        state.write(&addr, state.bv_from_u32(5, 32))?;
+        let old = state.atomic_rmw(AtomicOp::Add, &addr, &state.bv_from_u32(3, 32), 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&old)?.unwrap().as_u64(),
+            Some(5)
+        );
+        let new = state.read(&addr, 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&new)?.unwrap().as_u64(),
+            Some(8)
+        );
+
+        // signed min of 8 and -1 is -1; unsigned max of -1 and 2 is still -1
+        let minus_one = state.bv_from_i32(-1, 32);
+        state.atomic_rmw(AtomicOp::Min, &addr, &minus_one, 32)?;
+        let old = state.atomic_rmw(AtomicOp::UMax, &addr, &state.bv_from_u32(2, 32), 32)?;
+        assert!(state.bvs_must_be_equal(&old, &minus_one)?);
+        let new = state.read(&addr, 32)?;
+        assert!(state.bvs_must_be_equal(&new, &minus_one)?);
+
+        // the operand must have the given width
+        assert!(state
+            .atomic_rmw(AtomicOp::Xchg, &addr, &state.one(8), 32)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn individual_bits() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        let eight = state.bv_from_u32(0x08, 8);
+        assert_eq!(state.extract_bit(&eight, 3)?.as_bool(), Some(true));
+        assert_eq!(state.extract_bit(&eight, 2)?.as_bool(), Some(false));
+
+        let zero = state.zero(8);
+        let set = state.set_bit(&zero, 0, &state.bv_from_bool(true))?;
+        assert_eq!(set.as_u64(), Some(0x01));
+        let set = state.set_bit(&zero, 7, &state.bv_from_bool(true))?;
+        assert_eq!(set.as_u64(), Some(0x80));
+        let cleared = state.set_bit(&state.ones(8), 4, &state.bv_from_bool(false))?;
+        assert_eq!(cleared.as_u64(), Some(0xef));
+
+        // out-of-range indices are errors
+        assert!(state.extract_bit(&eight, 8).is_err());
+        assert!(state.set_bit(&eight, 8, &state.bv_from_bool(true)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn varargs() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let args = [
+            state.bv_from_u32(1, 32),
+            state.bv_from_u64(2, 64),
+            state.bv_from_u32(3, 8),
+        ];
+        state.set_varargs(&args);
+        assert_eq!(state.va_arg(32).and_then(|bv| bv.as_u64()), Some(1));
+        assert_eq!(state.va_arg(64).and_then(|bv| bv.as_u64()), Some(2));
+        // a narrower argument is zero-extended to the requested width
+        let third = state.va_arg(32).expect("Expected a third vararg");
+        assert_eq!(third.get_width(), 32);
+        assert_eq!(third.as_u64(), Some(3));
+        assert!(state.va_arg(32).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn eval_in_last_model() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // models aren't recorded until model capture is enabled
+        let x = state.new_bv_with_name(Name::from("x"), 8)?;
+        let x_plus_one = x.add(&state.one(8));
+        assert!(state.get_a_solution_for_bv(&x)?.is_some());
+        assert_eq!(state.eval_in_last_model(&x_plus_one), None);
+        state.enable_model_capture();
+        assert_eq!(state.eval_in_last_model(&x_plus_one), None); // no model yet
+        assert_eq!(state.eval_in_last_model(&state.bv_from_u32(3, 8)), Some(3));
+
+        state.assert(&x.ugt(&state.bv_from_u32(3, 8)))?;
+        let x_value = state
+            .get_a_solution_for_bv(&x)?
+            .and_then(|s| s.as_u64())
+            .unwrap();
+        assert!(x_value > 3);
+        assert_eq!(
+            state.eval_in_last_model(&x_plus_one),
+            Some((x_value + 1) & 0xff)
+        );
+        assert_eq!(
+            state.eval_in_last_model(&x.ugt(&state.bv_from_u32(3, 8))),
+            Some(1)
+        );
+
+        // a variable created since then isn't in the model
+        let y = state.new_bv_with_name(Name::from("y"), 8)?;
+        assert_eq!(state.eval_in_last_model(&x.add(&y)), None);
+        // and evaluating doesn't constrain the path
+        assert!(state
+            .sat_with_extra_constraints(std::iter::once(&x._ne(&state.bv_from_u64(x_value, 8))))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn model_diff() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let mut other = blank_state(&project, "test_func");
+
+        // `x` differs between the two states, and `y` doesn't
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        let y = state.new_bv_with_name(Name::from("y"), 8)?;
+        state.assert(&x._eq(&state.one(64)))?;
+        state.assert(&y._eq(&state.bv_from_u32(5, 8)))?;
+        let other_x = other.new_bv_with_name(Name::from("x"), 64)?;
+        let other_y = other.new_bv_with_name(Name::from("y"), 8)?;
+        other.assert(&other_x._eq(&other.bv_from_u64(2, 64)))?;
+        other.assert(&other_y._eq(&other.bv_from_u32(5, 8)))?;
+
+        let (name_x, name_y) = (Name::from("x"), Name::from("y"));
+        assert_eq!(
+            state.model_diff(&other, &[&name_x, &name_y])?,
+            vec![(Name::from("x"), 1, 2)]
+        );
+        assert_eq!(state.model_diff(&other, &[&name_y])?, vec![]);
+        assert!(state.model_diff(&other, &[&Name::from("z")]).is_err());
+
+        // an unsat state has no model
+        other.assert(&other_y._eq(&other.zero(8)))?;
+        assert_eq!(state.model_diff(&other, &[&name_x]), Err(Error::Unsat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn loop_back_edges() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("bb1"), Name::from("bb2")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.config.max_unroll = Some(3);
+
+        assert_eq!(state.on_loop_back_edge(0), LoopAction::Continue);
+        let t = state.bv_from_bool(true);
+        state.save_backtracking_point(&Name::from("bb2"), t)?;
+        assert_eq!(state.on_loop_back_edge(0), LoopAction::Continue);
+        assert_eq!(state.on_loop_back_edge(0), LoopAction::Continue);
+        assert_eq!(state.on_loop_back_edge(0), LoopAction::StopUnrolling);
+        // other loops are counted separately
+        assert_eq!(state.on_loop_back_edge(1), LoopAction::Continue);
+
+        // after backtracking, the back edge has only been taken once
+        assert!(state.revert_to_backtracking_point()?);
+        assert_eq!(state.on_loop_back_edge(0), LoopAction::Continue);
+        assert_eq!(state.on_loop_back_edge(0), LoopAction::Continue);
+        assert_eq!(state.on_loop_back_edge(0), LoopAction::StopUnrolling);
+
+        Ok(())
+    }
+
+    #[test]
+    fn with_temp_var() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let x = state.new_bv_with_name(Name::from("x"), 8)?;
+
+        let num_vars = state.with_temp_var(8, |state, temp| -> Result<usize> {
+            state.assert(&x.ugt(temp))?;
+            state.assert(&temp._eq(&state.bv_from_u32(3, 8)))?;
+            Ok(state.all_vars_in_cur_fn().count())
+        })??;
+        assert_eq!(num_vars, 2);
+
+        // the temporary's binding is gone, but its constraints remain
+        let names: Vec<Name> = state
+            .all_vars_in_cur_fn()
+            .map(|(name, _)| name.clone())
+            .collect();
+        assert_eq!(names, vec![Name::from("x")]);
+        assert_eq!(
+            state.min_possible_solution_for_irname_as_u64(
+                &"test_func".to_owned(),
+                &Name::from("x")
+            )?,
+            Some(4)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_unconstrained() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        assert!(state.is_unconstrained());
+
+        // trivially true constraints don't count
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.assert(&state.bv_from_bool(true))?;
+        assert!(state.is_unconstrained());
+
+        state.assert(&x.ugt(&state.zero(64)))?;
+        assert!(!state.is_unconstrained());
+
+        Ok(())
+    }
+
+    #[test]
+    fn handle_intrinsic() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // llvm.assume of x > 10 should constrain x
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        let cond = x.ugt(&state.bv_from_u64(10, 64));
+        assert_eq!(state.handle_intrinsic("llvm.assume", &[cond])?, None);
+        assert!(!state.bvs_can_be_equal(&x, &state.bv_from_u64(5, 64))?);
+        assert!(state.bvs_can_be_equal(&x, &state.bv_from_u64(11, 64))?);
+
+        // llvm.expect should return its first argument unchanged
+        let ret =
+            state.handle_intrinsic("llvm.expect.i64", &[x.clone(), state.bv_from_u64(12, 64)])?;
+        assert_eq!(ret, Some(x.clone()));
+
+        // llvm.lifetime is ignored
+        let ptr = state.allocate(64_u64).into_bv();
+        assert_eq!(
+            state.handle_intrinsic("llvm.lifetime.start.p0i8", &[state.bv_from_u64(8, 64), ptr])?,
+            None
+        );
+
+        // other intrinsics aren't handled here
+        assert!(state.handle_intrinsic("llvm.foo", &[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn allocate_symbolic() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let ptr = state.allocate_symbolic(16_u64)?;
+        let byte0 = state.read(&ptr, 8)?;
+        let byte1 = state.read(&ptr.add(&state.one(64)), 8)?;
+
+        // each byte can take any value, independently of the other
+        assert!(!byte0.is_const());
+        assert!(!byte1.is_const());
+        assert!(state.bvs_can_be_equal(&byte0, &state.bv_from_u32(3, 8))?);
+        state.assert(&byte0._eq(&state.bv_from_u32(3, 8)))?;
+        assert!(state.bvs_can_be_equal(&byte1, &state.bv_from_u32(200, 8))?);
+        assert!(state.bvs_can_be_equal(&byte1, &state.bv_from_u32(3, 8))?);
+        assert!(!state.bvs_must_be_equal(&byte0, &byte1)?);
+
+        // a symbolic allocation which reuses a freed address gets fresh bytes
+        state.config.reuse_freed_allocations = true;
+        state.free(&ptr)?;
+        let reused = state.allocate_symbolic(16_u64)?;
+        assert_eq!(reused.as_u64(), ptr.as_u64());
+        let new_byte0 = state.read(&reused, 8)?;
+        assert!(state.bvs_can_be_equal(&new_byte0, &state.bv_from_u32(200, 8))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_finite() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // an arbitrary bit pattern can be a NaN, until it's asserted finite
+        let f = state.new_bv_with_name(Name::from("f"), 32)?;
+        assert!(state.check_can_be_nan(&f)?);
+        state.assert_finite(&f)?;
+        assert!(!state.check_can_be_nan(&f)?);
+        let pos_inf = state.bv_from_u32(0x7f80_0000, 32);
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&f._eq(&pos_inf)))?);
+        let one = state.bv_from_u32(0x3f80_0000, 32);
+        assert!(state.sat_with_extra_constraints(std::iter::once(&f._eq(&one)))?);
+
+        // converting a large enough integer to a half-precision float gives
+        // infinity, so asserting the result is finite constrains the integer
+        let x = state.new_bv_with_name(Name::from("x"), 32)?;
+        let h = state.uitofp(&x, 16)?;
+        assert!(!state.check_can_be_nan(&h)?);
+        assert!(state
+            .sat_with_extra_constraints(std::iter::once(&x._eq(&state.bv_from_u32(70000, 32))))?);
+        state.assert_finite(&h)?;
+        assert!(!state
+            .sat_with_extra_constraints(std::iter::once(&x._eq(&state.bv_from_u32(70000, 32))))?);
+        // 65504 is the largest finite half-precision value
+        assert!(state
+            .sat_with_extra_constraints(std::iter::once(&x._eq(&state.bv_from_u32(65504, 32))))?);
+
+        // only floating-point widths are supported
+        match state.assert_finite(&state.zero(8)) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn float_approx_eq() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // 1.0f32, and the next float above it
+        let a = state.bv_from_u32(0x3f80_0000, 32);
+        let b = state.bv_from_u32(0x3f80_0001, 32);
+        assert_eq!(state.float_approx_eq(&a, &b, 1)?.as_bool(), Some(true));
+        assert_eq!(state.float_approx_eq(&a, &b, 0)?.as_bool(), Some(false));
+        assert_eq!(state.float_approx_eq(&a, &a, 0)?.as_bool(), Some(true));
+
+        // positive and negative zero are equal; the smallest positive and
+        // negative subnormals are 2 ULPs apart
+        let pos_zero = state.bv_from_u64(0x0000_0000_0000_0000, 64);
+        let neg_zero = state.bv_from_u64(0x8000_0000_0000_0000, 64);
+        assert_eq!(
+            state.float_approx_eq(&pos_zero, &neg_zero, 0)?.as_bool(),
+            Some(true)
+        );
+        let pos_min = state.bv_from_u64(0x0000_0000_0000_0001, 64);
+        let neg_min = state.bv_from_u64(0x8000_0000_0000_0001, 64);
+        assert_eq!(
+            state.float_approx_eq(&pos_min, &neg_min, 1)?.as_bool(),
+            Some(false)
+        );
+        assert_eq!(
+            state.float_approx_eq(&pos_min, &neg_min, 2)?.as_bool(),
+            Some(true)
+        );
+
+        // NaN isn't approximately equal to itself
+        let nan = state.bv_from_u32(0x7fc0_0000, 32);
+        assert_eq!(
+            state.float_approx_eq(&nan, &nan, u64::MAX)?.as_bool(),
+            Some(false)
+        );
+
+        // symbolic values within 1 ULP of 1.0f32
+        let x = state.new_bv_with_name(Name::from("x"), 32)?;
+        state.assert(&state.float_approx_eq(&x, &a, 1)?)?;
+        assert!(state.bvs_can_be_equal(&x, &b)?);
+        assert!(!state.bvs_can_be_equal(&x, &state.bv_from_u32(0x3f80_0002, 32))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn reset() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.config.loop_bound = 7;
+        let start_loc = state.cur_loc.clone();
+
+        // populate the state
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.assert(&x.ugt(&state.bv_from_u64(10, 64)))?;
+        let ptr = state.allocate(64_u64).into_bv();
+        state.write(&ptr, state.bv_from_u64(0x1234, 64))?;
+        state.save_backtracking_point(&Name::from("test_bb"), state.bv_from_bool(true))?;
+        state.record_path_entry();
+        assert_eq!(state.count_backtracking_points(), 1);
+        assert!(!state.is_unconstrained());
+
+        state.reset();
+
+        // all variables, constraints, and backtracking points are gone
+        assert_eq!(
+            state
+                .varmap
+                .width_of(&"test_func".to_owned(), &Name::from("x")),
+            None
+        );
+        assert!(state.is_unconstrained());
+        assert_eq!(state.count_backtracking_points(), 0);
+        assert!(state.get_path().is_empty());
+        assert!(state.sat()?);
+        assert!(state.cur_loc == start_loc);
+
+        // allocations start over, and the memory contents are forgotten
+        let new_ptr = state.allocate(64_u64).into_bv();
+        assert_eq!(new_ptr.as_u64(), ptr.as_u64());
+        let read = state.read(&new_ptr, 64)?;
+        assert!(state.bvs_can_be_equal(&read, &state.bv_from_u64(0x5678, 64))?);
+
+        // the config is unchanged
+        assert_eq!(state.config.loop_bound, 7);
+
+        // we can create the same variable again
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        assert!(state.bvs_can_be_equal(&x, &state.bv_from_u64(5, 64))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn int_float_conversions() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+        let solve = |bv: <BtorBackend as Backend>::BV| -> Result<u64> {
+            Ok(state
+                .get_a_solution_for_bv(&bv)?
+                .expect("Expected a solution")
+                .as_u64()
+                .unwrap())
+        };
+
+        // 5 to float and back
+        let five = state.sitofp(&state.bv_from_u32(5, 32), 32)?;
+        assert_eq!(solve(five.clone())?, 0x40a0_0000);
+        assert_eq!(solve(state.fptosi(&five, 32)?)?, 5);
+        assert_eq!(solve(state.fptoui(&five, 8)?)?, 5);
+        let minus_one = state.sitofp(&state.bv_from_i32(-1, 8), 64)?;
+        assert_eq!(solve(minus_one)?, 0xbff0_0000_0000_0000);
+
+        // 3.9 and -3.9 truncate toward zero
+        let three_point_nine = state.bv_from_u32(0x4079_999a, 32);
+        assert_eq!(solve(state.fptosi(&three_point_nine, 32)?)?, 3);
+        assert_eq!(solve(state.fptoui(&three_point_nine, 32)?)?, 3);
+        let minus_three_point_nine = state.bv_from_u32(0xc079_999a, 32);
+        assert_eq!(
+            solve(state.fptosi(&minus_three_point_nine, 32)?)?,
+            (-3_i32) as u32 as u64
+        );
+        assert_eq!(solve(state.fptoui(&minus_three_point_nine, 32)?)?, 0);
+
+        // 2^24 + 1 and 2^24 + 3 aren't representable in single precision, and round to even
+        assert_eq!(
+            solve(state.uitofp(&state.bv_from_u32(0x0100_0001, 32), 32)?)?,
+            0x4b80_0000
+        );
+        assert_eq!(
+            solve(state.uitofp(&state.bv_from_u32(0x0100_0003, 32), 32)?)?,
+            0x4b80_0002
+        );
+        // too large for half precision
+        assert_eq!(solve(state.uitofp(&state.ones(64), 16)?)?, 0x7c00);
+
+        // float-to-int conversions saturate
+        let ten_billion = state.bv_from_u32(0x5015_02f9, 32);
+        assert_eq!(solve(state.fptosi(&ten_billion, 32)?)?, 0x7fff_ffff);
+        assert_eq!(solve(state.fptoui(&ten_billion, 32)?)?, 0xffff_ffff);
+        let minus_infinity = state.bv_from_u32(0xff80_0000, 32);
+        assert_eq!(solve(state.fptosi(&minus_infinity, 16)?)?, 0x8000);
+        let nan = state.bv_from_u32(0x7fc0_0000, 32);
+        assert_eq!(solve(state.fptosi(&nan, 32)?)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_exactly_one() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let a = state.new_bv_with_name(Name::from("a"), 1)?;
+        let b = state.new_bv_with_name(Name::from("b"), 1)?;
+        let c = state.new_bv_with_name(Name::from("c"), 1)?;
+        state.assert_exactly_one(&[&a, &b, &c])?;
+        assert!(state.sat()?);
+
+        // none of them true is infeasible
+        let none = a.or(&b).or(&c).not();
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&none))?);
+
+        // two of them true is infeasible
+        state.assert(&a)?;
+        state.assert(&c)?;
+        assert!(!state.sat()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn assert_at_most_one() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // enough flags to use the sequential encoding
+        let flags = (0 .. 10)
+            .map(|i| state.new_bv_with_name(Name::from(format!("flag{}", i)), 1))
+            .collect::<Result<Vec<_>>>()?;
+        let flag_refs: Vec<&<BtorBackend as Backend>::BV> = flags.iter().collect();
+        state.assert_at_most_one(&flag_refs)?;
+
+        // none of them true is fine
+        let none = flags
+            .iter()
+            .fold(state.bv_from_bool(false), |acc, flag| acc.or(flag))
+            .not();
+        assert!(state.sat_with_extra_constraints(std::iter::once(&none))?);
+
+        // any one of them true is fine
+        assert!(state.sat_with_extra_constraints(std::iter::once(&flags[7]))?);
+
+        // any two of them true is infeasible
+        assert!(!state.sat_with_extra_constraints(vec![&flags[2], &flags[9]])?);
+        assert!(!state.sat_with_extra_constraints(vec![&flags[0], &flags[1]])?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn memory_usage() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let initial_usage = state.memory_usage();
+        let (_, first) = state.allocate_concrete(8 * 8_u64);
+        let (_, second) = state.allocate_concrete(16 * 8_u64);
+        assert!(state.memory_usage() - initial_usage >= 24);
+        assert!(state.allocation_high_water_mark() >= second + 16);
+        assert!(second >= first + 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn save_backtracking_point_pruned() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.assert(&x.ugt(&state.bv_from_u64(10, 64)))?;
+
+        // a branch constraint contradicting the current constraints isn't saved
+        let infeasible = x.ult(&state.bv_from_u64(5, 64));
+        assert!(!state.save_backtracking_point_pruned(&Name::from("test_bb"), infeasible)?);
+        assert_eq!(state.count_backtracking_points(), 0);
+        assert!(!state.revert_to_backtracking_point()?);
+
+        // a feasible one is
+        let feasible = x.ult(&state.bv_from_u64(20, 64));
+        assert!(state.save_backtracking_point_pruned(&Name::from("test_bb"), feasible)?);
+        assert_eq!(state.count_backtracking_points(), 1);
+        assert!(state.revert_to_backtracking_point()?);
+        assert!(!state.bvs_can_be_equal(&x, &state.bv_from_u64(25, 64))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn pointer_provenance() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let (ptr, addr) = state.allocate_concrete(64_u64);
+        let _ = state.allocate(32_u64).into_bv();
+        let expected = Some(AllocationInfo { addr, bits: 64 });
+
+        // a pointer 4 bytes into the allocation
+        let inner_ptr = ptr.add(&state.bv_from_u64(4, 64));
+        assert_eq!(state.pointer_provenance(&inner_ptr)?, expected);
+        assert_eq!(state.pointer_provenance(&ptr)?, expected);
+
+        // a symbolic pointer constrained to a single value
+        let sym_ptr = state.new_bv_with_name(Name::from("sym_ptr"), 64)?;
+        state.assert(&sym_ptr._eq(&ptr.add(&state.bv_from_u64(7, 64))))?;
+        assert_eq!(state.pointer_provenance(&sym_ptr)?, expected);
+
+        // a pointer outside any allocation
+        assert_eq!(
+            state.pointer_provenance(&state.bv_from_u64(0x10, 64))?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn assume_non_null() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let ptr = state.new_bv_with_name(Name::from("ptr"), 64)?;
+        assert!(!state.is_known_non_null(&ptr));
+        assert!(state.can_be_null(&ptr)?);
+        match state.read(&ptr, 8) {
+            Err(Error::NullPointerDereference) => {},
+            r => panic!("Expected a NullPointerDereference, got {:?}", r),
+        }
+
+        state.save_backtracking_point(&Name::from("test_bb"), state.bv_from_bool(true))?;
+        state.assume_non_null(&ptr)?;
+        // the check answers from the recorded fact, and so does an access
+        assert!(state.is_known_non_null(&ptr));
+        assert!(!state.can_be_null(&ptr)?);
+        state.write(&ptr, state.bv_from_u32(0xab, 8))?;
+        let val = state.read(&ptr, 8)?;
+        assert!(state.bvs_must_be_equal(&val, &state.bv_from_u32(0xab, 8))?);
+        // the facts are checked without a solve, but they're still constraints
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&ptr._eq(&state.zero(64))))?);
+
+        // the fact only holds on the path which assumed it
+        assert!(state.revert_to_backtracking_point()?);
+        assert!(!state.is_known_non_null(&ptr));
+        assert!(state.can_be_null(&ptr)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn alloc_event_handler() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        state.set_alloc_event_handler(Box::new(move |event| recorded.borrow_mut().push(event)));
+
+        let (ptr, addr) = state.allocate_concrete(64_u64);
+        state.free(&ptr)?;
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                AllocEvent::Alloc {
+                    base: addr,
+                    size: 64
+                },
+                AllocEvent::Free { base: addr },
+            ]
+        );
+
+        // freeing null, or something which isn't an allocation, reports nothing
+        state.free(&state.zero(64))?;
+        state.free(&state.bv_from_u64(addr + 1, 64))?;
+        assert_eq!(events.borrow().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn inttoptr_checked() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // the address of an allocation, or of a byte inside it, is fine
+        let (ptr, addr) = state.allocate_concrete(64_u64);
+        assert_eq!(state.inttoptr_checked(&ptr)?.as_u64(), Some(addr));
+        let inner = state.bv_from_u64(addr + 7, 64);
+        assert_eq!(state.inttoptr_checked(&inner)?.as_u64(), Some(addr + 7));
+
+        // an arbitrary constant, or an address just past the allocation, isn't
+        for bad in &[0x1234, addr + 8] {
+            match state.inttoptr_checked(&state.bv_from_u64(*bad, 64)) {
+                Err(Error::OtherError(_)) => {},
+                r => panic!("Expected an OtherError, got {:?}", r),
+            }
+        }
+
+        // a symbolic value which can point into the allocation is constrained to
+        let int = state.new_bv_with_name(Name::from("int"), 64)?;
+        state.inttoptr_checked(&int)?;
+        assert!(!state.sat_with_extra_constraints(std::iter::once(
+            &int._eq(&state.bv_from_u64(0x1234, 64))
+        ))?);
+        assert!(state.sat_with_extra_constraints(std::iter::once(
+            &int._eq(&state.bv_from_u64(addr + 4, 64))
+        ))?);
+
+        // and when that leaves only one allocation, it's recorded as the
+        // provenance, even though the pointer is still symbolic
+        let (_, other_addr) = state.allocate_concrete(64_u64);
+        let near = state.new_bv_with_name(Name::from("near"), 64)?;
+        state.assert(&near.ugte(&state.bv_from_u64(addr, 64)))?;
+        state.assert(&near.ult(&state.bv_from_u64(addr + 3, 64)))?;
+        let near_ptr = state.inttoptr_checked(&near)?;
+        assert_eq!(
+            state.pointer_provenance(&near_ptr)?,
+            Some(AllocationInfo { addr, bits: 64 })
+        );
+        let either = state.new_bv_with_name(Name::from("either"), 64)?;
+        state.assert(
+            &either
+                ._eq(&state.bv_from_u64(addr, 64))
+                .or(&either._eq(&state.bv_from_u64(other_addr, 64))),
+        )?;
+        let either_ptr = state.inttoptr_checked(&either)?;
+        assert!(state.pointer_provenance(&either_ptr).is_err());
+
+        // but one which can't is an error
+        let wild = state.new_bv_with_name(Name::from("wild"), 64)?;
+        state.assert(&wild.ult(&state.bv_from_u64(0x100, 64)))?;
+        match state.inttoptr_checked(&wild) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_conditional() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let p = state.allocate(32_u64).into_bv();
+        let q = state.allocate(32_u64).into_bv();
+        state.write(&p, state.bv_from_u32(111, 32))?;
+        state.write(&q, state.bv_from_u32(222, 32))?;
+
+        // with a symbolic condition, the result depends on the condition
+        let cond = state.new_bv_with_name(Name::from("cond"), 1)?;
+        let read = state.read_conditional(&cond, &p, &q, 32)?;
+        let is_111 = read._eq(&state.bv_from_u32(111, 32));
+        let is_222 = read._eq(&state.bv_from_u32(222, 32));
+        assert!(state.sat_with_extra_constraints(vec![&cond, &is_111])?);
+        assert!(!state.sat_with_extra_constraints(vec![&cond, &is_222])?);
+        let not_cond = cond.not();
+        assert!(state.sat_with_extra_constraints(vec![&not_cond, &is_222])?);
+        assert!(!state.sat_with_extra_constraints(vec![&not_cond, &is_111])?);
+
+        // with a constant condition, only that address is read
+        let read = state.read_conditional(&state.bv_from_bool(false), &p, &q, 32)?;
+        assert!(state.bvs_must_be_equal(&read, &state.bv_from_u32(222, 32))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn taint() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let input = state.new_bv_with_name(Name::from("input"), 64)?;
+        let other = state.new_bv_with_name(Name::from("other"), 64)?;
+        state.mark_tainted(&Name::from("input"))?;
+
+        // the input, and values computed from it, are tainted
+        let sum = input.add(&state.bv_from_u64(3, 64));
+        assert!(state.is_tainted(&input));
+        assert!(state.is_tainted(&sum));
+        assert!(state.is_tainted(&sum.mul(&other).slice(7, 0)));
+
+        // unrelated values are not
+        assert!(!state.is_tainted(&other));
+        assert!(!state.is_tainted(&other.add(&state.bv_from_u64(3, 64))));
+        assert!(!state.is_tainted(&state.bv_from_u64(3, 64)));
+
+        // nonexistent variables can't be tainted
+        assert!(state.mark_tainted(&Name::from("nope")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn var_metadata() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let len = Name::from("len");
+        let buf = Name::from("buf");
+        state.new_bv_with_name(len.clone(), 64)?;
+        state.new_bv_with_name(buf.clone(), 64)?;
+        state.set_var_metadata(&len, "role", "length_field".to_owned())?;
+        state.set_var_metadata(&len, "source", "user_input".to_owned())?;
+        state.set_var_metadata(&buf, "role", "buffer".to_owned())?;
+        assert_eq!(state.get_var_metadata(&len, "role"), Some("length_field"));
+        assert_eq!(state.get_var_metadata(&len, "source"), Some("user_input"));
+        assert_eq!(state.get_var_metadata(&buf, "role"), Some("buffer"));
+        assert_eq!(state.get_var_metadata(&buf, "source"), None);
+
+        // setting a key again replaces its value
+        state.set_var_metadata(&len, "role", "size_field".to_owned())?;
+        assert_eq!(state.get_var_metadata(&len, "role"), Some("size_field"));
+
+        // nonexistent variables can't have metadata
+        assert!(state
+            .set_var_metadata(&Name::from("nope"), "role", "none".to_owned())
+            .is_err());
+        assert_eq!(state.get_var_metadata(&Name::from("nope"), "role"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn binop_with_overflow() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        let three = state.bv_from_u32(3, 8);
+        let five = state.bv_from_u32(5, 8);
+        let split = |bv: <BtorBackend as Backend>::BV| {
+            assert_eq!(bv.get_width(), 9);
+            (bv.slice(7, 0).as_u64(), bv.slice(8, 8).as_bool())
+        };
+
+        // usub.with.overflow(3, 5) wraps, and overflows
+        let result = state.binop_with_overflow(BinOp::Sub, &three, &five, false)?;
+        assert_eq!(split(result), (Some(0xfe), Some(true)));
+        // as signed values it doesn't overflow
+        let result = state.binop_with_overflow(BinOp::Sub, &three, &five, true)?;
+        assert_eq!(split(result), (Some(0xfe), Some(false)));
+        // 100 + 100 overflows as signed but not as unsigned
+        let hundred = state.bv_from_u32(100, 8);
+        let result = state.binop_with_overflow(BinOp::Add, &hundred, &hundred, true)?;
+        assert_eq!(split(result), (Some(200), Some(true)));
+        let result = state.binop_with_overflow(BinOp::Add, &hundred, &hundred, false)?;
+        assert_eq!(split(result), (Some(200), Some(false)));
+        let result = state.binop_with_overflow(BinOp::Mul, &hundred, &three, false)?;
+        assert_eq!(split(result), (Some(44), Some(true)));
+
+        assert!(state
+            .binop_with_overflow(BinOp::UDiv, &three, &five, false)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn three_way_compare() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        let minus_five = state.bv_from_i64(-5, 8);
+        let three = state.bv_from_u32(3, 8);
+        let compare = |a, b, signed| -> Result<Option<i64>> {
+            let result = state.three_way_compare(a, b, signed)?;
+            assert_eq!(result.get_width(), 32);
+            state.get_a_solution_for_bv_signed(&result)
+        };
+        assert_eq!(compare(&minus_five, &three, true)?, Some(-1));
+        assert_eq!(compare(&three, &minus_five, true)?, Some(1));
+        assert_eq!(compare(&three, &three, true)?, Some(0));
+        // unsigned, -5 is 251
+        assert_eq!(compare(&minus_five, &three, false)?, Some(1));
+        assert!(state
+            .three_way_compare(&three, &state.zero(16), true)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn vector_binop_sat() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        // <2 x i8> vectors <200, 10> and <100, 20>; lane 0 is the low bits
+        let a = state.bv_from_u32(0x0ac8, 16);
+        let b = state.bv_from_u32(0x1464, 16);
+
+        // unsigned: lane 0 saturates to 255, lane 1 is 30
+        let sum = state.vector_binop_sat(BinOp::Add, &a, &b, 8, false)?;
+        assert_eq!(sum.as_u64(), Some(0x1eff));
+
+        // signed: lane 0 is -56 + 100 = 44, lane 1 is 30
+        let sum = state.vector_binop_sat(BinOp::Add, &a, &b, 8, true)?;
+        assert_eq!(sum.as_u64(), Some(0x1e2c));
+
+        // unsigned subtraction: lane 0 is 100, lane 1 saturates to 0
+        let diff = state.vector_binop_sat(BinOp::Sub, &a, &b, 8, false)?;
+        assert_eq!(diff.as_u64(), Some(0x0064));
+
+        // there's no saturating multiplication, and lanes must divide the width
+        assert!(state
+            .vector_binop_sat(BinOp::Mul, &a, &b, 8, false)
+            .is_err());
+        assert!(state
+            .vector_binop_sat(BinOp::Add, &a, &b, 3, false)
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn shuffle_vector() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        // <2 x i32> vectors <10, 11> and <20, 21>; lane 0 is the low bits
+        let a = state.bv_from_u32(11, 32).concat(&state.bv_from_u32(10, 32));
+        let b = state.bv_from_u32(21, 32).concat(&state.bv_from_u32(20, 32));
+
+        // mask [1, 2] selects <11, 20>
+        let shuffled = state.shuffle_vector(&a, &b, &[1, 2], 32)?;
+        assert_eq!(shuffled.get_width(), 64);
+        assert_eq!(shuffled.slice(31, 0).as_u64(), Some(11));
+        assert_eq!(shuffled.slice(63, 32).as_u64(), Some(20));
+
+        // the result can have a different number of lanes, and undef lanes
+        let shuffled = state.shuffle_vector(&a, &b, &[3, -1, 0], 32)?;
+        assert_eq!(shuffled.get_width(), 96);
+        assert_eq!(shuffled.slice(31, 0).as_u64(), Some(21));
+        assert_eq!(shuffled.slice(63, 32).as_u64(), Some(0));
+        assert_eq!(shuffled.slice(95, 64).as_u64(), Some(10));
+
+        // out-of-range indices are errors
+        assert!(state.shuffle_vector(&a, &b, &[4], 32).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn solutions_iter() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.assert(&x.ult(&state.bv_from_u64(100, 64)))?;
+
+        // the first three solutions are distinct, and all less than 100
+        let solutions: Vec<u64> = state.solutions_iter(&x)?.take(3).collect();
+        assert_eq!(solutions.len(), 3);
+        assert!(solutions.iter().all(|&s| s < 100));
+        assert_eq!(solutions.iter().collect::<HashSet<_>>().len(), 3);
+
+        // once the iterator is dropped, previous solutions are possible again
+        for &solution in &solutions {
+            assert!(state.bvs_can_be_equal(&x, &state.bv_from_u64(solution, 64))?);
+        }
+
+        // a BV with few solutions gives exactly those solutions
+        let y = state.new_bv_with_name(Name::from("y"), 8)?;
+        state.assert(&y.ult(&state.bv_from_u32(3, 8)))?;
+        let mut solutions: Vec<u64> = state.solutions_iter(&y)?.collect();
+        solutions.sort_unstable();
+        assert_eq!(solutions, vec![0, 1, 2]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn try_fold_binop() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // `a` and `b` are symbolic, but pinned to constants
+        let a = state.new_bv_with_name(Name::from("a"), 64)?;
+        let b = state.new_bv_with_name(Name::from("b"), 64)?;
+        state.assert(&a._eq(&state.bv_from_u64(3, 64)))?;
+        state.assert(&b._eq(&state.bv_from_u64(4, 64)))?;
+        let sum = state
+            .try_fold_binop(BinOp::Add, &a, &b)
+            .expect("expected a and b to be folded");
+        assert_eq!(sum.as_u64(), Some(7));
+
+        // an unpinned operand can't be folded
+        let c = state.new_bv_with_name(Name::from("c"), 64)?;
+        assert!(state.try_fold_binop(BinOp::Add, &a, &c).is_none());
+
+        // nor can division by zero
+        assert!(state
+            .try_fold_binop(BinOp::UDiv, &a, &state.zero(64))
+            .is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn bitfields() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        // 0b101_110_01: the 3-bit field at offset 2 is 0b110
+        let word = state.bv_from_u32(0b1011_1001, 8);
+        let field = state.bitfield_extract(&word, 2, 3)?;
+        assert_eq!(field.get_width(), 3);
+        assert_eq!(field.as_u64(), Some(0b110));
+
+        // replacing it with 0b011 leaves the other bits unchanged
+        let new_word = state.bitfield_insert(&word, &state.bv_from_u32(0b011, 3), 2, 3)?;
+        assert_eq!(new_word.get_width(), 8);
+        assert_eq!(new_word.as_u64(), Some(0b1010_1101));
+        assert_eq!(
+            state.bitfield_extract(&new_word, 2, 3)?.as_u64(),
+            Some(0b011)
+        );
+
+        // fields which don't fit in the word are rejected, even if computing
+        // their end would overflow
+        match state.bitfield_extract(&word, 6, 3) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+        match state.bitfield_extract(&word, u32::MAX, 2) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+        match state.bitfield_insert(&word, &state.bv_from_u32(0, 2), 2, 3) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn bool_value() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.assert(&x.ugt(&state.bv_from_u64(5, 64)))?;
+        assert_eq!(
+            state.bool_value(&x.ugt(&state.zero(64)))?,
+            BoolValue::AlwaysTrue
+        );
+        assert_eq!(
+            state.bool_value(&x.ult(&state.bv_from_u64(3, 64)))?,
+            BoolValue::AlwaysFalse
+        );
+        assert_eq!(
+            state.bool_value(&x.ugt(&state.bv_from_u64(10, 64)))?,
+            BoolValue::Either
+        );
+
+        // unsat path
+        state.assert(&x.ult(&state.bv_from_u64(3, 64)))?;
+        assert_eq!(state.bool_value(&x.ugt(&state.zero(64))), Err(Error::Unsat));
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_implied() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.assert(&x.ugt(&state.bv_from_u64(10, 64)))?;
+        assert!(state.is_implied(&x.ugt(&state.bv_from_u64(5, 64)))?);
+        assert!(!state.is_implied(&x.ugt(&state.bv_from_u64(20, 64)))?);
+        // the constraint itself is implied, and its negation isn't
+        assert!(state.is_implied(&x.ugt(&state.bv_from_u64(10, 64)))?);
+        assert!(!state.is_implied(&x.ulte(&state.bv_from_u64(10, 64)))?);
+        assert!(state.is_implied(&x).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reuse_freed_allocations() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // by default, freed addresses aren't reused
+        let (ptr, addr) = state.allocate_concrete(64_u64);
+        state.free(&ptr)?;
+        let (_, new_addr) = state.allocate_concrete(64_u64);
+        assert_ne!(new_addr, addr);
+
+        state.config.reuse_freed_allocations = true;
+        let (ptr, addr) = state.allocate_concrete(64_u64);
+        state.free(&ptr)?;
+        // a larger allocation can't reuse it
+        let (_, larger_addr) = state.allocate_concrete(128_u64);
+        assert_ne!(larger_addr, addr);
+        // an allocation of the same size reuses it
+        let (_, new_addr) = state.allocate_concrete(64_u64);
+        assert_eq!(new_addr, addr);
+        // but only once
+        let (_, another_addr) = state.allocate_concrete(64_u64);
+        assert_ne!(another_addr, addr);
+
+        // a smaller allocation can reuse it, without shrinking it for later reuse
+        let (ptr, addr) = state.allocate_concrete(64_u64);
+        state.free(&ptr)?;
+        let (smaller, smaller_addr) = state.allocate_concrete(32_u64);
+        assert_eq!(smaller_addr, addr);
+        assert_eq!(state.get_allocation_size(&smaller)?, Some(32));
+        state.free(&smaller)?;
+        let (_, new_addr) = state.allocate_concrete(64_u64);
+        assert_eq!(new_addr, addr);
+
+        // freeing on one path doesn't affect another
+        let (ptr, addr) = state.allocate_concrete(64_u64);
+        state.save_backtracking_point(&Name::from("test_bb"), state.bv_from_bool(true))?;
+        state.free(&ptr)?;
+        assert!(state.revert_to_backtracking_point()?);
+        let (_, new_addr) = state.allocate_concrete(64_u64);
+        assert_ne!(new_addr, addr);
+
+        Ok(())
     }
 
-    /// Given a `BV`, interpret it as a function pointer, and return a
-    /// description of the possible `Function`s which it would point to.
-    ///
-    /// `n`: Maximum number of distinct `Callable`s to check for.
-    /// If there are more than `n` possible `Callable`s, this returns a
-    /// `PossibleSolutions::AtLeast` with `n+1` `Callable`s.
-    ///
-    /// Possible errors:
-    ///   - `Error::SolverError` if the solver query fails
-    ///   - `Error::FailedToResolveFunctionPointer` if it finds that it is possible
-    ///     that the `BV` points to something that's not a `Function` in the
-    ///     `Project`
-    pub(crate) fn interpret_as_function_ptr(
-        &mut self,
-        bv: B::BV,
-        n: usize,
-    ) -> Result<PossibleSolutions<Callable<'p, B>>> {
-        if n == 0 {
-            unimplemented!("n == 0 in interpret_as_function_ptr")
-        }
+    #[test]
+    fn read_dynamic() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
 
-        // First try to interpret without a full solve (i.e., with `as_u64()`)
-        let addrs: Vec<u64> = match bv.as_u64() {
-            Some(addr) => vec![addr], // there is only one possible solution, and it's this `addr`
-            None => {
-                // Check if whatever solution we used last time for this `Location` still applies
-                // (see notes on the `function_ptr_cache` field of `State`)
-                match self.function_ptr_cache.get(&self.cur_loc) {
-                    Some(addr)
-                        if self
-                            .bvs_must_be_equal(&bv, &self.bv_from_u64(*addr, bv.get_width()))? =>
-                    {
-                        vec![*addr]
-                    },
-                    _ => {
-                        // Ok, use `get_possible_solutions_for_bv()`
-                        match self
-                            .get_possible_solutions_for_bv(&bv, n)?
-                            .as_u64_solutions()
-                            .unwrap()
-                        {
-                            PossibleSolutions::Exactly(v) => v.into_iter().collect(),
-                            PossibleSolutions::AtLeast(v) => v.into_iter().collect(),
-                        }
-                    },
-                }
-            },
-        };
+        // an array of 4 structs of 8 bytes each; write a value to a field of element 2
+        let base = state.allocate(256_u64).into_bv();
+        let field_addr = base.add(&state.bv_from_u64(2 * 8 + 4, 64));
+        state.write(&field_addr, state.bv_from_u32(0xbeef, 16))?;
+
+        // read it back via a symbolic offset, constrained to that value
+        let index = state.new_bv_with_name(Name::from("index"), 32)?;
+        let field = state.new_bv_with_name(Name::from("field"), 32)?;
+        let offset = index.mul(&state.bv_from_u32(8, 32)).add(&field);
+        state.assert(&offset._eq(&state.bv_from_u32(20, 32)))?;
+        let val = state.read_dynamic(&base, &offset, 16, false)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(0xbeef)
+        );
+        assert!(state.sat()?);
 
-        // save the value we found into the cache for next time
-        if addrs.len() == 1 {
-            self.function_ptr_cache
-                .insert(self.cur_loc.clone(), addrs[0]);
-        }
+        // with bounds checking, the read must stay within the allocation
+        let unconstrained = state.new_bv_with_name(Name::from("offset"), 64)?;
+        state.read_dynamic(&base, &unconstrained, 16, true)?;
+        assert_eq!(
+            state.max_possible_solution_for_irname_as_u64(
+                &"test_func".to_owned(),
+                &Name::from("offset")
+            )?,
+            Some(30)
+        );
+        assert!(state
+            .read_dynamic(&base, &unconstrained, 512, true)
+            .is_err());
 
-        let callables = addrs
-            .into_iter()
-            .map(|addr| {
-                self.global_allocations
-                    .get_func_for_address(addr, self.cur_loc.module)
-                    .ok_or_else(|| Error::FailedToResolveFunctionPointer(addr))
-            })
-            .collect::<Result<HashSet<_>>>()?;
-        if callables.len() > n {
-            Ok(PossibleSolutions::AtLeast(callables))
-        } else {
-            Ok(PossibleSolutions::Exactly(callables))
-        }
+        Ok(())
     }
 
-    /// Get a pointer to the given function name. The name must be the
-    /// fully-mangled function name, as it appears in the LLVM. The name will be
-    /// resolved in the current module; this means that it will first look for a
-    /// module-private (e.g., C `static`) definition in the current module, then
-    /// search for a public definition in the same or different module. It will
-    /// never return a module-private definition from a different module.
-    ///
-    /// Returns `None` if no function was found with that name.
-    pub fn get_pointer_to_function(&self, funcname: impl Into<String>) -> Option<&B::BV> {
-        self.global_allocations
-            .get_global_allocation(&Name::from(funcname.into()), self.cur_loc.module)
-            .map(|ga| ga.get_addr())
-    }
+    #[test]
+    fn touched_allocations() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("bb1"), Name::from("bb2")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
 
-    /// Get a pointer to the currently active _hook_ for the given function name.
-    ///
-    /// Returns `None` if no function was found with that name, _or_ if there is no currently
-    /// active hook for that function.
-    pub fn get_pointer_to_function_hook(&self, funcname: &str) -> Option<&B::BV> {
-        self.global_allocations
-            .get_function_hook_address(self.config.function_hooks.get_hook_for(funcname)?)
+        let first = state.allocate(64_u64).into_bv();
+        let second = state.allocate(64_u64).into_bv();
+        state.read(&first, 8)?;
+        assert!(state.touched_allocations().is_empty()); // not tracking yet
+        state.enable_access_tracking();
+
+        // an access anywhere in an allocation touches it
+        let t = state.bv_from_bool(true);
+        state.save_backtracking_point(&Name::from("bb2"), t)?;
+        let inside = second.add(&state.bv_from_u64(4, 64));
+        state.write(&inside, state.bv_from_u32(1, 8))?;
+        assert_eq!(state.touched_allocations(), vec![second.as_u64().unwrap()]);
+        state.read(&first, 8)?;
+        assert_eq!(
+            state.touched_allocations(),
+            vec![first.as_u64().unwrap(), second.as_u64().unwrap()]
+        );
+
+        // the accesses were only on the path we backtracked from
+        assert!(state.revert_to_backtracking_point()?);
+        assert!(state.touched_allocations().is_empty());
+
+        Ok(())
     }
 
-    /// Get a `Function` by name. The name must be the fully-mangled function
-    /// name, as it appears in the LLVM. The name will be resolved in the current
-    /// module; this means that it will first look for a module-private (e.g., C
-    /// `static`) definition in the current module, then search for a public
-    /// definition in the same or different module. It will never return a
-    /// module-private definition from a different module.
-    ///
-    /// Also returns the `Module` in which the prevailing definition of the `Function` was found.
-    ///
-    /// Returns `None` if no function was found with that name.
-    pub fn get_func_by_name(
-        &self,
-        funcname: impl Into<String>,
-    ) -> Option<(&'p Function, &'p Module)> {
-        let funcname = funcname.into();
-        self.global_allocations
-            .get_global_allocation(&Name::from(funcname.clone()), self.cur_loc.module)
-            .and_then(|ga| match ga {
-                GlobalAllocation::Function { func, module, .. } => Some((*func, *module)),
-                GlobalAllocation::GlobalVariable { .. } => panic!(
-                    "get_func_by_name: {} refers to a global variable, not a function",
-                    funcname
-                ),
-            })
+    #[test]
+    fn read_or_default() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.enable_write_tracking();
+
+        let ptr = state.allocate(64_u64).into_bv();
+        let second_half = ptr.add(&state.bv_from_u64(4, 64));
+        state.write(&ptr, state.bv_from_u32(0x1234_5678, 32))?;
+
+        // a written address gives the stored value, and an unwritten one the default
+        let val = state.read_or_default(&ptr, 32, 0xdead_beef)?;
+        assert_eq!(val.as_u64(), Some(0x1234_5678));
+        let val = state.read_or_default(&second_half, 32, 0xdead_beef)?;
+        assert_eq!(val.as_u64(), Some(0xdead_beef));
+
+        // a read spanning both takes each byte from the right place
+        let middle = ptr.add(&state.bv_from_u64(2, 64));
+        let val = state.read_or_default(&middle, 32, 0xdead_beef)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(0xdead_1234)
+        );
+
+        Ok(())
     }
 
-    /// Read a value `bits` bits long from memory at `addr`.
-    /// Note that `bits` can be arbitrarily large.
-    pub fn read(&self, addr: &B::BV, bits: u32) -> Result<B::BV> {
-        let retval = match self.mem.borrow().read(addr, bits) {
-            Ok(val) => val,
-            e @ Err(Error::NullPointerDereference) => {
-                if self.config.null_pointer_checking == NullPointerChecking::SplitPath {
-                    // save a backtracking point to re-execute the current
-                    // instruction with the address constrained to be non-null,
-                    // and continue from there
-                    self.save_backtracking_point_at_location(
-                        self.cur_loc.clone(),
-                        addr._ne(&self.zero(addr.get_width())),
-                    );
-                }
-                return e; // report the null-pointer dereference
-            },
-            e @ Err(_) => return e, // propagate any other kind of error
-        };
-        for (name, watchpoint) in self.mem_watchpoints.get_triggered_watchpoints(addr, bits)? {
-            let pretty_loc = if self.config.print_module_name {
-                self.cur_loc.to_string_with_module()
-            } else {
-                self.cur_loc.to_string_no_module()
-            };
-            info!(
-                "Memory watchpoint {:?} {} read by {{{}}}",
-                name, watchpoint, pretty_loc
-            );
+    #[test]
+    fn allocate_zeroed() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // a region too large to represent in bits is an error
+        match state.allocate_zeroed(u64::MAX / 4) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
         }
-        Ok(retval)
+
+        // a large region reads as zero, even at the end
+        let ptr = state.allocate_zeroed(1 << 20)?;
+        let end = ptr.add(&state.bv_from_u64((1 << 20) - 8, 64));
+        assert_eq!(state.read(&end, 64)?.as_u64(), Some(0));
+        assert!(!state.was_written(&end)?);
+
+        // until it's written
+        state.write(&end, state.bv_from_u32(0x1234_5678, 32))?;
+        assert_eq!(state.read(&end, 32)?.as_u64(), Some(0x1234_5678));
+        // a read spanning written and unwritten bytes takes each from the right place
+        let middle = end.add(&state.bv_from_u64(2, 64));
+        let val = state.read(&middle, 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(0x1234)
+        );
+
+        // the zeroes take precedence over the uninit handler, but only within the region
+        state.set_uninit_handler(UninitHandler::Error);
+        assert_eq!(state.read(&ptr, 8)?.as_u64(), Some(0));
+        let other = state.allocate(64_u64).into_bv();
+        match state.read(&other, 8) {
+            Err(Error::UninitializedRead(_)) => {},
+            r => panic!("Expected an UninitializedRead, got {:?}", r),
+        }
+
+        Ok(())
     }
 
-    /// Write a value into memory at `addr`.
-    /// Note that `val` can be an arbitrarily large bitvector.
-    pub fn write(&mut self, addr: &B::BV, val: B::BV) -> Result<()> {
-        self.write_without_mut(addr, val)
+    #[test]
+    fn zeroed_region_backtracking() -> Result<()> {
+        let func = blank_function(
+            "test_func",
+            vec![Name::from("bb_start"), Name::from("bb_target")],
+        );
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.config.reuse_freed_allocations = true;
+
+        let ptr = state.allocate_zeroed(16)?;
+        state.save_backtracking_point(&Name::from("bb_target"), state.bv_from_bool(true))?;
+
+        // freeing the region means it no longer reads as zero
+        state.free(&ptr)?;
+        assert_eq!(state.read(&ptr, 64)?.as_u64(), None);
+
+        // but backtracking to before the free restores it
+        assert!(state.revert_to_backtracking_point()?);
+        assert_eq!(state.read(&ptr, 64)?.as_u64(), Some(0));
+
+        Ok(())
     }
 
-    /// For internal use: since `self.mem` is a `RefCell`, we can write even
-    /// without having a `&mut self` reference. This is necessary to support,
-    /// for instance, lazy global initialization. But, we don't want to skip
-    /// watchpoint checks by calling `self.mem.borrow_mut()` directly, so we
-    /// have this
-    fn write_without_mut(&self, addr: &B::BV, val: B::BV) -> Result<()> {
-        let write_width = val.get_width();
-        let result = self.mem.borrow_mut().write(addr, val);
-        // we do this awkward `let result` / `match result` because it forces
-        // the mutable borrow of self.mem to end, which is necessary because
-        // save_backtracking_point_at_location requires a borrow of self.mem
-        match result {
-            Ok(()) => (),
-            e @ Err(Error::NullPointerDereference) => {
-                if self.config.null_pointer_checking == NullPointerChecking::SplitPath {
-                    // save a backtracking point to re-execute the current
-                    // instruction with the address constrained to be non-null,
-                    // and continue from there
-                    self.save_backtracking_point_at_location(
-                        self.cur_loc.clone(),
-                        addr._ne(&self.zero(addr.get_width())),
-                    );
-                }
-                return e; // report the null-pointer dereference
-            },
-            e @ Err(_) => return e, // propagate any other kind of error
-        };
-        for (name, watchpoint) in self
-            .mem_watchpoints
-            .get_triggered_watchpoints(addr, write_width)?
-        {
-            let pretty_loc = if self.config.print_module_name {
-                self.cur_loc.to_string_with_module()
-            } else {
-                self.cur_loc.to_string_no_module()
-            };
-            // Log the new value of the watched location (regardless of which part of the watched location the write may have touched).
-            // Note that the write operation itself has already been performed, so we get the updated value with a `read()`.
-            let watchpoint_low = self.bv_from_u64(
-                watchpoint.get_lower_bound(),
-                crate::layout::POINTER_SIZE_BITS as u32,
-            );
-            let watchpoint_size_bits =
-                (watchpoint.get_upper_bound() - watchpoint.get_lower_bound() + 1) * 8;
-            let new_value = self
-                .mem
-                .borrow()
-                .read(&watchpoint_low, watchpoint_size_bits as u32)?; // performs a read without using `state.read()` which would trigger watchpoints (we don't want to trigger watchpoints with this read)
-            info!(
-                "Memory watchpoint {:?} {} written by {{{}}}; new value is {:?}",
-                name, watchpoint, pretty_loc, new_value
-            );
-        }
+    #[test]
+    fn was_written() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let ptr = state.allocate(64_u64).into_bv();
+        let second_half = ptr.add(&state.bv_from_u64(4, 64));
+        assert!(state.was_written(&ptr).is_err()); // not tracking yet
+
+        state.enable_write_tracking();
+        assert!(!state.was_written(&ptr)?);
+        state.save_backtracking_point(&Name::from("test_bb"), state.bv_from_bool(true))?;
+        state.write(&ptr, state.bv_from_u32(0x1234, 32))?;
+        assert!(state.was_written(&ptr)?);
+        assert!(!state.was_written(&second_half)?);
+
+        // tracking continues even if an uninit handler is installed and removed
+        state.set_uninit_handler(UninitHandler::Warn);
+        state.set_uninit_handler(UninitHandler::Ignore);
+        assert!(state.was_written(&ptr)?);
+
+        // the write was only on the path we backtracked from
+        assert!(state.revert_to_backtracking_point()?);
+        assert!(!state.was_written(&ptr)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn clone_constraints() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.assert(&x.ugt(&state.bv_from_u64(5, 64)))?;
+
+        // the clone has the original's constraints
+        let clone = state.clone_constraints();
+        let cloned_x = clone.match_bv(&x).expect("Failed to find x in the clone");
+        let three = <BtorBackend as Backend>::BV::from_u64(clone.clone(), 3, 64);
+        assert!(!solver_utils::bvs_can_be_equal(&clone, &cloned_x, &three)?);
+
+        // a contradiction in the clone doesn't affect the original
+        cloned_x
+            .ult(&<BtorBackend as Backend>::BV::from_u64(
+                clone.clone(),
+                5,
+                64,
+            ))
+            .assert();
+        assert!(!solver_utils::sat(&clone)?);
+        assert!(state.sat()?);
+        assert!(state.bvs_can_be_equal(&x, &state.bv_from_u64(6, 64))?);
+
         Ok(())
     }
 
-    /// Add a memory watchpoint. It will be enabled unless/until
-    /// `disable_watchpoint()` is called on it.
-    ///
-    /// If a watchpoint with the same name was previously added, this will
-    /// replace that watchpoint and return `true`. Otherwise, this will return
-    /// `false`.
-    ///
-    /// When any watched memory is read or written to, an INFO-level log message
-    /// will be generated.
-    pub fn add_mem_watchpoint(&mut self, name: impl Into<String>, watchpoint: Watchpoint) -> bool {
-        self.mem_watchpoints.add(name, watchpoint)
-    }
+    #[test]
+    fn current_location() -> Result<()> {
+        let mut func = blank_function("foo", vec![Name::from("bb0"), Name::from("bb1")]);
+        let add = instruction::Add {
+            operand0: Operand::ConstantOperand(Constant::Int { bits: 32, value: 1 }),
+            operand1: Operand::ConstantOperand(Constant::Int { bits: 32, value: 2 }),
+            dest: Name::from("sum"),
+            debugloc: None,
+        };
+        func.basic_blocks[1].instrs = vec![Instruction::Add(add); 4];
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "foo");
+
+        state.set_current_location("foo", &Name::from("bb1"), 3)?;
+        let err = state.unsupported("instruction", &"Something");
+        assert!(err.to_string().contains("at foo:bb1:3"), "{}", err);
+
+        state.set_current_location("foo", &Name::from("bb1"), 4)?;
+        assert_eq!(state.cur_loc.instr, BBInstrIndex::Terminator);
+
+        // locations which don't exist are errors
+        assert!(state
+            .set_current_location("foo", &Name::from("bb1"), 5)
+            .is_err());
+        assert!(state
+            .set_current_location("foo", &Name::from("bb2"), 0)
+            .is_err());
+        assert!(state
+            .set_current_location("bar", &Name::from("bb1"), 0)
+            .is_err());
 
-    /// Remove the memory watchpoint with the given `name`.
-    ///
-    /// Returns `true` if the operation was successful, or `false` if no
-    /// watchpoint with that name was found.
-    pub fn rm_mem_watchpoint(&mut self, name: &str) -> bool {
-        self.mem_watchpoints.remove(name)
+        Ok(())
     }
 
-    /// Disable the memory watchpoint with the given `name`. Disabled
-    /// watchpoints will not generate any log messages unless/until
-    /// `enable_watchpoint()` is called on them.
-    ///
-    /// Returns `true` if the operation is successful, or `false` if no
-    /// watchpoint with that name was found. Disabling an already-disabled
-    /// watchpoint will have no effect and will return `true`.
-    pub fn disable_watchpoint(&mut self, name: &str) -> bool {
-        self.mem_watchpoints.disable(name)
-    }
+    #[test]
+    fn callstack_depth_error_limit() -> Result<()> {
+        let call = instruction::Call {
+            function: Either::Right(Operand::ConstantOperand(Constant::GlobalReference {
+                name: Name::from("callee"),
+                ty: Type::FuncType {
+                    result_type: Box::new(Type::VoidType),
+                    param_types: vec![],
+                    is_var_arg: false,
+                },
+            })),
+            arguments: vec![],
+            return_attributes: vec![],
+            dest: None,
+            function_attributes: vec![],
+            is_tail_call: false,
+            calling_convention: function::CallingConvention::C,
+            debugloc: None,
+        };
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.config.callstack_depth_error_limit = Some(3);
 
-    /// Enable the memory watchpoint(s) with the given name.
-    ///
-    /// Returns `true` if the operation is successful, or `false` if no
-    /// watchpoint with that name was found. Enabling an already-enabled
-    /// watchpoint will have no effect and will return `true`.
-    pub fn enable_watchpoint(&mut self, name: &str) -> bool {
-        self.mem_watchpoints.enable(name)
+        // the first three calls are fine
+        for _ in 0 .. 3 {
+            state.push_callsite(&call)?;
+        }
+        // the fourth exceeds the limit
+        assert_eq!(state.push_callsite(&call), Err(Error::CallDepthExceeded(3)));
+
+        // popping a call makes room for another
+        assert!(state.pop_callsite().is_some());
+        state.push_callsite(&call)?;
+
+        Ok(())
     }
 
-    /// Allocate a value of size `bits`; return a pointer to the newly allocated object
-    pub fn allocate(&mut self, bits: impl Into<u64>) -> B::BV {
-        let raw_ptr = self.alloc.alloc(bits);
-        self.bv_from_u64(raw_ptr, 64)
+    #[test]
+    fn presimplify() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.set_presimplify(true);
+
+        // a satisfiable query involving memory is still satisfiable
+        let addr = state.allocate(64_u64).into_bv();
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.write(&addr, x.clone())?;
+        let val = state.read(&addr, 64)?;
+        assert_eq!(
+            state.sat_with_extra_constraints(std::iter::once(&val._eq(&x))),
+            Ok(true)
+        );
+
+        // a constraint which simplifies to false is unsat
+        state.assert(&val.and(&x.not())._eq(&state.ones(64)))?;
+        assert_eq!(state.sat(), Ok(false));
+
+        // and the setting persists across a reset
+        state.reset();
+        assert!(state.presimplify);
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
     }
 
-    /// Get the size, in bits, of the allocation at the given address, or `None`
-    /// if that address is not the result of an `alloc()`.
-    pub fn get_allocation_size(&mut self, addr: &B::BV) -> Result<Option<u64>> {
-        // First try to obtain the address without a full solve (i.e., with `as_u64()`)
-        match addr.as_u64() {
-            Some(addr) => Ok(self.alloc.get_allocation_size(addr)),
-            None => {
-                match self.get_possible_solutions_for_bv(addr, 1)? {
-                    PossibleSolutions::AtLeast(_) => Err(Error::OtherError(format!(
-                        "get_allocation_size: address is not a constant: {:?}",
-                        addr
-                    ))), // must be at least 2 solutions, since we passed in n==1
-                    PossibleSolutions::Exactly(v) => {
-                        let addr =
-                            v.iter()
-                                .next()
-                                .ok_or(Error::Unsat)?
-                                .as_u64()
-                                .ok_or_else(|| {
-                                    Error::OtherError(
-                                        "get_allocation_size: address is more than 64 bits wide"
-                                            .to_owned(),
-                                    )
-                                })?;
-                        Ok(self.alloc.get_allocation_size(addr))
-                    },
-                }
-            },
+    #[test]
+    fn reason_unknown() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // a conclusive query has no reason
+        assert_eq!(state.sat(), Ok(true));
+        assert_eq!(state.reason_unknown(), None);
+
+        // factoring a semiprime using 128-bit multiplication can't be done within a millisecond
+        let timeout = Some(std::time::Duration::from_millis(1));
+        state.config.solver_query_timeout = timeout;
+        state.solver.set_opt(BtorOption::SolverTimeout(timeout));
+        let x = state.new_bv_with_name(Name::from("x"), 128)?;
+        let y = state.new_bv_with_name(Name::from("y"), 128)?;
+        // (2^61 - 1) * (2^31 - 1), both of which are prime
+        let product = <BtorBackend as Backend>::BV::from_dec_str(
+            state.solver.clone(),
+            "4951760154835678088235319297",
+            128,
+        );
+        let one = state.one(128);
+        let bound = state.bv_from_u64(1, 128).sll(&state.bv_from_u32(64, 128));
+        let factoring = x
+            .mul(&y)
+            ._eq(&product)
+            .and(&x.ugt(&one))
+            .and(&y.ugt(&one))
+            .and(&x.ult(&bound))
+            .and(&y.ult(&bound));
+        match state.sat_with_extra_constraints(std::iter::once(&factoring)) {
+            Err(Error::SolverError(_)) => {},
+            r => panic!("Expected the query to time out, got {:?}", r),
         }
-    }
+        let reason = state
+            .reason_unknown()
+            .expect("Expected a reason for the unknown result");
+        assert_eq!(reason, "timeout");
 
-    /// Record the current location as a `PathEntry` in the current path.
-    pub fn record_path_entry(&mut self) {
-        let entry = PathEntry(self.cur_loc.clone());
-        debug!("Recording a path entry {:?}", entry);
-        self.path.push(entry);
+        Ok(())
     }
 
-    /// Get the `PathEntry`s that have been recorded, in order
-    pub fn get_path(&self) -> &Vec<PathEntry<'p>> {
-        &self.path
-    }
+    #[test]
+    fn query_complexity() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        assert_eq!(
+            state.query_complexity(),
+            QueryComplexity {
+                constraints: 0,
+                variables: 0,
+                nodes: 0
+            }
+        );
 
-    /// Record entering a normal `Call` at the current location
-    pub fn push_callsite(&mut self, call: &'p instruction::Call) {
-        self.push_generic_callsite(Either::Left(call))
+        let x = state.new_bv_with_name(Name::from("x"), 8)?;
+        let y = state.new_bv_with_name(Name::from("y"), 8)?;
+        state.assert(&x.ugt(&state.one(8)))?;
+        state.assert(&y.ult(&state.bv_from_u32(10, 8)))?;
+        state.assert(&x._ne(&y))?;
+
+        let complexity = state.query_complexity();
+        assert_eq!(complexity.constraints, 3);
+        assert_eq!(complexity.variables, 2);
+        // each constraint is at least an operation on two terms
+        assert!(complexity.nodes >= 9);
+
+        Ok(())
     }
 
-    /// Record entering the given `Invoke` at the current location
-    pub fn push_invokesite(&mut self, invoke: &'p terminator::Invoke) {
-        self.push_generic_callsite(Either::Right(invoke))
+    #[test]
+    fn constraints_mentioning() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        let y = state.new_bv_with_name(Name::from("y"), 64)?;
+        let x_positive = x.sgt(&state.zero(64));
+        let y_small = y.slt(&state.bv_from_u64(5, 64));
+        state.assert(&x_positive)?;
+        state.assert(&y_small)?;
+
+        assert_eq!(
+            state.constraints_mentioning(&Name::from("x")),
+            vec![x_positive]
+        );
+        assert_eq!(
+            state.constraints_mentioning(&Name::from("y")),
+            vec![y_small]
+        );
+        assert!(state.constraints_mentioning(&Name::from("z")).is_empty());
+
+        Ok(())
     }
 
-    fn push_generic_callsite(
-        &mut self,
-        instr: Either<&'p instruction::Call, &'p terminator::Invoke>,
-    ) {
-        self.stack.push(StackFrame {
-            callsite: Callsite {
-                loc: self.cur_loc.clone(),
-                instr,
-            },
-            // TODO: taking this `restore_info` every time a callsite is pushed
-            // may be expensive, and is only necessary if the call we're going
-            // to make will eventually (directly or indirectly) recurse. In the
-            // future we could check the LLVM 'norecurse' attribute to know when
-            // this is not necessary.
-            restore_info: self
-                .varmap
-                .get_restore_info_for_fn(self.cur_loc.func.name.clone()),
-        })
+    #[test]
+    fn get_unsat_core() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        let x = <BtorBackend as Backend>::BV::new(state.solver.clone(), 32, Some("x"));
+        let y = <BtorBackend as Backend>::BV::new(state.solver.clone(), 32, Some("y"));
+        let x_gt_5 = x.ugt(&state.bv_from_u32(5, 32));
+        state.assert(&x_gt_5)?;
+        state.assert(&y._eq(&state.bv_from_u32(3, 32)))?;
+        // no core while the path is sat
+        assert_eq!(state.get_unsat_core()?, None);
+
+        // x > 5 contradicts x < 2
+        let x_lt_2 = x.ult(&state.bv_from_u32(2, 32));
+        state.assert(&x_lt_2)?;
+        let core = state
+            .get_unsat_core()?
+            .expect("expected a core for an unsat path");
+        assert!(!core.is_empty());
+        assert!(core.len() <= 3);
+        assert!(core.contains(&x_lt_2));
+
+        Ok(())
     }
 
-    /// Record leaving the current function. Returns the `Callsite` at which the
-    /// current function was called, or `None` if the current function was the
-    /// top-level function.
-    ///
-    /// Also restores the caller's local variables.
-    pub fn pop_callsite(&mut self) -> Option<Callsite<'p>> {
-        if let Some(StackFrame {
-            callsite,
-            restore_info,
-        }) = self.stack.pop()
-        {
-            self.varmap.restore_fn_vars(restore_info);
-            Some(callsite)
-        } else {
-            None
+    #[test]
+    fn serialize_constraints() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // x > 3 && x + y == 10 && (y & 1) == 1
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        let y = state.new_bv_with_name(Name::from("y"), 64)?;
+        state.assert(&x.ugt(&state.bv_from_u64(3, 64)))?;
+        state.assert(&x.add(&y)._eq(&state.bv_from_u64(10, 64)))?;
+        state.assert(&y.and(&state.one(64))._eq(&state.one(64)))?;
+        let bytes = state.serialize_constraints()?;
+
+        let project = blank_project(
+            "test_mod",
+            blank_function("test_func", vec![Name::from("test_bb")]),
+        );
+        let mut fresh = blank_state(&project, "test_func");
+        fresh.deserialize_constraints(&bytes)?;
+        assert!(fresh.sat()?);
+        assert_eq!(fresh.sat()?, state.sat()?);
+
+        // the deserialized constraints still restrict the variables the same way
+        let x_symbol = x.get_symbol().unwrap().to_owned();
+        let fresh_x = Btor::get_bv_by_symbol(fresh.solver.clone(), &x_symbol).unwrap();
+        let x_10 = fresh_x._eq(&boolector::BV::from_u64(fresh.solver.clone(), 10, 64));
+        assert!(!fresh.sat_with_extra_constraints(std::iter::once(&x_10))?);
+        let x_9 = fresh_x._eq(&boolector::BV::from_u64(fresh.solver.clone(), 9, 64));
+        assert!(fresh.sat_with_extra_constraints(std::iter::once(&x_9))?);
+
+        // adding an unsat constraint before serializing gives an unsat state
+        state.assert(&x.ult(&state.bv_from_u64(4, 64)))?;
+        let bytes = state.serialize_constraints()?;
+        let project = blank_project(
+            "test_mod",
+            blank_function("test_func", vec![Name::from("test_bb")]),
+        );
+        let mut fresh = blank_state(&project, "test_func");
+        fresh.deserialize_constraints(&bytes)?;
+        assert!(!fresh.sat()?);
+
+        // malformed bytes are rejected
+        let mut fresh = blank_state(&project, "test_func");
+        assert!(fresh
+            .deserialize_constraints(&bytes[.. bytes.len() - 1])
+            .is_err());
+
+        // a variable which the solver substituted away still has its symbol
+        // and its constraint
+        let mut state = blank_state(&project, "test_func");
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        let y = state.new_bv_with_name(Name::from("y"), 64)?;
+        state.assert(&x._eq(&y.add(&state.one(64))))?;
+        assert!(state.sat()?);
+        let bytes = state.serialize_constraints()?;
+        let mut fresh = blank_state(&project, "test_func");
+        fresh.deserialize_constraints(&bytes)?;
+        let fresh_bv = |bv: &<BtorBackend as Backend>::BV| {
+            Btor::get_bv_by_symbol(fresh.solver.clone(), bv.get_symbol().unwrap()).unwrap()
+        };
+        assert!(fresh.bvs_must_be_equal(&fresh_bv(&x), &fresh_bv(&y).add(&fresh.one(64)))?);
+
+        // constraints on memory, on variables without a symbol, and too large
+        // for `Debug` to print in full are serialized too
+        let mut state = blank_state(&project, "test_func");
+        let p = state.new_bv_with_name(Name::from("p"), 64)?;
+        let addr = state.bv_from_u64(0x1000, 64);
+        state.assert(&p._ne(&state.zero(64)))?;
+        state.assert(&state.read(&p, 8)?._eq(&state.bv_from_u64(7, 8)))?;
+        let anonymous = boolector::BV::new(state.solver.clone(), 64, None);
+        let mut sum = anonymous.clone();
+        for i in 0 .. 200 {
+            sum = sum.add(&state.new_bv_with_name(Name::from(format!("z{}", i)), 64)?);
+        }
+        assert_eq!(format!("{:?}", sum), "<output too large to display>");
+        state.assert(&sum._eq(&p))?;
+        let bytes = state.serialize_constraints()?;
+        let mut fresh = blank_state(&project, "test_func");
+        fresh.deserialize_constraints(&bytes)?;
+        assert!(fresh.sat()?);
+        // the memory read at `p` is still 7
+        state.assert(&p._eq(&addr))?;
+        state.assert(&state.read(&addr, 8)?._eq(&state.bv_from_u64(8, 8)))?;
+        assert!(!state.sat()?);
+        let bytes = state.serialize_constraints()?;
+        let mut fresh = blank_state(&project, "test_func");
+        fresh.deserialize_constraints(&bytes)?;
+        assert!(!fresh.sat()?);
+
+        // a variable with the same symbol as one in the serialized constraints
+        // can't be created again
+        let mut fresh = blank_state(&project, "test_func");
+        fresh.new_bv_with_name(Name::from("p"), 64)?;
+        match fresh.deserialize_constraints(&bytes) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
         }
+
+        Ok(())
     }
 
-    /// Returns the current callstack depth. `0` indicates we're in the toplevel
-    /// function, `1` indicates we're in a function directly called by the
-    /// toplevel function, etc.
-    pub fn current_callstack_depth(&self) -> usize {
-        self.stack.len()
+    #[test]
+    fn merge() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let funcname = state.cur_loc.func.name.clone();
+        let x = Name::from("x");
+
+        // one path sets x to 1, the other sets x to 2
+        let mut other = state.clone();
+        state.assign_bv_to_name(x.clone(), state.bv_from_u64(1, 64))?;
+        other.assign_bv_to_name(x.clone(), other.bv_from_u64(2, 64))?;
+
+        let path_pred = state.new_bv_with_name(Name::from("path_pred"), 1)?;
+        state.merge(other, &path_pred)?;
+
+        // in the merged state, x can be either value
+        let solutions = state
+            .get_possible_solutions_for_irname(&funcname, &x, 2)?
+            .as_u64_solutions();
+        assert_eq!(
+            solutions,
+            Some(PossibleSolutions::Exactly(vec![1, 2].into_iter().collect()))
+        );
+
+        // and the path predicate selects between them
+        path_pred.not().assert();
+        let solutions = state
+            .get_possible_solutions_for_irname(&funcname, &x, 2)?
+            .as_u64_solutions();
+        assert_eq!(
+            solutions,
+            Some(PossibleSolutions::Exactly(std::iter::once(2).collect()))
+        );
+
+        Ok(())
     }
 
-    /// Save the current state, about to enter the `BasicBlock` with the given `Name` (which must be
-    /// in the same `Module` and `Function` as `state.cur_loc`), as a backtracking point.
-    /// The constraint will be added only if we end up backtracking to this point, and only then.
-    pub fn save_backtracking_point(&mut self, bb_to_enter: &Name, constraint: B::BV) {
-        debug!(
-            "Saving a backtracking point, which would enter bb {:?} with constraint {:?}",
-            bb_to_enter, constraint
+    #[test]
+    fn merge_retracts_path_constraints() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        state.save_backtracking_point(&Name::from("test_bb"), state.bv_from_bool(true))?;
+
+        // one path constrains x to 1, the other to 2
+        let other = state.clone();
+        state.assert(&x._eq(&state.bv_from_u64(1, 64)))?;
+        other.assert(&x._eq(&other.bv_from_u64(2, 64)))?;
+        assert!(!state.sat()?);
+
+        let path_pred = state.new_bv_with_name(Name::from("path_pred"), 1)?;
+        state.merge(other, &path_pred)?;
+
+        // in the merged state, x can again be either value
+        assert!(state.sat()?);
+        let solutions = state
+            .get_possible_solutions_for_bv(&x, 2)?
+            .as_u64_solutions();
+        assert_eq!(
+            solutions,
+            Some(PossibleSolutions::Exactly(vec![1, 2].into_iter().collect()))
         );
-        let bb_to_enter = self
-            .cur_loc
-            .func
-            .get_bb_by_name(&bb_to_enter)
-            .unwrap_or_else(|| {
-                panic!(
-                    "Failed to find bb named {} in function {:?}",
-                    bb_to_enter, self.cur_loc.func.name
-                )
-            });
-        let backtrack_loc = Location {
-            module: self.cur_loc.module,
-            func: self.cur_loc.func,
-            bb: bb_to_enter,
-            instr: BBInstrIndex::Instr(0),
-            source_loc: None,
-        };
-        self.save_backtracking_point_at_location(backtrack_loc, constraint);
+
+        // and backtracking still works
+        assert!(state.revert_to_backtracking_point()?);
+        assert!(state.sat()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge_rejects_unretractable_constraints() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+
+        // no solver context was pushed before the paths diverged
+        let other = state.clone();
+        state.assert(&x._eq(&state.bv_from_u64(1, 64)))?;
+        other.assert(&x._eq(&other.bv_from_u64(2, 64)))?;
+
+        let path_pred = state.new_bv_with_name(Name::from("path_pred"), 1)?;
+        match state.merge(other, &path_pred) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        Ok(())
     }
 
-    /// Internal version of `save_backtracking_point()` which takes an arbitrary
-    /// `Location` instead of just the basic block to start at.
-    ///
-    /// Also it doesn't require `&mut self`. This allows us to save backtracking
-    /// points even when we're inside methods that only have `&self`.
-    fn save_backtracking_point_at_location(
-        &self,
-        loc_to_start_at: Location<'p>,
-        constraint: B::BV,
-    ) {
-        self.solver.push(1);
-        self.backtrack_points.borrow_mut().push(BacktrackPoint {
-            loc: loc_to_start_at,
-            stack: self.stack.clone(),
-            constraint,
-            varmap: self.varmap.clone(),
-            mem: self.mem.borrow().clone(),
-            path_len: self.path.len(),
-        });
+    #[test]
+    fn deref() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // store a value, and a pointer to that value
+        let val_addr = state.allocate(32_u64).into_bv();
+        let ptr_addr = state.allocate(POINTER_SIZE_BITS as u64).into_bv();
+        state.write(&val_addr, state.bv_from_u32(0x1234_5678, 32))?;
+        state.write(&ptr_addr, val_addr.clone())?;
+
+        let ptr = state.read_pointer(&ptr_addr)?;
+        assert_eq!(ptr.get_width(), POINTER_SIZE_BITS as u32);
+        assert_eq!(
+            state.get_a_solution_for_bv(&ptr)?.and_then(|s| s.as_u64()),
+            val_addr.as_u64()
+        );
+        let val = state.deref(&ptr_addr, 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(0x1234_5678)
+        );
+
+        Ok(())
     }
 
-    /// returns `Ok(true)` if the operation was successful, `Ok(false)` if there are
-    /// no saved backtracking points, or `Err` for other errors
-    pub fn revert_to_backtracking_point(&mut self) -> Result<bool> {
-        if let Some(bp) = self.backtrack_points.borrow_mut().pop() {
-            debug!("Reverting to backtracking point {}", bp);
-            self.solver.pop(1);
-            self.varmap = bp.varmap;
-            self.mem.replace(bp.mem);
-            self.stack = bp.stack;
-            self.path.truncate(bp.path_len);
-            self.cur_loc = bp.loc;
-            bp.constraint.assert()?;
-            Ok(true)
-        } else {
-            Ok(false)
+    #[test]
+    fn dump_memory() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let buffer: Vec<u8> = vec![0xde, 0xad, 0xbe, 0xef, 0x00, 0x01, 0x7f, 0x80, 0xff];
+        let (addr, raw_addr) = state.allocate_concrete(buffer.len() as u64 * 8);
+        for (i, &byte) in buffer.iter().enumerate() {
+            let byte_addr = addr.add(&state.bv_from_u64(i as u64, 64));
+            state.write(&byte_addr, state.bv_from_u32(u32::from(byte), 8))?;
         }
+        assert_eq!(
+            state.dump_memory(raw_addr, buffer.len())?,
+            Some(buffer.clone())
+        );
+        assert_eq!(
+            state.dump_memory(raw_addr + 2, 3)?,
+            Some(buffer[2 .. 5].to_vec())
+        );
+
+        // a region with unconstrained contents isn't uniquely determined
+        let (_, raw_addr) = state.allocate_concrete(64_u64);
+        assert_eq!(state.dump_memory(raw_addr, 8)?, None);
+
+        Ok(())
     }
 
-    /// returns the number of saved backtracking points
-    pub fn count_backtracking_points(&self) -> usize {
-        self.backtrack_points.borrow().len()
+    #[test]
+    fn memmove() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // bytes 01 02 03 04 05 06 07 08, in order in memory
+        let addr = state.allocate(64_u64).into_bv();
+        state.write(&addr, state.bv_from_u64(0x0807_0605_0403_0201, 64))?;
+
+        // moving the first 7 bytes up by one, as C `memmove()` would, gives 01 01 02 03 04 05 06 07
+        let dst = addr.add(&state.one(64));
+        state.memmove(&dst, &addr, 7)?;
+        let val = state.read(&addr, 64)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(0x0706_0504_0302_0101)
+        );
+
+        // and moving them back down gives 01 02 03 04 05 06 07 07
+        state.memmove(&addr, &dst, 7)?;
+        let val = state.read(&addr, 64)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(0x0707_0605_0403_0201)
+        );
+
+        Ok(())
     }
 
-    /// returns a `String` containing a formatted view of the current backtrace
-    /// (in terms of LLVM locations, and possibly also source locations depending
-    /// on the `Config`)
-    pub fn pretty_backtrace(&self) -> String {
-        let mut locdescrs = std::iter::once(LocationDescription::from(self.cur_loc.clone()))
-            .chain(
-                self.stack
-                    .iter()
-                    .rev()
-                    .map(|frame| LocationDescription::from(frame.callsite.loc.clone())),
-            )
-            .collect::<Vec<LocationDescription>>();
-        for locdescr in locdescrs.iter_mut() {
-            self.demangle_locdescr(locdescr);
-        }
-        locdescrs
-            .into_iter()
-            .zip(1 ..)
-            .map(|(locdescr, framenum)| {
-                let pretty_locdescr = if self.config.print_module_name {
-                    locdescr.to_string_with_module()
-                } else {
-                    locdescr.to_string_no_module()
-                };
-                let mut frame_string = format!("  #{}: {}\n", framenum, pretty_locdescr);
-                match locdescr.source_loc {
-                    Some(source_loc) if self.config.print_source_info => {
-                        frame_string
-                            .push_str(&format!("         ({})\n", pretty_source_loc(source_loc)));
-                    },
-                    _ => {},
-                };
-                frame_string
-            })
-            .collect()
+    #[test]
+    fn memset_elements() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let addr = state.allocate(80_u64).into_bv();
+        state.write(&addr, state.zero(80))?;
+        let val = state.bv_from_u32(0xabcd, 16);
+        state.memset_elements(&addr, &val, 4, 2)?;
+
+        // element 2 was set, and the bytes after the last element weren't
+        let elem2 = state.read(&addr.add(&state.bv_from_u64(4, 64)), 16)?;
+        assert_eq!(
+            state
+                .get_a_solution_for_bv(&elem2)?
+                .and_then(|s| s.as_u64()),
+            Some(0xabcd)
+        );
+        let after = state.read(&addr.add(&state.bv_from_u64(8, 64)), 16)?;
+        assert_eq!(
+            state
+                .get_a_solution_for_bv(&after)?
+                .and_then(|s| s.as_u64()),
+            Some(0)
+        );
+
+        // the value must be exactly one element wide
+        assert!(state.memset_elements(&addr, &val, 4, 4).is_err());
+
+        Ok(())
     }
 
-    /// returns a `String` containing a formatted view of the full path which led
-    /// to this point, in terms of LLVM locations
-    pub fn pretty_path_llvm(&self) -> String {
-        let mut path_str = String::new();
-        for path_entry in self.get_path() {
-            path_str.push_str(&format!(
-                "  {}\n",
-                if self.config.print_module_name {
-                    path_entry.to_string_with_module()
-                } else {
-                    path_entry.to_string_no_module()
-                },
-            ));
-        }
-        path_str
+    #[test]
+    fn regions_equal() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // a region with symbolic contents, and a copy of it
+        let src = state.allocate(128_u64).into_bv();
+        let dst = state.allocate(128_u64).into_bv();
+        let contents = state.new_bv_with_name(Name::from("contents"), 128)?;
+        state.write(&src, contents)?;
+        state.memmove(&dst, &src, 16)?;
+        assert!(state.check_regions_equal(&dst, &src, 16)?);
+
+        // after overwriting one byte of the copy, the regions can differ,
+        // though the bytes before it must still be equal
+        let corrupted = dst.add(&state.bv_from_u64(5, 64));
+        let corrupted_byte = state.new_bv_with_name(Name::from("corrupted"), 8)?;
+        state.write(&corrupted, corrupted_byte)?;
+        assert!(!state.check_regions_equal(&dst, &src, 16)?);
+        assert!(state.check_regions_equal(&dst, &src, 5)?);
+
+        // unless we assert that they're equal
+        state.assert_regions_equal(&dst, &src, 16)?;
+        assert!(state.check_regions_equal(&dst, &src, 16)?);
+        assert!(state.sat()?);
+
+        Ok(())
     }
 
-    /// returns a `String` containing a formatted view of the full path which led
-    /// to this point, in terms of source locations
-    pub fn pretty_path_source(&self) -> String {
-        let mut path_str = String::new();
-        let mut source_locs = self
-            .get_path()
-            .iter()
-            .flat_map(|path_entry| path_entry.get_all_source_locs());
-        // handle the first one special, so we can print this help message if necessary
-        match source_locs.next() {
-            None => {
-                path_str.push_str("  No source locations available in the path.\n");
-                path_str.push_str(
-                    "  This may be because the LLVM bitcode was not compiled with debuginfo.\n",
-                );
-                path_str.push_str(
-                    "  To compile C/C++ or Rust sources with debuginfo, pass the `-g` flag\n",
-                );
-                path_str.push_str("    to `clang`, `clang++`, or `rustc`.\n");
-            },
-            Some(first_source_loc) => {
-                path_str.push_str(&format!("  {}\n", pretty_source_loc(first_source_loc)))
-            },
-        }
-        for source_loc in source_locs {
-            path_str.push_str(&format!("  {}\n", pretty_source_loc(source_loc)));
+    #[test]
+    fn memory_equivalent() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+
+        // each state has the same input `x`, and writes something computed from it to memory
+        let new_state = || -> Result<(State<BtorBackend>, <BtorBackend as Backend>::BV, u64)> {
+            let mut state = blank_state(&project, "test_func");
+            let x = state.new_bv_with_name(Name::from("x"), 64)?;
+            let addr = state.allocate(64_u64).as_bv().as_u64().unwrap();
+            Ok((state, x, addr))
+        };
+        let (mut a, x, addr) = new_state()?;
+        let ptr = a.bv_from_u64(addr, 64);
+        a.write(&ptr, x.add(&a.one(64)))?;
+        let (mut b, x, b_addr) = new_state()?;
+        b.write(&ptr, b.one(64).add(&x))?;
+        let (mut c, x, c_addr) = new_state()?;
+        c.write(&ptr, x.add(&c.bv_from_u64(2, 64)))?;
+        assert_eq!(addr, b_addr);
+        assert_eq!(addr, c_addr);
+
+        // x + 1 and 1 + x are the same for every x
+        assert!(a.memory_equivalent(&b, &[(addr, 8)])?);
+        assert!(b.memory_equivalent(&a, &[(addr, 8)])?);
+        // but x + 1 and x + 2 differ, in the lowest byte at least
+        assert!(!a.memory_equivalent(&c, &[(addr, 8)])?);
+        assert!(!a.memory_equivalent(&c, &[(addr, 1)])?);
+
+        // x * 2 is x + 1 only when x is 1
+        let (mut d, x, _) = new_state()?;
+        d.write(&ptr, x.mul(&d.bv_from_u64(2, 64)))?;
+        assert!(!a.memory_equivalent(&d, &[(addr, 8)])?);
+        d.assert(&x._eq(&d.one(64)))?;
+        assert!(a.memory_equivalent(&d, &[(addr, 8)])?);
+
+        // single bytes, written to part of a memory cell, and copied from
+        // bytes which were never written
+        let byte_ptr =
+            |state: &State<BtorBackend>, offset: u64| state.bv_from_u64(addr + offset, 64);
+        let (mut e, x, _) = new_state()?;
+        e.write(&byte_ptr(&e, 1), x.slice(7, 0))?;
+        let original = e.read(&byte_ptr(&e, 4), 8)?;
+        e.write(&byte_ptr(&e, 2), original)?;
+        let (mut f, x, _) = new_state()?;
+        let original = f.read(&byte_ptr(&f, 4), 8)?;
+        f.write(&byte_ptr(&f, 2), original)?;
+        f.write(
+            &byte_ptr(&f, 1),
+            x.and(&f.bv_from_u64(0xff, 64)).slice(7, 0),
+        )?;
+        assert!(e.memory_equivalent(&f, &[(addr, 8)])?);
+        assert!(f.memory_equivalent(&e, &[(addr, 16)])?);
+        // the byte which was never written at `addr + 2` needn't equal the one at `addr + 4`
+        let (mut g, x, _) = new_state()?;
+        g.write(&byte_ptr(&g, 1), x.slice(7, 0))?;
+        assert!(g.memory_equivalent(&e, &[(addr, 2)])?);
+        assert!(!g.memory_equivalent(&e, &[(addr, 8)])?);
+        // nor the byte which was never written at `addr` the lowest byte of `x + 1`
+        assert!(!g.memory_equivalent(&a, &[(addr, 1)])?);
+
+        // a region past the end of the address space is an error
+        match g.memory_equivalent(&a, &[(addr, u64::MAX)]) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
         }
-        path_str
+
+        Ok(())
     }
 
-    /// returns a `String` containing a formatted view of the full path which led
-    /// to this point, in terms of both LLVM and source locations (interleaved
-    /// appropriately)
-    pub fn pretty_path_interleaved(&self) -> String {
-        let mut path_str = String::new();
-        for path_entry in self.get_path() {
-            path_str.push_str(&format!(
-                "  {}:\n",
-                if self.config.print_module_name {
-                    path_entry.to_string_with_module()
-                } else {
-                    path_entry.to_string_no_module()
-                },
-            ));
-            let mut source_locs = path_entry.get_all_source_locs();
-            // handle the first one special, so we can print this help message if necessary
-            match source_locs.next() {
-                None => path_str.push_str("    (no source locations available)\n"),
-                Some(first_source_loc) => {
-                    path_str.push_str(&format!("    {}\n", pretty_source_loc(first_source_loc)))
-                },
-            }
-            for source_loc in source_locs {
-                path_str.push_str(&format!("    {}\n", pretty_source_loc(source_loc)));
-            }
-        }
-        path_str
+    #[test]
+    fn allocate_at() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // reserve 0x100 bytes at 0x1000
+        let ptr = state.allocate_at(0x1000, 0x800_u64)?;
+        assert_eq!(ptr.as_bv().as_u64(), Some(0x1000));
+        state.write(ptr.as_bv(), state.bv_from_u32(0x1234, 32))?;
+        let val = state.read(ptr.as_bv(), 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(0x1234)
+        );
+
+        // an overlapping reservation fails; a disjoint one succeeds
+        assert!(state.allocate_at(0x10ff, 8_u64).is_err());
+        assert!(state.allocate_at(0xf00, 0x800_u64).is_err());
+        assert!(state.allocate_at(0x1100, 8_u64).is_ok());
+
+        // the null address can't be reserved
+        assert!(state.allocate_at(0, 8_u64).is_err());
+
+        // nor can regions which don't respect memory cell boundaries
+        assert!(state.allocate_at(0x2006, 32_u64).is_err());
+        assert!(state.allocate_at(0x2004, 128_u64).is_err());
+        assert!(state.allocate_at(0x2004, 32_u64).is_ok());
+
+        // later allocations don't overlap a reservation above the allocator's cursor
+        let high = (state.alloc.high_water_mark() + 0x40) & !0x7;
+        state.allocate_at(high, 0x800_u64)?;
+        let next = state.allocate(64_u64).into_bv().as_u64().unwrap();
+        assert!(next >= high + 0x100);
+
+        Ok(())
     }
 
-    /// Attempt to demangle the given `funcname` as appropriate based on the
-    /// `Config`.
-    ///
-    /// If this fails to demangle `funcname`, it just returns a copy of
-    /// `funcname` unchanged.
-    pub fn demangle(&self, funcname: &str) -> String {
-        match self.config.demangling {
-            Some(demangling) => demangling.maybe_demangle(funcname),
-            None => panic!("Demangling shouldn't be None here"), // we should resolve it to Some() in the State constructor
+    #[test]
+    fn address_spaces() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // the allocators for different address spaces are independent: the
+        // first allocation in a new address space gets the same address as
+        // the first allocation in another new address space
+        let ptr1 = state.allocate_in_space(1, 32_u64);
+        let ptr2 = state.allocate_in_space(2, 32_u64);
+        assert_eq!(ptr1.as_bv().as_u64(), ptr2.as_bv().as_u64());
+        let addr = ptr1.into_bv();
+
+        // writes to the same numeric address in different address spaces don't alias
+        state.write(&addr, state.bv_from_u32(0, 32))?;
+        state.write_in_space(1, &addr, state.bv_from_u32(1, 32))?;
+        state.write_in_space(2, &addr, state.bv_from_u32(2, 32))?;
+        for space in 0 .. 3 {
+            let val = state.read_in_space(space, &addr, 32)?;
+            assert_eq!(
+                state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+                Some(u64::from(space))
+            );
         }
+
+        // address space 0 is the ordinary memory
+        let val = state.read(&addr, 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(0)
+        );
+
+        // repeated reads from a never-written address space are consistent
+        let a = state.read_in_space(3, &addr, 32)?;
+        let b = state.read_in_space(3, &addr, 32)?;
+        assert!(state.bvs_must_be_equal(&a, &b)?);
+
+        Ok(())
     }
 
-    /// Attempts to demangle the function name in the `LocationDescription`, as
-    /// appropriate based on the `Config`.
-    fn demangle_locdescr(&self, locdescr: &mut LocationDescription) {
-        locdescr.funcname = self.demangle(&locdescr.funcname);
+    #[test]
+    fn merge_address_spaces() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let addr = state.allocate_in_space(1, 32_u64).into_bv();
+
+        // only the other path uses address space 1, so merging has to create
+        // a second memory for it on the shared solver
+        let mut other = state.clone();
+        other.write_in_space(1, &addr, other.bv_from_u32(7, 32))?;
+        let path_pred = state.new_bv_with_name(Name::from("path_pred"), 1)?;
+        state.merge(other, &path_pred)?;
+
+        path_pred.not().assert();
+        let val = state.read_in_space(1, &addr, 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(7)
+        );
+
+        Ok(())
     }
 
-    /// Get the most recent `BV` created for each `Name` in the current function.
-    /// Returns pairs of the `Name` and the `BV` assigned to that `Name`.
-    ///
-    /// Returned pairs will be sorted by `Name`.
-    pub fn all_vars_in_cur_fn(&self) -> impl Iterator<Item = (&Name, &B::BV)> {
-        self.varmap.get_all_vars_in_fn(&self.cur_loc.func.name)
+    #[test]
+    fn pointer_offset() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        // write a 64-bit value, and read back its two halves through offset pointers
+        let ptr = state.allocate(64_u64);
+        state.write(ptr.as_bv(), state.bv_from_u64(0x1234_5678_9abc_def0, 64))?;
+        let hi_ptr = ptr.offset(4);
+        let hi = state.read(hi_ptr.as_bv(), 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&hi)?.and_then(|s| s.as_u64()),
+            Some(0x1234_5678)
+        );
+        let lo = state.read(hi_ptr.offset(-4).as_bv(), 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&lo)?.and_then(|s| s.as_u64()),
+            Some(0x9abc_def0)
+        );
+
+        // symbolic offsets work too, and the difference between pointers is an integer
+        let sym_offset = state.new_bv_with_name(Name::from("offset"), 64)?;
+        state.assert(&sym_offset._eq(&state.bv_from_u64(4, 64)))?;
+        let sym_ptr = ptr.offset_by(&sym_offset);
+        let hi = state.read(sym_ptr.as_bv(), 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&hi)?.and_then(|s| s.as_u64()),
+            Some(0x1234_5678)
+        );
+        assert_eq!(hi_ptr.diff(&ptr).as_u64(), Some(4));
+
+        Ok(())
     }
 
-    /// returns a `String` describing a set of satisfying assignments for all variables
-    pub fn current_assignments_as_pretty_string(&self) -> Result<String> {
-        self.solver.set_opt(BtorOption::ModelGen(ModelGen::All));
-        let string = if self.sat()? {
-            let printed = self.solver.print_model();
-            let sorted = itertools::sorted(printed.lines());
-            sorted.fold(String::new(), |s, line| s + "\n" + line)
-        } else {
-            "<state is unsatisfiable>".to_owned()
-        };
-        self.solver
-            .set_opt(BtorOption::ModelGen(ModelGen::Disabled));
-        Ok(string)
-    }
+    #[test]
+    fn walk_linked_list() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
 
-    /// Returns a `String` describing both the error and the context in which it
-    /// occurred (backtrace, full path to error, variable values at the point of
-    /// error, etc). Exactly which information is included is partially dependent
-    /// on the environment variables `HAYBALE_DUMP_PATH` and `HAYBALE_DUMP_VARS`,
-    /// as explained in the message.
-    pub fn full_error_message_with_context(&self, e: Error) -> String {
-        let mut err_msg = format!("{}\n\n", e);
-        err_msg.push_str(&format!("Backtrace:\n{}\n", self.pretty_backtrace()));
-        match PathDumpType::get_from_env_var() {
-            PathDumpType::None => {
-                err_msg.push_str("note: For a dump of the path that led to this error, rerun with the environment variable `HAYBALE_DUMP_PATH` set to:\n");
-                err_msg
-                    .push_str("        `LLVM` for a list of the LLVM basic blocks in the path\n");
-                err_msg.push_str(
-                    "        `SRC` for a list of the source-language locations in the path\n",
-                );
-                err_msg.push_str("        `BOTH` for both of the above\n");
-                err_msg.push_str(
-                    "      To get source-language locations, the LLVM bitcode must also contain\n",
-                );
-                err_msg.push_str("      debuginfo. For example, C/C++ or Rust sources must be compiled with the\n");
-                err_msg.push_str("      `-g` flag to `clang`, `clang++`, or `rustc`.\n");
-            },
-            PathDumpType::LLVM => {
-                err_msg.push_str("LLVM path to error:\n");
-                err_msg.push_str(&self.pretty_path_llvm());
-                err_msg.push_str("note: to also get a dump of the source-language locations in this path, rerun with `HAYBALE_DUMP_PATH=BOTH`.\n");
-            },
-            PathDumpType::Source => {
-                err_msg.push_str("Source-language path to error:\n");
-                err_msg.push_str(&self.pretty_path_source());
-                err_msg.push_str("note: to also get a dump of the LLVM basic blocks in this path, rerun with `HAYBALE_DUMP_PATH=BOTH`.\n");
-            },
-            PathDumpType::Interleaved => {
-                err_msg.push_str("Full path to error:\n");
-                err_msg.push_str(&self.pretty_path_interleaved());
-            },
+        // three nodes, each a 32-bit value followed (at offset 8) by a next pointer
+        let node_bits = 64 + POINTER_SIZE_BITS as u64;
+        let nodes: Vec<_> = (0 .. 3)
+            .map(|_| state.allocate(node_bits).into_bv())
+            .collect();
+        let next_offset = state.bv_from_u64(8, 64);
+        for (i, node) in nodes.iter().enumerate() {
+            state.write(node, state.bv_from_u32(i as u32, 32))?;
+            let next = nodes
+                .get(i + 1)
+                .cloned()
+                .unwrap_or_else(|| state.zero(POINTER_SIZE_BITS as u32));
+            state.write(&node.add(&next_offset), next)?;
         }
-        if std::env::var("HAYBALE_DUMP_VARS") == Ok("1".to_owned()) {
-            err_msg
-                .push_str("\nLatest values of variables at time of error, in current function:\n");
-            err_msg.push_str("(Ignore any values from past the point of error, they may be from other paths)\n\n");
-            for (varname, value) in self.all_vars_in_cur_fn() {
-                err_msg.push_str(&format!("  {}: {:?}\n", varname, value));
-            }
-        } else {
-            err_msg.push_str("\nnote: For a dump of variable values at time of error, rerun with `HAYBALE_DUMP_VARS=1` environment variable.\n");
+
+        let walked = state.walk_linked_list(&nodes[0], 8, 10)?;
+        assert_eq!(walked.len(), 3);
+        for (walked, node) in walked.iter().zip(nodes.iter()) {
+            assert_eq!(
+                state
+                    .get_a_solution_for_bv(walked)?
+                    .and_then(|s| s.as_u64()),
+                node.as_u64()
+            );
         }
-        err_msg.push_str("\nnote: to enable detailed logs, ensure that debug-level logging messages are visible.\n");
-        err_msg.push_str("  See the documentation for your chosen logging backend (e.g., env_logger, log4rs, etc)\n");
-        err_msg.push_str("  for help with configuration.\n");
-        err_msg.push_str("\n");
-        err_msg
+
+        // the walk respects `max_nodes`
+        assert_eq!(state.walk_linked_list(&nodes[0], 8, 2)?.len(), 2);
+
+        Ok(())
     }
-}
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
-enum PathDumpType {
-    /// Don't dump the path
-    None,
-    /// Dump just the LLVM path
-    LLVM,
-    /// Dump just the source path
-    Source,
-    /// Dump both LLVM and source path (interleaved)
-    Interleaved,
-}
+    #[test]
+    fn memory_trace() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let addr = state.allocate(32_u64).into_bv();
+        let addr_str = format!("{:#x}", addr.as_u64().unwrap());
+
+        state.enable_memory_tracing();
+        state.write(&addr, state.bv_from_u32(0x1234_5678, 32))?;
+        let _ = state.read(&addr, 16)?;
+
+        let trace = state.memory_trace();
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[0].kind, MemOpKind::Write);
+        assert_eq!(trace[0].addr, addr_str);
+        assert_eq!(trace[0].bits, 32);
+        assert_eq!(trace[1].kind, MemOpKind::Read);
+        assert_eq!(trace[1].addr, addr_str);
+        assert_eq!(trace[1].bits, 16);
 
-impl PathDumpType {
-    fn get_from_env_var() -> Self {
-        match std::env::var("HAYBALE_DUMP_PATH") {
-            Err(_) => Self::None,
-            Ok(mut val) => {
-                val.make_ascii_uppercase();
-                match val.deref() {
-                    "" => Self::None,
-                    "LLVM" => Self::LLVM,
-                    "SRC" => Self::Source,
-                    "BOTH" => Self::Interleaved,
-                    "1" => Self::Interleaved, // previous versions of `haybale` used HAYBALE_DUMP_PATH=1, we now treat that equivalently to `BOTH`
-                    _ => Self::Interleaved,
-                }
-            },
-        }
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::solver_utils::SolutionCount;
-    use crate::test_utils::*;
+    #[test]
+    fn allocate_concrete() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
 
-    // we don't include tests here for Memory, Alloc, VarMap, or Watchpoints; those are tested in their own modules.
-    // Instead, here we just test the nontrivial functionality that `State` has itself.
-    // We do repeat many of the tests from the `solver_utils` module, making sure that they also pass when
-    // we use the `State` interfaces.
+        let (ptr, addr) = state.allocate_concrete(64_u64);
+        assert_eq!(
+            state.get_a_solution_for_bv(&ptr)?.and_then(|s| s.as_u64()),
+            Some(addr)
+        );
+        assert_eq!(state.get_allocation_size(&ptr)?, Some(64));
+
+        // a second allocation gets a different address
+        let (_, addr2) = state.allocate_concrete(64_u64);
+        assert_ne!(addr, addr2);
+
+        Ok(())
+    }
 
     #[test]
-    fn sat() -> Result<()> {
+    fn signed_solution() -> Result<()> {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
         let project = blank_project("test_mod", func);
         let mut state = blank_state(&project, "test_func");
 
-        // empty state should be sat
-        assert_eq!(state.sat(), Ok(true));
+        let x = state.new_bv_with_name(Name::from("x"), 8)?;
+        x._eq(&state.bv_from_u32(0xFF, 8)).assert();
+        assert_eq!(state.get_a_solution_for_bv_signed(&x)?, Some(-1));
+        assert_eq!(
+            state.get_a_solution_for_bv(&x)?.and_then(|s| s.as_u64()),
+            Some(255)
+        );
 
-        // adding True constraint should still be sat
-        state.bv_from_bool(true).assert();
-        assert_eq!(state.sat(), Ok(true));
+        // positive values, and values of the full 64 bits
+        let y = state.bv_from_u32(0x7F, 8);
+        assert_eq!(state.get_a_solution_for_bv_signed(&y)?, Some(127));
+        let z = state.bv_from_i64(-5, 64);
+        assert_eq!(state.get_a_solution_for_bv_signed(&z)?, Some(-5));
 
-        // adding x > 0 constraint should still be sat
-        let x = state.new_bv_with_name(Name::from("x"), 64)?;
-        x.sgt(&state.zero(64)).assert();
-        assert_eq!(state.sat(), Ok(true));
+        Ok(())
+    }
+
+    #[test]
+    fn uninit_handler() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let addr = state.allocate(64_u64).into_bv();
+        let other_addr = state.allocate(64_u64).into_bv();
+
+        state.set_uninit_handler(UninitHandler::Custom(Rc::new(
+            |state: &State<BtorBackend>, _addr: &<BtorBackend as Backend>::BV, bits: u32| {
+                Ok(state.bv_from_u64(0xdead_beef, bits))
+            },
+        )));
+
+        // a fresh read yields the sentinel
+        let val = state.read(&addr, 32)?;
+        assert_eq!(val.as_u64(), Some(0xdead_beef));
+
+        // but a read from memory we've written doesn't
+        state.write(&other_addr, state.bv_from_u32(3, 32))?;
+        let val = state.read(&other_addr, 32)?;
+        assert_eq!(
+            state.get_a_solution_for_bv(&val)?.and_then(|s| s.as_u64()),
+            Some(3)
+        );
+
+        // with the `Error` handler, a fresh read is an error
+        state.set_uninit_handler(UninitHandler::Error);
+        assert_eq!(
+            state.read(&addr, 32),
+            Err(Error::UninitializedRead(addr.as_u64().unwrap()))
+        );
 
         Ok(())
     }
 
     #[test]
-    fn unsat() -> Result<()> {
+    fn const_bool() {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
         let project = blank_project("test_mod", func);
         let state = blank_state(&project, "test_func");
 
-        // adding False constraint should be unsat
-        state.bv_from_bool(false).assert();
+        // create llvm-ir constants true and false
+        let consttrue = Constant::Int { bits: 1, value: 1 };
+        let constfalse = Constant::Int { bits: 1, value: 0 };
+
+        // this should create BV values true and false
+        let bvtrue = state
+            .operand_to_bv(&Operand::ConstantOperand(consttrue))
+            .unwrap();
+        let bvfalse = state
+            .operand_to_bv(&Operand::ConstantOperand(constfalse))
+            .unwrap();
+
+        // check that the BV values are evaluated to true and false respectively
+        assert_eq!(
+            state
+                .get_a_solution_for_bv(&bvtrue)
+                .unwrap()
+                .expect("Expected a solution for bvtrue")
+                .as_bool()
+                .unwrap(),
+            true,
+        );
+        assert_eq!(
+            state
+                .get_a_solution_for_bv(&bvfalse)
+                .unwrap()
+                .expect("Expected a solution for bvfalse")
+                .as_bool()
+                .unwrap(),
+            false,
+        );
+
+        // assert the first one, which should be true, so we should still be sat
+        bvtrue.assert();
+        assert_eq!(state.sat(), Ok(true));
+
+        // assert the second one, which should be false, so we should be unsat
+        bvfalse.assert();
         assert_eq!(state.sat(), Ok(false));
+    }
+
+    #[test]
+    fn influencing_inputs() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let a_name = Name::from("a");
+        let b_name = Name::from("b");
+        let a = state.new_bv_with_name(a_name.clone(), 32)?;
+        let b = state.new_bv_with_name(b_name.clone(), 32)?;
+        let inputs = [(&a_name, 32), (&b_name, 32)];
+
+        let output = a.add(&state.one(32));
+        assert_eq!(
+            state.influencing_inputs(&output, &inputs)?,
+            vec![a_name.clone()]
+        );
+        let output = b.mul(&a);
+        assert_eq!(
+            state.influencing_inputs(&output, &inputs)?,
+            vec![a_name.clone(), b_name.clone()]
+        );
+        let output = state.bv_from_u32(7, 32);
+        assert!(state.influencing_inputs(&output, &inputs)?.is_empty());
+
+        // a wrong width, or a nonexistent input, is an error
+        assert!(state.influencing_inputs(&output, &[(&a_name, 8)]).is_err());
+        assert!(state
+            .influencing_inputs(&output, &[(&Name::from("c"), 32)])
+            .is_err());
 
         Ok(())
     }
 
     #[test]
-    fn unsat_with_extra_constraints() -> Result<()> {
+    fn export_testcase() -> Result<()> {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
         let project = blank_project("test_mod", func);
         let mut state = blank_state(&project, "test_func");
 
-        // adding x > 3 constraint should still be sat
-        let x = state.new_bv_with_name(Name::from("x"), 64)?;
-        x.ugt(&state.bv_from_u64(3, 64)).assert();
-        assert_eq!(state.sat(), Ok(true));
+        // x + y == 10 and x == 3
+        let x = state.new_bv_with_name(Name::from("x"), 32)?;
+        let y = state.new_bv_with_name(Name::from("y"), 16)?;
+        x.add(&y.zext(16))._eq(&state.bv_from_u32(10, 32)).assert();
+        x._eq(&state.bv_from_u32(3, 32)).assert();
 
-        // adding x < 3 constraint should make us unsat
-        let bad_constraint = x.ult(&state.bv_from_u64(3, 64));
+        let testcase = state.export_testcase(&[(&Name::from("x"), 32), (&Name::from("y"), 16)])?;
         assert_eq!(
-            state.sat_with_extra_constraints(std::iter::once(&bad_constraint)),
-            Ok(false)
+            testcase.inputs,
+            vec![
+                TestCaseInput {
+                    name: "x".to_owned(),
+                    bits: 32,
+                    value: 3
+                },
+                TestCaseInput {
+                    name: "y".to_owned(),
+                    bits: 16,
+                    value: 7
+                },
+            ]
         );
+        // the function itself was allocated when the `State` was created
+        assert!(!testcase.allocations.is_empty());
 
-        // the state itself should still be sat, extra constraints weren't permanently added
-        assert_eq!(state.sat(), Ok(true));
+        // a mismatched width should be rejected
+        match state.export_testcase(&[(&Name::from("x"), 64)]) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        // as should an input which doesn't exist
+        match state.export_testcase(&[(&Name::from("z"), 32)]) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn export_testcase_json() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 32)?;
+        let y = state.new_bv_with_name(Name::from("y"), 32)?;
+        x._eq(&state.bv_from_u32(1234, 32)).assert();
+        y._eq(&x.add(&state.bv_from_u32(1, 32))).assert();
+
+        let testcase = state.export_testcase(&[(&Name::from("x"), 32), (&Name::from("y"), 32)])?;
+        let json = serde_json::to_string(&testcase).expect("Failed to serialize to JSON");
+        assert!(json.contains(r#"{"name":"x","bits":32,"value":1234}"#));
+        assert!(json.contains(r#"{"name":"y","bits":32,"value":1235}"#));
+
+        // and it should round-trip
+        let deserialized: TestCase =
+            serde_json::from_str(&json).expect("Failed to deserialize from JSON");
+        assert_eq!(deserialized, testcase);
+
+        Ok(())
+    }
+
+    #[test]
+    fn funnel_shifts() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let state = blank_state(&project, "test_func");
+
+        let ff = state.bv_from_u32(0xFF, 8);
+        let zero = state.zero(8);
+        let four = state.bv_from_u32(4, 8);
+
+        // 0xFF00 shifted left by 4 is 0xF000, and the high byte is 0xF0
+        assert_eq!(state.fshl(&ff, &zero, &four)?.as_u64(), Some(0xF0));
+        // 0xFF00 shifted right by 4 is 0x0FF0, and the low byte is 0xF0
+        assert_eq!(state.fshr(&ff, &zero, &four)?.as_u64(), Some(0xF0));
+
+        // shift amounts are taken modulo the width
+        let hi = state.bv_from_u32(0x12, 8);
+        let lo = state.bv_from_u32(0x34, 8);
+        let twelve = state.bv_from_u32(12, 8);
+        assert_eq!(state.fshl(&hi, &lo, &twelve)?.as_u64(), Some(0x23));
+        assert_eq!(state.fshr(&hi, &lo, &twelve)?.as_u64(), Some(0x23));
+        assert_eq!(state.fshl(&hi, &lo, &zero)?.as_u64(), Some(0x12));
+        assert_eq!(state.fshr(&hi, &lo, &zero)?.as_u64(), Some(0x34));
+
+        // operands must all have the same width
+        match state.fshl(&hi, &lo, &state.bv_from_u32(4, 32)) {
+            Err(Error::MalformedInstruction(_)) => {},
+            r => panic!("Expected MalformedInstruction, got {:?}", r),
+        }
 
         Ok(())
     }
 
     #[test]
-    fn get_a_solution() -> Result<()> {
+    fn saturating_arithmetic() -> Result<()> {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
         let project = blank_project("test_mod", func);
-        let mut state = blank_state(&project, "test_func");
-
-        // add x > 3 constraint
-        let x = state.new_bv_with_name(Name::from("x"), 64)?;
-        x.ugt(&state.bv_from_u64(3, 64)).assert();
-
-        // check that the computed value of x is > 3
-        let x_value = state
-            .get_a_solution_for_bv(&x)
-            .unwrap()
-            .expect("Expected a solution for x")
-            .as_u64()
-            .unwrap();
-        assert!(x_value > 3);
+        let state = blank_state(&project, "test_func");
+        let bv = |val: i64| state.bv_from_i64(val, 8);
+
+        // unsigned: saturate to max, and to zero
+        assert_eq!(state.uadd_sat(&bv(200), &bv(100))?.as_u64(), Some(255));
+        assert_eq!(state.uadd_sat(&bv(100), &bv(100))?.as_u64(), Some(200));
+        assert_eq!(state.usub_sat(&bv(100), &bv(200))?.as_u64(), Some(0));
+        assert_eq!(state.usub_sat(&bv(200), &bv(100))?.as_u64(), Some(100));
+
+        // signed: saturate to max, and to min
+        assert_eq!(state.sadd_sat(&bv(100), &bv(100))?.as_u64(), Some(0x7F));
+        assert_eq!(state.sadd_sat(&bv(-100), &bv(-100))?.as_u64(), Some(0x80));
+        assert_eq!(state.sadd_sat(&bv(100), &bv(-100))?.as_u64(), Some(0));
+        assert_eq!(state.ssub_sat(&bv(100), &bv(-100))?.as_u64(), Some(0x7F));
+        assert_eq!(state.ssub_sat(&bv(-100), &bv(100))?.as_u64(), Some(0x80));
+        assert_eq!(state.ssub_sat(&bv(-100), &bv(-100))?.as_u64(), Some(0));
+
+        // subtracting the min value from a nonnegative value saturates to max
+        assert_eq!(state.ssub_sat(&bv(0), &bv(-128))?.as_u64(), Some(0x7F));
+        assert_eq!(state.ssub_sat(&bv(1), &bv(-128))?.as_u64(), Some(0x7F));
+
+        // operands must have the same width
+        match state.uadd_sat(&bv(1), &state.bv_from_u32(1, 32)) {
+            Err(Error::MalformedInstruction(_)) => {},
+            r => panic!("Expected MalformedInstruction, got {:?}", r),
+        }
 
         Ok(())
     }
 
     #[test]
-    fn possible_solutions() -> Result<()> {
+    fn fixed_point_multiplication() -> Result<()> {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
         let project = blank_project("test_mod", func);
-        let mut state = blank_state(&project, "test_func");
-
-        // add x > 3 constraint
-        let x = state.new_bv_with_name(Name::from("x"), 64)?;
-        x.ugt(&state.bv_from_u64(3, 64)).assert();
-
-        // check that there are more than 2 solutions
-        let num_solutions = state.get_possible_solutions_for_bv(&x, 2).unwrap().count();
-        assert_eq!(num_solutions, SolutionCount::AtLeast(3));
-
-        // add x < 6 constraint
-        x.ult(&state.bv_from_u64(6, 64)).assert();
+        let state = blank_state(&project, "test_func");
+        // 8-bit values with 4 fractional bits
+        let q4 = |val: f64| state.bv_from_i64((val * 16.0) as i64, 8);
 
-        // check that there are now exactly two solutions
-        let solutions = state
-            .get_possible_solutions_for_bv(&x, 2)
-            .unwrap()
-            .as_u64_solutions();
+        // 2.5 * 1.5 = 3.75
         assert_eq!(
-            solutions,
-            Some(PossibleSolutions::Exactly(vec![4, 5].into_iter().collect()))
+            state.umul_fix(&q4(2.5), &q4(1.5), 4)?.as_u64(),
+            q4(3.75).as_u64()
+        );
+        assert_eq!(
+            state.smul_fix(&q4(2.5), &q4(1.5), 4)?.as_u64(),
+            q4(3.75).as_u64()
+        );
+        // -2.5 * 1.5 = -3.75
+        assert_eq!(
+            state.smul_fix(&q4(-2.5), &q4(1.5), 4)?.as_u64(),
+            q4(-3.75).as_u64()
         );
 
-        // add x < 5 constraint
-        x.ult(&state.bv_from_u64(5, 64)).assert();
+        // 0.0625 * 0.5 = 0.03125, which rounds toward zero, for either sign
+        assert_eq!(state.umul_fix(&q4(0.0625), &q4(0.5), 4)?.as_u64(), Some(0));
+        assert_eq!(state.smul_fix(&q4(-0.0625), &q4(0.5), 4)?.as_u64(), Some(0));
 
-        // check that there is now exactly one solution
-        let solutions = state
-            .get_possible_solutions_for_bv(&x, 2)
-            .unwrap()
-            .as_u64_solutions();
+        // with a scale of 0, this is just multiplication
         assert_eq!(
-            solutions,
-            Some(PossibleSolutions::Exactly(std::iter::once(4).collect()))
+            state
+                .umul_fix(&state.bv_from_u32(3, 8), &state.bv_from_u32(5, 8), 0)?
+                .as_u64(),
+            Some(15)
         );
 
-        // add x < 3 constraint
-        x.ult(&state.bv_from_u64(3, 64)).assert();
-
-        // check that there are now no solutions
-        let solutions = state
-            .get_possible_solutions_for_bv(&x, 2)
-            .unwrap()
-            .as_u64_solutions();
-        assert_eq!(solutions, Some(PossibleSolutions::Exactly(HashSet::new())));
+        // the scale must fit in the operand width
+        match state.smul_fix(&q4(1.0), &q4(1.0), 8) {
+            Err(Error::MalformedInstruction(_)) => {},
+            r => panic!("Expected MalformedInstruction, got {:?}", r),
+        }
 
         Ok(())
     }
 
     #[test]
-    fn lookup_vars_via_operand() {
+    fn abs_min_max() -> Result<()> {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
         let project = blank_project("test_mod", func);
-        let mut state = blank_state(&project, "test_func");
-
-        // create llvm-ir names
-        let name1 = Name::from("val");
-        let name2 = Name::from(2);
-
-        // create corresponding BV values
-        let var1 = state.new_bv_with_name(name1.clone(), 64).unwrap();
-        let var2 = state.new_bv_with_name(name2.clone(), 1).unwrap(); // these clone()s wouldn't normally be necessary but we want to reuse the names to create `Operand`s later
+        let state = blank_state(&project, "test_func");
+        let bv = |val: i64| state.bv_from_i64(val, 8);
+
+        assert_eq!(state.smin(&bv(-3), &bv(2))?.as_u64(), bv(-3).as_u64());
+        assert_eq!(state.smax(&bv(-3), &bv(2))?.as_u64(), Some(2));
+        assert_eq!(state.umax(&bv(0xFF), &bv(1))?.as_u64(), Some(0xFF));
+        assert_eq!(state.umin(&bv(0xFF), &bv(1))?.as_u64(), Some(1));
+
+        let (result, poison) = state.abs(&bv(-5), true);
+        assert_eq!(result.as_u64(), Some(5));
+        assert_eq!(poison.and_then(|p| p.as_bool()), Some(false));
+
+        // abs of the min value is itself, and poison if requested
+        let (result, poison) = state.abs(&bv(0x80), false);
+        assert_eq!(result.as_u64(), Some(0x80));
+        assert!(poison.is_none());
+        let (result, poison) = state.abs(&bv(0x80), true);
+        assert_eq!(result.as_u64(), Some(0x80));
+        assert_eq!(poison.and_then(|p| p.as_bool()), Some(true));
+
+        // operands must have the same width
+        match state.smax(&bv(1), &state.bv_from_u32(1, 32)) {
+            Err(Error::MalformedInstruction(_)) => {},
+            r => panic!("Expected MalformedInstruction, got {:?}", r),
+        }
 
-        // check that we can look up the correct BV values via LocalOperands
-        let op1 = Operand::LocalOperand {
-            name: name1,
-            ty: Type::i32(),
-        };
-        let op2 = Operand::LocalOperand {
-            name: name2,
-            ty: Type::bool(),
-        };
-        assert_eq!(state.operand_to_bv(&op1), Ok(var1));
-        assert_eq!(state.operand_to_bv(&op2), Ok(var2));
+        Ok(())
     }
 
     #[test]
-    fn const_bv() {
+    fn bitcast() -> Result<()> {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
         let project = blank_project("test_mod", func);
         let state = blank_state(&project, "test_func");
 
-        // create an llvm-ir value which is constant 3
-        let constint = Constant::Int { bits: 64, value: 3 };
+        // 0x3f800000 is the bit pattern of 1.0f
+        let i32_ty = Type::i32();
+        let float_ty = Type::FPType(types::FPType::Single);
+        let int_val = state.bv_from_u32(0x3f80_0000, 32);
+        let float_val = state.bitcast(&int_val, &i32_ty, &float_ty)?;
+        assert_eq!(float_val.get_width(), 32);
+        assert_eq!(float_val.as_u64(), Some(0x3f80_0000));
+
+        // and back again
+        let roundtrip = state.bitcast(&float_val, &float_ty, &i32_ty)?;
+        assert_eq!(state.bvs_must_be_equal(&roundtrip, &int_val), Ok(true));
+
+        // bitcasts between types of different sizes should be rejected
+        match state.bitcast(&int_val, &i32_ty, &Type::FPType(types::FPType::Double)) {
+            Err(Error::MalformedInstruction(_)) => {},
+            r => panic!("Expected a MalformedInstruction error, got {:?}", r),
+        }
 
-        // this should create a corresponding BV value which is also constant 3
-        let bv = state
-            .operand_to_bv(&Operand::ConstantOperand(constint))
-            .unwrap();
+        // but bitcasts involving types of unknown size are passed through unchecked
+        let opaque_ty = Type::NamedStructType {
+            name: "opaque".to_owned(),
+            ty: None,
+        };
+        let passed = state.bitcast(&int_val, &i32_ty, &opaque_ty)?;
+        assert_eq!(passed.as_u64(), Some(0x3f80_0000));
 
-        // check that the BV value was evaluated to 3
-        let solution = state
-            .get_a_solution_for_bv(&bv)
-            .unwrap()
-            .expect("Expected a solution for the bv")
-            .as_u64()
-            .unwrap();
-        assert_eq!(solution, 3);
+        Ok(())
     }
 
     #[test]
-    fn const_bool() {
+    fn bswap() -> Result<()> {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
         let project = blank_project("test_mod", func);
         let state = blank_state(&project, "test_func");
 
-        // create llvm-ir constants true and false
-        let consttrue = Constant::Int { bits: 1, value: 1 };
-        let constfalse = Constant::Int { bits: 1, value: 0 };
+        let swapped = state.bswap(&state.bv_from_u32(0x1122_3344, 32))?;
+        assert_eq!(swapped.get_width(), 32);
+        assert_eq!(swapped.as_u64(), Some(0x4433_2211));
 
-        // this should create BV values true and false
-        let bvtrue = state
-            .operand_to_bv(&Operand::ConstantOperand(consttrue))
-            .unwrap();
-        let bvfalse = state
-            .operand_to_bv(&Operand::ConstantOperand(constfalse))
-            .unwrap();
+        // a single byte is unchanged
+        let swapped = state.bswap(&state.bv_from_u32(0xab, 8))?;
+        assert_eq!(swapped.as_u64(), Some(0xab));
 
-        // check that the BV values are evaluated to true and false respectively
-        assert_eq!(
-            state
-                .get_a_solution_for_bv(&bvtrue)
-                .unwrap()
-                .expect("Expected a solution for bvtrue")
-                .as_bool()
-                .unwrap(),
-            true,
-        );
-        assert_eq!(
-            state
-                .get_a_solution_for_bv(&bvfalse)
-                .unwrap()
-                .expect("Expected a solution for bvfalse")
-                .as_bool()
-                .unwrap(),
-            false,
+        // widths which aren't a multiple of 8 bits should be rejected
+        match state.bswap(&state.bv_from_u32(0x123, 12)) {
+            Err(Error::OtherError(_)) => {},
+            r => panic!("Expected an OtherError, got {:?}", r),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn suspend_and_resume() -> Result<()> {
+        let func = blank_function(
+            "test_func",
+            vec![Name::from("bb_start"), Name::from("bb_target")],
         );
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.record_path_entry();
 
-        // assert the first one, which should be true, so we should still be sat
-        bvtrue.assert();
-        assert_eq!(state.sat(), Ok(true));
+        // x > 3, and memory holds x + 1 in one byte of a cell, next to a byte
+        // z which was never written
+        let x = state.new_bv_with_name(Name::from("x"), 8)?;
+        state.assert(&x.ugt(&state.bv_from_u32(3, 8)))?;
+        let ptr = state.allocate(64_u64).into_bv();
+        let ptr_addr = ptr.as_u64().unwrap();
+        let x_plus_one = x.add(&state.one(8));
+        state.write(&ptr, x_plus_one)?;
+        let next = state.bv_from_u64(ptr_addr + 1, 64);
+        let z = state.read(&next, 8)?;
+        state.assign_bv_to_name(Name::from("z"), z)?;
+        // and address space 1 holds x at address 0
+        state.write_in_space(1, &state.zero(64), x.clone())?;
+
+        // save a backtracking point which would enter bb_target with x < 10
+        let constraint = x.ult(&state.bv_from_u32(10, 8));
+        state.save_backtracking_point(&Name::from("bb_target"), constraint)?;
+
+        // meanwhile, the current path has x == 200, y == x * 2 pending as a
+        // vararg, and a constraint too long for `Debug` to print
+        state.assert(&x._eq(&state.bv_from_u32(200, 8)))?;
+        let y = x.mul(&state.bv_from_u32(2, 8));
+        state.assign_bv_to_name(Name::from("y"), y.clone())?;
+        state.varargs.push_back(y);
+        let mut sum = state.zero(64);
+        for i in 0 .. 200 {
+            sum = sum.add(&state.new_bv_with_name(Name::from(format!("w{}", i)), 64)?);
+        }
+        state.assert(&sum._eq(&state.bv_from_u64(1234, 64)))?;
 
-        // assert the second one, which should be false, so we should be unsat
-        bvfalse.assert();
-        assert_eq!(state.sat(), Ok(false));
+        let suspended = state.suspend()?;
+        #[cfg(feature = "serde")]
+        let suspended: SuspendedState = serde_json::from_str(
+            &serde_json::to_string(&suspended).expect("Failed to serialize to JSON"),
+        )
+        .expect("Failed to deserialize from JSON");
+
+        // resume in a fresh `Project` and `State`
+        let project = blank_project(
+            "test_mod",
+            blank_function(
+                "test_func",
+                vec![Name::from("bb_start"), Name::from("bb_target")],
+            ),
+        );
+        let mut resumed: State<BtorBackend> =
+            State::resume(&suspended, &project, Config::default())?;
+        let funcname = "test_func".to_owned();
+        let lookup = |state: &State<BtorBackend>, name: &str| {
+            state
+                .varmap
+                .lookup_var(&funcname, &Name::from(name))
+                .clone()
+        };
+        assert_eq!(resumed.cur_loc.bb.name, Name::from("bb_start"));
+        assert_eq!(resumed.count_backtracking_points(), 1);
+        assert!(resumed.sat()?);
+
+        // the current path continues where it left off
+        let x = lookup(&resumed, "x");
+        let y = lookup(&resumed, "y");
+        assert!(resumed.bvs_must_be_equal(&x, &resumed.bv_from_u32(200, 8))?);
+        assert!(resumed.bvs_must_be_equal(&y, &resumed.bv_from_u32(144, 8))?);
+        assert_eq!(resumed.varargs.len(), 1);
+        assert!(resumed.bvs_must_be_equal(&resumed.varargs[0], &y)?);
+        let mut sum = resumed.zero(64);
+        for i in 0 .. 200 {
+            sum = sum.add(&lookup(&resumed, &format!("w{}", i)));
+        }
+        assert!(resumed.bvs_must_be_equal(&sum, &resumed.bv_from_u64(1234, 64))?);
+        let ptr = resumed.bv_from_u64(ptr_addr, 64);
+        let next = resumed.bv_from_u64(ptr_addr + 1, 64);
+        let val = resumed.read(&ptr, 8)?;
+        assert!(resumed.bvs_must_be_equal(&val, &resumed.bv_from_u32(201, 8))?);
+        let val = resumed.read(&next, 8)?;
+        assert!(resumed.bvs_must_be_equal(&val, &lookup(&resumed, "z"))?);
+        let val = resumed.read_in_space(1, &resumed.zero(64), 8)?;
+        assert!(resumed.bvs_must_be_equal(&val, &x)?);
+        // new allocations and variables don't collide with the existing ones
+        let new_ptr = resumed.allocate(8_u64).into_bv();
+        assert_ne!(new_ptr.as_u64(), Some(ptr_addr));
+        resumed.new_bv_with_name(Name::from("x"), 8)?;
+
+        // revert to the backtracking point, and explore from there
+        assert!(resumed.revert_to_backtracking_point()?);
+        assert_eq!(resumed.cur_loc.bb.name, Name::from("bb_target"));
+        assert_eq!(resumed.get_path().len(), 1);
+        assert_eq!(resumed.count_backtracking_points(), 0);
+        assert_eq!(resumed.varmap.width_of(&funcname, &Name::from("y")), None);
+        assert!(resumed.varargs.is_empty());
+        let x = lookup(&resumed, "x");
+        let x_is = |n| x._eq(&resumed.bv_from_u32(n, 8));
+        assert!(resumed.sat_with_extra_constraints(std::iter::once(&x_is(5)))?);
+        assert!(!resumed.sat_with_extra_constraints(std::iter::once(&x_is(3)))?);
+        assert!(!resumed.sat_with_extra_constraints(std::iter::once(&x_is(200)))?);
+        let val = resumed.read(&ptr, 8)?;
+        assert!(resumed.bvs_must_be_equal(&val, &x.add(&resumed.one(8)))?);
+        let val = resumed.read(&next, 8)?;
+        assert!(resumed.bvs_must_be_equal(&val, &lookup(&resumed, "z"))?);
+        let val = resumed.read_in_space(1, &resumed.zero(64), 8)?;
+        assert!(resumed.bvs_must_be_equal(&val, &x)?);
+
+        // under another search strategy, the backtracking point keeps its own
+        // copy of the constraints
+        let mut state = blank_state(&project, "test_func");
+        state.config.search_strategy = SearchStrategy::BreadthFirst;
+        state.record_path_entry();
+        let x = state.new_bv_with_name(Name::from("x"), 8)?;
+        state.assert(&x.ugt(&state.bv_from_u32(3, 8)))?;
+        let constraint = x.ult(&state.bv_from_u32(10, 8));
+        state.save_backtracking_point(&Name::from("bb_target"), constraint)?;
+        state.assert(&x._eq(&state.bv_from_u32(200, 8)))?;
+        let suspended = state.suspend()?;
+        // which a `State` searching depth-first can't resume
+        assert!(State::<BtorBackend>::resume(&suspended, &project, Config::default()).is_err());
+        let config = Config {
+            search_strategy: SearchStrategy::BreadthFirst,
+            ..Config::default()
+        };
+        let mut resumed: State<BtorBackend> = State::resume(&suspended, &project, config)?;
+        let x = lookup(&resumed, "x");
+        let x_is = |n| x._eq(&resumed.bv_from_u32(n, 8));
+        assert!(resumed.sat_with_extra_constraints(std::iter::once(&x_is(200)))?);
+        assert!(!resumed.sat_with_extra_constraints(std::iter::once(&x_is(5)))?);
+        assert!(resumed.revert_to_backtracking_point()?);
+        let x = lookup(&resumed, "x");
+        let x_is = |n| x._eq(&resumed.bv_from_u32(n, 8));
+        assert!(resumed.sat_with_extra_constraints(std::iter::once(&x_is(5)))?);
+        assert!(!resumed.sat_with_extra_constraints(std::iter::once(&x_is(3)))?);
+        assert!(!resumed.sat_with_extra_constraints(std::iter::once(&x_is(200)))?);
+
+        Ok(())
     }
 
     #[test]
@@ -2183,7 +10603,7 @@ mod tests {
             .expect("Expected to find function named 'test_func'")
             .get_bb_by_name(&Name::from("bb_target"))
             .expect("Expected to find bb named 'bb_target'");
-        state.save_backtracking_point(&bb.name, constraint);
+        state.save_backtracking_point(&bb.name, constraint)?;
 
         // check that the constraint y > 5 wasn't added: adding y < 4 should keep us sat
         assert_eq!(
@@ -2242,6 +10662,204 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn null_split_at_backtrack_limit() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let start_loc = blank_state(&project, "test_func").cur_loc.clone();
+        let config: Config<BtorBackend> = Config {
+            null_pointer_checking: NullPointerChecking::SplitPath,
+            max_backtrack_depth: Some(0),
+            ..Config::default()
+        };
+        let mut state = State::new(&project, start_loc, config);
+        let ptr = state.new_bv_with_name(Name::from("ptr"), 64)?;
+
+        // with no room for the non-null backtracking point, the dereferences
+        // are still reported as such
+        assert!(matches!(
+            state.read(&ptr, 8),
+            Err(Error::NullPointerDereference)
+        ));
+        assert_eq!(
+            state.write(&ptr, state.zero(8)),
+            Err(Error::NullPointerDereference)
+        );
+        assert_eq!(state.count_backtracking_points(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn backtrack_depth_limit() -> Result<()> {
+        let func = blank_function(
+            "test_func",
+            vec![Name::from("bb_start"), Name::from("bb_target")],
+        );
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.config.max_backtrack_depth = Some(2);
+        let bb_target = Name::from("bb_target");
+
+        // the first two backtrack points should be saved successfully
+        state.save_backtracking_point(&bb_target, state.bv_from_bool(true))?;
+        state.save_backtracking_point(&bb_target, state.bv_from_bool(true))?;
+        assert_eq!(state.count_backtracking_points(), 2);
+
+        // make the current path unsat
+        state.bv_from_bool(false).assert();
+        assert_eq!(state.sat(), Ok(false));
+
+        // the third should fail, without saving anything
+        assert_eq!(
+            state.save_backtracking_point(&bb_target, state.bv_from_bool(true)),
+            Err(Error::BacktrackLimitExceeded(2))
+        );
+        assert_eq!(state.count_backtracking_points(), 2);
+
+        // if the failed save had pushed a solver context, reverting would only pop
+        // that context, and the False constraint would still be in effect
+        assert!(state.revert_to_backtracking_point()?);
+        assert_eq!(state.sat(), Ok(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn backtracking_scopes() -> Result<()> {
+        let func = blank_function(
+            "test_func",
+            vec![
+                Name::from("bb_start"),
+                Name::from("bb_before"),
+                Name::from("bb_inside"),
+            ],
+        );
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let bb_before = Name::from("bb_before");
+        let bb_inside = Name::from("bb_inside");
+
+        // this point is saved outside the scope
+        state.save_backtracking_point(&bb_before, state.bv_from_bool(true))?;
+
+        state.begin_scope();
+        state.save_backtracking_point(&bb_inside, state.bv_from_bool(true))?;
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        let x_is_three = x._eq(&state.bv_from_u64(3, 64));
+        state.assert(&x_is_three)?;
+        state.save_backtracking_point(&bb_inside, state.bv_from_bool(true))?;
+        assert_eq!(state.count_backtracking_points(), 3);
+        state.end_scope()?;
+
+        // the points saved inside the scope are gone
+        assert_eq!(state.count_backtracking_points(), 1);
+        // but the current path's constraints are still in effect
+        assert!(!state.sat_with_extra_constraints(std::iter::once(&x_is_three.not()))?);
+
+        // reverting gives us the point saved before the scope, and nothing else
+        assert!(state.revert_to_backtracking_point()?);
+        assert_eq!(state.cur_loc.bb.name, bb_before);
+        assert!(!state.revert_to_backtracking_point()?);
+
+        // ending a scope which was never begun is an error
+        assert!(state.end_scope().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn breadth_first_search() -> Result<()> {
+        let func = blank_function(
+            "test_func",
+            vec![
+                Name::from("bb_start"),
+                Name::from("bb1"),
+                Name::from("bb2"),
+                Name::from("bb3"),
+            ],
+        );
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        state.config.search_strategy = SearchStrategy::BreadthFirst;
+
+        // save three backtracking points, each constraining x differently,
+        // with a constraint on y asserted on the current path after the first
+        let x = state.new_bv_with_name(Name::from("x"), 64)?;
+        let y = state.new_bv_with_name(Name::from("y"), 64)?;
+        for (i, bbname) in ["bb1", "bb2", "bb3"].iter().enumerate() {
+            let constraint = x._eq(&state.bv_from_u64(i as u64 + 1, 64));
+            state.save_backtracking_point(&Name::from(*bbname), constraint)?;
+            if i == 0 {
+                state.assert(&y._eq(&state.bv_from_u64(7, 64)))?;
+            }
+        }
+
+        // points are explored in the order they were saved
+        assert!(state.revert_to_backtracking_point()?);
+        assert_eq!(state.cur_loc.bb.name, Name::from("bb1"));
+        assert!(state.bvs_must_be_equal(&x, &state.bv_from_u64(1, 64))?);
+        // the constraint on y was asserted after this point was saved
+        assert!(state.bvs_can_be_equal(&y, &state.bv_from_u64(8, 64))?);
+        state.assert(&y._eq(&state.bv_from_u64(9, 64)))?;
+
+        assert!(state.revert_to_backtracking_point()?);
+        assert_eq!(state.cur_loc.bb.name, Name::from("bb2"));
+        assert!(state.bvs_must_be_equal(&x, &state.bv_from_u64(2, 64))?);
+        // this point was saved after the constraint on y, and doesn't see the constraint from bb1's path
+        assert!(state.bvs_must_be_equal(&y, &state.bv_from_u64(7, 64))?);
+
+        assert!(state.revert_to_backtracking_point()?);
+        assert_eq!(state.cur_loc.bb.name, Name::from("bb3"));
+        assert!(state.bvs_must_be_equal(&x, &state.bv_from_u64(3, 64))?);
+
+        assert!(!state.revert_to_backtracking_point()?);
+
+        // a custom strategy choosing a nonexistent point is an error
+        state.config.search_strategy = SearchStrategy::Custom(Rc::new(|locs| locs.len()));
+        let t = state.bv_from_bool(true);
+        state.save_backtracking_point(&Name::from("bb1"), t)?;
+        assert!(state.revert_to_backtracking_point().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn resolve_call_target() -> Result<()> {
+        let func = blank_function("test_func", vec![Name::from("test_bb")]);
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+        let foo = blank_function("foo", vec![Name::from("foo_bb")]);
+        let bar = blank_function("bar", vec![Name::from("bar_bb")]);
+        let candidates = [(0x1000, &foo), (0x2000, &bar)];
+
+        // a function pointer which is either 0x1000 or 0x3000
+        let fptr = state.new_bv_with_name(Name::from("fptr"), 64)?;
+        fptr._eq(&state.bv_from_u64(0x1000, 64))
+            .or(&fptr._eq(&state.bv_from_u64(0x3000, 64)))
+            .assert();
+
+        // only `foo` matches
+        let targets = state.resolve_call_target(&fptr, &candidates)?;
+        assert_eq!(
+            targets.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+            vec!["foo"]
+        );
+
+        // after registering 0x3000 as the address of `test_func`, it matches too
+        let (test_func, _) = project
+            .get_func_by_name("test_func")
+            .expect("Expected to find function named 'test_func'");
+        state.register_function_address(0x3000, test_func);
+        let targets = state.resolve_call_target(&fptr, &candidates)?;
+        assert_eq!(
+            targets.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
+            vec!["foo", "test_func"]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn fork() {
         let func = blank_function("test_func", vec![Name::from("test_bb")]);
@@ -2309,4 +10927,39 @@ mod tests {
             .unwrap();
         assert!(y_2_solution < 10);
     }
+
+    #[test]
+    fn fork_moves_all_per_path_state() -> Result<()> {
+        let func = blank_function(
+            "test_func",
+            vec![Name::from("bb_start"), Name::from("bb_target")],
+        );
+        let project = blank_project("test_mod", func);
+        let mut state = blank_state(&project, "test_func");
+
+        let x = state.new_bv_with_name(Name::from("x"), 8)?;
+        state.assert(&x.ugt(&state.bv_from_u32(10, 8)))?;
+        let p = state.allocate_in_space(1, 8_u64).into_bv();
+        state.write_in_space(1, &p, x.clone())?;
+        state.set_varargs(std::slice::from_ref(&x));
+        state
+            .save_backtracking_point(&Name::from("bb_target"), x.ult(&state.bv_from_u32(20, 8)))?;
+
+        // everything the fork holds must belong to its own solver, or using it
+        // would mix `BV`s from two solvers
+        let mut forked = state.fork();
+        let forked_x = forked.get_solver().match_bv(&x).unwrap();
+        let forked_p = forked.get_solver().match_bv(&p).unwrap();
+        let val = forked.read_in_space(1, &forked_p, 8)?;
+        assert!(forked.bvs_must_be_equal(&val, &forked_x)?);
+        let arg = forked.va_arg(8).unwrap();
+        assert!(forked.bvs_must_be_equal(&arg, &forked_x)?);
+        forked.bv_from_bool(false).assert();
+        assert!(forked.revert_to_backtracking_point()?);
+        assert!(forked.sat()?);
+        assert!(!forked.bvs_can_be_equal(&forked_x, &forked.bv_from_u32(25, 8))?);
+        assert!(!forked.bvs_can_be_equal(&forked_x, &forked.bv_from_u32(5, 8))?);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,209 @@
+//! The `SuspendedState` structure for pausing an exploration and resuming it later
+
+use crate::alloc::Alloc;
+use crate::backend::{Backend, BV};
+use crate::state::{BBInstrIndex, Location};
+use llvm_ir::Name;
+use std::collections::HashMap;
+
+/// A snapshot of a `State`, including its saved backtracking points, produced
+/// by [`State.suspend()`](struct.State.html#method.suspend). The exploration
+/// can be continued later, possibly in another process, with
+/// [`State::resume()`](struct.State.html#method.resume).
+///
+/// Nothing in a `SuspendedState` refers to the `Project` or the solver
+/// instance it came from: code locations are stored by name, and symbolic
+/// values and memories in a dump of the solver's constraints (in the form
+/// produced by
+/// [`State.serialize_constraints()`](struct.State.html#method.serialize_constraints)).
+///
+/// With the `serde` feature enabled, `SuspendedState` implements `Serialize`
+/// and `Deserialize`, so it can be written out with (for instance)
+/// `serde_json`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuspendedState {
+    /// The serialized symbolic values and memories, which the rest of the
+    /// `SuspendedState` refers to by their index among the dumped values or
+    /// memories
+    pub(crate) dump: Vec<u8>,
+    /// The constraints on the current path, in the order they were asserted
+    pub(crate) constraints: Vec<usize>,
+    /// Under a search strategy other than `DepthFirst`, how many of the
+    /// `constraints` are shared by the current path and all backtracking
+    /// points, if any backtracking point has been saved
+    pub(crate) search_base_len: Option<usize>,
+    /// Where the current path was
+    pub(crate) loc: SuspendedLocation,
+    /// The current path's `path`
+    pub(crate) path: Vec<SuspendedLocation>,
+    /// Variables and memory on the current path
+    pub(crate) snapshot: Snapshot,
+    /// The saved backtracking points, in the order they were saved
+    pub(crate) backtrack_points: Vec<SuspendedBacktrackPoint>,
+    /// The open backtracking scopes
+    pub(crate) backtrack_scopes: Vec<usize>,
+    /// The allocator on the current path
+    pub(crate) alloc: Alloc,
+    /// Addresses of the global variables which had been initialized
+    pub(crate) initialized_globals: Vec<u64>,
+    /// Address and size in bytes of each region allocated with
+    /// `allocate_zeroed()`
+    pub(crate) zeroed_regions: Vec<(u64, u64)>,
+    /// Whether write tracking was explicitly enabled
+    pub(crate) write_tracking: bool,
+}
+
+/// A `Location`, by name. The terminator of a basic block has `instr` equal
+/// to the number of (non-terminator) instructions in it.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct SuspendedLocation {
+    pub(crate) module: String,
+    pub(crate) func: String,
+    pub(crate) bb: SuspendedName,
+    pub(crate) instr: usize,
+}
+
+impl<'p> From<&Location<'p>> for SuspendedLocation {
+    fn from(loc: &Location<'p>) -> Self {
+        Self {
+            module: loc.module.name.clone(),
+            func: loc.func.name.clone(),
+            bb: SuspendedName::from(&loc.bb.name),
+            instr: match loc.instr {
+                BBInstrIndex::Instr(i) => i,
+                BBInstrIndex::Terminator => loc.bb.instrs.len(),
+            },
+        }
+    }
+}
+
+/// A `Name`, in a form which can be serialized
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum SuspendedName {
+    Name(String),
+    Number(usize),
+}
+
+impl From<&Name> for SuspendedName {
+    fn from(name: &Name) -> Self {
+        match name {
+            Name::Name(s) => SuspendedName::Name(s.clone()),
+            Name::Number(n) => SuspendedName::Number(*n),
+        }
+    }
+}
+
+impl From<&SuspendedName> for Name {
+    fn from(name: &SuspendedName) -> Self {
+        match name {
+            SuspendedName::Name(s) => Name::Name(s.clone()),
+            SuspendedName::Number(n) => Name::Number(*n),
+        }
+    }
+}
+
+/// A saved backtracking point
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct SuspendedBacktrackPoint {
+    /// Where to resume execution
+    pub(crate) loc: SuspendedLocation,
+    /// The constraint to add when reverting to the backtracking point
+    pub(crate) constraint: usize,
+    /// The length of the `path` at the backtracking point
+    pub(crate) path_len: usize,
+    /// How many constraints had been asserted at the backtracking point
+    pub(crate) constraints_len: usize,
+    /// Under a search strategy other than `DepthFirst`, the constraints
+    /// asserted at the backtracking point
+    pub(crate) constraints: Option<Vec<usize>>,
+    /// Variables and memory at the backtracking point
+    pub(crate) snapshot: Snapshot,
+    /// The allocator at the backtracking point, if it was saved
+    pub(crate) alloc: Option<Alloc>,
+    /// The regions allocated with `allocate_zeroed()` at the backtracking
+    /// point, if they were saved along with the allocator
+    pub(crate) zeroed_regions: Option<Vec<(u64, u64)>>,
+}
+
+/// The per-path state of the call stack, variables, and memory. Symbolic
+/// values and memories are given by their index among the dumped ones.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Snapshot {
+    /// The call stack, starting with the top-level caller
+    pub(crate) stack: Vec<SuspendedFrame>,
+    /// Function name, variable name, and value of each active variable
+    pub(crate) vars: Vec<(String, SuspendedName, usize)>,
+    /// Function name, variable name, and latest version number of each
+    /// variable which has been assigned
+    pub(crate) version_nums: Vec<(String, SuspendedName, usize)>,
+    /// The memory for address space 0
+    pub(crate) mem: usize,
+    /// Address space and memory, for each other address space which has
+    /// been used
+    pub(crate) space_mems: Vec<(u32, usize)>,
+    /// See `State.enable_write_tracking()`
+    pub(crate) initialized_bytes: Option<Vec<u64>>,
+    /// See `State.enable_access_tracking()`
+    pub(crate) touched_allocs: Option<Vec<u64>>,
+    /// The variadic arguments which haven't been consumed yet
+    pub(crate) varargs: Vec<usize>,
+    /// Loop ID and count, for each loop back-edge which has been taken
+    pub(crate) back_edge_counts: Vec<(usize, usize)>,
+}
+
+/// A frame of the call stack
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct SuspendedFrame {
+    /// The location of the call or invoke instruction
+    pub(crate) callsite: SuspendedLocation,
+    /// The caller's name
+    pub(crate) caller: String,
+    /// Name and value of each of the caller's variables to restore when the
+    /// call returns
+    pub(crate) caller_vars: Vec<(SuspendedName, usize)>,
+}
+
+/// The symbolic values and memories which a `SuspendedState` refers to, each
+/// collected once, in the order they were first seen
+pub(crate) struct Collected<B: Backend> {
+    pub(crate) bvs: Vec<B::BV>,
+    pub(crate) mems: Vec<B::Memory>,
+    /// Index in `bvs` of each value, by AST node ID
+    indices: HashMap<i32, usize>,
+}
+
+impl<B: Backend> Collected<B> {
+    pub(crate) fn new() -> Self {
+        Self {
+            bvs: Vec::new(),
+            mems: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Get the index of `bv`, collecting it if it hasn't been already
+    pub(crate) fn bv(&mut self, bv: &B::BV) -> usize {
+        let bvs = &mut self.bvs;
+        *self.indices.entry(bv.get_id()).or_insert_with(|| {
+            bvs.push(bv.clone());
+            bvs.len() - 1
+        })
+    }
+
+    /// Get the index of `mem`, collecting it if it hasn't been already
+    pub(crate) fn mem(&mut self, mem: &B::Memory) -> usize {
+        match self.mems.iter().position(|collected| collected == mem) {
+            Some(index) => index,
+            None => {
+                self.mems.push(mem.clone());
+                self.mems.len() - 1
+            },
+        }
+    }
+}
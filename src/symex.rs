@@ -15,7 +15,22 @@ use crate::layout::*;
 use crate::project::Project;
 use crate::return_value::*;
 use crate::solver_utils::PossibleSolutions;
-pub use crate::state::{BBInstrIndex, Location, LocationDescription, PathEntry, State};
+use crate::state::reduce_shift_amount;
+pub use crate::state::{
+    AtomicOp,
+    BBInstrIndex,
+    BinOp,
+    BoolValue,
+    CoverageMap,
+    Location,
+    LocationDescription,
+    LoopAction,
+    PathEntry,
+    QueryComplexity,
+    State,
+    UninitHandler,
+    UnsupportedItem,
+};
 
 /// Begin symbolic execution of the function named `funcname`, obtaining an
 /// `ExecutionManager`. The function's parameters will start completely
@@ -220,7 +235,19 @@ where
                     Instruction::Trunc(trunc) => self.symex_trunc(trunc),
                     Instruction::PtrToInt(pti) => self.symex_cast_op(pti),
                     Instruction::IntToPtr(itp) => self.symex_cast_op(itp),
-                    Instruction::BitCast(bitcast) => self.symex_cast_op(bitcast),
+                    Instruction::BitCast(bitcast) => self.symex_bitcast(bitcast),
+                    Instruction::SIToFP(sitofp) => {
+                        self.symex_int_float_conversion(sitofp, State::sitofp)
+                    },
+                    Instruction::UIToFP(uitofp) => {
+                        self.symex_int_float_conversion(uitofp, State::uitofp)
+                    },
+                    Instruction::FPToSI(fptosi) => {
+                        self.symex_int_float_conversion(fptosi, State::fptosi)
+                    },
+                    Instruction::FPToUI(fptoui) => {
+                        self.symex_int_float_conversion(fptoui, State::fptoui)
+                    },
                     Instruction::Phi(phi) => self.symex_phi(phi),
                     Instruction::Select(select) => self.symex_select(select),
                     Instruction::CmpXchg(cmpxchg) => self.symex_cmpxchg(cmpxchg),
@@ -230,7 +257,7 @@ where
                         Ok(Some(symexresult)) => return Ok(Some(symexresult)),
                     },
                     Instruction::LandingPad(_) => return Err(Error::UnsupportedInstruction("Encountered an LLVM `LandingPad` instruction, but wasn't expecting it (there is no inflight exception)".to_owned())),
-                    _ => return Err(Error::UnsupportedInstruction(format!("instruction {:?}", inst))),
+                    _ => return Err(self.state.unsupported("instruction", inst)),
                 }
             };
             match result {
@@ -261,10 +288,7 @@ where
             Terminator::Invoke(invoke) => self.symex_invoke(invoke),
             Terminator::Resume(resume) => self.symex_resume(resume),
             Terminator::Unreachable(_) => Err(Error::UnreachableInstruction),
-            _ => Err(Error::UnsupportedInstruction(format!(
-                "terminator {:?}",
-                term
-            ))),
+            _ => Err(self.state.unsupported("terminator", term)),
         }
     }
 
@@ -450,7 +474,22 @@ where
     #[allow(clippy::type_complexity)]
     fn binop_to_bvbinop<'a, V: BV + 'a>(
         bop: &instruction::groups::BinaryOp,
+        shift_semantics: ShiftSemantics,
     ) -> Result<Box<dyn for<'b> Fn(&'b V, &'b V) -> V + 'a>> {
+        if shift_semantics == ShiftSemantics::ModularReduce {
+            match bop {
+                instruction::groups::BinaryOp::Shl(_) => {
+                    return Ok(Box::new(|a: &V, b: &V| a.sll(&reduce_shift_amount(b))))
+                },
+                instruction::groups::BinaryOp::LShr(_) => {
+                    return Ok(Box::new(|a: &V, b: &V| a.srl(&reduce_shift_amount(b))))
+                },
+                instruction::groups::BinaryOp::AShr(_) => {
+                    return Ok(Box::new(|a: &V, b: &V| a.sra(&reduce_shift_amount(b))))
+                },
+                _ => {},
+            }
+        }
         match bop {
             // TODO: how to not clone the inner instruction here
             instruction::groups::BinaryOp::Add(_) => Ok(Box::new(V::add)),
@@ -546,7 +585,7 @@ where
         let op_type = op0_type;
         let bvop0 = self.state.operand_to_bv(op0)?;
         let bvop1 = self.state.operand_to_bv(op1)?;
-        let bvoperation = Self::binop_to_bvbinop(bop)?;
+        let bvoperation = Self::binop_to_bvbinop(bop, self.state.config.shift_semantics)?;
         match op_type {
             Type::IntegerType { .. } => {
                 self.state.record_bv_result(bop, bvoperation(&bvop0, &bvop1))
@@ -736,6 +775,36 @@ where
         self.state.record_bv_result(cast, bvop) // from Boolector's perspective a cast is simply a no-op; the bit patterns are equal
     }
 
+    fn symex_bitcast(&mut self, bitcast: &'p instruction::BitCast) -> Result<()> {
+        debug!("Symexing bitcast {:?}", bitcast);
+        let bvop = self.state.operand_to_bv(&bitcast.operand)?;
+        let result = self
+            .state
+            .bitcast(&bvop, &bitcast.operand.get_type(), &bitcast.to_type)?;
+        self.state.record_bv_result(bitcast, result)
+    }
+
+    /// Use this for `SIToFP`, `UIToFP`, `FPToSI`, and `FPToUI`. `convert` is the
+    /// corresponding `State` method.
+    fn symex_int_float_conversion(
+        &mut self,
+        conv: &'p impl instruction::UnaryOp,
+        convert: impl FnOnce(&State<'p, B>, &B::BV, u32) -> Result<B::BV>,
+    ) -> Result<()> {
+        debug!("Symexing int/float conversion {:?}", conv);
+        match conv.get_type() {
+            Type::VectorType { .. } => Err(Error::UnsupportedInstruction(format!(
+                "int/float conversion on vectors: {:?}",
+                conv
+            ))),
+            ty => {
+                let bvop = self.state.operand_to_bv(conv.get_operand())?;
+                let result = convert(&self.state, &bvop, size(&ty) as u32)?;
+                self.state.record_bv_result(conv, result)
+            },
+        }
+    }
+
     fn symex_load(&mut self, load: &'p instruction::Load) -> Result<()> {
         debug!("Symexing load {:?}", load);
         let bvaddr = self.state.operand_to_bv(&load.address)?;
@@ -755,14 +824,9 @@ where
         debug!("Symexing gep {:?}", gep);
         match gep.get_type() {
             Type::PointerType { .. } => {
-                let bvbase = self.state.operand_to_bv(&gep.address)?;
-                let offset = Self::get_offset_recursive(
-                    &self.state,
-                    gep.indices.iter(),
-                    &gep.address.get_type(),
-                    bvbase.get_width(),
-                )?;
-                self.state.record_bv_result(gep, bvbase.add(&offset))
+                // we don't compute the `inbounds` poison condition here; see notes on `State::gep()`
+                let (result, _) = self.state.gep(&gep.address, &gep.indices, false)?;
+                self.state.record_bv_result(gep, result)
             },
             Type::VectorType { .. } => Err(Error::UnsupportedInstruction(
                 "GEP calculating a vector of pointers".to_owned(),
@@ -774,67 +838,6 @@ where
         }
     }
 
-    /// Get the offset of the element (in bytes, as a `BV` of `result_bits` bits)
-    fn get_offset_recursive(
-        state: &State<'p, B>,
-        mut indices: impl Iterator<Item = &'p Operand>,
-        base_type: &Type,
-        result_bits: u32,
-    ) -> Result<B::BV> {
-        match indices.next() {
-            None => Ok(state.zero(result_bits)),
-            Some(index) => {
-                match base_type {
-                    Type::PointerType { .. } | Type::ArrayType { .. } | Type::VectorType { .. } => {
-                        let index = state.operand_to_bv(index)?.zero_extend_to_bits(result_bits);
-                        let (offset, nested_ty) =
-                            get_offset_bv_index(base_type, &index, state.solver.clone())?;
-                        Self::get_offset_recursive(state, indices, nested_ty, result_bits)
-                            .map(|bv| bv.add(&offset))
-                    },
-                    Type::StructType { .. } => match index {
-                        Operand::ConstantOperand(Constant::Int { value: index, .. }) => {
-                            let (offset, nested_ty) =
-                                get_offset_constant_index(base_type, *index as usize)?;
-                            Self::get_offset_recursive(state, indices, &nested_ty, result_bits)
-                                .map(|bv| bv.add(&state.bv_from_u32(offset as u32, result_bits)))
-                        },
-                        _ => Err(Error::MalformedInstruction(format!(
-                            "Expected index into struct type to be constant, but got index {:?}",
-                            index
-                        ))),
-                    },
-                    Type::NamedStructType { ty, .. } => {
-                        let arc: Arc<RwLock<Type>> = ty
-                            .as_ref()
-                            .ok_or_else(|| {
-                                Error::MalformedInstruction(
-                                    "get_offset on an opaque struct type".to_owned(),
-                                )
-                            })?
-                            .upgrade()
-                            .expect("Failed to upgrade weak reference");
-                        let actual_ty: &Type = &arc.read().unwrap();
-                        if let Type::StructType { .. } = actual_ty {
-                            // this code copied from the StructType case
-                            match index {
-                            Operand::ConstantOperand(Constant::Int { value: index, .. }) => {
-                                let (offset, nested_ty) = get_offset_constant_index(actual_ty, *index as usize)?;
-                                Self::get_offset_recursive(state, indices, &nested_ty, result_bits)
-                                    .map(|bv| bv.add(&state.bv_from_u32(offset as u32, result_bits)))
-                            },
-                            _ => Err(Error::MalformedInstruction(format!("Expected index into struct type to be constant, but got index {:?}", index))),
-                        }
-                        } else {
-                            Err(Error::MalformedInstruction(format!("Expected NamedStructType inner type to be a StructType, but got {:?}", actual_ty)))
-                        }
-                    },
-                    _ => panic!("get_offset_recursive with base type {:?}", base_type),
-                }
-            },
-        }
-    }
-
     fn symex_alloca(&mut self, alloca: &'p instruction::Alloca) -> Result<()> {
         debug!("Symexing alloca {:?}", alloca);
         match &alloca.num_elements {
@@ -853,7 +856,7 @@ where
                 } else {
                     allocation_size_bits
                 };
-                let allocated = self.state.allocate(allocation_size_bits);
+                let allocated = self.state.allocate(allocation_size_bits).into_bv();
                 self.state.record_bv_result(alloca, allocated)
             },
             op => Err(Error::UnsupportedInstruction(format!(
@@ -963,42 +966,29 @@ where
                 element_type,
                 num_elements,
             } => {
-                let mask: Vec<u32> = match &sv.mask {
+                let mask: Vec<i32> = match &sv.mask {
                     Constant::Vector(mask) => mask.iter()
                         .map(|c| match c {
-                            Constant::Int { value: idx, .. } => Ok(*idx as u32),
-                            Constant::Undef(_) => Ok(0),
+                            Constant::Int { value: idx, .. } => Ok(*idx as i32),
+                            Constant::Undef(_) => Ok(-1),
                             _ => Err(Error::UnsupportedInstruction(format!("ShuffleVector with a mask entry which is not a Constant::Int or Constant::Undef but instead {:?}", c))),
                         })
-                        .collect::<Result<Vec<u32>>>()?,
-                    Constant::AggregateZero(ty) | Constant::Undef(ty) => match ty {
+                        .collect::<Result<Vec<i32>>>()?,
+                    Constant::AggregateZero(ty) => match ty {
                         Type::VectorType { num_elements, .. } => itertools::repeat_n(0, *num_elements).collect(),
-                        _ => return Err(Error::MalformedInstruction(format!("Expected ShuffleVector mask (which is an AggregateZero or Undef) to have vector type, but its type is {:?}", ty))),
+                        _ => return Err(Error::MalformedInstruction(format!("Expected ShuffleVector mask (which is an AggregateZero) to have vector type, but its type is {:?}", ty))),
+                    },
+                    Constant::Undef(ty) => match ty {
+                        Type::VectorType { num_elements, .. } => itertools::repeat_n(-1, *num_elements).collect(),
+                        _ => return Err(Error::MalformedInstruction(format!("Expected ShuffleVector mask (which is an Undef) to have vector type, but its type is {:?}", ty))),
                     },
                     c => return Err(Error::MalformedInstruction(format!("Expected ShuffleVector mask to be a Constant::Vector, Constant::AggregateZero, or Constant::Undef, but got {:?}", c))),
                 };
                 let op0 = self.state.operand_to_bv(&sv.operand0)?;
                 let op1 = self.state.operand_to_bv(&sv.operand1)?;
-                if op0.get_width() != op1.get_width() {
-                    return Err(Error::OtherError(format!("ShuffleVector operands are the same type, but somehow we got two different sizes: {} bits and {} bits", op0.get_width(), op1.get_width())));
-                }
                 let el_size = size(&element_type) as u32;
-                let num_elements = num_elements as u32;
-                assert_eq!(op0.get_width(), el_size * num_elements);
-                let final_bv = mask
-                    .into_iter()
-                    .map(|idx| {
-                        if idx < num_elements {
-                            op0.slice((idx + 1) * el_size - 1, idx * el_size)
-                        } else {
-                            let idx = idx - num_elements;
-                            op1.slice((idx + 1) * el_size - 1, idx * el_size)
-                        }
-                    })
-                    .reduce(|a, b| b.concat(&a))
-                    .ok_or_else(|| {
-                        Error::MalformedInstruction("ShuffleVector mask had 0 elements".to_owned())
-                    })?;
+                assert_eq!(op0.get_width(), el_size * num_elements as u32);
+                let final_bv = self.state.shuffle_vector(&op0, &op1, &mask, el_size)?;
                 self.state.record_bv_result(sv, final_bv)
             },
             ty => Err(Error::MalformedInstruction(format!(
@@ -1238,7 +1228,7 @@ where
                         .map(|arg| self.state.operand_to_bv(&arg.0)) // have to do this before changing state.cur_loc, so that the lookups happen in the caller function
                         .collect::<Result<Vec<B::BV>>>()?;
                     let saved_loc = self.state.cur_loc.clone();
-                    self.state.push_callsite(call);
+                    self.state.push_callsite(call)?;
                     self.state.cur_loc = Location {
                         module: callee_mod,
                         func: callee,
@@ -1427,6 +1417,16 @@ where
                                 .expect("Failed to find LLVM intrinsic assume hook"),
                             hooked_thing: HookedThing::Intrinsic(funcname),
                         })
+                    } else if funcname.starts_with("llvm.expect") {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.expect")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic expect hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
                     } else if funcname.starts_with("llvm.uadd.with.overflow") {
                         Ok(ResolvedFunction::HookActive {
                             hook: self
@@ -1527,6 +1527,100 @@ where
                                 .expect("Failed to find LLVM intrinsic ssub.sat hook"),
                             hooked_thing: HookedThing::Intrinsic(funcname),
                         })
+                    } else if funcname.starts_with("llvm.abs") {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.abs")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic abs hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
+                    } else if funcname.starts_with("llvm.smax") {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.smax")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic smax hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
+                    } else if funcname.starts_with("llvm.smin") {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.smin")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic smin hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
+                    } else if funcname.starts_with("llvm.umax") {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.umax")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic umax hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
+                    } else if funcname.starts_with("llvm.umin") {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.umin")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic umin hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
+                    } else if funcname.starts_with("llvm.umul.fix")
+                        && !funcname.starts_with("llvm.umul.fix.sat")
+                    {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.umul.fix")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic umul.fix hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
+                    } else if funcname.starts_with("llvm.smul.fix")
+                        && !funcname.starts_with("llvm.smul.fix.sat")
+                    {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.smul.fix")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic smul.fix hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
+                    } else if funcname.starts_with("llvm.fshl") {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.fshl")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic fshl hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
+                    } else if funcname.starts_with("llvm.fshr") {
+                        Ok(ResolvedFunction::HookActive {
+                            hook: self
+                                .state
+                                .intrinsic_hooks
+                                .get_hook_for("intrinsic: llvm.fshr")
+                                .cloned()
+                                .expect("Failed to find LLVM intrinsic fshr hook"),
+                            hooked_thing: HookedThing::Intrinsic(funcname),
+                        })
                     } else if funcname.starts_with("llvm.read_register")
                         || funcname.starts_with("llvm.write_register")
                     {
@@ -1665,22 +1759,22 @@ where
             debug!("both true and false branches are feasible");
             // for now we choose to explore true first, and backtrack to false if necessary
             self.state
-                .save_backtracking_point(&condbr.false_dest, bvcond.not());
-            bvcond.assert()?;
+                .save_backtracking_point(&condbr.false_dest, bvcond.not())?;
+            self.state.assert(&bvcond)?;
             self.state
                 .cur_loc
                 .move_to_start_of_bb_by_name(&condbr.true_dest);
             self.symex_from_cur_loc_through_end_of_function()
         } else if true_feasible {
             debug!("only the true branch is feasible");
-            bvcond.assert()?; // unnecessary, but may help Boolector more than it hurts?
+            self.state.assert(&bvcond)?; // unnecessary, but may help Boolector more than it hurts?
             self.state
                 .cur_loc
                 .move_to_start_of_bb_by_name(&condbr.true_dest);
             self.symex_from_cur_loc_through_end_of_function()
         } else if false_feasible {
             debug!("only the false branch is feasible");
-            bvcond.not().assert()?; // unnecessary, but may help Boolector more than it hurts?
+            self.state.assert(&bvcond.not())?; // unnecessary, but may help Boolector more than it hurts?
             self.state
                 .cur_loc
                 .move_to_start_of_bb_by_name(&condbr.false_dest);
@@ -1728,7 +1822,7 @@ where
             // make backtracking points for all but the first destination
             for (val, name) in feasible_dests.iter().skip(1) {
                 self.state
-                    .save_backtracking_point(name, val._eq(&switchval));
+                    .save_backtracking_point(name, val._eq(&switchval))?;
             }
             // if the default dest is feasible, make a backtracking point for it
             let default_dest_constraint = dests
@@ -1741,11 +1835,11 @@ where
                 .sat_with_extra_constraints(std::iter::once(&default_dest_constraint))?
             {
                 self.state
-                    .save_backtracking_point(&switch.default_dest, default_dest_constraint);
+                    .save_backtracking_point(&switch.default_dest, default_dest_constraint)?;
             }
             // follow the first destination
             let (val, name) = &feasible_dests[0];
-            val._eq(&switchval).assert()?; // unnecessary, but may help Boolector more than it hurts?
+            self.state.assert(&val._eq(&switchval))?; // unnecessary, but may help Boolector more than it hurts?
             self.state.cur_loc.move_to_start_of_bb_by_name(name);
             self.symex_from_cur_loc_through_end_of_function()
         }
@@ -1852,7 +1946,7 @@ where
                         .map(|arg| self.state.operand_to_bv(&arg.0)) // have to do this before changing state.cur_loc, so that the lookups happen in the caller function
                         .collect::<Result<Vec<B::BV>>>()?;
                     let saved_loc = self.state.cur_loc.clone();
-                    self.state.push_invokesite(invoke);
+                    self.state.push_invokesite(invoke)?;
                     self.state.cur_loc = Location {
                         module: callee_mod,
                         func: callee,
@@ -2137,10 +2231,10 @@ where
                         self.state
                             .record_bv_result(select, bvcond.cond_bv(&bvtrueval, &bvfalseval))
                     } else if true_feasible {
-                        bvcond.assert()?; // unnecessary, but may help Boolector more than it hurts?
+                        self.state.assert(&bvcond)?; // unnecessary, but may help Boolector more than it hurts?
                         self.state.record_bv_result(select, bvtrueval)
                     } else if false_feasible {
-                        bvcond.not().assert()?; // unnecessary, but may help Boolector more than it hurts?
+                        self.state.assert(&bvcond.not())?; // unnecessary, but may help Boolector more than it hurts?
                         self.state.record_bv_result(select, bvfalseval)
                     } else {
                         // this path is unsat
@@ -0,0 +1,43 @@
+//! The `TestCase` structure for replaying a path found during symbolic execution
+
+/// A concrete reproducer for a path, produced by
+/// [`State.export_testcase()`](struct.State.html#method.export_testcase).
+///
+/// With the `serde` feature enabled, `TestCase` implements `Serialize` and
+/// `Deserialize`, so it can be written out with (for instance) `serde_json`.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TestCase {
+    /// A concrete value for each of the requested inputs, in the order they
+    /// were requested. All of these values come from a single solution, so
+    /// they are consistent with each other and with the path constraints.
+    pub inputs: Vec<TestCaseInput>,
+    /// Every allocation which had been made at the time the `TestCase` was
+    /// exported (including allocations for global variables and functions), in
+    /// the order they were made. Replaying the path requires laying out
+    /// memory the same way, as concrete pointer values (for instance, in the
+    /// `inputs`) may refer to these allocations.
+    pub allocations: Vec<TestCaseAllocation>,
+}
+
+/// A concrete value for a single input; see [`TestCase`](struct.TestCase.html)
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TestCaseInput {
+    /// The name of the input, as it appears in the LLVM IR
+    pub name: String,
+    /// Width of the input, in bits
+    pub bits: u32,
+    /// The concrete value of the input
+    pub value: u64,
+}
+
+/// A single allocation made on the path; see [`TestCase`](struct.TestCase.html)
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TestCaseAllocation {
+    /// Address of the allocation
+    pub addr: u64,
+    /// Size of the allocation, in bits
+    pub bits: u64,
+}
@@ -104,6 +104,27 @@ impl<V: BV> VarMap<V> {
         }
     }
 
+    /// Bind `to` (in the function `funcname`) to the same `BV` which `from`
+    /// (in the same function) is currently bound to, as with an
+    /// `llvm.ssa.copy`. No new `BV` or constraint is created.
+    ///
+    /// As with `assign_bv_to_name()`, this creates a new version of `to`, so
+    /// it returns `Error::LoopBoundExceeded` if that would exceed
+    /// `max_versions_of_name`; it also returns an error if `from` isn't bound.
+    pub fn copy_binding(&mut self, funcname: String, from: &Name, to: Name) -> Result<()> {
+        let bv = self
+            .active_version
+            .get(&funcname, from)
+            .cloned()
+            .ok_or_else(|| {
+                Error::OtherError(format!(
+                    "copy_binding: var {:?} from function {:?} isn't bound",
+                    from, funcname
+                ))
+            })?;
+        self.assign_bv_to_name(funcname, to, bv)
+    }
+
     /// Look up the most recent `BV` created for the given `(String, Name)` pair.
     #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
     pub fn lookup_var(&self, funcname: &String, name: &Name) -> &V {
@@ -116,6 +137,71 @@ impl<V: BV> VarMap<V> {
         })
     }
 
+    /// Like `lookup_var()`, but returns `None` rather than panicking if the
+    /// given `(String, Name)` pair has not been assigned a value.
+    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
+    pub fn try_lookup_var(&self, funcname: &String, name: &Name) -> Option<&V> {
+        self.active_version.get(funcname, name)
+    }
+
+    /// Remove the binding for the given `(String, Name)` pair, returning the
+    /// `BV` it was bound to, if any.
+    ///
+    /// The version counter for the pair is kept, so any `BV`s created for it
+    /// later still get distinct versioned names.
+    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
+    pub fn remove_var(&mut self, funcname: &String, name: &Name) -> Option<V> {
+        self.active_version.remove(funcname, name)
+    }
+
+    /// Get the width (in bits) of the most recent `BV` created for the given
+    /// `(String, Name)` pair, or `None` if that pair has not been assigned a
+    /// value. (Booleans are `BV`s of width 1.)
+    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
+    pub fn width_of(&self, funcname: &String, name: &Name) -> Option<u32> {
+        self.active_version
+            .get(funcname, name)
+            .map(|bv| bv.get_width())
+    }
+
+    /// Get the solver symbols of all `BV`s which may have been created with
+    /// `new_bv_with_name()` for the given `(String, Name)` pair, that is, the
+    /// versioned names of all versions created so far.
+    #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
+    pub fn symbols_of(&self, funcname: &String, name: &Name) -> Vec<String> {
+        match self.version_num.get(funcname, name) {
+            None => Vec::new(),
+            Some(&latest) => (0 ..= latest)
+                .map(|version_num| Self::build_versioned_name(funcname, name, version_num))
+                .collect(),
+        }
+    }
+
+    /// Merge the variables of `other` into this `VarMap`.
+    ///
+    /// Afterwards, each variable bound in both `VarMap`s (to different `BV`s)
+    /// will be bound to a `BV` which has the value from (the original) `self`
+    /// when `cond` (a `BV` of width 1) is true, and the value from `other` when
+    /// `cond` is false. Variables bound only in `other` will be bound to their
+    /// `BV` from `other`.
+    pub fn merge(&mut self, other: Self, cond: &V) {
+        for (funcname, name, other_bv) in other.active_version.iter() {
+            let merged = match self.active_version.get(funcname, name) {
+                Some(self_bv) if self_bv == other_bv => continue,
+                Some(self_bv) => cond.cond_bv(self_bv, other_bv),
+                None => other_bv.clone(),
+            };
+            self.active_version
+                .insert(funcname.clone(), name.clone(), merged);
+        }
+        for (funcname, name, &other_version) in other.version_num.iter() {
+            self.version_num
+                .entry(funcname.clone(), name.clone())
+                .and_modify(|v| *v = std::cmp::max(*v, other_version))
+                .or_insert(other_version);
+        }
+    }
+
     /// Overwrite the latest version of the given `(String, Name)` pair to instead be `bv`.
     /// The `(String, Name)` pair must have already been previously assigned a value.
     #[allow(clippy::ptr_arg)] // as of this writing, clippy warns that the &String argument should be &str; but it actually needs to be &String here
@@ -144,6 +230,34 @@ impl<V: BV> VarMap<V> {
             .sorted_by_key(|&(name, _)| name)
     }
 
+    /// Get every `(String, Name)` pair which has an active version, along with
+    /// the `BV` it is bound to
+    pub fn active_vars(&self) -> impl Iterator<Item = (&String, &Name, &V)> {
+        self.active_version.iter()
+    }
+
+    /// Get the version number of the latest version of every `(String, Name)`
+    /// pair which has been assigned a value
+    pub fn version_nums(&self) -> impl Iterator<Item = (&String, &Name, usize)> {
+        self.version_num
+            .iter()
+            .map(|(funcname, name, &version_num)| (funcname, name, version_num))
+    }
+
+    /// Bind the given `(String, Name)` pair to the given `BV`, without
+    /// creating a new version of it. Together with `restore_version_num()`,
+    /// this rebuilds a `VarMap` from the results of `active_vars()` and
+    /// `version_nums()`.
+    pub fn restore_active_var(&mut self, funcname: String, name: Name, bv: V) {
+        self.active_version.insert(funcname, name, bv);
+    }
+
+    /// Set the version number of the latest version of the given
+    /// `(String, Name)` pair; see `restore_active_var()`
+    pub fn restore_version_num(&mut self, funcname: String, name: Name, version_num: usize) {
+        self.version_num.insert(funcname, name, version_num);
+    }
+
     /// Given a `Name` (from a particular function), creates a new version of it
     /// and returns the corresponding versioned name
     /// (or `Error::LoopBoundExceeded` if it would exceed the `max_version_num`)
@@ -229,10 +343,40 @@ pub struct RestoreInfo<V: BV> {
     pairs_to_restore: Vec<(Name, V)>,
 }
 
+impl<V: BV> RestoreInfo<V> {
+    /// Create a `RestoreInfo` which restores the given variables of the given
+    /// function to the given values, as if it had been generated by
+    /// `get_restore_info_for_fn()` when they were active
+    pub fn new(funcname: String, pairs_to_restore: Vec<(Name, V)>) -> Self {
+        Self {
+            funcname,
+            pairs_to_restore,
+        }
+    }
+
+    /// The function whose variables this `RestoreInfo` restores
+    pub fn funcname(&self) -> &str {
+        &self.funcname
+    }
+
+    /// The variables this `RestoreInfo` restores, and their values
+    pub fn pairs_to_restore(&self) -> &[(Name, V)] {
+        &self.pairs_to_restore
+    }
+
+    /// Adapt the `RestoreInfo` to a new solver instance; see notes on
+    /// `VarMap::change_solver()`.
+    pub fn change_solver(&mut self, new_solver: V::SolverRef) {
+        for (_, v) in self.pairs_to_restore.iter_mut() {
+            *v = new_solver.match_bv(v).unwrap();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::solver_utils;
+    use crate::solver_utils::{self, PossibleSolutions};
     use boolector::Btor;
     use std::rc::Rc;
 
@@ -259,6 +403,140 @@ mod tests {
         // check that looking up the llvm-ir values gives the correct BV ones
         assert_eq!(varmap.lookup_var(&funcname, &name1), &var1);
         assert_eq!(varmap.lookup_var(&funcname, &name2), &var2);
+
+        // and that looking up an unbound name doesn't panic
+        assert_eq!(varmap.try_lookup_var(&funcname, &name1), Some(&var1));
+        assert_eq!(varmap.try_lookup_var(&funcname, &Name::from(3)), None);
+    }
+
+    #[test]
+    fn width_of() {
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut varmap: VarMap<BV> = VarMap::new(btor.clone(), 20);
+        let funcname = "foo".to_owned();
+
+        // a 17-bit var, and a boolean assigned from a comparison
+        let name17 = Name::from("val17");
+        let namebool = Name::from("cond");
+        let var17 = varmap
+            .new_bv_with_name(funcname.clone(), name17.clone(), 17)
+            .unwrap();
+        let cond = var17._eq(&BV::zero(btor, 17));
+        varmap
+            .assign_bv_to_name(funcname.clone(), namebool.clone(), cond)
+            .unwrap();
+
+        assert_eq!(varmap.width_of(&funcname, &name17), Some(17));
+        assert_eq!(varmap.width_of(&funcname, &namebool), Some(1));
+
+        // vars which haven't been assigned, or are in another function, have no width
+        assert_eq!(varmap.width_of(&funcname, &Name::from("other")), None);
+        assert_eq!(varmap.width_of(&"bar".to_owned(), &name17), None);
+    }
+
+    #[test]
+    fn copy_binding() {
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut varmap: VarMap<BV> = VarMap::new(btor, 20);
+        let funcname = "foo".to_owned();
+        let from = Name::from("from");
+        let to = Name::from(3);
+
+        let var = varmap
+            .new_bv_with_name(funcname.clone(), from.clone(), 64)
+            .unwrap();
+        varmap
+            .copy_binding(funcname.clone(), &from, to.clone())
+            .unwrap();
+        assert_eq!(varmap.lookup_var(&funcname, &to), &var);
+        assert_eq!(varmap.lookup_var(&funcname, &from), &var);
+
+        // rebinding `from` afterwards doesn't affect `to`
+        varmap
+            .new_bv_with_name(funcname.clone(), from.clone(), 64)
+            .unwrap();
+        assert_eq!(varmap.lookup_var(&funcname, &to), &var);
+
+        // copying an unbound var is an error
+        assert!(varmap
+            .copy_binding(funcname.clone(), &Name::from("other"), to)
+            .is_err());
+        assert!(varmap
+            .copy_binding("bar".to_owned(), &from, Name::from("x"))
+            .is_err());
+    }
+
+    #[test]
+    fn symbols_of() {
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut varmap: VarMap<BV> = VarMap::new(btor, 20);
+        let funcname = "foo".to_owned();
+        let name = Name::from("val");
+
+        assert!(varmap.symbols_of(&funcname, &name).is_empty());
+
+        // each version of the name gets its own symbol
+        let var0 = varmap
+            .new_bv_with_name(funcname.clone(), name.clone(), 64)
+            .unwrap();
+        let var1 = varmap
+            .new_bv_with_name(funcname.clone(), name.clone(), 64)
+            .unwrap();
+        let symbols = varmap.symbols_of(&funcname, &name);
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(Some(symbols[0].as_str()), var0.get_symbol());
+        assert_eq!(Some(symbols[1].as_str()), var1.get_symbol());
+    }
+
+    #[test]
+    fn merge() {
+        let btor = <Rc<Btor> as SolverRef>::new();
+        let mut varmap: VarMap<BV> = VarMap::new(btor.clone(), 20);
+        let funcname = "foo".to_owned();
+        let name = Name::from("val");
+        let same = Name::from("same");
+        let only_other = Name::from("only_other");
+
+        let same_bv = BV::from_u64(btor.clone(), 7, 8);
+        varmap
+            .assign_bv_to_name(funcname.clone(), same.clone(), same_bv.clone())
+            .unwrap();
+        let mut other = varmap.clone();
+        varmap
+            .assign_bv_to_name(
+                funcname.clone(),
+                name.clone(),
+                BV::from_u64(btor.clone(), 1, 8),
+            )
+            .unwrap();
+        other
+            .assign_bv_to_name(
+                funcname.clone(),
+                name.clone(),
+                BV::from_u64(btor.clone(), 2, 8),
+            )
+            .unwrap();
+        other
+            .new_bv_with_name(funcname.clone(), only_other.clone(), 8)
+            .unwrap();
+        let only_other_bv = other.lookup_var(&funcname, &only_other).clone();
+
+        let cond = BV::new(btor.clone(), 1, Some("cond"));
+        varmap.merge(other, &cond);
+
+        // variables bound to the same BV in both are unchanged
+        assert_eq!(varmap.lookup_var(&funcname, &same), &same_bv);
+        // variables bound only in `other` are taken from `other`
+        assert_eq!(varmap.lookup_var(&funcname, &only_other), &only_other_bv);
+        // variables bound differently are selected by `cond`
+        let merged = varmap.lookup_var(&funcname, &name).clone();
+        cond.assert();
+        assert_eq!(
+            solver_utils::get_possible_solutions_for_bv(btor, &merged, 1)
+                .unwrap()
+                .as_u64_solutions(),
+            Some(PossibleSolutions::Exactly(std::iter::once(1).collect()))
+        );
     }
 
     #[test]
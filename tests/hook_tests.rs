@@ -1,5 +1,5 @@
-use haybale::backend::Backend;
-use haybale::function_hooks::IsCall;
+use haybale::backend::{Backend, BV};
+use haybale::function_hooks::{HookResult, IsCall};
 use haybale::solver_utils::PossibleSolutions;
 use haybale::*;
 use std::collections::HashSet;
@@ -98,3 +98,42 @@ fn hook_a_function_ptr() {
         PossibleSolutions::Exactly(HashSet::from_iter(std::iter::once(ReturnValue::Return(15)))),
     );
 }
+
+// Hook call.c's "simple_callee" to return an unknown value which is at most 10
+fn constrained_hook_for_simple_callee<'p, B: Backend>(
+    _proj: &'p Project,
+    state: &mut State<'p, B>,
+    call: &'p dyn IsCall,
+) -> Result<HookResult<B::BV>> {
+    assert_eq!(call.get_arguments().len(), 2);
+    let width = layout::size(&call.get_type()) as u32;
+    let value = B::BV::new(state.solver.clone(), width, Some("simple_callee_retval"));
+    let constraint = value.ulte(&state.bv_from_u32(10, width));
+    Ok(HookResult {
+        value,
+        constraints: vec![constraint],
+    })
+}
+
+#[test]
+fn hook_with_constrained_return_value() {
+    init_logging();
+    let proj = Project::from_bc_path(&Path::new("tests/bcfiles/call.bc"))
+        .unwrap_or_else(|e| panic!("Failed to parse module call.bc: {}", e));
+    let mut config = Config::default();
+    config
+        .function_hooks
+        .add_constrained("simple_callee", &constrained_hook_for_simple_callee);
+    // with that hook, simple_caller can return exactly the values 0 through 10
+    assert_eq!(
+        get_possible_return_values_of_func(
+            "simple_caller",
+            std::iter::once(None),
+            &proj,
+            config,
+            None,
+            20
+        ),
+        PossibleSolutions::Exactly((0 ..= 10).map(ReturnValue::Return).collect()),
+    );
+}